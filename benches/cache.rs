@@ -0,0 +1,61 @@
+// The lookups of relocation processing, the same symbols over and over, through
+// the plain tables and through the cached ones, of short and of long names,
+// `cargo bench --features std --bench cache`
+
+mod common;
+
+use elf64::{Elf64, CachedTable, CachedStringTable};
+
+use self::common::{bench, object};
+
+fn main() {
+    // a thousand hot symbols spread over the table, a million references
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let indices = (0..1_000_000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            1 + (state % 1_000) as usize * 97
+        })
+        .collect::<Vec<_>>();
+
+    for padding in [0, 1_000] {
+        let raw = object(100_000, padding);
+        let elf = Elf64::new(&raw).unwrap();
+        let symbols = elf.symbol_table().unwrap().unwrap();
+        let offsets = indices
+            .iter()
+            .map(|&index| symbols.table.pick(index).unwrap().name as usize)
+            .collect::<Vec<_>>();
+        println!("names of {} more bytes", padding);
+
+        bench("symbols, Table", || {
+            indices
+                .iter()
+                .map(|&index| symbols.table.pick(index).unwrap().size)
+                .sum::<u64>()
+        });
+        bench("symbols, CachedTable", || {
+            // a new cache every iteration, the first look up of a symbol decodes it
+            let table = CachedTable::new(symbols.table.clone());
+            indices
+                .iter()
+                .map(|&index| table.pick(index).unwrap().size)
+                .sum::<u64>()
+        });
+        bench("names, StringTable", || {
+            offsets
+                .iter()
+                .map(|&offset| symbols.names.pick(offset).unwrap().len())
+                .sum::<usize>()
+        });
+        bench("names, CachedStringTable", || {
+            let names = CachedStringTable::new(symbols.names.clone());
+            offsets
+                .iter()
+                .map(|&offset| names.pick(offset).unwrap().len())
+                .sum::<usize>()
+        });
+    }
+}
@@ -0,0 +1,61 @@
+// There is no benchmark harness on stable without a dependency, each benchmark
+// is a `main` that times its closures and prints the mean time of an iteration
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use elf64::{
+    ElfBuilder, Encoding, Type, Machine, Index, SectionType, SectionFlags, SymbolInfo,
+    SymbolBinding, SymbolType,
+};
+
+// An object of many functions of mangled names, the way a large C++ or Rust
+// object has them, the symbol dumps of these spend the time in the names,
+// `padding` makes the names longer, e.g. of nested templates
+pub fn object(number: usize, padding: usize) -> Vec<u8> {
+    let mut builder = ElfBuilder::new(Encoding::Little, Type::Relocatable, Machine::X86_64);
+    let text = builder.add_section(
+        b".text",
+        SectionType::ProgramBits,
+        SectionFlags::ALLOC | SectionFlags::EXECINSTR,
+        16,
+        vec![0xc3; number],
+    );
+    let info = SymbolInfo {
+        binding: SymbolBinding::Global,
+        ty: SymbolType::Function,
+    };
+    for index in 0..number {
+        let name = format!(
+            "_ZN4core3fmt9Formatter{}12debug_struct{}17h{:016x}E",
+            index,
+            "x".repeat(padding),
+            index
+        );
+        builder.add_symbol(
+            name.as_bytes(),
+            info.clone(),
+            Index::Regular(text),
+            index as u64,
+            1,
+        );
+    }
+    builder.build().unwrap()
+}
+
+// runs the closure for about a second after a warm up
+pub fn bench<F, R>(name: &str, mut f: F)
+where
+    F: FnMut() -> R,
+{
+    black_box(f());
+    let (mut iterations, start) = (0u32, Instant::now());
+    while start.elapsed() < Duration::from_secs(1) {
+        black_box(f());
+        iterations += 1;
+    }
+    let mean = start.elapsed() / iterations;
+    println!("{:<48} {:>12?} ({} iterations)", name, mean, iterations);
+}
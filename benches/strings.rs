@@ -0,0 +1,35 @@
+// The terminator search of `StringTable` against a search a byte at a time,
+// `cargo bench --features std --bench strings`
+
+mod common;
+
+use elf64::Elf64;
+
+use self::common::{bench, object};
+
+fn main() {
+    let raw = object(100_000, 0);
+    let elf = Elf64::new(&raw).unwrap();
+    let symbols = elf.symbol_table().unwrap().unwrap();
+    let names = symbols.names.clone();
+    let offsets = (1..symbols.number)
+        .map(|index| symbols.table.pick(index).unwrap().name as usize)
+        .collect::<Vec<_>>();
+
+    bench("pick every name, a byte at a time", || {
+        let slice = names.as_raw();
+        offsets
+            .iter()
+            .map(|&offset| slice[offset..].iter().position(|b| *b == 0).unwrap())
+            .sum::<usize>()
+    });
+    bench("pick every name, StringTable::pick", || {
+        offsets
+            .iter()
+            .map(|&offset| names.pick(offset).unwrap().len())
+            .sum::<usize>()
+    });
+    bench("iterate the strings, StringTable::strings", || {
+        names.strings().count()
+    });
+}
@@ -0,0 +1,36 @@
+// The symbols with their names, one pass and many, with and without the index
+// of the terminators of `Symbols::with_name_index`, of short and of long names,
+// `cargo bench --features std --bench symbol_names`
+
+mod common;
+
+use elf64::{Elf64, Symbols};
+
+use self::common::{bench, object};
+
+fn names(symbols: Symbols) -> usize {
+    symbols
+        .map(|symbol| symbol.unwrap().name.len())
+        .sum::<usize>()
+}
+
+fn main() {
+    for padding in [0, 1_000] {
+        let raw = object(20_000, padding);
+        let elf = Elf64::new(&raw).unwrap();
+        println!("names of {} more bytes", padding);
+
+        bench("one pass", || names(elf.symbols().unwrap().unwrap()));
+        bench("one pass, with_name_index", || {
+            names(elf.symbols().unwrap().unwrap().with_name_index())
+        });
+        bench("ten passes", || {
+            let symbols = elf.symbols().unwrap().unwrap();
+            (0..10).map(|_| names(symbols.clone())).sum::<usize>()
+        });
+        bench("ten passes, with_name_index", || {
+            let symbols = elf.symbols().unwrap().unwrap().with_name_index();
+            (0..10).map(|_| names(symbols.clone())).sum::<usize>()
+        });
+    }
+}
@@ -0,0 +1,312 @@
+use super::{Error, Elf64};
+
+const ARCHIVE_MAGIC: &[u8] = b"!<arch>\n";
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+const ELFCLASS32: u8 = 1;
+const ELFCLASS64: u8 = 2;
+const MEMBER_HEADER_SIZE: usize = 60;
+
+// What `probe` finds at the start of a file
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum FileKind {
+    Elf64,
+    Elf32,
+    // `!<arch>`, a static library, a thin archive `!<thin>` has no members in it
+    // and is `Unknown`
+    Archive,
+    // a text the linker reads instead of a library, e.g. `libc.so` of glibc
+    LinkerScript,
+    Unknown,
+}
+
+// the commands a linker script usually starts with
+const SCRIPT_COMMANDS: [&[u8]; 8] = [
+    b"INPUT",
+    b"GROUP",
+    b"OUTPUT_FORMAT",
+    b"OUTPUT_ARCH",
+    b"SEARCH_DIR",
+    b"ENTRY",
+    b"SECTIONS",
+    b"INCLUDE",
+];
+
+// The kind of the file by its first bytes, ELF of both classes, both encodings,
+// a file of the ELF magic and of an unknown class is `Unknown`
+pub fn probe(raw: &[u8]) -> FileKind {
+    if raw.starts_with(ELF_MAGIC) {
+        return match raw.get(4) {
+            Some(&ELFCLASS64) => FileKind::Elf64,
+            Some(&ELFCLASS32) => FileKind::Elf32,
+            _ => FileKind::Unknown,
+        };
+    }
+    if raw.starts_with(ARCHIVE_MAGIC) {
+        return FileKind::Archive;
+    }
+    if is_linker_script(raw) {
+        return FileKind::LinkerScript;
+    }
+    FileKind::Unknown
+}
+
+fn skip_spaces(text: &[u8]) -> usize {
+    text.iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(text.len())
+}
+
+// the field of a member header, padded with spaces
+fn trim_field(field: &[u8]) -> &[u8] {
+    let end = field
+        .iter()
+        .rposition(|b| *b != b' ')
+        .map_or(0, |end| end + 1);
+    &field[..end]
+}
+
+// A text without zeros that starts with a command of `SCRIPT_COMMANDS` after the spaces
+// and the comments, or with the `/* GNU ld script` comment of glibc and others
+fn is_linker_script(raw: &[u8]) -> bool {
+    let text = &raw[..raw.len().min(4096)];
+    if text.contains(&0) {
+        return false;
+    }
+    let mut rest = text;
+    loop {
+        rest = &rest[skip_spaces(rest)..];
+        if !rest.starts_with(b"/*") {
+            break;
+        }
+        // the search starts after `/*`, `/*/` is not a comment
+        let end = match rest[2..].windows(2).position(|w| w == b"*/") {
+            Some(end) => end + 2,
+            None => return false,
+        };
+        let comment = &rest[2..end];
+        if comment[skip_spaces(comment)..].starts_with(b"GNU ld script") {
+            return true;
+        }
+        rest = &rest[(end + 2)..];
+    }
+    SCRIPT_COMMANDS.iter().any(|command| {
+        let next = match rest.strip_prefix(*command) {
+            Some(after) => after.get(skip_spaces(after)),
+            None => return false,
+        };
+        next == Some(&b'(') || (*command == b"SECTIONS" && next == Some(&b'{'))
+    })
+}
+
+// A member of an archive, `name` is without the `/` of GNU ar, the name
+// of the extended name table `//`, or the one of BSD `#1/`, is resolved
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchiveMember<'a> {
+    pub name: &'a [u8],
+    // the offset of the header of the member in the archive
+    pub offset: usize,
+    pub data: &'a [u8],
+}
+
+impl<'a> ArchiveMember<'a> {
+    pub fn kind(&self) -> FileKind {
+        probe(self.data)
+    }
+}
+
+// An `ar` archive, the members reference the bytes of the archive
+#[derive(Clone)]
+pub struct Archive<'a> {
+    raw: &'a [u8],
+    // `//` of GNU ar
+    names: Option<&'a [u8]>,
+}
+
+// a member before its name is resolved, the name as in the header
+struct RawMember<'a> {
+    name: &'a [u8],
+    data: &'a [u8],
+    next: usize,
+}
+
+fn decimal(field: &[u8]) -> Result<usize, Error> {
+    let digits = trim_field(field);
+    if digits.is_empty() {
+        return Err(Error::CorruptedArchive);
+    }
+    digits.iter().try_fold(0usize, |value, digit| match digit {
+        b'0'..=b'9' => value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(usize::from(digit - b'0')))
+            .ok_or(Error::CorruptedArchive),
+        _ => Err(Error::CorruptedArchive),
+    })
+}
+
+impl<'a> Archive<'a> {
+    pub fn new(raw: &'a [u8]) -> Result<Self, Error> {
+        if !raw.starts_with(ARCHIVE_MAGIC) {
+            return Err(Error::WrongMagicNumber);
+        }
+        let mut archive = Archive { raw, names: None };
+        // the symbol tables and the name table are the first members
+        let mut offset = ARCHIVE_MAGIC.len();
+        while let Some(member) = archive.raw_member(offset)? {
+            match member.name {
+                b"//" => archive.names = Some(member.data),
+                b"/" | b"/SYM64/" => (),
+                _ => break,
+            }
+            offset = member.next;
+        }
+        Ok(archive)
+    }
+
+    fn raw_member(&self, offset: usize) -> Result<Option<RawMember<'a>>, Error> {
+        if offset >= self.raw.len() {
+            return Ok(None);
+        }
+        let header = self
+            .raw
+            .get(offset..)
+            .and_then(|rest| rest.get(..MEMBER_HEADER_SIZE))
+            .ok_or(Error::CorruptedArchive)?;
+        if &header[58..] != b"`\n" {
+            return Err(Error::CorruptedArchive);
+        }
+        let size = decimal(&header[48..58])?;
+        let start = offset + MEMBER_HEADER_SIZE;
+        let data = self
+            .raw
+            .get(start..)
+            .and_then(|rest| rest.get(..size))
+            .ok_or(Error::CorruptedArchive)?;
+        // the data is aligned to two bytes
+        let next = start + size + (size & 1);
+        Ok(Some(RawMember {
+            name: trim_field(&header[..16]),
+            data,
+            next,
+        }))
+    }
+
+    // the name at the offset of the `//` table, `/\n` ends it
+    fn extended_name(&self, offset: &[u8]) -> Result<&'a [u8], Error> {
+        let names = self.names.ok_or(Error::CorruptedArchive)?;
+        let rest = names
+            .get(decimal(offset)?..)
+            .ok_or(Error::CorruptedArchive)?;
+        let end = rest
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or(Error::CorruptedArchive)?;
+        let name = &rest[..end];
+        Ok(name.strip_suffix(b"/").unwrap_or(name))
+    }
+
+    fn member(&self, raw: RawMember<'a>, offset: usize) -> Result<ArchiveMember<'a>, Error> {
+        let RawMember { name, mut data, .. } = raw;
+        let name = if let Some(length) = name.strip_prefix(b"#1/") {
+            let length = decimal(length)?;
+            if length > data.len() {
+                return Err(Error::CorruptedArchive);
+            }
+            let (name, rest) = data.split_at(length);
+            data = rest;
+            let end = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+            &name[..end]
+        } else if let Some(offset) = name.strip_prefix(b"/") {
+            self.extended_name(offset)?
+        } else {
+            name.strip_suffix(b"/").unwrap_or(name)
+        };
+        Ok(ArchiveMember { name, offset, data })
+    }
+
+    // The members of the archive in order, without the symbol tables, `/`, `/SYM64/`
+    // and `__.SYMDEF`, and without the name table. A corrupted member is the last item.
+    pub fn members(&self) -> ArchiveMembers<'a> {
+        ArchiveMembers {
+            archive: self.clone(),
+            offset: ARCHIVE_MAGIC.len(),
+        }
+    }
+
+    // the members that are ELF64 files, parsed
+    pub fn elf_members(
+        &self,
+    ) -> impl Iterator<Item = Result<(ArchiveMember<'a>, Elf64<'a>), Error>> + 'a {
+        self.members().filter_map(|member| match member {
+            Ok(member) if member.kind() == FileKind::Elf64 => {
+                Some(Elf64::new(member.data).map(|elf| (member, elf)))
+            }
+            Ok(_) => None,
+            Err(error) => Some(Err(error)),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct ArchiveMembers<'a> {
+    archive: Archive<'a>,
+    offset: usize,
+}
+
+impl<'a> Iterator for ArchiveMembers<'a> {
+    type Item = Result<ArchiveMember<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let offset = self.offset;
+            let raw = match self.archive.raw_member(offset) {
+                Ok(Some(raw)) => raw,
+                Ok(None) => return None,
+                Err(error) => {
+                    self.offset = self.archive.raw.len();
+                    return Some(Err(error));
+                }
+            };
+            self.offset = raw.next;
+            match raw.name {
+                b"/" | b"/SYM64/" | b"//" => continue,
+                _ => (),
+            }
+            let member = self.archive.member(raw, offset);
+            match &member {
+                Ok(member) if member.name.starts_with(b"__.SYMDEF") => continue,
+                Ok(_) => (),
+                Err(_) => self.offset = self.archive.raw.len(),
+            }
+            return Some(member);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{probe, FileKind};
+
+    #[test]
+    fn unterminated_comments() {
+        for raw in [
+            &b"/*/"[..],
+            b"/*",
+            b"/**",
+            b" /* */ /*/",
+            b"/*/ GNU ld script */",
+        ] {
+            assert_eq!(probe(raw), FileKind::Unknown);
+        }
+    }
+
+    #[test]
+    fn linker_scripts() {
+        assert_eq!(
+            probe(b"/* GNU ld script\n */\nGROUP ( libc.so.6 )"),
+            FileKind::LinkerScript
+        );
+        assert_eq!(probe(b"/**/INPUT(-lfoo)"), FileKind::LinkerScript);
+        assert_eq!(probe(b"SECTIONS {"), FileKind::LinkerScript);
+        assert_eq!(probe(b"GROUPS(x)"), FileKind::Unknown);
+    }
+}
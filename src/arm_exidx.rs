@@ -0,0 +1,215 @@
+use super::{
+    Error, SectionIndex, Address, Encoding, Elf64, Machine, SectionType, ProgramType, SectionData,
+};
+use super::common::file_slice;
+
+// `SHT_ARM_EXIDX` and `PT_ARM_EXIDX`
+const ARM_EXIDX: u32 = 0x70000001;
+// `EXIDX_CANTUNWIND`
+const EXIDX_CANTUNWIND: u32 = 1;
+
+// The unwind instructions of EHABI, one byte after another the way the personality
+// routine reads them, from the most significant byte of each word
+#[derive(Clone, Debug)]
+pub struct UnwindInstructions<'a> {
+    words: &'a [u8],
+    encoding: Encoding,
+    position: usize,
+    end: usize,
+}
+
+impl<'a> Iterator for UnwindInstructions<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.end {
+            return None;
+        }
+        let word = self.words.get((self.position & !3)..)?;
+        let word = if word.len() < 4 {
+            return None;
+        } else {
+            read_int!(word, &self.encoding, u32)
+        };
+        let shift = 24 - (self.position & 3) * 8;
+        self.position += 1;
+        Some((word >> shift) as u8)
+    }
+}
+
+// An entry of `.ARM.extab`
+#[derive(Clone, Debug)]
+pub enum ExtabEntry<'a> {
+    // the personality routine `__aeabi_unwind_cpp_pr0`, `pr1` or `pr2` by the index
+    Compact {
+        personality: u8,
+        instructions: UnwindInstructions<'a>,
+    },
+    // the address of the personality routine and what follows the word, only
+    // the routine knows its size, e.g. the language specific data area
+    Generic {
+        personality: Address,
+        data: &'a [u8],
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum Unwind<'a> {
+    // `EXIDX_CANTUNWIND`
+    CantUnwind,
+    // the compact model in the index itself, the personality routine 0
+    Inline(UnwindInstructions<'a>),
+    // the entry is in `.ARM.extab` at the address, `None` if the file has no such section
+    Table {
+        address: Address,
+        entry: Option<ExtabEntry<'a>>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct ExidxEntry<'a> {
+    pub start: Address,
+    // the start of the next entry, `None` for the last one, the table is sorted
+    pub end: Option<Address>,
+    pub unwind: Unwind<'a>,
+}
+
+// The entries of `.ARM.exidx`, or of `PT_ARM_EXIDX` of a file without section headers,
+// the offsets are of a linked file, a relocatable file has them in the relocations
+#[derive(Clone)]
+pub struct ExidxEntries<'a> {
+    index: &'a [u8],
+    address: Address,
+    extab: Option<(Address, &'a [u8])>,
+    encoding: Encoding,
+    position: usize,
+}
+
+impl<'a> ExidxEntries<'a> {
+    fn word(&self, offset: usize) -> u32 {
+        read_int!(&self.index[offset..], &self.encoding, u32)
+    }
+
+    // a signed 31 bits offset from the place of the word
+    fn prel31(place: Address, word: u32) -> Address {
+        let offset = ((word << 1) as i32 >> 1) as i64;
+        place.wrapping_add(offset as u64)
+    }
+
+    fn extab_entry(&self, address: Address) -> Option<ExtabEntry<'a>> {
+        let (start, slice) = self.extab?;
+        let offset = address.checked_sub(start)? as usize;
+        let slice = slice.get(offset..)?;
+        if slice.len() < 4 {
+            return None;
+        }
+        let word = read_int!(slice, &self.encoding, u32);
+        if word & 0x80000000 == 0 {
+            return Some(ExtabEntry::Generic {
+                personality: Self::prel31(address, word),
+                data: &slice[4..],
+            });
+        }
+        let personality = ((word >> 24) & 0xf) as u8;
+        let (position, end) = match personality {
+            0 => (1, 4),
+            // the number of the words that follow in the next byte
+            _ => (2, 4 + ((word >> 16) & 0xff) as usize * 4),
+        };
+        Some(ExtabEntry::Compact {
+            personality,
+            instructions: UnwindInstructions {
+                words: slice,
+                encoding: self.encoding.clone(),
+                position,
+                end: end.min(slice.len() & !3),
+            },
+        })
+    }
+}
+
+impl<'a> Iterator for ExidxEntries<'a> {
+    type Item = ExidxEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.position;
+        if offset + 8 > self.index.len() {
+            return None;
+        }
+        self.position += 8;
+        let place = self.address.wrapping_add(offset as u64);
+        let start = Self::prel31(place, self.word(offset));
+        let end = if offset + 16 <= self.index.len() {
+            Some(Self::prel31(place.wrapping_add(8), self.word(offset + 8)))
+        } else {
+            None
+        };
+        let word = self.word(offset + 4);
+        let unwind = if word == EXIDX_CANTUNWIND {
+            Unwind::CantUnwind
+        } else if word & 0x80000000 != 0 {
+            Unwind::Inline(UnwindInstructions {
+                words: &self.index[(offset + 4)..(offset + 8)],
+                encoding: self.encoding.clone(),
+                position: 1,
+                end: 4,
+            })
+        } else {
+            let address = Self::prel31(place.wrapping_add(4), word);
+            Unwind::Table {
+                address,
+                entry: self.extab_entry(address),
+            }
+        };
+        Some(ExidxEntry { start, end, unwind })
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The ARM exception index table, `None` if it is not an ARM file or there is no table
+    pub fn arm_exidx(&self) -> Result<Option<ExidxEntries<'a>>, Error> {
+        if self.machine() != Machine::Arm {
+            return Ok(None);
+        }
+        let mut index = None;
+        let mut extab = None;
+        for i in 0..self.section_number() {
+            let header = self.section_table.pick(i)?;
+            match header.ty {
+                SectionType::ProcessorSprcific(ARM_EXIDX) if index.is_none() => {
+                    if let Some(SectionData::ProcessorSprcific { slice, .. }) =
+                        self.section(SectionIndex(i))?.map(|s| s.data)
+                    {
+                        index = Some((header.address, slice));
+                    }
+                }
+                SectionType::ProgramBits => {
+                    let section = self.section(SectionIndex(i))?;
+                    if let Some(section) = section.filter(|s| s.name == b".ARM.extab") {
+                        if let SectionData::ProgramBits(slice) = section.data {
+                            extab = Some((header.address, slice));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        if index.is_none() {
+            for i in 0..self.program_number() {
+                let header = self.program_table.pick(i)?;
+                if header.ty == ProgramType::ProcessorSprcific(ARM_EXIDX) {
+                    let slice = file_slice(self.raw, header.file_offset, header.file_size)?;
+                    index = Some((header.virtual_address, slice));
+                    break;
+                }
+            }
+        }
+        Ok(index.map(|(address, index)| ExidxEntries {
+            index,
+            address,
+            extab,
+            encoding: self.encoding(),
+            position: 0,
+        }))
+    }
+}
@@ -0,0 +1,143 @@
+use super::{Error, Address, Elf64, Entry, ProgramHeader};
+
+// The types of the auxiliary vector, the ones that come from the executable
+// and the ones a core file has of the system
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum AuxiliaryType {
+    // `AT_NULL`, the end of the vector
+    Null,
+    // `AT_EXECFD`
+    ExecutableDescriptor,
+    // `AT_PHDR`
+    ProgramHeaders,
+    // `AT_PHENT`
+    ProgramHeaderSize,
+    // `AT_PHNUM`
+    ProgramHeaderNumber,
+    // `AT_PAGESZ`
+    PageSize,
+    // `AT_BASE`, where the interpreter is
+    Base,
+    // `AT_FLAGS`
+    Flags,
+    // `AT_ENTRY`, of the executable, not of the interpreter
+    Entry,
+    // `AT_UID`
+    Uid,
+    // `AT_EUID`
+    EffectiveUid,
+    // `AT_GID`
+    Gid,
+    // `AT_EGID`
+    EffectiveGid,
+    // `AT_PLATFORM`, the address of the string
+    Platform,
+    // `AT_HWCAP`
+    HardwareCapabilities,
+    // `AT_CLKTCK`
+    ClockTick,
+    // `AT_SECURE`
+    Secure,
+    // `AT_RANDOM`, the address of 16 random bytes
+    Random,
+    // `AT_HWCAP2`
+    HardwareCapabilities2,
+    // `AT_EXECFN`, the address of the path the executable was run by
+    ExecutableName,
+    // `AT_SYSINFO_EHDR`, where the vDSO is
+    VdsoHeader,
+    Unknown(u64),
+}
+
+impl From<u64> for AuxiliaryType {
+    fn from(v: u64) -> Self {
+        match v {
+            0 => AuxiliaryType::Null,
+            2 => AuxiliaryType::ExecutableDescriptor,
+            3 => AuxiliaryType::ProgramHeaders,
+            4 => AuxiliaryType::ProgramHeaderSize,
+            5 => AuxiliaryType::ProgramHeaderNumber,
+            6 => AuxiliaryType::PageSize,
+            7 => AuxiliaryType::Base,
+            8 => AuxiliaryType::Flags,
+            9 => AuxiliaryType::Entry,
+            11 => AuxiliaryType::Uid,
+            12 => AuxiliaryType::EffectiveUid,
+            13 => AuxiliaryType::Gid,
+            14 => AuxiliaryType::EffectiveGid,
+            15 => AuxiliaryType::Platform,
+            16 => AuxiliaryType::HardwareCapabilities,
+            17 => AuxiliaryType::ClockTick,
+            23 => AuxiliaryType::Secure,
+            25 => AuxiliaryType::Random,
+            26 => AuxiliaryType::HardwareCapabilities2,
+            31 => AuxiliaryType::ExecutableName,
+            33 => AuxiliaryType::VdsoHeader,
+            t => AuxiliaryType::Unknown(t),
+        }
+    }
+}
+
+impl From<AuxiliaryType> for u64 {
+    fn from(v: AuxiliaryType) -> Self {
+        match v {
+            AuxiliaryType::Null => 0,
+            AuxiliaryType::ExecutableDescriptor => 2,
+            AuxiliaryType::ProgramHeaders => 3,
+            AuxiliaryType::ProgramHeaderSize => 4,
+            AuxiliaryType::ProgramHeaderNumber => 5,
+            AuxiliaryType::PageSize => 6,
+            AuxiliaryType::Base => 7,
+            AuxiliaryType::Flags => 8,
+            AuxiliaryType::Entry => 9,
+            AuxiliaryType::Uid => 11,
+            AuxiliaryType::EffectiveUid => 12,
+            AuxiliaryType::Gid => 13,
+            AuxiliaryType::EffectiveGid => 14,
+            AuxiliaryType::Platform => 15,
+            AuxiliaryType::HardwareCapabilities => 16,
+            AuxiliaryType::ClockTick => 17,
+            AuxiliaryType::Secure => 23,
+            AuxiliaryType::Random => 25,
+            AuxiliaryType::HardwareCapabilities2 => 26,
+            AuxiliaryType::ExecutableName => 31,
+            AuxiliaryType::VdsoHeader => 33,
+            AuxiliaryType::Unknown(t) => t,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct AuxiliaryEntry {
+    pub ty: AuxiliaryType,
+    pub value: u64,
+}
+
+impl<'a> Elf64<'a> {
+    // The entries of the auxiliary vector a loader gives to the process of the executable
+    // loaded at the bias, zero for `ET_EXEC`, and to its interpreter loaded at its own bias.
+    // `AT_BASE` is zero without an interpreter. The rest of the vector, e.g. `AT_PAGESZ`
+    // or `AT_RANDOM`, is of the system, not of the file. `Error::NotPresent` if no segment
+    // loads the program header table.
+    pub fn auxiliary_vector(
+        &self,
+        bias: Address,
+        interpreter: Option<Address>,
+    ) -> Result<[AuxiliaryEntry; 5], Error> {
+        let program_headers = self.program_header_address()?.ok_or(Error::NotPresent)?;
+        let entry = |ty, value| AuxiliaryEntry { ty, value };
+        Ok([
+            entry(
+                AuxiliaryType::ProgramHeaders,
+                bias.wrapping_add(program_headers.0).0,
+            ),
+            entry(AuxiliaryType::ProgramHeaderSize, ProgramHeader::SIZE as u64),
+            entry(
+                AuxiliaryType::ProgramHeaderNumber,
+                self.program_number() as u64,
+            ),
+            entry(AuxiliaryType::Base, interpreter.map_or(0, |base| base.0)),
+            entry(AuxiliaryType::Entry, bias.wrapping_add(self.entry().0).0),
+        ])
+    }
+}
@@ -0,0 +1,86 @@
+use super::{Error, Encoding, NoteEntry, ProgramType, Elf64};
+
+bitflags! {
+    pub struct NetBsdPaxFlags: u32 {
+        const MPROTECT = 0b00000001;
+        const NOMPROTECT = 0b00000010;
+        const GUARD = 0b00000100;
+        const NOGUARD = 0b00001000;
+        const ASLR = 0b00010000;
+        const NOASLR = 0b00100000;
+    }
+}
+
+bitflags! {
+    pub struct FreeBsdFeatureFlags: u32 {
+        const ASLR_DISABLE = 0b00000001;
+        const PROTMAX_DISABLE = 0b00000010;
+        const STKGAP_DISABLE = 0b00000100;
+        const WXNEEDED = 0b00001000;
+        const LA48 = 0b00010000;
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BsdNote<'a> {
+    NetBsdIdent { version: u32 },
+    NetBsdEmulation(&'a [u8]),
+    NetBsdPax(NetBsdPaxFlags),
+    NetBsdMachineArch(&'a [u8]),
+    FreeBsdAbiTag { os_release: u32 },
+    FreeBsdNoInit,
+    FreeBsdArch(&'a [u8]),
+    FreeBsdFeatureControl(FreeBsdFeatureFlags),
+    OpenBsdIdent { version: u32 },
+}
+
+impl<'a> NoteEntry<'a> {
+    pub fn bsd(&self) -> Option<BsdNote<'a>> {
+        let word = || {
+            if self.description.len() < 4 {
+                None
+            } else {
+                Some(read_int!(self.description, self.encoding(), u32))
+            }
+        };
+        let string = || {
+            let mut end = self.description.len();
+            while end > 0 && self.description[end - 1] == 0 {
+                end -= 1;
+            }
+            &self.description[..end]
+        };
+
+        match (self.name, self.ty) {
+            (b"NetBSD", 1) => word().map(|version| BsdNote::NetBsdIdent { version }),
+            (b"NetBSD", 2) => Some(BsdNote::NetBsdEmulation(string())),
+            (b"PaX", 3) => {
+                word().map(|v| BsdNote::NetBsdPax(NetBsdPaxFlags::from_bits_truncate(v)))
+            }
+            (b"NetBSD", 5) => Some(BsdNote::NetBsdMachineArch(string())),
+            (b"FreeBSD", 1) => word().map(|os_release| BsdNote::FreeBsdAbiTag { os_release }),
+            (b"FreeBSD", 2) => Some(BsdNote::FreeBsdNoInit),
+            (b"FreeBSD", 3) => Some(BsdNote::FreeBsdArch(string())),
+            (b"FreeBSD", 4) => word().map(|v| {
+                BsdNote::FreeBsdFeatureControl(FreeBsdFeatureFlags::from_bits_truncate(v))
+            }),
+            (b"OpenBSD", 1) => word().map(|version| BsdNote::OpenBsdIdent { version }),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // OpenBSD does not use a note for it, but a dedicated program header
+    pub fn openbsd_wxneeded(&self) -> Result<bool, Error> {
+        const PT_OPENBSD_WXNEEDED: u32 = 0x65a3dbe7;
+
+        for index in 0..self.program_number() {
+            let program_header = self.program_table.pick(index)?;
+            if program_header.ty == ProgramType::OsSpecific(PT_OPENBSD_WXNEEDED) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,438 @@
+use alloc::{vec, vec::Vec};
+
+use super::{
+    Error, Address, Offset, SectionIndex, Encoding, Entry, Encode, Class, Abi, Type, Machine,
+    Header, Index, SectionType, SectionFlags, SectionHeader, ProgramType, ProgramFlags,
+    ProgramHeader, SymbolEntry, SymbolInfo, SymbolBinding, StringTableBuilder, Layout,
+    LayoutOptions, LayoutSection,
+};
+use super::header::Identifier;
+
+#[derive(Clone)]
+struct BuilderSection {
+    name: Vec<u8>,
+    ty: SectionType,
+    flags: SectionFlags,
+    address_alignment: u64,
+    link: Index,
+    info: u32,
+    number_of_entries: u64,
+    data: Vec<u8>,
+    // of `SHT_NOBITS`, there is no data
+    size: u64,
+}
+
+#[derive(Clone)]
+struct BuilderSymbol {
+    name: Vec<u8>,
+    info: SymbolInfo,
+    section_index: Index,
+    // relative to the section, the address of the section is added when it is placed
+    value: u64,
+    size: u64,
+}
+
+#[derive(Clone)]
+struct BuilderProgram {
+    ty: ProgramType,
+    flags: ProgramFlags,
+    // the segment spans the section, e.g. `.interp` for `PT_INTERP`
+    section: Option<SectionIndex>,
+}
+
+// Assembles sections, a symbol table and program headers into a new file. The sections
+// are placed by `Layout`, the loaded ones get `PT_LOAD` segments, the addresses of the
+// symbols and of the entry point are relative to their sections until then. Index zero
+// is the null section, the added ones follow in the order they are added, then `.symtab`
+// and `.strtab` if there are symbols, and `.shstrtab`.
+#[derive(Clone)]
+pub struct ElfBuilder {
+    encoding: Encoding,
+    abi: Abi,
+    ty: Type,
+    machine: Machine,
+    flags: u32,
+    entry: Option<(SectionIndex, u64)>,
+    options: LayoutOptions,
+    sections: Vec<BuilderSection>,
+    symbols: Vec<BuilderSymbol>,
+    programs: Vec<BuilderProgram>,
+}
+
+impl ElfBuilder {
+    pub fn new(encoding: Encoding, ty: Type, machine: Machine) -> Self {
+        ElfBuilder {
+            encoding,
+            abi: Abi::SystemV,
+            ty,
+            machine,
+            flags: 0,
+            entry: None,
+            options: LayoutOptions::default(),
+            sections: Vec::new(),
+            symbols: Vec::new(),
+            programs: Vec::new(),
+        }
+    }
+
+    pub fn set_abi(&mut self, abi: Abi) -> &mut Self {
+        self.abi = abi;
+        self
+    }
+
+    // `e_flags`
+    pub fn set_flags(&mut self, flags: u32) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+
+    // the base address and the page size, `extra_program_headers` is counted by the builder
+    pub fn set_layout_options(&mut self, options: LayoutOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    // `e_entry` is the address of the section plus the offset
+    pub fn set_entry(&mut self, section: SectionIndex, offset: u64) -> &mut Self {
+        self.entry = Some((section, offset));
+        self
+    }
+
+    pub fn add_section(
+        &mut self,
+        name: &[u8],
+        ty: SectionType,
+        flags: SectionFlags,
+        address_alignment: u64,
+        data: Vec<u8>,
+    ) -> SectionIndex {
+        self.sections.push(BuilderSection {
+            name: name.to_vec(),
+            ty,
+            flags,
+            address_alignment,
+            link: Index::Undefined,
+            info: 0,
+            number_of_entries: 0,
+            size: data.len() as u64,
+            data,
+        });
+        SectionIndex(self.sections.len())
+    }
+
+    // `SHT_NOBITS`, e.g. `.bss`, it takes memory, but not the file
+    pub fn add_no_bits(
+        &mut self,
+        name: &[u8],
+        flags: SectionFlags,
+        address_alignment: u64,
+        size: u64,
+    ) -> SectionIndex {
+        let index = self.add_section(
+            name,
+            SectionType::NoBits,
+            flags,
+            address_alignment,
+            Vec::new(),
+        );
+        self.sections[index.0 - 1].size = size;
+        index
+    }
+
+    // `sh_link` of an added section
+    pub fn set_link(&mut self, section: SectionIndex, link: SectionIndex) -> Result<(), Error> {
+        self.section_mut(section)?.link = Index::Regular(link);
+        Ok(())
+    }
+
+    // `sh_info` of an added section
+    pub fn set_info(&mut self, section: SectionIndex, info: u32) -> Result<(), Error> {
+        self.section_mut(section)?.info = info;
+        Ok(())
+    }
+
+    // `sh_entsize` of an added section
+    pub fn set_entry_size(&mut self, section: SectionIndex, size: u64) -> Result<(), Error> {
+        self.section_mut(section)?.number_of_entries = size;
+        Ok(())
+    }
+
+    fn section_mut(&mut self, section: SectionIndex) -> Result<&mut BuilderSection, Error> {
+        section
+            .0
+            .checked_sub(1)
+            .and_then(move |index| self.sections.get_mut(index))
+            .ok_or(Error::IndexOutOfBounds)
+    }
+
+    // the index `.symtab` gets, a relocation section links to it, only valid
+    // once every section is added
+    pub fn symbol_table(&self) -> SectionIndex {
+        SectionIndex(self.sections.len() + 1)
+    }
+
+    // The value of a symbol of a regular section is relative to it, the local symbols
+    // go before the others in `.symtab`, the order is kept otherwise
+    pub fn add_symbol(
+        &mut self,
+        name: &[u8],
+        info: SymbolInfo,
+        section_index: Index,
+        value: u64,
+        size: u64,
+    ) -> &mut Self {
+        self.symbols.push(BuilderSymbol {
+            name: name.to_vec(),
+            info,
+            section_index,
+            value,
+            size,
+        });
+        self
+    }
+
+    // a program header that is not `PT_LOAD`, spanning the section if there is one,
+    // e.g. `PT_INTERP` of `.interp`, or `PT_GNU_STACK` of none
+    pub fn add_program(
+        &mut self,
+        ty: ProgramType,
+        flags: ProgramFlags,
+        section: Option<SectionIndex>,
+    ) -> &mut Self {
+        self.programs.push(BuilderProgram { ty, flags, section });
+        self
+    }
+
+    // Lays the sections out and writes the file, a relocatable file has no segments
+    // and no addresses
+    pub fn build(&self) -> Result<Vec<u8>, Error> {
+        let loaded = self.ty != Type::Relocatable;
+
+        let mut symbols = self.symbols.iter().collect::<Vec<_>>();
+        symbols.sort_by_key(|symbol| symbol.info.binding != SymbolBinding::Local);
+        let first_global = symbols
+            .iter()
+            .position(|symbol| symbol.info.binding != SymbolBinding::Local)
+            .unwrap_or(symbols.len())
+            + 1;
+        let mut names = StringTableBuilder::new();
+        let symbol_names = symbols
+            .iter()
+            .map(|symbol| names.add(&symbol.name))
+            .collect::<Vec<_>>();
+        let (strings, string_offsets) = names.build();
+
+        let mut sections = self.sections.clone();
+        if !symbols.is_empty() {
+            let symbol_table = self.symbol_table();
+            sections.push(BuilderSection {
+                name: b".symtab".to_vec(),
+                ty: SectionType::SymbolTable,
+                flags: SectionFlags::empty(),
+                address_alignment: 8,
+                link: Index::Regular(SectionIndex(symbol_table.0 + 1)),
+                info: first_global as u32,
+                number_of_entries: SymbolEntry::SIZE as u64,
+                data: Vec::new(),
+                size: ((symbols.len() + 1) * SymbolEntry::SIZE) as u64,
+            });
+            sections.push(BuilderSection {
+                name: b".strtab".to_vec(),
+                ty: SectionType::StringTable,
+                flags: SectionFlags::empty(),
+                address_alignment: 1,
+                link: Index::Undefined,
+                info: 0,
+                number_of_entries: 0,
+                size: strings.len() as u64,
+                data: strings,
+            });
+        }
+        let mut names = StringTableBuilder::new();
+        let mut section_names = sections
+            .iter()
+            .map(|section| names.add(&section.name))
+            .collect::<Vec<_>>();
+        section_names.push(names.add(b".shstrtab"));
+        let (strings, name_offsets) = names.build();
+        sections.push(BuilderSection {
+            name: b".shstrtab".to_vec(),
+            ty: SectionType::StringTable,
+            flags: SectionFlags::empty(),
+            address_alignment: 1,
+            link: Index::Undefined,
+            info: 0,
+            number_of_entries: 0,
+            size: strings.len() as u64,
+            data: strings,
+        });
+
+        let layout_sections = sections
+            .iter()
+            .map(|section| LayoutSection {
+                size: section.size,
+                address_alignment: section.address_alignment,
+                flags: if loaded {
+                    section.flags
+                } else {
+                    section.flags - SectionFlags::ALLOC
+                },
+                no_bits: section.ty == SectionType::NoBits,
+            })
+            .collect::<Vec<_>>();
+        let options = LayoutOptions {
+            extra_program_headers: if loaded { self.programs.len() } else { 0 },
+            ..self.options.clone()
+        };
+        let layout = Layout::new(&layout_sections, &options);
+        let address_of = |index: SectionIndex| -> Result<Address, Error> {
+            index
+                .0
+                .checked_sub(1)
+                .and_then(|index| layout.placements.get(index))
+                .map(|placement| placement.address)
+                .ok_or(Error::IndexOutOfBounds)
+        };
+
+        let mut program_headers = Vec::new();
+        if loaded {
+            for program in &self.programs {
+                let (file_offset, virtual_address, size, address_alignment) = match program.section
+                {
+                    Some(index) => {
+                        let section = index
+                            .0
+                            .checked_sub(1)
+                            .and_then(|index| sections.get(index))
+                            .ok_or(Error::IndexOutOfBounds)?;
+                        let placement = &layout.placements[index.0 - 1];
+                        (
+                            placement.offset,
+                            placement.address,
+                            section.size,
+                            section.address_alignment.max(1),
+                        )
+                    }
+                    None => (Offset(0), Address(0), 0, 0x10),
+                };
+                program_headers.push(ProgramHeader {
+                    ty: program.ty.clone(),
+                    flags: program.flags,
+                    file_offset,
+                    virtual_address,
+                    physical_address: virtual_address,
+                    file_size: size,
+                    memory_size: size,
+                    address_alignment,
+                });
+            }
+            for segment in &layout.segments {
+                program_headers.push(ProgramHeader {
+                    ty: ProgramType::Load,
+                    flags: segment.flags,
+                    file_offset: segment.file_offset,
+                    virtual_address: segment.virtual_address,
+                    physical_address: segment.virtual_address,
+                    file_size: segment.file_size,
+                    memory_size: segment.memory_size,
+                    address_alignment: segment.address_alignment,
+                });
+            }
+        }
+
+        if !symbols.is_empty() {
+            let mut entries = vec![SymbolEntry {
+                name: 0,
+                info: SymbolInfo::from(0),
+                reserved: 0,
+                section_index: Index::Undefined,
+                value: Address(0),
+                size: 0,
+            }];
+            for (symbol, name) in symbols.iter().zip(symbol_names) {
+                let value = match &symbol.section_index {
+                    Index::Regular(index) if loaded => {
+                        address_of(*index)?.wrapping_add(symbol.value)
+                    }
+                    _ => Address(symbol.value),
+                };
+                entries.push(SymbolEntry {
+                    name: string_offsets[name],
+                    info: symbol.info.clone(),
+                    reserved: 0,
+                    section_index: symbol.section_index.clone(),
+                    value,
+                    size: symbol.size,
+                });
+            }
+            let symbol_table = self.symbol_table().0 - 1;
+            let mut data = vec![0; entries.len() * SymbolEntry::SIZE];
+            for (entry, slice) in entries.iter().zip(data.chunks_exact_mut(SymbolEntry::SIZE)) {
+                entry.encode(slice, self.encoding.clone())?;
+            }
+            sections[symbol_table].data = data;
+        }
+
+        let entry = match self.entry {
+            Some((index, offset)) if loaded => address_of(index)?.wrapping_add(offset),
+            _ => Address(0),
+        };
+        let section_number = sections.len() + 1;
+        let header = Header {
+            identifier: Identifier {
+                class: Class::_64,
+                encoding: self.encoding.clone(),
+                version: 1,
+                abi: self.abi.clone(),
+                abi_version: 0,
+                padding: [0; 7],
+            },
+            ty: self.ty.clone(),
+            machine: self.machine.clone(),
+            format_version: 1,
+            entry,
+            program_headers_offset: if program_headers.is_empty() {
+                Offset(0)
+            } else {
+                layout.program_headers_offset
+            },
+            section_headers_offset: layout.section_headers_offset,
+            flags: self.flags,
+            program_header_size: ProgramHeader::SIZE as u16,
+            section_header_size: SectionHeader::SIZE as u16,
+            program_header_number: program_headers.len() as u16,
+            section_header_number: section_number as u16,
+            section_names: Index::Regular(SectionIndex(sections.len())),
+        };
+
+        let size = layout.section_headers_offset.0 as usize + section_number * SectionHeader::SIZE;
+        let mut raw = vec![0; size];
+        header.encode(&mut raw)?;
+        let start = layout.program_headers_offset.0 as usize;
+        for (index, program) in program_headers.iter().enumerate() {
+            let offset = start + index * ProgramHeader::SIZE;
+            program.encode(&mut raw[offset..], self.encoding.clone())?;
+        }
+        let start = layout.section_headers_offset.0 as usize;
+        for ((index, section), placement) in sections.iter().enumerate().zip(&layout.placements) {
+            let offset = placement.offset.0 as usize;
+            raw[offset..(offset + section.data.len())].clone_from_slice(&section.data);
+            let header = SectionHeader {
+                name: name_offsets[section_names[index]],
+                ty: section.ty.clone(),
+                flags: section.flags,
+                address: placement.address,
+                offset: placement.offset,
+                size: section.size,
+                link: section.link.clone(),
+                info: section.info,
+                address_alignment: section.address_alignment,
+                number_of_entries: section.number_of_entries,
+            };
+            let offset = start + (index + 1) * SectionHeader::SIZE;
+            header.encode(&mut raw[offset..], self.encoding.clone())?;
+        }
+
+        Ok(raw)
+    }
+}
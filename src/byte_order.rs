@@ -0,0 +1,362 @@
+use alloc::vec::Vec;
+
+use super::{
+    Error, Offset, Encoding, Machine, Elf64, Entry, ProgramHeader, ProgramType, SectionHeader,
+    SectionType, SectionFlags, Structure,
+};
+use super::common::{file_slice, to_usize};
+use super::hash::SHT_GNU_HASH;
+use super::symbols::SHT_SYMTAB_SHNDX;
+use super::version::{SHT_GNU_VERDEF, SHT_GNU_VERNEED, SHT_GNU_VERSYM};
+
+const SHT_INIT_ARRAY: u32 = 0xe;
+const SHT_FINI_ARRAY: u32 = 0xf;
+const SHT_PREINIT_ARRAY: u32 = 0x10;
+const SHT_GROUP: u32 = 0x11;
+const SHT_RELR: u32 = 0x13;
+
+// the widths of the fields of the structures, in order
+const HEADER: [usize; 13] = [2, 2, 4, 8, 8, 8, 4, 2, 2, 2, 2, 2, 2];
+const PROGRAM_HEADER: [usize; 8] = [4, 4, 8, 8, 8, 8, 8, 8];
+const SECTION_HEADER: [usize; 10] = [4, 4, 8, 8, 8, 8, 4, 4, 8, 8];
+const SYMBOL: [usize; 6] = [4, 1, 1, 2, 8, 8];
+const REL: [usize; 2] = [8, 8];
+const RELA: [usize; 3] = [8, 8, 8];
+// `r_info` of MIPS64 is a 32 bit symbol and four bytes of the types
+const MIPS_REL: [usize; 6] = [8, 4, 1, 1, 1, 1];
+const MIPS_RELA: [usize; 7] = [8, 4, 1, 1, 1, 1, 8];
+const DYNAMIC: [usize; 2] = [8, 8];
+const COMPRESSION_HEADER: [usize; 4] = [4, 4, 8, 8];
+const VERDEF: [usize; 7] = [2, 2, 2, 2, 4, 4, 4];
+const VERDAUX: [usize; 2] = [4, 4];
+const VERNEED: [usize; 5] = [2, 2, 4, 4, 4];
+const VERNAUX: [usize; 5] = [4, 2, 2, 4, 4];
+const NOTE_HEADER: [usize; 3] = [4, 4, 4];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Unconverted {
+    // the content is not known to the parser, code, data, DWARF, it is copied as it is
+    Opaque,
+    // the headers of the notes are converted, the descriptions are copied as they are
+    NoteDescriptions,
+    // the structure is converted up to the error
+    Corrupted(Error),
+}
+
+// A section, or a segment of a file without sections, that is not fully converted
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnconvertedPart {
+    pub structure: Structure,
+    pub reason: Unconverted,
+}
+
+// The file in the opposite byte order and the parts of it that are not converted
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ByteOrderConversion {
+    pub raw: Vec<u8>,
+    pub unconverted: Vec<UnconvertedPart>,
+}
+
+// Reverses the bytes of the fields of the layout of every entry in the range,
+// `stride` bytes apart, the bytes of an incomplete last entry stay as they are
+fn swap(
+    raw: &mut [u8],
+    offset: u64,
+    size: u64,
+    layout: &[usize],
+    stride: usize,
+) -> Result<(), Error> {
+    let start = to_usize(offset)?;
+    let end = start
+        .checked_add(to_usize(size)?)
+        .ok_or(Error::ArithmeticOverflow)?;
+    let range = raw.get_mut(start..end).ok_or(Error::SliceTooShort)?;
+    let length: usize = layout.iter().sum();
+    let stride = stride.max(length);
+    for entry in range.chunks_mut(stride) {
+        if entry.len() < length {
+            break;
+        }
+        let mut position = 0;
+        for width in layout {
+            entry[position..(position + width)].reverse();
+            position += width;
+        }
+    }
+    Ok(())
+}
+
+// `sh_entsize` if it is larger than the entry
+fn stride(header: &SectionHeader) -> usize {
+    to_usize(header.number_of_entries).unwrap_or(0)
+}
+
+struct Converter<'e, 'a> {
+    elf: &'e Elf64<'a>,
+    encoding: Encoding,
+    raw: Vec<u8>,
+    unconverted: Vec<UnconvertedPart>,
+}
+
+impl<'e, 'a> Converter<'e, 'a> {
+    fn u16(&self, offset: u64) -> Result<u16, Error> {
+        Ok(read_int!(
+            file_slice(self.elf.raw, Offset(offset), 2)?,
+            &self.encoding,
+            u16
+        ))
+    }
+
+    fn u32(&self, offset: u64) -> Result<u32, Error> {
+        Ok(read_int!(
+            file_slice(self.elf.raw, Offset(offset), 4)?,
+            &self.encoding,
+            u32
+        ))
+    }
+
+    fn report(&mut self, structure: Structure, result: Result<Option<Unconverted>, Error>) {
+        let reason = match result {
+            Ok(None) => return,
+            Ok(Some(reason)) => reason,
+            Err(error) => Unconverted::Corrupted(error),
+        };
+        self.unconverted.push(UnconvertedPart { structure, reason });
+    }
+
+    fn section(&mut self, header: &SectionHeader) -> Result<Option<Unconverted>, Error> {
+        let (offset, size) = (header.offset.0, header.size);
+        let mips = self.elf.machine() == Machine::Mips;
+        if matches!(header.ty, SectionType::Null | SectionType::NoBits) {
+            return Ok(None);
+        }
+        // the range is in the file, the offsets in it do not overflow
+        file_slice(self.elf.raw, header.offset, size)?;
+        if header.flags.contains(SectionFlags::COMPRESSED) {
+            swap(&mut self.raw, offset, size.min(24), &COMPRESSION_HEADER, 24)?;
+            return Ok(Some(Unconverted::Opaque));
+        }
+        // an array of words, `sh_entsize` is the distance of the entries of a table only
+        let words = |width: usize| -> (&'static [usize], usize) {
+            match width {
+                2 => (&[2], 2),
+                4 => (&[4], 4),
+                _ => (&[8], 8),
+            }
+        };
+        let (layout, stride): (&[usize], usize) = match header.ty {
+            SectionType::StringTable => return Ok(None),
+            SectionType::SymbolTable | SectionType::DynamicSymbolTable => (&SYMBOL, stride(header)),
+            SectionType::Rel if mips => (&MIPS_REL, stride(header)),
+            SectionType::Rel => (&REL, stride(header)),
+            SectionType::Rela if mips => (&MIPS_RELA, stride(header)),
+            SectionType::Rela => (&RELA, stride(header)),
+            SectionType::Dynamic => (&DYNAMIC, stride(header)),
+            // the words are of 8 bytes on s390x and Alpha
+            SectionType::Hash if header.number_of_entries == 8 => words(8),
+            SectionType::Hash => words(4),
+            SectionType::Note => return self.notes(offset, size, header.address_alignment),
+            SectionType::Unknown(SHT_GROUP) | SectionType::Unknown(SHT_SYMTAB_SHNDX) => words(4),
+            SectionType::Unknown(SHT_INIT_ARRAY)
+            | SectionType::Unknown(SHT_FINI_ARRAY)
+            | SectionType::Unknown(SHT_PREINIT_ARRAY)
+            | SectionType::Unknown(SHT_RELR) => words(8),
+            SectionType::OsSpecific(SHT_GNU_VERSYM) => words(2),
+            SectionType::OsSpecific(SHT_GNU_HASH) => {
+                return self.gnu_hash(offset, size).map(|_| None)
+            }
+            SectionType::OsSpecific(SHT_GNU_VERDEF) => {
+                return self.verdef(offset, size, header.info).map(|_| None)
+            }
+            SectionType::OsSpecific(SHT_GNU_VERNEED) => {
+                return self.verneed(offset, size, header.info).map(|_| None)
+            }
+            _ => return Ok(Some(Unconverted::Opaque)),
+        };
+        swap(&mut self.raw, offset, size, layout, stride)?;
+        Ok(None)
+    }
+
+    fn notes(
+        &mut self,
+        offset: u64,
+        size: u64,
+        alignment: u64,
+    ) -> Result<Option<Unconverted>, Error> {
+        // relative to the start of the table
+        let align = |x: u64| match alignment {
+            8 => (x + 7) & !7,
+            _ => (x + 3) & !3,
+        };
+        let mut position = 0;
+        while position + 12 <= size {
+            let name_size = u64::from(self.u32(offset + position)?);
+            let description_size = u64::from(self.u32(offset + position + 4)?);
+            swap(&mut self.raw, offset + position, 12, &NOTE_HEADER, 12)?;
+            position = align(align(position + 12 + name_size) + description_size);
+        }
+        Ok(Some(Unconverted::NoteDescriptions))
+    }
+
+    // `nbuckets`, `symoffset`, `bloom_size` and `bloom_shift`, the bloom filter of 64 bit
+    // words, the buckets and the chains of 32 bit words
+    fn gnu_hash(&mut self, offset: u64, size: u64) -> Result<(), Error> {
+        let bloom = u64::from(self.u32(offset + 8)?) * 8;
+        if 16 + bloom > size {
+            return Err(Error::SliceTooShort);
+        }
+        swap(&mut self.raw, offset, 16, &[4], 4)?;
+        swap(&mut self.raw, offset + 16, bloom, &[8], 8)?;
+        swap(
+            &mut self.raw,
+            offset + 16 + bloom,
+            size - 16 - bloom,
+            &[4],
+            4,
+        )
+    }
+
+    // `vd_next` and `vda_next` link the entries, the sum stays in the section
+    fn verdef(&mut self, offset: u64, size: u64, number: u32) -> Result<(), Error> {
+        let end = offset.saturating_add(size);
+        let mut entry = offset;
+        for _ in 0..number {
+            if entry.saturating_add(20) > end {
+                return Err(Error::SliceTooShort);
+            }
+            let count = self.u16(entry + 6)?;
+            let aux = u64::from(self.u32(entry + 12)?);
+            let next = u64::from(self.u32(entry + 16)?);
+            swap(&mut self.raw, entry, 20, &VERDEF, 20)?;
+            let mut position = entry.saturating_add(aux);
+            for _ in 0..count {
+                if position.saturating_add(8) > end {
+                    return Err(Error::SliceTooShort);
+                }
+                let next = u64::from(self.u32(position + 4)?);
+                swap(&mut self.raw, position, 8, &VERDAUX, 8)?;
+                if next == 0 {
+                    break;
+                }
+                position = position.saturating_add(next);
+            }
+            if next == 0 {
+                break;
+            }
+            entry = entry.saturating_add(next);
+        }
+        Ok(())
+    }
+
+    fn verneed(&mut self, offset: u64, size: u64, number: u32) -> Result<(), Error> {
+        let end = offset.saturating_add(size);
+        let mut entry = offset;
+        for _ in 0..number {
+            if entry.saturating_add(16) > end {
+                return Err(Error::SliceTooShort);
+            }
+            let count = self.u16(entry + 2)?;
+            let aux = u64::from(self.u32(entry + 8)?);
+            let next = u64::from(self.u32(entry + 12)?);
+            swap(&mut self.raw, entry, 16, &VERNEED, 16)?;
+            let mut position = entry.saturating_add(aux);
+            for _ in 0..count {
+                if position.saturating_add(16) > end {
+                    return Err(Error::SliceTooShort);
+                }
+                let next = u64::from(self.u32(position + 12)?);
+                swap(&mut self.raw, position, 16, &VERNAUX, 16)?;
+                if next == 0 {
+                    break;
+                }
+                position = position.saturating_add(next);
+            }
+            if next == 0 {
+                break;
+            }
+            entry = entry.saturating_add(next);
+        }
+        Ok(())
+    }
+
+    // the segments of a file without sections, the same way
+    fn segment(&mut self, header: &ProgramHeader) -> Result<Option<Unconverted>, Error> {
+        let (offset, size) = (header.file_offset.0, header.file_size);
+        file_slice(self.elf.raw, header.file_offset, size)?;
+        match header.ty {
+            _ if size == 0 => Ok(None),
+            ProgramType::Dynamic => swap(&mut self.raw, offset, size, &DYNAMIC, 16).map(|_| None),
+            ProgramType::Note => self.notes(offset, size, header.address_alignment),
+            // the path of the interpreter
+            ProgramType::Interpreter => Ok(None),
+            _ => Ok(Some(Unconverted::Opaque)),
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Rewrites the file in the other byte order, `EI_DATA`, the headers and the tables
+    // of the sections of known types, the symbols, the relocations, the dynamic entries,
+    // the hash tables, the versions, the arrays of words and the headers of the notes.
+    // The offsets and the sizes stay, the file keeps its layout. The other sections,
+    // the code, the data, DWARF, and the content of the compressed sections, are copied
+    // as they are and listed in `unconverted`, and so is a table the conversion stopped
+    // in. A file without sections is converted by its segments.
+    pub fn to_opposite_byte_order(&self) -> Result<ByteOrderConversion, Error> {
+        let raw = match self.raw.contiguous() {
+            Some(raw) => raw.to_vec(),
+            None => self.serialize()?,
+        };
+        let mut converter = Converter {
+            elf: self,
+            encoding: self.encoding(),
+            raw,
+            unconverted: Vec::new(),
+        };
+
+        // `EI_DATA`, then the fields after `e_ident`
+        let data = converter.raw.get_mut(5).ok_or(Error::SliceTooShort)?;
+        *data = match self.encoding() {
+            Encoding::Little => 2,
+            Encoding::Big => 1,
+        };
+        swap(&mut converter.raw, 16, 48, &HEADER, 48)?;
+        let start = self.header.program_headers_offset.0;
+        let size = (self.program_number() * ProgramHeader::SIZE) as u64;
+        swap(
+            &mut converter.raw,
+            start,
+            size,
+            &PROGRAM_HEADER,
+            ProgramHeader::SIZE,
+        )?;
+        // the first section header holds the extended numbers, it is one of them
+        let start = self.header.section_headers_offset.0;
+        let size = (self.section_number() * SectionHeader::SIZE) as u64;
+        swap(
+            &mut converter.raw,
+            start,
+            size,
+            &SECTION_HEADER,
+            SectionHeader::SIZE,
+        )?;
+
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            let result = converter.section(&header);
+            converter.report(Structure::Section(index), result);
+        }
+        if self.section_number() == 0 {
+            for index in 0..self.program_number() {
+                let header = self.program_table.pick(index)?;
+                let result = converter.segment(&header);
+                converter.report(Structure::Segment(index), result);
+            }
+        }
+
+        Ok(ByteOrderConversion {
+            raw: converter.raw,
+            unconverted: converter.unconverted,
+        })
+    }
+}
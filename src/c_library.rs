@@ -0,0 +1,167 @@
+use core::cmp::Ordering;
+
+use super::{Error, Elf64, ProgramData};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CLibrary {
+    Glibc,
+    Musl,
+    Bionic,
+    Uclibc,
+    Unknown,
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+// the library by the path of the interpreter or the name of a needed library
+fn by_file(name: &[u8]) -> Option<CLibrary> {
+    if contains(name, b"ld-musl-") || contains(name, b"libc.musl-") {
+        Some(CLibrary::Musl)
+    } else if contains(name, b"uClibc") {
+        Some(CLibrary::Uclibc)
+    } else if name.starts_with(b"/system/bin/linker") || name.starts_with(b"/apex/") {
+        Some(CLibrary::Bionic)
+    } else if contains(name, b"ld-linux") || name == b"libc.so.6" {
+        Some(CLibrary::Glibc)
+    } else {
+        None
+    }
+}
+
+// symbols only one of the libraries has, for static files
+fn by_symbol(name: &[u8]) -> Option<CLibrary> {
+    match name {
+        b"__libc_setup_tls" | b"_dl_relocate_static_pie" | b"__libc_csu_init" => {
+            Some(CLibrary::Glibc)
+        }
+        b"__init_libc" | b"__libc_start_init" => Some(CLibrary::Musl),
+        b"__libc_init" | b"__libc_preinit" => Some(CLibrary::Bionic),
+        b"__uClibc_main" => Some(CLibrary::Uclibc),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MaximumVersions<'a> {
+    // the whole version name, e.g. `GLIBC_2.34`
+    pub glibc: Option<&'a [u8]>,
+    pub glibcxx: Option<&'a [u8]>,
+    pub cxxabi: Option<&'a [u8]>,
+}
+
+// `GLIBC_2.34` is `GLIBC` and `2.34`, `None` for `GLIBC_PRIVATE`
+fn split(name: &[u8]) -> Option<(&[u8], &[u8])> {
+    let position = name.iter().rposition(|&c| c == b'_')?;
+    let (prefix, numbers) = (&name[..position], &name[(position + 1)..]);
+    let valid = !numbers.is_empty()
+        && numbers
+            .split(|&c| c == b'.')
+            .all(|n| !n.is_empty() && n.iter().all(u8::is_ascii_digit));
+    if valid {
+        Some((prefix, numbers))
+    } else {
+        None
+    }
+}
+
+fn number(digits: &[u8]) -> u64 {
+    digits.iter().fold(0u64, |n, &d| {
+        n.saturating_mul(10).saturating_add((d - b'0') as u64)
+    })
+}
+
+// Compares names like `GLIBC_2.3.4` and `GLIBC_2.34` by the numbers, missing numbers are zero.
+// `None` if the prefixes differ or a name has no numbers.
+pub fn compare_versions(a: &[u8], b: &[u8]) -> Option<Ordering> {
+    let (a_prefix, a) = split(a)?;
+    let (b_prefix, b) = split(b)?;
+    if a_prefix != b_prefix {
+        return None;
+    }
+    let mut a = a.split(|&c| c == b'.').map(number);
+    let mut b = b.split(|&c| c == b'.').map(number);
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return Some(Ordering::Equal),
+            (x, y) => match x.unwrap_or(0).cmp(&y.unwrap_or(0)) {
+                Ordering::Equal => (),
+                ordering => return Some(ordering),
+            },
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The newest versions of the C and C++ runtime the file requires, the oldest
+    // system it runs on has at least these.
+    pub fn maximum_versions(&self) -> Result<MaximumVersions<'a>, Error> {
+        let mut maximum = MaximumVersions::default();
+        self.versions()?.find_requirement(|_, requirement| {
+            let slot = match split(requirement.name) {
+                Some((b"GLIBC", _)) => &mut maximum.glibc,
+                Some((b"GLIBCXX", _)) => &mut maximum.glibcxx,
+                Some((b"CXXABI", _)) => &mut maximum.cxxabi,
+                _ => return false,
+            };
+            let newer = match slot {
+                Some(current) => {
+                    compare_versions(current, requirement.name) == Some(Ordering::Less)
+                }
+                None => true,
+            };
+            if newer {
+                *slot = Some(requirement.name);
+            }
+            false
+        })?;
+        Ok(maximum)
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Guesses the C library the file is built against, by the interpreter,
+    // then by the needed libraries, the required versions and the symbols.
+    pub fn c_library(&self) -> Result<CLibrary, Error> {
+        for index in 0..self.program_number() {
+            if let Some(ProgramData::Interpreter(path)) = self.program(index)?.map(|p| p.data) {
+                let path = match path.iter().position(|&c| c == 0) {
+                    Some(end) => &path[..end],
+                    None => path,
+                };
+                if let Some(library) = by_file(path) {
+                    return Ok(library);
+                }
+            }
+        }
+
+        for name in self.needed()? {
+            if let Some(library) = by_file(name?) {
+                return Ok(library);
+            }
+        }
+
+        let versions = self.versions()?;
+        if versions
+            .find_requirement(|_, r| r.name.starts_with(b"GLIBC_"))?
+            .is_some()
+        {
+            return Ok(CLibrary::Glibc);
+        }
+        if versions
+            .find_requirement(|_, r| r.file == b"libc.so" && r.name.starts_with(b"LIBC"))?
+            .is_some()
+        {
+            return Ok(CLibrary::Bionic);
+        }
+
+        for symbol in self.merged_symbols()? {
+            if let Some(library) = by_symbol(symbol?.name) {
+                return Ok(library);
+            }
+        }
+
+        Ok(CLibrary::Unknown)
+    }
+}
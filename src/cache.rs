@@ -0,0 +1,165 @@
+use core::cell::RefCell;
+use alloc::vec::Vec;
+
+use super::{Error, Entry, Table, StringTable};
+use super::nul::find_nul;
+
+// A table that decodes each entry once, e.g. the symbols the relocations
+// refer to over and over, the errors are not kept
+pub struct CachedTable<'a, E>
+where
+    E: Entry,
+{
+    table: Table<'a, E>,
+    entries: RefCell<Vec<Option<E>>>,
+}
+
+impl<'a, E> CachedTable<'a, E>
+where
+    E: Entry<Error = Error> + Clone,
+{
+    pub fn new(table: Table<'a, E>) -> Self {
+        CachedTable {
+            table,
+            entries: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn pick(&self, index: usize) -> Result<E, Error> {
+        if let Some(Some(entry)) = self.entries.borrow().get(index) {
+            return Ok(entry.clone());
+        }
+        let entry = self.table.pick(index)?;
+        let mut entries = self.entries.borrow_mut();
+        if entries.len() <= index {
+            entries.resize(index + 1, None);
+        }
+        entries[index] = Some(entry.clone());
+        Ok(entry)
+    }
+
+    pub fn table(&self) -> &Table<'a, E> {
+        &self.table
+    }
+}
+
+// A string table that keeps the strings it found in a fixed number of slots
+// by the hash of the offset, a slot holds the last string of its hash, so the
+// memory is bounded, and a string another one took the slot of is searched
+// again. A map of every offset is slower than the scan for the usual names.
+pub struct CachedStringTable<'a> {
+    table: StringTable<'a>,
+    strings: RefCell<Vec<Slot<'a>>>,
+}
+
+// the offset and the string
+type Slot<'a> = Option<(usize, &'a [u8])>;
+
+// a power of two, `SLOTS.trailing_zeros()` bits of the hash
+const SLOTS: usize = 0x4000;
+
+impl<'a> CachedStringTable<'a> {
+    pub fn new(table: StringTable<'a>) -> Self {
+        CachedStringTable {
+            table,
+            strings: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn pick(&self, index: usize) -> Result<&'a [u8], Error> {
+        // Fibonacci hashing, the neighbouring offsets get distant slots
+        let hash = (index as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let slot = (hash >> (64 - SLOTS.trailing_zeros())) as usize;
+        let mut strings = self.strings.borrow_mut();
+        // the slots are allocated at the first string
+        if strings.is_empty() {
+            strings.resize(SLOTS, None);
+        }
+        if let Some((cached, string)) = strings[slot] {
+            if cached == index {
+                return Ok(string);
+            }
+        }
+        let string = self.table.pick(index)?;
+        strings[slot] = Some((index, string));
+        Ok(string)
+    }
+
+    pub fn table(&self) -> &StringTable<'a> {
+        &self.table
+    }
+}
+
+// A string table with the position of every terminator found in one pass, a string
+// is a binary search away instead of a scan, e.g. for the names of a huge `.symtab`.
+// The strings are the ones `StringTable::pick` gives.
+#[derive(Clone)]
+pub struct IndexedStringTable<'a> {
+    table: StringTable<'a>,
+    ends: Vec<usize>,
+}
+
+impl<'a> IndexedStringTable<'a> {
+    pub fn new(table: StringTable<'a>) -> Self {
+        let slice = table.as_raw();
+        let mut ends = Vec::new();
+        let mut position = 0;
+        while let Some(length) = find_nul(&slice[position..]) {
+            ends.push(position + length);
+            position += length + 1;
+        }
+        IndexedStringTable { table, ends }
+    }
+
+    pub fn pick(&self, index: usize) -> Result<&'a [u8], Error> {
+        let tail = self
+            .table
+            .as_raw()
+            .get(index..)
+            .ok_or(Error::SliceTooShort)?;
+        let next = self.ends.partition_point(|&end| end < index);
+        let length = match self.ends.get(next) {
+            Some(&end) => end - index,
+            None => return Err(Error::UnterminatedString),
+        };
+        Ok(&tail[..length])
+    }
+
+    pub fn table(&self) -> &StringTable<'a> {
+        &self.table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{CachedStringTable, IndexedStringTable, SLOTS};
+    use crate::StringTable;
+
+    #[test]
+    fn strings_of_shared_slots() {
+        // more strings than slots, some of them share a slot
+        let mut slice = Vec::new();
+        let mut offsets = Vec::new();
+        for index in 0..SLOTS * 2 {
+            offsets.push(slice.len());
+            slice.extend_from_slice(&index.to_le_bytes()[..2]);
+            slice.extend_from_slice(b"name\0");
+        }
+        let table = StringTable::new(&slice);
+        let (cached, indexed) = (
+            CachedStringTable::new(table.clone()),
+            IndexedStringTable::new(table.clone()),
+        );
+        for _ in 0..2 {
+            for &offset in offsets.iter().rev() {
+                let expected = table.pick(offset);
+                assert_eq!(cached.pick(offset), expected);
+                assert_eq!(indexed.pick(offset), expected);
+                // the tail of a string
+                assert_eq!(cached.pick(offset + 2), Ok(&b"name"[..]));
+            }
+        }
+    }
+}
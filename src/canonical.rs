@@ -0,0 +1,265 @@
+use alloc::vec::Vec;
+
+use super::{
+    Error, Address, Elf64, Class, Encoding, Abi, Type, Machine, ProgramHeader, SectionType,
+    SectionFlags, SectionIndex, Index, SymbolInfo, SymbolTable, DynamicTag,
+};
+
+// A section by its name, the index of a section depends on the order of the table,
+// the link is the name of the linked section as well
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CanonicalSection<'a> {
+    pub name: &'a [u8],
+    pub ty: SectionType,
+    pub flags: SectionFlags,
+    pub address: Address,
+    pub size: u64,
+    pub address_alignment: u64,
+    pub entry_size: u64,
+    pub link: Option<&'a [u8]>,
+}
+
+// The value of a dynamic entry, the string rather than its offset in `DT_STRTAB`
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CanonicalValue<'a> {
+    Number(u64),
+    String(&'a [u8]),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CanonicalDynamic<'a> {
+    pub tag: DynamicTag,
+    pub value: CanonicalValue<'a>,
+}
+
+// The section of a symbol, a regular or an extended index is the name of the section
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CanonicalIndex<'a> {
+    Section(&'a [u8]),
+    // `SHN_UNDEF`, `SHN_ABS`, `SHN_COMMON` and the reserved ones, or an index
+    // past the section table
+    Special(Index),
+}
+
+// A symbol without `st_name`, the offset of the name in the string table
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CanonicalSymbol<'a> {
+    pub name: &'a [u8],
+    pub info: SymbolInfo,
+    pub reserved: u8,
+    pub section: CanonicalIndex<'a>,
+    pub value: Address,
+    pub size: u64,
+}
+
+// The metadata of the file in an order that does not depend on the layout of
+// the file, the same content gives the same `Debug` or `serde` output whatever
+// the order of the tables is. The program headers, the sections and the symbols
+// are sorted by all their fields, by the type of a program header and by the name
+// of a section or of a symbol first. The dynamic entries are sorted by the tag,
+// the entries of one tag keep their order, that of `DT_NEEDED` is the lookup order.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CanonicalDump<'a> {
+    pub class: Class,
+    pub encoding: Encoding,
+    pub abi: Abi,
+    pub abi_version: u8,
+    pub ty: Type,
+    pub machine: Machine,
+    pub entry: Address,
+    pub flags: u32,
+    pub programs: Vec<ProgramHeader>,
+    pub sections: Vec<CanonicalSection<'a>>,
+    pub symbols: Vec<CanonicalSymbol<'a>>,
+    pub dynamic_symbols: Vec<CanonicalSymbol<'a>>,
+    pub dynamic: Vec<CanonicalDynamic<'a>>,
+}
+
+impl<'a> Elf64<'a> {
+    // the name of the section, empty for an index past the table
+    fn canonical_name(&self, index: usize) -> Result<&'a [u8], Error> {
+        Ok(self
+            .section_info(SectionIndex(index))?
+            .map_or(&[][..], |info| info.name))
+    }
+
+    // the symbols of the table, sorted
+    fn canonical_symbols(
+        &self,
+        table: Option<SymbolTable<'a>>,
+    ) -> Result<Vec<CanonicalSymbol<'a>>, Error> {
+        let table = match table {
+            Some(table) => table.index,
+            None => return Ok(Vec::new()),
+        };
+        let mut symbols = Vec::new();
+        // the symbol zero is skipped, the first one is at the index one
+        for (position, symbol) in self.symbols_of(table)?.enumerate() {
+            let symbol = symbol?;
+            let section = match self.symbol_section_index(table, position + 1)? {
+                Index::Regular(index) if index.0 < self.section_number() => {
+                    CanonicalIndex::Section(self.canonical_name(index.0)?)
+                }
+                index => CanonicalIndex::Special(index),
+            };
+            symbols.push(CanonicalSymbol {
+                name: symbol.name,
+                info: symbol.entry.info,
+                reserved: symbol.entry.reserved,
+                section,
+                value: symbol.entry.value,
+                size: symbol.entry.size,
+            });
+        }
+        symbols.sort();
+        Ok(symbols)
+    }
+
+    pub fn canonical_dump(&self) -> Result<CanonicalDump<'a>, Error> {
+        // no capacity up front, the numbers of a crafted header are not the tables
+        let mut programs = Vec::new();
+        for index in 0..self.program_number() {
+            programs.push(self.program_table.pick(index)?);
+        }
+        programs.sort();
+
+        let name = |index: usize| self.canonical_name(index);
+        let mut sections = Vec::new();
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            let link = match header.link {
+                Index::Regular(link) if link.0 < self.section_number() => Some(name(link.0)?),
+                _ => None,
+            };
+            sections.push(CanonicalSection {
+                name: name(index)?,
+                ty: header.ty,
+                flags: header.flags,
+                address: header.address,
+                size: header.size,
+                address_alignment: header.address_alignment,
+                entry_size: header.number_of_entries,
+                link,
+            });
+        }
+        sections.sort();
+
+        let mut dynamic = Vec::new();
+        if let Some(source) = self.dynamic_source()? {
+            for entry in source.entries() {
+                let entry = entry?;
+                // an offset out of the string table stays a number
+                let value = match source.entry_string(&entry) {
+                    Ok(Some(string)) => CanonicalValue::String(string),
+                    _ => CanonicalValue::Number(entry.value),
+                };
+                dynamic.push(CanonicalDynamic {
+                    tag: entry.tag,
+                    value,
+                });
+            }
+        }
+        dynamic.sort_by_key(|entry| u64::from(entry.tag.clone()));
+
+        Ok(CanonicalDump {
+            class: self.class(),
+            encoding: self.encoding(),
+            abi: self.abi(),
+            abi_version: self.abi_version(),
+            ty: self.ty(),
+            machine: self.machine(),
+            entry: self.entry(),
+            flags: self.flags(),
+            programs,
+            sections,
+            symbols: self.canonical_symbols(self.symbol_table()?)?,
+            dynamic_symbols: self.canonical_symbols(self.dynamic_symbol_table()?)?,
+            dynamic,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::CanonicalIndex;
+    use crate::{
+        Elf64, Encoding, Type, Machine, ElfBuilder, Index, SectionType, SectionFlags, SymbolInfo,
+        SymbolBinding, SymbolType,
+    };
+
+    // `.text` and `.data` in the order, the symbols are added in the order as well
+    fn object(text_first: bool) -> Vec<u8> {
+        let mut builder = ElfBuilder::new(Encoding::Little, Type::Relocatable, Machine::X86_64);
+        let add = |builder: &mut ElfBuilder, name: &[u8]| {
+            builder.add_section(
+                name,
+                SectionType::ProgramBits,
+                SectionFlags::ALLOC,
+                8,
+                vec![0; 8],
+            )
+        };
+        let (text, data) = if text_first {
+            let text = add(&mut builder, b".text");
+            (text, add(&mut builder, b".data"))
+        } else {
+            let data = add(&mut builder, b".data");
+            (add(&mut builder, b".text"), data)
+        };
+        let info = SymbolInfo {
+            binding: SymbolBinding::Global,
+            ty: SymbolType::Object,
+        };
+        let symbols = [(&b"main"[..], text), (&b"counter"[..], data)];
+        for i in 0..2 {
+            let (name, section) = symbols[if text_first { i } else { 1 - i }];
+            builder.add_symbol(name, info.clone(), Index::Regular(section), 4, 4);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn layout_independent() {
+        let (a, b) = (object(true), object(false));
+        assert_ne!(a, b);
+        let a = Elf64::new(&a).unwrap().canonical_dump().unwrap();
+        let b = Elf64::new(&b).unwrap().canonical_dump().unwrap();
+        assert_eq!(a, b);
+        let sections = a
+            .symbols
+            .iter()
+            .map(|symbol| (symbol.name, symbol.section.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sections,
+            [
+                (&b"counter"[..], CanonicalIndex::Section(b".data")),
+                (&b"main"[..], CanonicalIndex::Section(b".text")),
+            ]
+        );
+    }
+
+    #[test]
+    fn crafted_numbers() {
+        let mut raw = object(true);
+        let elf = Elf64::new(&raw).unwrap();
+        let zero = elf.header.section_headers_offset.0 as usize;
+        // `e_shnum` zero, the count is `sh_size` of the section zero
+        raw[0x3c..0x3e].copy_from_slice(&[0, 0]);
+        raw[zero + 0x20..zero + 0x28].copy_from_slice(&u64::MAX.to_le_bytes());
+        // `e_phnum` of the tables past the end of the file
+        raw[0x20..0x28].copy_from_slice(&0x40u64.to_le_bytes());
+        raw[0x38..0x3a].copy_from_slice(&0xfffeu16.to_le_bytes());
+        if let Ok(elf) = Elf64::new(&raw) {
+            let _ = elf.canonical_dump();
+        }
+    }
+}
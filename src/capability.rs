@@ -0,0 +1,62 @@
+use super::{Error, Elf64, SectionType};
+
+bitflags! {
+    // What the file has, a stripped file lacks some of it and the queries that
+    // need it return `None` or `Error::NotPresent`
+    pub struct Capabilities: u32 {
+        const PROGRAM_HEADERS = 0b00000001;
+        const SECTION_HEADERS = 0b00000010;
+        const SECTION_NAMES = 0b00000100;
+        const SYMBOL_TABLE = 0b00001000;
+        const DYNAMIC_SYMBOL_TABLE = 0b00010000;
+        // from `SHT_DYNAMIC` or `PT_DYNAMIC`
+        const DYNAMIC = 0b00100000;
+        const INTERPRETER = 0b01000000;
+        const NOTES = 0b10000000;
+    }
+}
+
+impl<'a> Elf64<'a> {
+    pub fn capabilities(&self) -> Result<Capabilities, Error> {
+        let mut capabilities = Capabilities::empty();
+        capabilities.set(Capabilities::PROGRAM_HEADERS, self.program_number() != 0);
+        capabilities.set(Capabilities::SECTION_HEADERS, self.section_number() != 0);
+        capabilities.set(Capabilities::SECTION_NAMES, self.names.is_some());
+        for index in 0..self.section_number() {
+            match self.section_table.pick(index)?.ty {
+                SectionType::SymbolTable => capabilities.insert(Capabilities::SYMBOL_TABLE),
+                SectionType::DynamicSymbolTable => {
+                    capabilities.insert(Capabilities::DYNAMIC_SYMBOL_TABLE)
+                }
+                _ => (),
+            }
+        }
+        capabilities.set(Capabilities::DYNAMIC, self.dynamic_source()?.is_some());
+        let interpreter = self.program_number() != 0 && self.interpreter()?.is_some();
+        capabilities.set(Capabilities::INTERPRETER, interpreter);
+        capabilities.set(Capabilities::NOTES, self.notes().next().is_some());
+        Ok(capabilities)
+    }
+
+    // `Error::NotPresent` unless the file has all of them, the segment based queries
+    // of a relocatable file fail this way instead of reading a table at offset zero
+    pub fn require(&self, capabilities: Capabilities) -> Result<(), Error> {
+        let cheap = Capabilities::PROGRAM_HEADERS
+            | Capabilities::SECTION_HEADERS
+            | Capabilities::SECTION_NAMES;
+        let present = if cheap.contains(capabilities) {
+            let mut present = Capabilities::empty();
+            present.set(Capabilities::PROGRAM_HEADERS, self.program_number() != 0);
+            present.set(Capabilities::SECTION_HEADERS, self.section_number() != 0);
+            present.set(Capabilities::SECTION_NAMES, self.names.is_some());
+            present
+        } else {
+            self.capabilities()?
+        };
+        if present.contains(capabilities) {
+            Ok(())
+        } else {
+            Err(Error::NotPresent)
+        }
+    }
+}
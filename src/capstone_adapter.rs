@@ -0,0 +1,86 @@
+use capstone::{Arch, Capstone, Endian, ExtraMode, Insn, Mode};
+
+use super::{Error, Encoding, Machine, Elf64, CodeRange, CodeConsumer};
+
+// `EF_MIPS_ARCH_ASE_MICROMIPS`
+const EF_MIPS_MICROMIPS: u32 = 0x02000000;
+
+struct Disassembler<'c, F> {
+    capstone: &'c Capstone,
+    f: F,
+    error: Option<Error>,
+}
+
+impl<'c, F> CodeConsumer for Disassembler<'c, F>
+where
+    F: FnMut(&Insn<'_>),
+{
+    fn consume(&mut self, range: CodeRange<'_>) {
+        if self.error.is_some() {
+            return;
+        }
+        match self.capstone.disasm_iter(range.code, range.address.0) {
+            Ok(mut instructions) => {
+                while let Some(instruction) = instructions.next() {
+                    (self.f)(&instruction);
+                }
+            }
+            Err(_) => self.error = Some(Error::UnsupportedMachine),
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Capstone configured for the machine, the byte order and the flags of the file.
+    // ARM code is Thumb if the entry point is odd.
+    pub fn capstone(&self) -> Result<Capstone, Error> {
+        let endian = match self.encoding() {
+            Encoding::Little => Endian::Little,
+            Encoding::Big => Endian::Big,
+        };
+        let flags = self.flags();
+        let mut extra = None;
+        let (arch, mode) = match self.machine() {
+            Machine::X86 => (Arch::X86, Mode::Mode32),
+            Machine::X86_64 => (Arch::X86, Mode::Mode64),
+            Machine::Arm if self.entry().0 & 1 != 0 => (Arch::ARM, Mode::Thumb),
+            Machine::Arm => (Arch::ARM, Mode::Arm),
+            Machine::AArch64 => (Arch::ARM64, Mode::Arm),
+            Machine::Mips => {
+                if flags & EF_MIPS_MICROMIPS != 0 {
+                    extra = Some(ExtraMode::Micro);
+                }
+                (Arch::MIPS, Mode::Mips64)
+            }
+            Machine::PowerPC | Machine::PowerPC64 => (Arch::PPC, Mode::Mode64),
+            Machine::Sparc => (Arch::SPARC, Mode::V9),
+            Machine::Bpf => (Arch::BPF, Mode::Ebpf),
+            _ => return Err(Error::UnsupportedMachine),
+        };
+        let endian = match arch {
+            Arch::X86 => None,
+            _ => Some(endian),
+        };
+        Capstone::new_raw(arch, mode, extra.into_iter(), endian)
+            .map_err(|_| Error::UnsupportedMachine)
+    }
+
+    // Disassembles the code `feed_code` finds, in each range until the first
+    // bytes that are not an instruction.
+    pub fn disassemble<F>(&self, f: F) -> Result<(), Error>
+    where
+        F: FnMut(&Insn<'_>),
+    {
+        let capstone = self.capstone()?;
+        let mut disassembler = Disassembler {
+            capstone: &capstone,
+            f,
+            error: None,
+        };
+        self.feed_code(&mut disassembler)?;
+        match disassembler.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use super::{Error, Elf64, Machine, Relocation};
+
+// `EF_AARCH64_CHERI_PURECAP`
+const EF_AARCH64_CHERI_PURECAP: u32 = 0x00010000;
+// `EF_RISCV_CHERIABI`
+const EF_RISCV_CHERIABI: u32 = 0x00010000;
+// `EF_RISCV_CAP_MODE`
+const EF_RISCV_CAP_MODE: u32 = 0x00020000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum CheriPlatform {
+    // `EM_AARCH64`
+    Morello,
+    // `EM_RISCV`
+    RiscV,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum CheriAbi {
+    // every pointer is a capability, the flag is in `e_flags`
+    PureCapability,
+    // the usual ABI, some pointers are capabilities, only the relocations tell
+    Hybrid,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Cheri {
+    pub platform: CheriPlatform,
+    pub abi: CheriAbi,
+    // `EF_RISCV_CAP_MODE`, the code starts in the capability mode
+    pub capability_mode: bool,
+}
+
+// A relocation that makes a capability, not just an address
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum CapabilityRelocation {
+    // `R_MORELLO_CAPINIT`, `R_RISCV_CHERI_CAPABILITY`, a capability to the symbol
+    Init,
+    // `R_MORELLO_GLOB_DAT`
+    GlobalData,
+    // `R_MORELLO_JUMP_SLOT`
+    JumpSlot,
+    // `R_MORELLO_RELATIVE`, the fragment at the place has the bounds and the permissions
+    Relative,
+    // `R_MORELLO_IRELATIVE`
+    IndirectRelative,
+    // `R_MORELLO_TLSDESC`
+    TlsDescriptor,
+}
+
+impl CapabilityRelocation {
+    pub fn new(machine: Machine, ty: u32) -> Option<Self> {
+        Some(match (machine, ty) {
+            (Machine::AArch64, 0xe800) => CapabilityRelocation::Init,
+            (Machine::AArch64, 0xe801) => CapabilityRelocation::GlobalData,
+            (Machine::AArch64, 0xe802) => CapabilityRelocation::JumpSlot,
+            (Machine::AArch64, 0xe803) => CapabilityRelocation::Relative,
+            (Machine::AArch64, 0xe804) => CapabilityRelocation::IndirectRelative,
+            (Machine::AArch64, 0xe805) => CapabilityRelocation::TlsDescriptor,
+            (Machine::RiscV, 193) => CapabilityRelocation::Init,
+            _ => return None,
+        })
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The CHERI ABI of a Morello or a CHERI-RISC-V file, `None` for any other file,
+    // a hybrid file is recognized by its capability relocations
+    pub fn cheri(&self) -> Result<Option<Cheri>, Error> {
+        let (platform, purecap) = match self.machine() {
+            Machine::AArch64 => (
+                CheriPlatform::Morello,
+                self.flags() & EF_AARCH64_CHERI_PURECAP != 0,
+            ),
+            Machine::RiscV => (CheriPlatform::RiscV, self.flags() & EF_RISCV_CHERIABI != 0),
+            _ => return Ok(None),
+        };
+        let capability_mode =
+            platform == CheriPlatform::RiscV && self.flags() & EF_RISCV_CAP_MODE != 0;
+        let abi = if purecap {
+            CheriAbi::PureCapability
+        } else {
+            match self.capability_relocations().next() {
+                Some(Ok(_)) => CheriAbi::Hybrid,
+                Some(Err(error)) => return Err(error),
+                None => return Ok(None),
+            }
+        };
+        Ok(Some(Cheri {
+            platform,
+            abi,
+            capability_mode,
+        }))
+    }
+
+    // The relocations that make capabilities, with the index of the relocation section
+    pub fn capability_relocations(
+        &self,
+    ) -> impl Iterator<Item = Result<(usize, Relocation, CapabilityRelocation), Error>> + 'a {
+        let machine = self.machine();
+        self.relocations().filter_map(move |relocation| {
+            let (section, relocation) = match relocation {
+                Ok(relocation) => relocation,
+                Err(error) => return Some(Err(error)),
+            };
+            let ty = relocation.relocation_type()?;
+            CapabilityRelocation::new(machine.clone(), ty)
+                .map(|kind| Ok((section, relocation, kind)))
+        })
+    }
+}
@@ -0,0 +1,161 @@
+use alloc::vec::Vec;
+
+use super::{Error, Address, Offset, Elf64, Type, ProgramType, ProgramFlags};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Image {
+    Executable,
+    // of `PT_INTERP`
+    Interpreter,
+}
+
+// A `PT_LOAD` at its address, widened to the pages, the loader maps `file_size`
+// bytes of the file at the address and zeroes the rest up to `memory_size`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoadCommand {
+    pub image: Image,
+    pub program: usize,
+    pub flags: ProgramFlags,
+    pub address: Address,
+    pub memory_size: u64,
+    pub file_offset: Offset,
+    pub file_size: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProcessImage {
+    // zero for `ET_EXEC`
+    pub executable_bias: Address,
+    // `AT_BASE` of the usual interpreter, it is linked at zero
+    pub interpreter_bias: Address,
+    // of the executable, then of the interpreter, each in the order of its program headers
+    pub commands: Vec<LoadCommand>,
+    // `e_entry` of the interpreter, where the control goes first
+    pub entry: Address,
+}
+
+fn align_down(x: u64, alignment: u64) -> u64 {
+    x & !(alignment - 1)
+}
+
+fn align_up(x: u64, alignment: u64) -> Result<u64, Error> {
+    x.checked_add(alignment - 1)
+        .map(|x| align_down(x, alignment))
+        .ok_or(Error::ArithmeticOverflow)
+}
+
+// the pages `PT_LOAD` segments span and the largest alignment of them
+fn extent(elf: &Elf64<'_>, page_size: u64) -> Result<(u64, u64, u64), Error> {
+    let (mut start, mut end, mut alignment) = (u64::MAX, 0, page_size);
+    for header in elf.load_segments()? {
+        let header = header?;
+        let address = header.virtual_address.0;
+        let last = address
+            .checked_add(header.memory_size)
+            .ok_or(Error::ArithmeticOverflow)?;
+        start = start.min(align_down(address, page_size));
+        end = end.max(align_up(last, page_size)?);
+        if header.address_alignment.is_power_of_two() {
+            alignment = alignment.max(header.address_alignment);
+        }
+    }
+    if start > end {
+        return Err(Error::NotPresent);
+    }
+    Ok((start, end, alignment))
+}
+
+fn commands(
+    elf: &Elf64<'_>,
+    image: Image,
+    bias: Address,
+    page_size: u64,
+    commands: &mut Vec<LoadCommand>,
+) -> Result<(), Error> {
+    for (program, header) in elf.program_headers().enumerate() {
+        let header = header?;
+        if header.ty != ProgramType::Load {
+            continue;
+        }
+        let address = header.virtual_address.0;
+        let delta = address - align_down(address, page_size);
+        let offset = header
+            .file_offset
+            .0
+            .checked_sub(delta)
+            .ok_or(Error::ArithmeticOverflow)?;
+        let memory_size = align_up(header.memory_size.saturating_add(delta), page_size)?;
+        commands.push(LoadCommand {
+            image,
+            program,
+            flags: header.flags,
+            address: bias.wrapping_add(address - delta),
+            memory_size,
+            file_offset: Offset(offset),
+            file_size: header.file_size.saturating_add(delta),
+        });
+    }
+    Ok(())
+}
+
+impl<'a> Elf64<'a> {
+    // Places the executable and the interpreter of its `PT_INTERP` the way a kernel
+    // starts a dynamic executable. An `ET_DYN` executable goes to the base, the `ET_DYN`
+    // interpreter goes past the end of the executable, both aligned to their segments.
+    // `Error::OverlappingSegments` if two `ET_EXEC` images, or an `ET_EXEC` and the base,
+    // collide.
+    pub fn load_with_interpreter(
+        &self,
+        interpreter: &Elf64<'_>,
+        base: Address,
+        page_size: u64,
+    ) -> Result<ProcessImage, Error> {
+        if !page_size.is_power_of_two() {
+            return Err(Error::BadAlignment(page_size));
+        }
+        let bias = |elf: &Elf64<'_>, at: u64, start: u64, alignment: u64| match elf.ty() {
+            Type::Executable => Ok(Address(0)),
+            Type::SharedObject => Ok(Address(align_up(at, alignment)?.wrapping_sub(start))),
+            _ => Err(Error::UnexpectedFileType),
+        };
+
+        let (start, end, alignment) = extent(self, page_size)?;
+        let executable_bias = bias(self, base.0, start, alignment)?;
+        let (executable_start, executable_end) = (
+            executable_bias.wrapping_add(start).0,
+            executable_bias.wrapping_add(end).0,
+        );
+
+        let (start, end, alignment) = extent(interpreter, page_size)?;
+        let interpreter_bias = bias(interpreter, executable_end, start, alignment)?;
+        let (interpreter_start, interpreter_end) = (
+            interpreter_bias.wrapping_add(start).0,
+            interpreter_bias.wrapping_add(end).0,
+        );
+        if executable_start < interpreter_end && interpreter_start < executable_end {
+            return Err(Error::OverlappingSegments);
+        }
+
+        let mut image = ProcessImage {
+            executable_bias,
+            interpreter_bias,
+            commands: Vec::new(),
+            entry: interpreter_bias.wrapping_add(interpreter.entry().0),
+        };
+        commands(
+            self,
+            Image::Executable,
+            executable_bias,
+            page_size,
+            &mut image.commands,
+        )?;
+        commands(
+            interpreter,
+            Image::Interpreter,
+            interpreter_bias,
+            page_size,
+            &mut image.commands,
+        )?;
+        Ok(image)
+    }
+}
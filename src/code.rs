@@ -0,0 +1,65 @@
+use super::{
+    Error, Address, Encoding, Machine, Elf64, SectionType, SectionFlags, ProgramType, ProgramFlags,
+};
+use super::common::file_slice;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CodeRange<'a> {
+    pub machine: Machine,
+    pub encoding: Encoding,
+    // `e_flags`, selects the instruction set on some machines
+    pub flags: u32,
+    pub address: Address,
+    pub code: &'a [u8],
+}
+
+pub trait CodeConsumer {
+    fn consume(&mut self, range: CodeRange<'_>);
+}
+
+impl<'a> Elf64<'a> {
+    // Feeds every executable section to the consumer, or every executable segment
+    // if the file has no executable sections, in the order of headers.
+    pub fn feed_code<C>(&self, consumer: &mut C) -> Result<(), Error>
+    where
+        C: CodeConsumer,
+    {
+        let range = |address, code| CodeRange {
+            machine: self.machine(),
+            encoding: self.encoding(),
+            flags: self.flags(),
+            address,
+            code,
+        };
+
+        let mut fed = false;
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            if header.ty == SectionType::NoBits
+                || !header.flags.contains(SectionFlags::EXECINSTR)
+                || header.size == 0
+            {
+                continue;
+            }
+            let slice = file_slice(self.raw, header.offset, header.size)?;
+            consumer.consume(range(header.address, slice));
+            fed = true;
+        }
+        if fed {
+            return Ok(());
+        }
+
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            if header.ty != ProgramType::Load
+                || !header.flags.contains(ProgramFlags::EXECUTE)
+                || header.file_size == 0
+            {
+                continue;
+            }
+            let slice = file_slice(self.raw, header.file_offset, header.file_size)?;
+            consumer.consume(range(header.virtual_address, slice));
+        }
+        Ok(())
+    }
+}
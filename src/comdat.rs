@@ -0,0 +1,96 @@
+use alloc::{collections::BTreeMap, collections::BTreeSet, vec::Vec};
+
+use super::{Error, SectionIndex, Encoding, Elf64, Index, SectionType, SectionData};
+
+// `SHT_GROUP`
+const SHT_GROUP: u32 = 0x11;
+// `GRP_COMDAT`
+const GRP_COMDAT: u32 = 0x1;
+
+// A `SHT_GROUP` section of a relocatable file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Group<'a> {
+    pub section: usize,
+    // the name of the symbol `sh_info` refers to
+    pub signature: &'a [u8],
+    pub comdat: bool,
+    pub members: Vec<usize>,
+}
+
+impl<'a> Elf64<'a> {
+    pub fn groups(&self) -> Result<Vec<Group<'a>>, Error> {
+        let encoding = self.encoding();
+        let mut groups = Vec::new();
+        for section in 0..self.section_number() {
+            let header = self.section_table.pick(section)?;
+            if header.ty != SectionType::Unknown(SHT_GROUP) {
+                continue;
+            }
+            let slice = match self.section(SectionIndex(section))?.map(|s| s.data) {
+                Some(SectionData::Unknown { slice, .. }) => slice,
+                _ => continue,
+            };
+            if slice.len() < 4 {
+                return Err(Error::SliceTooShort);
+            }
+            let word = |offset: usize| read_int!(&slice[offset..], &encoding, u32);
+            let signature = match header.link {
+                Index::Regular(link) => {
+                    self.symbol_source_at(link.0)?
+                        .get(header.info as usize)?
+                        .name
+                }
+                _ => return Err(Error::UnexpectedSectionType),
+            };
+            groups.push(Group {
+                section,
+                signature,
+                comdat: word(0) & GRP_COMDAT != 0,
+                members: (1..(slice.len() / 4))
+                    .map(|i| word(i * 4) as usize)
+                    .collect(),
+            });
+        }
+        Ok(groups)
+    }
+}
+
+// Which sections of the objects being linked together are dropped,
+// of the COMDAT groups with the same signature only the first one is kept
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ComdatSelection {
+    discarded: BTreeSet<(usize, usize)>,
+}
+
+impl ComdatSelection {
+    pub fn new(objects: &[Elf64<'_>]) -> Result<Self, Error> {
+        let mut kept = BTreeMap::new();
+        let mut discarded = BTreeSet::new();
+        for (object, elf) in objects.iter().enumerate() {
+            for group in elf.groups()? {
+                if !group.comdat {
+                    continue;
+                }
+                if kept.contains_key(group.signature) {
+                    discarded.insert((object, group.section));
+                    discarded.extend(group.members.iter().map(|&member| (object, member)));
+                } else {
+                    kept.insert(group.signature, object);
+                }
+            }
+        }
+        Ok(ComdatSelection { discarded })
+    }
+
+    // the section of the object, by the index in the slice, is not loaded,
+    // relocations against it or in it are ignored
+    pub fn is_discarded(&self, object: usize, section: usize) -> bool {
+        self.discarded.contains(&(object, section))
+    }
+
+    pub fn discarded(&self, object: usize) -> impl Iterator<Item = usize> + '_ {
+        self.discarded
+            .range((object, 0)..(object + 1, 0))
+            .map(|&(_, section)| section)
+    }
+}
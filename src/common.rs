@@ -1,17 +1,345 @@
-pub type Address = u64;
-pub type Offset = u64;
+use core::{convert::TryFrom, fmt, ops};
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+use super::fragments::Source;
+use super::header::Class;
+use super::limits::Limit;
+use super::relocation_formula::Operand;
+
+macro_rules! position {
+    ($name:ident) => {
+        #[derive(Clone, Copy, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+        pub struct $name(pub u64);
+
+        impl $name {
+            pub const MAX: Self = $name(u64::MAX);
+
+            pub fn checked_add(self, size: u64) -> Option<Self> {
+                self.0.checked_add(size).map($name)
+            }
+
+            pub fn saturating_add(self, size: u64) -> Self {
+                $name(self.0.saturating_add(size))
+            }
+
+            pub fn wrapping_add(self, size: u64) -> Self {
+                $name(self.0.wrapping_add(size))
+            }
+
+            pub fn saturating_sub(self, size: u64) -> Self {
+                $name(self.0.saturating_sub(size))
+            }
+
+            // the distance from `start`, `None` if it is after this one
+            pub fn checked_sub(self, start: Self) -> Option<u64> {
+                self.0.checked_sub(start.0)
+            }
+
+            // zero and one mean no alignment, `None` if it is not a power of two
+            // or the result overflows
+            pub fn align_up(self, alignment: u64) -> Option<Self> {
+                match alignment {
+                    0 | 1 => Some(self),
+                    _ if alignment.is_power_of_two() => {
+                        let mask = alignment - 1;
+                        self.0.checked_add(mask).map(|x| $name(x & !mask))
+                    }
+                    _ => None,
+                }
+            }
+
+            pub fn is_aligned(self, alignment: u64) -> bool {
+                alignment <= 1 || self.0 % alignment == 0
+            }
+        }
+
+        impl ops::Add<u64> for $name {
+            type Output = Self;
+
+            fn add(self, size: u64) -> Self {
+                $name(self.0 + size)
+            }
+        }
+
+        impl ops::AddAssign<u64> for $name {
+            fn add_assign(&mut self, size: u64) {
+                self.0 += size;
+            }
+        }
+
+        impl ops::Sub<u64> for $name {
+            type Output = Self;
+
+            fn sub(self, size: u64) -> Self {
+                $name(self.0 - size)
+            }
+        }
+
+        impl ops::Sub for $name {
+            type Output = u64;
+
+            fn sub(self, start: Self) -> u64 {
+                self.0 - start.0
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "0x{:x}", self.0)
+            }
+        }
+
+        impl fmt::LowerHex for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::LowerHex::fmt(&self.0, f)
+            }
+        }
+
+        impl fmt::UpperHex for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::UpperHex::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+macro_rules! index {
+    ($name:ident) => {
+        #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+        pub struct $name(pub usize);
+
+        impl From<$name> for usize {
+            fn from(v: $name) -> Self {
+                v.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+// A virtual address, or a value of a symbol
+position!(Address);
+// A position in the file, the types do not mix
+position!(Offset);
+
+// An index in the section header table
+index!(SectionIndex);
+// An index in a symbol table, which one depends on where it comes from
+index!(SymbolIndex);
+
+// `sh_link` and `sh_info` are 32 bits wide, but the reserved
+// indices are the same as of `st_shndx`
+impl TryFrom<u32> for SectionIndex {
+    type Error = Error;
+
+    fn try_from(v: u32) -> Result<Self, Self::Error> {
+        match v {
+            0xff00..=0xffff => Err(Error::IndexOutOfBounds),
+            v => Ok(SectionIndex(v as usize)),
+        }
+    }
+}
+
+impl From<u32> for SymbolIndex {
+    fn from(v: u32) -> Self {
+        SymbolIndex(v as usize)
+    }
+}
+
+// Offsets and sizes of a huge file, e.g. a core dump, might not fit in `usize`
+// of a 32-bit host, it is `Error::ArithmeticOverflow` rather than a truncated value
+pub(crate) fn to_usize(value: u64) -> Result<usize, Error> {
+    usize::try_from(value).map_err(|_| Error::ArithmeticOverflow)
+}
+
+// A position in the file to compare with others, a value past `usize` is past
+// the end of any file the host can hold
+pub(crate) fn saturating_usize(value: u64) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
+
+// `raw[offset..(offset + size)]`
+pub(crate) fn file_slice(raw: Source<'_>, offset: Offset, size: u64) -> Result<&[u8], Error> {
+    let end = offset.checked_add(size).ok_or(Error::ArithmeticOverflow)?;
+    raw.get(to_usize(offset.0)?, Some(to_usize(end.0)?))
+}
+
+// `raw[offset..]`
+pub(crate) fn file_tail(raw: Source<'_>, offset: Offset) -> Result<&[u8], Error> {
+    raw.get(to_usize(offset.0)?, None)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Error {
     SliceTooShort,
     WrongMagicNumber,
     UnknownEncoding(u8),
     UnexpectedSize(UnexpectedSize),
+    IndexOutOfBounds,
+    UnexpectedSectionType,
+    CannotGrowSection,
+    SymbolInUse,
+    NotPresent,
+    UnsupportedMachine,
+    ArithmeticOverflow,
+    UnexpectedFileType,
+    UnsupportedRelocation(u32),
+    UnresolvedSymbol,
+    UnsupportedCompression(u32),
+    CorruptedCompression,
+    // a symbol in `SHN_COMMON` has no address until it is allocated
+    CommonSymbol,
+    SectionNotPlaced(usize),
+    // not a power of two
+    BadAlignment(u64),
+    // the bytes at the offset are in the file, but not in a single captured fragment
+    NotCaptured(Offset),
+    OverlappingFragments,
+    // a replacement in place must be of the size of the original, that is the size
+    SizeMismatch(usize),
+    // two images to load in one address space need the same addresses
+    OverlappingSegments,
+    // e.g. a 64 bits file given to `Elf32`
+    UnexpectedClass(Class),
+    // the range of addresses goes past the end of the segment it starts in
+    SpansSegments,
+    // the range reaches past `p_filesz`, the loader fills the rest of the segment
+    // with zeros, they are not in the file
+    ZeroFilled,
+    // e.g. a symbol that is not `STT_GNU_IFUNC` given to `indirect_symbol`
+    UnexpectedSymbolType,
+    // a table of `FixedBuilder` has no room for one more entry
+    CapacityExceeded,
+    // the file is past a bound of `Limits`
+    LimitExceeded(Limit),
+    // no terminator up to the end of the string table
+    UnterminatedString,
+    InvalidUtf8,
+    // a `DW_EH_PE_*` pointer encoding the unwind data parser does not decode
+    UnsupportedPointerEncoding(u8),
+    // the input of `FormulaInputs` a relocation formula needs is `None`
+    MissingOperand(Operand),
+    // a member header of an `ar` archive is malformed, or a member is past the end
+    CorruptedArchive,
+    // `sh_entsize` of a `SHF_MERGE | SHF_STRINGS` section, the width of a character,
+    // that is not 1, 2, 4 or 8
+    UnsupportedCharacterSize(u64),
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum UnexpectedSize {
     Header,
     ProgramHeader,
     SectionHeader,
 }
+
+impl fmt::Display for UnexpectedSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnexpectedSize::Header => f.write_str("file header"),
+            UnexpectedSize::ProgramHeader => f.write_str("program header"),
+            UnexpectedSize::SectionHeader => f.write_str("section header"),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::SliceTooShort => f.write_str("slice too short"),
+            Error::WrongMagicNumber => f.write_str("wrong magic number"),
+            Error::UnknownEncoding(code) => write!(f, "unknown encoding {}", code),
+            Error::UnexpectedSize(what) => write!(f, "unexpected size of the {}", what),
+            Error::IndexOutOfBounds => f.write_str("index out of bounds"),
+            Error::UnexpectedSectionType => f.write_str("unexpected section type"),
+            Error::CannotGrowSection => f.write_str("cannot grow the section"),
+            Error::SymbolInUse => f.write_str("the symbol is in use"),
+            Error::NotPresent => f.write_str("not present"),
+            Error::UnsupportedMachine => f.write_str("unsupported machine"),
+            Error::ArithmeticOverflow => f.write_str("arithmetic overflow"),
+            Error::UnexpectedFileType => f.write_str("unexpected file type"),
+            Error::UnsupportedRelocation(ty) => write!(f, "unsupported relocation {}", ty),
+            Error::UnresolvedSymbol => f.write_str("unresolved symbol"),
+            Error::UnsupportedCompression(ty) => write!(f, "unsupported compression {}", ty),
+            Error::CorruptedCompression => f.write_str("corrupted compressed data"),
+            Error::CommonSymbol => f.write_str("the symbol is common, it has no address"),
+            Error::SectionNotPlaced(index) => write!(f, "the section {} is not placed", index),
+            Error::BadAlignment(alignment) => {
+                write!(f, "the alignment {} is not a power of two", alignment)
+            }
+            Error::NotCaptured(offset) => {
+                write!(f, "the bytes at 0x{:x} are not captured", offset.0)
+            }
+            Error::OverlappingFragments => f.write_str("overlapping fragments"),
+            Error::SizeMismatch(size) => write!(f, "the size must be {}", size),
+            Error::OverlappingSegments => f.write_str("overlapping segments"),
+            Error::UnexpectedClass(class) => write!(f, "unexpected class {:?}", class),
+            Error::SpansSegments => f.write_str("the range spans several segments"),
+            Error::ZeroFilled => f.write_str("the range is filled with zeros, not in the file"),
+            Error::UnexpectedSymbolType => f.write_str("unexpected symbol type"),
+            Error::CapacityExceeded => f.write_str("capacity exceeded"),
+            Error::LimitExceeded(limit) => write!(f, "limit exceeded: {:?}", limit),
+            Error::UnterminatedString => f.write_str("the string is not terminated"),
+            Error::InvalidUtf8 => f.write_str("the string is not valid UTF-8"),
+            Error::UnsupportedPointerEncoding(encoding) => {
+                write!(f, "unsupported pointer encoding: 0x{:02x}", encoding)
+            }
+            Error::MissingOperand(operand) => write!(f, "missing operand: {}", operand),
+            Error::CorruptedArchive => f.write_str("corrupted archive"),
+            Error::UnsupportedCharacterSize(size) => {
+                write!(f, "unsupported character size: {}", size)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Offset, to_usize, saturating_usize, file_slice};
+    use crate::fragments::Source;
+
+    const ABOVE_4_GIB: u64 = 0x1_0000_1000;
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn usize_above_4_gib() {
+        assert_eq!(to_usize(ABOVE_4_GIB), Ok(0x1_0000_1000));
+        assert_eq!(saturating_usize(ABOVE_4_GIB), 0x1_0000_1000);
+        assert_eq!(saturating_usize(u64::MAX), usize::MAX);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "32")]
+    fn usize_above_4_gib() {
+        assert_eq!(to_usize(ABOVE_4_GIB), Err(Error::ArithmeticOverflow));
+        assert_eq!(saturating_usize(ABOVE_4_GIB), usize::MAX);
+    }
+
+    #[test]
+    fn slices_above_4_gib() {
+        let raw = Source::Contiguous(&[0; 0x10]);
+        // past the end of the file on a 64-bit host, not a `usize` on a 32-bit one
+        let error = file_slice(raw, Offset(ABOVE_4_GIB), 1).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::SliceTooShort | Error::ArithmeticOverflow
+        ));
+        let error = file_slice(raw, Offset(0), ABOVE_4_GIB).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::SliceTooShort | Error::ArithmeticOverflow
+        ));
+        let error = file_slice(raw, Offset(u64::MAX), 2).unwrap_err();
+        assert_eq!(error, Error::ArithmeticOverflow);
+        assert_eq!(file_slice(raw, Offset(8), 8), Ok(&[0; 8][..]));
+    }
+}
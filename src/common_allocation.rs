@@ -0,0 +1,64 @@
+use core::cmp::Reverse;
+use alloc::vec::Vec;
+
+use super::{Error, Elf64, Index, SectionType};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommonSymbol<'a> {
+    // the index in `.symtab`
+    pub index: usize,
+    pub name: &'a [u8],
+    // from the start of the block
+    pub offset: u64,
+    pub size: u64,
+}
+
+// A zeroed block the loader allocates for the `SHN_COMMON` symbols
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CommonAllocation<'a> {
+    pub symbols: Vec<CommonSymbol<'a>>,
+    pub size: u64,
+    // the largest alignment of the symbols
+    pub alignment: u64,
+}
+
+impl<'a> Elf64<'a> {
+    // The symbols go in order of decreasing alignment, so there is as little padding
+    // as possible; the value of a common symbol is its alignment.
+    pub fn common_allocation(&self) -> Result<CommonAllocation<'a>, Error> {
+        let symbols = match self.symbol_source(SectionType::SymbolTable)? {
+            Some(symbols) => symbols,
+            None => return Ok(CommonAllocation::default()),
+        };
+        let mut commons = Vec::new();
+        for index in 1..symbols.number {
+            let symbol = symbols.get(index)?;
+            if symbol.entry.section_index == Index::Common {
+                let alignment = symbol.entry.value.0.max(1);
+                if !alignment.is_power_of_two() {
+                    return Err(Error::BadAlignment(alignment));
+                }
+                commons.push((alignment, index, symbol));
+            }
+        }
+        commons.sort_by_key(|c| Reverse(c.0));
+
+        let mut allocation = CommonAllocation {
+            alignment: commons.first().map(|c| c.0).unwrap_or(1),
+            ..CommonAllocation::default()
+        };
+        for (alignment, index, symbol) in commons {
+            let offset = (allocation.size + alignment - 1) & !(alignment - 1);
+            allocation.size = offset
+                .checked_add(symbol.entry.size)
+                .ok_or(Error::ArithmeticOverflow)?;
+            allocation.symbols.push(CommonSymbol {
+                index,
+                name: symbol.name,
+                offset,
+                size: symbol.entry.size,
+            });
+        }
+        Ok(allocation)
+    }
+}
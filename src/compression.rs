@@ -0,0 +1,204 @@
+#[cfg(feature = "compression")]
+use alloc::{borrow::Cow, vec::Vec};
+
+use super::{Error, Encoding, Entry};
+#[cfg(feature = "compression")]
+use super::SectionData;
+#[cfg(feature = "compression")]
+use super::common::to_usize;
+
+// `ch_type` of `Elf64_Chdr`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum CompressionType {
+    // `ELFCOMPRESS_ZLIB`, and the old `.zdebug_*` sections
+    Zlib,
+    // `ELFCOMPRESS_ZSTD`
+    Zstd,
+    Unknown(u32),
+}
+
+impl From<u32> for CompressionType {
+    fn from(v: u32) -> Self {
+        match v {
+            1 => CompressionType::Zlib,
+            2 => CompressionType::Zstd,
+            t => CompressionType::Unknown(t),
+        }
+    }
+}
+
+impl From<CompressionType> for u32 {
+    fn from(v: CompressionType) -> Self {
+        match v {
+            CompressionType::Zlib => 1,
+            CompressionType::Zstd => 2,
+            CompressionType::Unknown(t) => t,
+        }
+    }
+}
+
+// `Elf64_Chdr`, the content of a `SHF_COMPRESSED` section starts with it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompressionHeader {
+    pub ty: CompressionType,
+    // of the content once it is decompressed
+    pub size: u64,
+    pub alignment: u64,
+}
+
+impl Entry for CompressionHeader {
+    type Error = Error;
+
+    const SIZE: usize = 0x18;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        // `ch_reserved` at 0x04
+        Ok(CompressionHeader {
+            ty: read_int!(&slice[0x00..], &encoding, u32).into(),
+            size: read_int!(&slice[0x08..], &encoding, u64),
+            alignment: read_int!(&slice[0x10..], &encoding, u64),
+        })
+    }
+}
+
+// The old style of GNU, `.zdebug_*` without `SHF_COMPRESSED`, "ZLIB"
+// and the big endian size
+pub(crate) fn legacy_header(slice: &[u8]) -> Option<CompressionHeader> {
+    match slice.get(..12) {
+        Some(header) if header.starts_with(b"ZLIB") => Some(CompressionHeader {
+            ty: CompressionType::Zlib,
+            size: read_int!(&header[0x04..], &Encoding::Big, u64),
+            alignment: 1,
+        }),
+        _ => None,
+    }
+}
+
+// The content of the compressed section, it must be of the size the header gives
+#[cfg(feature = "compression")]
+pub fn decompress(ty: &CompressionType, size: u64, payload: &[u8]) -> Result<Vec<u8>, Error> {
+    let size = to_usize(size)?;
+    let data = match ty {
+        CompressionType::Zlib => {
+            miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(payload, size)
+                .map_err(|_| Error::CorruptedCompression)?
+        }
+        CompressionType::Zstd => decompress_zstd(payload, size)?,
+        ty => return Err(Error::UnsupportedCompression(ty.clone().into())),
+    };
+    if data.len() != size {
+        return Err(Error::CorruptedCompression);
+    }
+    Ok(data)
+}
+
+// `ch_size` comes from the file, the buffer grows with what is decoded, a block
+// at a time, and the decoding stops once it is past the size, like the limit of zlib
+#[cfg(feature = "compression")]
+fn decompress_zstd(mut payload: &[u8], size: usize) -> Result<Vec<u8>, Error> {
+    use ruzstd::decoding::{BlockDecodingStrategy, FrameDecoder};
+    use ruzstd::decoding::errors::{FrameDecoderError, ReadFrameHeaderError};
+
+    let mut decoder = FrameDecoder::new();
+    let mut data = Vec::new();
+    while !payload.is_empty() {
+        match decoder.init(&mut payload) {
+            Ok(()) => (),
+            // a skippable frame has no content
+            Err(FrameDecoderError::ReadFrameHeaderError(ReadFrameHeaderError::SkipFrame {
+                length,
+                ..
+            })) => {
+                payload = payload
+                    .get((length as usize)..)
+                    .ok_or(Error::CorruptedCompression)?;
+                continue;
+            }
+            Err(_) => return Err(Error::CorruptedCompression),
+        }
+        loop {
+            decoder
+                .decode_blocks(&mut payload, BlockDecodingStrategy::UptoBlocks(1))
+                .map_err(|_| Error::CorruptedCompression)?;
+            // the window stays in the decoder until the frame is finished
+            if let Some(chunk) = decoder.collect() {
+                data.extend_from_slice(&chunk);
+            }
+            if data.len() > size {
+                return Err(Error::CorruptedCompression);
+            }
+            if decoder.is_finished() {
+                break;
+            }
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(feature = "compression")]
+impl<'a> SectionData<'a> {
+    // The content a consumer of the section expects, of `SectionData::Compressed`
+    // decompressed, of the others as they are in the file, empty for `SHT_NOBITS`
+    pub fn decompressed(&self) -> Result<Cow<'a, [u8]>, Error> {
+        let slice = match self {
+            SectionData::Compressed {
+                ty,
+                uncompressed_size,
+                payload,
+                ..
+            } => return decompress(ty, *uncompressed_size, payload).map(Cow::Owned),
+            SectionData::ProgramBits(slice)
+            | SectionData::Shlib(slice)
+            | SectionData::OsSpecific { slice, .. }
+            | SectionData::ProcessorSprcific { slice, .. }
+            | SectionData::Unknown { slice, .. } => slice,
+            SectionData::StringTable(table) => table.as_raw(),
+            SectionData::NoBits { .. } | SectionData::Null => &[],
+            _ => return Err(Error::UnexpectedSectionType),
+        };
+        Ok(Cow::Borrowed(slice))
+    }
+}
+
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use alloc::vec::Vec;
+
+    use ruzstd::encoding::{CompressionLevel, compress_to_vec};
+
+    use super::{CompressionType, decompress};
+    use crate::Error;
+
+    fn content() -> Vec<u8> {
+        (0..0x30000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn zstd() {
+        let content = content();
+        let payload = compress_to_vec(&content[..], CompressionLevel::Fastest);
+        let size = content.len() as u64;
+        assert_eq!(
+            decompress(&CompressionType::Zstd, size, &payload),
+            Ok(content)
+        );
+    }
+
+    // `ch_size` of a forged header is not allocated up front, the content
+    // is shorter, or longer, than it
+    #[test]
+    fn zstd_of_a_wrong_size() {
+        let content = content();
+        let payload = compress_to_vec(&content[..], CompressionLevel::Fastest);
+        for size in [0x6_0000_0001, content.len() as u64 - 1, 0] {
+            assert!(matches!(
+                decompress(&CompressionType::Zstd, size, &payload),
+                Err(Error::CorruptedCompression) | Err(Error::ArithmeticOverflow)
+            ));
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use alloc::vec::Vec;
+
+use super::{Error, Address, Elf64, Symbol, Index};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CopyRelocation<'a> {
+    // the relocation section and the index of the entry in it
+    pub section: usize,
+    pub index: usize,
+    // where the loader copies the object to
+    pub address: Address,
+    // the size the executable was linked against, the definition in the library
+    // must not be larger
+    pub size: u64,
+    pub symbol: Symbol<'a>,
+}
+
+impl<'a> Elf64<'a> {
+    pub fn copy_relocations(&self) -> Result<Vec<CopyRelocation<'a>>, Error> {
+        let mut relocations = Vec::new();
+        let machine = self.machine();
+        self.visit_rela(|section, header, index, entry| {
+            if !entry.kind(&machine).is_copy() {
+                return Ok(());
+            }
+            let symbols = match header.link {
+                Index::Regular(link) => self.symbol_source_at(link.0)?,
+                _ => return Err(Error::UnexpectedSectionType),
+            };
+            let symbol = symbols.get(entry.symbol_index.0)?;
+            relocations.push(CopyRelocation {
+                section,
+                index,
+                address: entry.address,
+                size: symbol.entry.size,
+                symbol,
+            });
+            Ok(())
+        })?;
+        Ok(relocations)
+    }
+}
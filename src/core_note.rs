@@ -0,0 +1,434 @@
+use super::{
+    Error, Address, Offset, Encoding, Machine, NoteEntry, Elf64, AuxiliaryType, AuxiliaryEntry,
+};
+use super::common::saturating_usize;
+
+// The size of `struct elf_prpsinfo` of a 64 bits Linux
+const PRPSINFO_SIZE: usize = 0x88;
+
+// `NT_PRPSINFO`, the process that dumped the core
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProcessInfo<'a> {
+    // `pr_state`, the numeric state
+    pub state_number: u8,
+    // `pr_sname`, the letter of the state, e.g. `R` or `S`, the way `ps` shows it
+    pub state: u8,
+    // `pr_zomb`
+    pub zombie: bool,
+    // `pr_nice`
+    pub nice: i8,
+    // `pr_flag`, the flags of the task
+    pub flags: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: i32,
+    pub parent_pid: i32,
+    pub group_id: i32,
+    pub session_id: i32,
+    // `pr_fname`, the name of the executable, at most 16 bytes, without the terminator
+    pub name: &'a [u8],
+    // `pr_psargs`, the command line joined with spaces, cut at 80 bytes, without the terminator
+    pub command_line: &'a [u8],
+}
+
+impl<'a> ProcessInfo<'a> {
+    // the words of the command line, an argument with a space in it splits here,
+    // the kernel has joined them already
+    pub fn arguments(&self) -> impl Iterator<Item = &'a [u8]> {
+        self.command_line
+            .split(|c| *c == b' ')
+            .filter(|argument| !argument.is_empty())
+    }
+}
+
+impl<'a> NoteEntry<'a> {
+    // `None` if it is not `NT_PRPSINFO` of the `CORE` owner or it is too short
+    pub fn process_info(&self) -> Option<ProcessInfo<'a>> {
+        if self.name != b"CORE" || self.ty != 3 || self.description.len() < PRPSINFO_SIZE {
+            return None;
+        }
+        let d = self.description;
+        let word = |offset: usize| read_int!(&d[offset..], self.encoding(), u32);
+        let string = |slice: &'a [u8]| {
+            let end = slice.iter().position(|c| *c == 0).unwrap_or(slice.len());
+            &slice[..end]
+        };
+        Some(ProcessInfo {
+            state_number: d[0x00],
+            state: d[0x01],
+            zombie: d[0x02] != 0,
+            nice: d[0x03] as i8,
+            flags: read_int!(&d[0x08..], self.encoding(), u64),
+            uid: word(0x10),
+            gid: word(0x14),
+            pid: word(0x18) as i32,
+            parent_pid: word(0x1c) as i32,
+            group_id: word(0x20) as i32,
+            session_id: word(0x24) as i32,
+            name: string(&d[0x28..0x38]),
+            command_line: {
+                // the kernel turns the terminator of the last argument into a space too
+                let mut command_line = string(&d[0x38..0x88]);
+                while let Some((b' ', init)) = command_line.split_last() {
+                    command_line = init;
+                }
+                command_line
+            },
+        })
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The process of a core file, `None` if there is no `NT_PRPSINFO`
+    pub fn process_info(&self) -> Result<Option<ProcessInfo<'a>>, Error> {
+        for note in self.notes() {
+            if let Some(info) = note?.process_info() {
+                return Ok(Some(info));
+            }
+        }
+        Ok(None)
+    }
+}
+
+// `struct timeval`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct TimeValue {
+    pub seconds: i64,
+    pub microseconds: i64,
+}
+
+// `struct user_regs_struct` of x86-64, in its order
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct X86_64Registers {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub rbp: u64,
+    pub rbx: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rax: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    // the number of the system call the thread was in
+    pub orig_rax: u64,
+    pub rip: u64,
+    pub cs: u64,
+    pub eflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+    pub fs_base: u64,
+    pub gs_base: u64,
+    pub ds: u64,
+    pub es: u64,
+    pub fs: u64,
+    pub gs: u64,
+}
+
+// `struct user_pt_regs` of AArch64
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct AArch64Registers {
+    // `x30` is the link register
+    pub x: [u64; 31],
+    pub sp: u64,
+    pub pc: u64,
+    pub pstate: u64,
+}
+
+// `pr_reg` by the machine of the core
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Registers<'a> {
+    X86_64(X86_64Registers),
+    AArch64(AArch64Registers),
+    Unknown(&'a [u8]),
+}
+
+// `NT_PRSTATUS`, a thread of the process, the first one got the signal
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProcessStatus<'a> {
+    // `pr_info`
+    pub signal: i32,
+    pub code: i32,
+    pub errno: i32,
+    // `pr_cursig`
+    pub current_signal: u16,
+    // `pr_sigpend` and `pr_sighold`, the masks
+    pub pending: u64,
+    pub held: u64,
+    pub pid: i32,
+    pub parent_pid: i32,
+    pub group_id: i32,
+    pub session_id: i32,
+    pub user_time: TimeValue,
+    pub system_time: TimeValue,
+    // of the children the process waited for
+    pub children_user_time: TimeValue,
+    pub children_system_time: TimeValue,
+    pub registers: Registers<'a>,
+    // `pr_fpvalid`, there is `NT_FPREGSET` of the thread
+    pub floating_point_valid: bool,
+}
+
+// where `pr_reg` is in `struct elf_prstatus`, the size of it is of the machine
+const PRSTATUS_REGISTERS: usize = 0x70;
+
+impl<'a> NoteEntry<'a> {
+    // `None` if it is not `NT_PRSTATUS` of the `CORE` owner or it is too short, the
+    // registers are known for x86-64 and AArch64, for the rest they are raw and
+    // `pr_fpvalid` is assumed right after them
+    pub fn process_status(&self, machine: &Machine) -> Option<ProcessStatus<'a>> {
+        if self.name != b"CORE" || self.ty != 1 || self.description.len() < PRSTATUS_REGISTERS {
+            return None;
+        }
+        let d = self.description;
+        let encoding = self.encoding();
+        let word = |offset: usize| read_int!(&d[offset..], encoding, u32) as i32;
+        let double = |offset: usize| read_int!(&d[offset..], encoding, u64);
+        let time = |offset: usize| TimeValue {
+            seconds: double(offset) as i64,
+            microseconds: double(offset + 8) as i64,
+        };
+        let register_number = match machine {
+            Machine::X86_64 => 27,
+            Machine::AArch64 => 34,
+            // `pr_fpvalid` and the padding
+            _ => d.len().saturating_sub(PRSTATUS_REGISTERS + 8) / 8,
+        };
+        let end = PRSTATUS_REGISTERS + register_number * 8;
+        if d.len() < end + 4 {
+            return None;
+        }
+        let mut registers = [0; 34];
+        for (index, register) in registers
+            .iter_mut()
+            .take(register_number.min(34))
+            .enumerate()
+        {
+            *register = double(PRSTATUS_REGISTERS + index * 8);
+        }
+        let registers = match machine {
+            Machine::X86_64 => Registers::X86_64(x86_64_registers(&registers)),
+            Machine::AArch64 => {
+                let mut x = [0; 31];
+                x.copy_from_slice(&registers[..31]);
+                Registers::AArch64(AArch64Registers {
+                    x,
+                    sp: registers[31],
+                    pc: registers[32],
+                    pstate: registers[33],
+                })
+            }
+            _ => Registers::Unknown(&d[PRSTATUS_REGISTERS..end]),
+        };
+        Some(ProcessStatus {
+            signal: word(0x00),
+            code: word(0x04),
+            errno: word(0x08),
+            current_signal: read_int!(&d[0x0c..], encoding, u16),
+            pending: double(0x10),
+            held: double(0x18),
+            pid: word(0x20),
+            parent_pid: word(0x24),
+            group_id: word(0x28),
+            session_id: word(0x2c),
+            user_time: time(0x30),
+            system_time: time(0x40),
+            children_user_time: time(0x50),
+            children_system_time: time(0x60),
+            registers,
+            floating_point_valid: word(end) != 0,
+        })
+    }
+
+    // `None` if it is not `NT_FILE` of the `CORE` owner or the header is too short
+    pub fn mapped_files(&self) -> Option<MappedFiles<'a>> {
+        if self.name != b"CORE" || self.ty != 0x46494c45 || self.description.len() < 0x10 {
+            return None;
+        }
+        let d = self.description;
+        let encoding = self.encoding().clone();
+        // more than the host holds is a table past the end of the note
+        let number = saturating_usize(read_int!(d, &encoding, u64));
+        let page_size = read_int!(&d[0x08..], &encoding, u64);
+        let names = number
+            .checked_mul(0x18)
+            .and_then(|size| size.checked_add(0x10))
+            .and_then(|start| d.get(start..))
+            .unwrap_or(&[]);
+        Some(MappedFiles {
+            slice: &d[0x10..],
+            names,
+            encoding,
+            page_size,
+            number,
+            index: 0,
+        })
+    }
+
+    // `None` if it is not `NT_AUXV` of the `CORE` owner
+    pub fn auxiliary_vector(&self) -> Option<AuxiliaryEntries<'a>> {
+        if self.name != b"CORE" || self.ty != 6 {
+            return None;
+        }
+        Some(AuxiliaryEntries {
+            slice: self.description,
+            encoding: self.encoding().clone(),
+        })
+    }
+}
+
+fn x86_64_registers(r: &[u64; 34]) -> X86_64Registers {
+    X86_64Registers {
+        r15: r[0],
+        r14: r[1],
+        r13: r[2],
+        r12: r[3],
+        rbp: r[4],
+        rbx: r[5],
+        r11: r[6],
+        r10: r[7],
+        r9: r[8],
+        r8: r[9],
+        rax: r[10],
+        rcx: r[11],
+        rdx: r[12],
+        rsi: r[13],
+        rdi: r[14],
+        orig_rax: r[15],
+        rip: r[16],
+        cs: r[17],
+        eflags: r[18],
+        rsp: r[19],
+        ss: r[20],
+        fs_base: r[21],
+        gs_base: r[22],
+        ds: r[23],
+        es: r[24],
+        fs: r[25],
+        gs: r[26],
+    }
+}
+
+// An entry of `NT_FILE`, a file the process had mapped
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MappedFile<'a> {
+    pub start: Address,
+    pub end: Address,
+    // in bytes, the note has it in pages
+    pub file_offset: Offset,
+    pub name: &'a [u8],
+}
+
+// The entries of `NT_FILE`, the ranges first, then the names, one for each range
+#[derive(Clone)]
+pub struct MappedFiles<'a> {
+    slice: &'a [u8],
+    names: &'a [u8],
+    encoding: Encoding,
+    page_size: u64,
+    number: usize,
+    index: usize,
+}
+
+impl<'a> MappedFiles<'a> {
+    pub fn page_size(&self) -> u64 {
+        self.page_size
+    }
+}
+
+impl<'a> Iterator for MappedFiles<'a> {
+    type Item = Result<MappedFile<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.number {
+            return None;
+        }
+        let offset = self.index * 0x18;
+        self.index += 1;
+        let (range, name) = match (self.slice.get(offset..(offset + 0x18)), self.names) {
+            (Some(range), names) if !names.is_empty() => {
+                let end = names.iter().position(|c| *c == 0).unwrap_or(names.len());
+                self.names = names.get((end + 1)..).unwrap_or(&[]);
+                (range, &names[..end])
+            }
+            _ => {
+                self.index = self.number;
+                return Some(Err(Error::SliceTooShort));
+            }
+        };
+        let double = |offset: usize| read_int!(&range[offset..], &self.encoding, u64);
+        let file_offset = double(0x10).checked_mul(self.page_size);
+        Some(match file_offset {
+            Some(file_offset) => Ok(MappedFile {
+                start: Address(double(0x00)),
+                end: Address(double(0x08)),
+                file_offset: Offset(file_offset),
+                name,
+            }),
+            None => Err(Error::ArithmeticOverflow),
+        })
+    }
+}
+
+// The entries of `NT_AUXV` up to `AT_NULL`
+#[derive(Clone)]
+pub struct AuxiliaryEntries<'a> {
+    slice: &'a [u8],
+    encoding: Encoding,
+}
+
+impl<'a> Iterator for AuxiliaryEntries<'a> {
+    type Item = AuxiliaryEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.slice.len() < 0x10 {
+            return None;
+        }
+        let ty = read_int!(self.slice, &self.encoding, u64).into();
+        let value = read_int!(&self.slice[0x08..], &self.encoding, u64);
+        self.slice = &self.slice[0x10..];
+        match ty {
+            AuxiliaryType::Null => {
+                self.slice = &[];
+                None
+            }
+            ty => Some(AuxiliaryEntry { ty, value }),
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // `NT_PRSTATUS` of every thread of a core file, in the order of the notes
+    pub fn process_statuses(&self) -> impl Iterator<Item = Result<ProcessStatus<'a>, Error>> + 'a {
+        let machine = self.machine();
+        self.notes().filter_map(move |note| match note {
+            Ok(note) => note.process_status(&machine).map(Ok),
+            Err(error) => Some(Err(error)),
+        })
+    }
+
+    // `NT_FILE` of a core file, `None` if there is no such note
+    pub fn mapped_files(&self) -> Result<Option<MappedFiles<'a>>, Error> {
+        for note in self.notes() {
+            if let Some(files) = note?.mapped_files() {
+                return Ok(Some(files));
+            }
+        }
+        Ok(None)
+    }
+
+    // `NT_AUXV` of a core file, the vector the process was started with
+    pub fn core_auxiliary_vector(&self) -> Result<Option<AuxiliaryEntries<'a>>, Error> {
+        for note in self.notes() {
+            if let Some(entries) = note?.auxiliary_vector() {
+                return Ok(Some(entries));
+            }
+        }
+        Ok(None)
+    }
+}
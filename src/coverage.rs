@@ -0,0 +1,384 @@
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+
+use super::{Error, Offset, Elf64, Entry, Header, ProgramHeader, SectionHeader, SectionType};
+use super::common::saturating_usize;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Structure {
+    Header,
+    ProgramHeader(usize),
+    SectionHeader(usize),
+    Section(usize),
+    Segment(usize),
+    // zeros nothing claims, e.g. the alignment gaps
+    Padding,
+    Unclaimed,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoverageRange {
+    pub offset: Offset,
+    pub size: u64,
+    pub structure: Structure,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnclaimedRegion<'a> {
+    pub offset: Offset,
+    pub data: &'a [u8],
+}
+
+impl<'a> UnclaimedRegion<'a> {
+    // alignment gaps are zeros, anything else deserves a look
+    pub fn is_zero(&self) -> bool {
+        self.data.iter().all(|&b| b == 0)
+    }
+}
+
+#[derive(Clone)]
+pub struct Coverage<'a> {
+    elf: Elf64<'a>,
+    offset: usize,
+    // the boundaries and the claimants of the bytes from there on, found on the first call
+    #[cfg(feature = "alloc")]
+    claims: Option<Vec<(usize, Structure)>>,
+    // the claim at `offset`
+    #[cfg(feature = "alloc")]
+    position: usize,
+}
+
+impl<'a> Coverage<'a> {
+    // the claimant of the byte and the end of its range
+    #[cfg(feature = "alloc")]
+    fn claimed(&mut self, start: usize) -> Result<(Structure, usize), Error> {
+        if self.claims.is_none() {
+            self.claims = Some(self.elf.claims()?);
+        }
+        let claims = self.claims.as_deref().unwrap_or_default();
+        let structure = match claims.get(self.position) {
+            Some((offset, structure)) if *offset == start => structure.clone(),
+            _ => return Err(Error::SliceTooShort),
+        };
+        self.position = claims[self.position..]
+            .iter()
+            .position(|(_, claimant)| *claimant != structure)
+            .map_or(claims.len(), |distance| self.position + distance);
+        let end = claims
+            .get(self.position)
+            .map_or(self.elf.raw.len(), |&(offset, _)| offset);
+        Ok((structure, end))
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn claimed(&mut self, start: usize) -> Result<(Structure, usize), Error> {
+        let length = self.elf.raw.len();
+        let structure = self.elf.claimant(start)?;
+        let mut end = self.elf.next_boundary(start)?;
+        while end < length && self.elf.claimant(end)? == structure {
+            end = self.elf.next_boundary(end)?;
+        }
+        Ok((structure, end))
+    }
+}
+
+impl<'a> Iterator for Coverage<'a> {
+    type Item = Result<CoverageRange, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let length = self.elf.raw.len();
+        if self.offset >= length {
+            return None;
+        }
+
+        let start = self.offset;
+        let range = (|| {
+            let (structure, end) = self.claimed(start)?;
+            let structure = match structure {
+                // bytes that were not captured are not known to be zero
+                Structure::Unclaimed
+                    if matches!(
+                        self.elf.raw.get(start, Some(end)),
+                        Ok(data) if data.iter().all(|&b| b == 0)
+                    ) =>
+                {
+                    Structure::Padding
+                }
+                structure => structure,
+            };
+            Ok(CoverageRange {
+                offset: Offset(start as u64),
+                size: (end - start) as u64,
+                structure,
+            })
+        })();
+        self.offset = match &range {
+            Ok(range) => start + range.size as usize,
+            Err(_) => length,
+        };
+        Some(range)
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Splits the file into ranges by the structure that claims the bytes, in the order of offsets.
+    // When structures overlap, headers win over sections and sections win over segments.
+    pub fn coverage(&self) -> Coverage<'a> {
+        Coverage {
+            elf: self.clone(),
+            offset: 0,
+            #[cfg(feature = "alloc")]
+            claims: None,
+            #[cfg(feature = "alloc")]
+            position: 0,
+        }
+    }
+
+    // The ranges no header, section or segment refers to, padding included,
+    // e.g. data appended to the file
+    pub fn unclaimed_regions(
+        &self,
+    ) -> impl Iterator<Item = Result<UnclaimedRegion<'a>, Error>> + 'a {
+        let raw = self.raw;
+        self.coverage().filter_map(move |range| match range {
+            Ok(CoverageRange {
+                offset,
+                size,
+                structure: Structure::Padding,
+            })
+            | Ok(CoverageRange {
+                offset,
+                size,
+                structure: Structure::Unclaimed,
+            }) => Some(
+                raw.get(
+                    saturating_usize(offset.0),
+                    Some(saturating_usize(offset.0.saturating_add(size))),
+                )
+                .map(|data| UnclaimedRegion { offset, data }),
+            ),
+            Ok(_) => None,
+            Err(error) => Some(Err(error)),
+        })
+    }
+
+    // the structure the byte belongs to, never `Structure::Padding`
+    pub(crate) fn claimant(&self, offset: usize) -> Result<Structure, Error> {
+        if let Some(structure) = self.header_claimant(offset) {
+            return Ok(structure);
+        }
+
+        for i in 0..self.section_number() {
+            let header = self.section_table.pick(i)?;
+            let start = saturating_usize(header.offset.0);
+            let end = start.saturating_add(saturating_usize(header.size));
+            if header.ty != SectionType::NoBits && (start..end).contains(&offset) {
+                return Ok(Structure::Section(i));
+            }
+        }
+        for i in 0..self.program_number() {
+            let header = self.program_table.pick(i)?;
+            let start = saturating_usize(header.file_offset.0);
+            let end = start.saturating_add(saturating_usize(header.file_size));
+            if (start..end).contains(&offset) {
+                return Ok(Structure::Segment(i));
+            }
+        }
+
+        Ok(Structure::Unclaimed)
+    }
+
+    // the header or the entry of a table the byte belongs to
+    fn header_claimant(&self, offset: usize) -> Option<Structure> {
+        if offset < Header::SIZE {
+            return Some(Structure::Header);
+        }
+
+        let table = saturating_usize(self.header.program_headers_offset.0);
+        let end = table.saturating_add(self.program_number() * ProgramHeader::SIZE);
+        if (table..end).contains(&offset) {
+            return Some(Structure::ProgramHeader(
+                (offset - table) / ProgramHeader::SIZE,
+            ));
+        }
+
+        let table = saturating_usize(self.header.section_headers_offset.0);
+        let end = table.saturating_add(self.section_number() * SectionHeader::SIZE);
+        if (table..end).contains(&offset) {
+            return Some(Structure::SectionHeader(
+                (offset - table) / SectionHeader::SIZE,
+            ));
+        }
+
+        None
+    }
+
+    // the tables of headers, the offset, the number of entries and the size of one
+    fn header_tables(&self) -> [(usize, usize, usize); 2] {
+        [
+            (
+                saturating_usize(self.header.program_headers_offset.0),
+                self.program_number(),
+                ProgramHeader::SIZE,
+            ),
+            (
+                saturating_usize(self.header.section_headers_offset.0),
+                self.section_number(),
+                SectionHeader::SIZE,
+            ),
+        ]
+    }
+
+    // Every offset where the claimant may change, in order, and `claimant` of it.
+    // Sorts the boundaries once and sweeps them keeping the sections and the segments
+    // that span the offset, instead of walking all of them at each boundary.
+    #[cfg(feature = "alloc")]
+    fn claims(&self) -> Result<Vec<(usize, Structure)>, Error> {
+        let length = self.raw.len();
+        let mut boundaries = vec![0, Header::SIZE];
+        for &(table, number, size) in &self.header_tables() {
+            for i in 0..=number {
+                let entry = table.saturating_add(i.saturating_mul(size));
+                if entry >= length {
+                    break;
+                }
+                boundaries.push(entry);
+            }
+        }
+
+        // the offset, whether it is the end, whether it is a segment, and the index
+        let mut events = Vec::new();
+        let mut claim = |start: usize, end: usize, segment: bool, index: usize| {
+            if start < end {
+                boundaries.push(start);
+                boundaries.push(end);
+                events.push((start, false, segment, index));
+                events.push((end, true, segment, index));
+            }
+        };
+        for i in 0..self.section_number() {
+            let header = self.section_table.pick(i)?;
+            if header.ty != SectionType::NoBits {
+                let start = saturating_usize(header.offset.0);
+                claim(
+                    start,
+                    start.saturating_add(saturating_usize(header.size)),
+                    false,
+                    i,
+                );
+            }
+        }
+        for i in 0..self.program_number() {
+            let header = self.program_table.pick(i)?;
+            let start = saturating_usize(header.file_offset.0);
+            claim(
+                start,
+                start.saturating_add(saturating_usize(header.file_size)),
+                true,
+                i,
+            );
+        }
+
+        boundaries.retain(|&offset| offset < length);
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        events.sort_unstable();
+
+        let (mut sections, mut segments) = (BTreeSet::new(), BTreeSet::new());
+        let mut events = events.into_iter().peekable();
+        let mut claims = Vec::with_capacity(boundaries.len());
+        for offset in boundaries {
+            while let Some((_, end, segment, index)) = events.next_if(|event| event.0 <= offset) {
+                let spanning = if segment {
+                    &mut segments
+                } else {
+                    &mut sections
+                };
+                if end {
+                    spanning.remove(&index);
+                } else {
+                    spanning.insert(index);
+                }
+            }
+            let structure = self
+                .header_claimant(offset)
+                .or_else(|| sections.iter().next().cloned().map(Structure::Section))
+                .or_else(|| segments.iter().next().cloned().map(Structure::Segment))
+                .unwrap_or(Structure::Unclaimed);
+            claims.push((offset, structure));
+        }
+        Ok(claims)
+    }
+
+    // the nearest offset after this one where the claimant may change
+    #[cfg(not(feature = "alloc"))]
+    fn next_boundary(&self, offset: usize) -> Result<usize, Error> {
+        let mut boundary = self.raw.len();
+        let mut candidate = |x: usize| {
+            if x > offset && x < boundary {
+                boundary = x;
+            }
+        };
+
+        candidate(Header::SIZE);
+        for &(table, number, size) in &self.header_tables() {
+            let end = table.saturating_add(number * size);
+            if offset < table {
+                candidate(table);
+            } else if offset < end {
+                candidate(table.saturating_add(((offset - table) / size + 1) * size));
+            }
+        }
+
+        for i in 0..self.section_number() {
+            let header = self.section_table.pick(i)?;
+            if header.ty != SectionType::NoBits {
+                let start = saturating_usize(header.offset.0);
+                candidate(start);
+                candidate(start.saturating_add(saturating_usize(header.size)));
+            }
+        }
+        for i in 0..self.program_number() {
+            let header = self.program_table.pick(i)?;
+            let start = saturating_usize(header.file_offset.0);
+            candidate(start);
+            candidate(start.saturating_add(saturating_usize(header.file_size)));
+        }
+
+        Ok(boundary)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::{Elf64, Encoding, Structure};
+
+    // the ranges are the bytes of the same claimant, and the next range is another one
+    fn check(raw: &[u8]) {
+        let elf = Elf64::new(raw).unwrap();
+        let mut previous = None;
+        let mut offset = 0;
+        for range in elf.coverage() {
+            let range = range.unwrap();
+            assert_eq!(range.offset.0, offset);
+            let structure = match range.structure {
+                Structure::Padding => Structure::Unclaimed,
+                ref structure => structure.clone(),
+            };
+            for byte in offset..(offset + range.size) {
+                assert_eq!(elf.claimant(byte as usize).unwrap(), structure);
+            }
+            assert_ne!(previous.as_ref(), Some(&range.structure));
+            previous = Some(range.structure);
+            offset += range.size;
+        }
+        assert_eq!(offset, raw.len() as u64);
+    }
+
+    #[test]
+    fn claimants() {
+        check(&crate::tests::executable(Encoding::Little));
+        check(&crate::tests::executable(Encoding::Big));
+        check(&crate::tests::with_extended_numbering(0x10000, [0, 0]));
+    }
+}
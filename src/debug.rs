@@ -0,0 +1,265 @@
+use core::{fmt, str};
+
+use super::{Elf64, Program, ProgramData, Section, SectionData};
+
+// the size of the content instead of the content
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> fmt::Debug for Bytes<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} bytes>", self.0.len())
+    }
+}
+
+// a name as a string, if it is one
+pub(crate) struct Name<'a>(pub &'a [u8]);
+
+impl<'a> fmt::Debug for Name<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match str::from_utf8(self.0) {
+            Ok(name) => fmt::Debug::fmt(name, f),
+            Err(_) => fmt::Debug::fmt(self.0, f),
+        }
+    }
+}
+
+struct Hex(u64);
+
+impl fmt::Debug for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:016x}", self.0)
+    }
+}
+
+impl<'a> fmt::Debug for ProgramData<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgramData::Null => f.write_str("Null"),
+            ProgramData::Load { data, address } => f
+                .debug_struct("Load")
+                .field("data", &Bytes(data))
+                .field("address", &Hex(address.0))
+                .finish(),
+            ProgramData::ZeroFilled { address } => f
+                .debug_struct("ZeroFilled")
+                .field("address", &Hex(address.0))
+                .finish(),
+            ProgramData::Dynamic { table, address } => f
+                .debug_struct("Dynamic")
+                .field("table", table)
+                .field("address", &Hex(address.0))
+                .finish(),
+            ProgramData::Interpreter(path) => {
+                f.debug_tuple("Interpreter").field(&Name(path)).finish()
+            }
+            ProgramData::Note(table) => f.debug_tuple("Note").field(table).finish(),
+            ProgramData::Shlib { data, address } => f
+                .debug_struct("Shlib")
+                .field("data", &Bytes(data))
+                .field("address", &Hex(address.0))
+                .finish(),
+            ProgramData::ProgramHeaderTable { data, address } => f
+                .debug_struct("ProgramHeaderTable")
+                .field("data", &Bytes(data))
+                .field("address", &Hex(address.0))
+                .finish(),
+            ProgramData::Tls { data, template } => f
+                .debug_struct("Tls")
+                .field("data", &Bytes(data))
+                .field("template", template)
+                .finish(),
+            ProgramData::EhFrameHeader { data, address } => f
+                .debug_struct("EhFrameHeader")
+                .field("data", &Bytes(data))
+                .field("address", &Hex(address.0))
+                .finish(),
+            ProgramData::GnuStack(stack) => f.debug_tuple("GnuStack").field(stack).finish(),
+            ProgramData::GnuRelro(relro) => f.debug_tuple("GnuRelro").field(relro).finish(),
+            ProgramData::GnuProperty(table) => f.debug_tuple("GnuProperty").field(table).finish(),
+            ProgramData::OsSpecific {
+                code,
+                data,
+                address,
+            } => f
+                .debug_struct("OsSpecific")
+                .field("code", &format_args!("0x{:08x}", code))
+                .field("data", &Bytes(data))
+                .field("address", &Hex(address.0))
+                .finish(),
+            ProgramData::ProcessorSprcific {
+                code,
+                data,
+                address,
+            } => f
+                .debug_struct("ProcessorSprcific")
+                .field("code", &format_args!("0x{:08x}", code))
+                .field("data", &Bytes(data))
+                .field("address", &Hex(address.0))
+                .finish(),
+            ProgramData::Unknown {
+                code,
+                data,
+                address,
+            } => f
+                .debug_struct("Unknown")
+                .field("code", &format_args!("0x{:08x}", code))
+                .field("data", &Bytes(data))
+                .field("address", &Hex(address.0))
+                .finish(),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for Program<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Program")
+            .field("data", &self.data)
+            .field("flags", &self.flags)
+            .field("file_size", &Hex(self.file_size))
+            .field("memory_size", &Hex(self.memory_size))
+            .field("address_alignment", &Hex(self.address_alignment))
+            .finish()
+    }
+}
+
+impl<'a> fmt::Debug for SectionData<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SectionData::Null => f.write_str("Null"),
+            SectionData::ProgramBits(data) => {
+                f.debug_tuple("ProgramBits").field(&Bytes(data)).finish()
+            }
+            SectionData::SymbolTable {
+                table,
+                number_of_locals,
+            } => f
+                .debug_struct("SymbolTable")
+                .field("table", table)
+                .field("number_of_locals", number_of_locals)
+                .finish(),
+            SectionData::StringTable(table) => f.debug_tuple("StringTable").field(table).finish(),
+            SectionData::Hash(table) => f.debug_tuple("Hash").field(table).finish(),
+            SectionData::GnuHash(table) => f.debug_tuple("GnuHash").field(table).finish(),
+            SectionData::Dynamic(table) => f.debug_tuple("Dynamic").field(table).finish(),
+            SectionData::Rela {
+                table,
+                apply_to_section,
+            } => f
+                .debug_struct("Rela")
+                .field("table", table)
+                .field("apply_to_section", apply_to_section)
+                .finish(),
+            SectionData::Note(table) => f.debug_tuple("Note").field(table).finish(),
+            SectionData::NoBits { size } => {
+                f.debug_struct("NoBits").field("size", &Hex(*size)).finish()
+            }
+            SectionData::Rel {
+                table,
+                apply_to_section,
+            } => f
+                .debug_struct("Rel")
+                .field("table", table)
+                .field("apply_to_section", apply_to_section)
+                .finish(),
+            SectionData::Shlib(data) => f.debug_tuple("Shlib").field(&Bytes(data)).finish(),
+            SectionData::DynamicSymbolTable {
+                table,
+                number_of_locals,
+            } => f
+                .debug_struct("DynamicSymbolTable")
+                .field("table", table)
+                .field("number_of_locals", number_of_locals)
+                .finish(),
+            SectionData::OsSpecific { code, slice } => f
+                .debug_struct("OsSpecific")
+                .field("code", &format_args!("0x{:08x}", code))
+                .field("slice", &Bytes(slice))
+                .finish(),
+            SectionData::ProcessorSprcific { code, slice } => f
+                .debug_struct("ProcessorSprcific")
+                .field("code", &format_args!("0x{:08x}", code))
+                .field("slice", &Bytes(slice))
+                .finish(),
+            SectionData::Unknown { code, slice } => f
+                .debug_struct("Unknown")
+                .field("code", &format_args!("0x{:08x}", code))
+                .field("slice", &Bytes(slice))
+                .finish(),
+            SectionData::Compressed {
+                ty,
+                uncompressed_size,
+                alignment,
+                payload,
+            } => f
+                .debug_struct("Compressed")
+                .field("ty", ty)
+                .field("uncompressed_size", &Hex(*uncompressed_size))
+                .field("alignment", &Hex(*alignment))
+                .field("payload", &Bytes(payload))
+                .finish(),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for Section<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Section")
+            .field("name", &Name(self.name))
+            .field("data", &self.data)
+            .field("flags", &self.flags)
+            .field("address", &Hex(self.address.0))
+            .field("address_alignment", &Hex(self.address_alignment))
+            .field("link", &self.link)
+            .finish()
+    }
+}
+
+impl<'a> fmt::Debug for Elf64<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Programs<'a, 'b>(&'b Elf64<'a>);
+
+        impl<'a, 'b> fmt::Debug for Programs<'a, 'b> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let mut list = f.debug_list();
+                for index in 0..self.0.program_number() {
+                    match self.0.program_table.pick(index) {
+                        Ok(header) => list.entry(&header.ty),
+                        Err(_) => break,
+                    };
+                }
+                list.finish()
+            }
+        }
+
+        struct Sections<'a, 'b>(&'b Elf64<'a>);
+
+        impl<'a, 'b> fmt::Debug for Sections<'a, 'b> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let mut list = f.debug_list();
+                for index in 0..self.0.section_number() {
+                    let header = match self.0.section_table.pick(index) {
+                        Ok(header) => header,
+                        Err(_) => break,
+                    };
+                    let name = match &self.0.names {
+                        Some(names) => names.pick(header.name as usize).unwrap_or(&[]),
+                        None => &[],
+                    };
+                    list.entry(&format_args!("{:?} {:?}", Name(name), header.ty));
+                }
+                list.finish()
+            }
+        }
+
+        f.debug_struct("Elf64")
+            .field("size", &self.raw.len())
+            .field("class", &self.class())
+            .field("encoding", &self.encoding())
+            .field("type", &self.ty())
+            .field("machine", &self.machine())
+            .field("entry", &Hex(self.entry().0))
+            .field("programs", &Programs(self))
+            .field("sections", &Sections(self))
+            .finish()
+    }
+}
@@ -0,0 +1,130 @@
+use super::{Encoding, NoteEntry, BsdNote, GnuProperties};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GnuAbiOs {
+    Linux,
+    Hurd,
+    Solaris,
+    FreeBsd,
+    Unknown(u32),
+}
+
+impl From<u32> for GnuAbiOs {
+    fn from(v: u32) -> Self {
+        match v {
+            0 => GnuAbiOs::Linux,
+            1 => GnuAbiOs::Hurd,
+            2 => GnuAbiOs::Solaris,
+            3 => GnuAbiOs::FreeBsd,
+            t => GnuAbiOs::Unknown(t),
+        }
+    }
+}
+
+// Notes of the `GNU` owner
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GnuNote<'a> {
+    // `NT_GNU_ABI_TAG`, the minimal kernel version
+    AbiTag {
+        os: GnuAbiOs,
+        major: u32,
+        minor: u32,
+        patch: u32,
+    },
+    // `NT_GNU_HWCAP`
+    HardwareCapabilities(&'a [u8]),
+    // `NT_GNU_BUILD_ID`
+    BuildId(&'a [u8]),
+    // `NT_GNU_GOLD_VERSION`
+    GoldVersion(&'a [u8]),
+    // `NT_GNU_PROPERTY_TYPE_0`
+    Property(GnuProperties<'a>),
+    Other {
+        ty: u64,
+        description: &'a [u8],
+    },
+}
+
+// Notes of the `CORE` owner, the content is machine specific
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CoreNote<'a> {
+    // `NT_PRSTATUS`
+    Status(&'a [u8]),
+    // `NT_FPREGSET`
+    FloatingPointRegisters(&'a [u8]),
+    // `NT_PRPSINFO`
+    ProcessInfo(&'a [u8]),
+    // `NT_TASKSTRUCT`
+    TaskStruct(&'a [u8]),
+    // `NT_AUXV`
+    AuxiliaryVector(&'a [u8]),
+    // `NT_SIGINFO`
+    SignalInfo(&'a [u8]),
+    // `NT_FILE`
+    MappedFiles(&'a [u8]),
+    Other { ty: u64, description: &'a [u8] },
+}
+
+// Notes of the `LINUX` owner, extra register sets of core files
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LinuxNote<'a> {
+    // `NT_PRXFPREG`
+    ExtendedFloatingPointRegisters(&'a [u8]),
+    // `NT_X86_XSTATE`
+    X86ExtendedState(&'a [u8]),
+    Other { ty: u64, description: &'a [u8] },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DecodedNote<'a> {
+    Gnu(GnuNote<'a>),
+    Core(CoreNote<'a>),
+    Linux(LinuxNote<'a>),
+    Bsd(BsdNote<'a>),
+    Unknown(NoteEntry<'a>),
+}
+
+impl<'a> NoteEntry<'a> {
+    // The note by its owner and type
+    pub fn decode(&self) -> DecodedNote<'a> {
+        let description = self.description;
+        match self.name {
+            b"GNU" => DecodedNote::Gnu(match self.ty {
+                1 if description.len() >= 0x10 => {
+                    let word =
+                        |offset: usize| read_int!(&description[offset..], self.encoding(), u32);
+                    GnuNote::AbiTag {
+                        os: word(0x00).into(),
+                        major: word(0x04),
+                        minor: word(0x08),
+                        patch: word(0x0c),
+                    }
+                }
+                2 => GnuNote::HardwareCapabilities(description),
+                3 => GnuNote::BuildId(description),
+                4 => GnuNote::GoldVersion(description),
+                5 => GnuNote::Property(GnuProperties::new(description, self.encoding().clone())),
+                ty => GnuNote::Other { ty, description },
+            }),
+            b"CORE" => DecodedNote::Core(match self.ty {
+                1 => CoreNote::Status(description),
+                2 => CoreNote::FloatingPointRegisters(description),
+                3 => CoreNote::ProcessInfo(description),
+                4 => CoreNote::TaskStruct(description),
+                6 => CoreNote::AuxiliaryVector(description),
+                0x53494749 => CoreNote::SignalInfo(description),
+                0x46494c45 => CoreNote::MappedFiles(description),
+                ty => CoreNote::Other { ty, description },
+            }),
+            b"LINUX" => DecodedNote::Linux(match self.ty {
+                0x46e62b7f => LinuxNote::ExtendedFloatingPointRegisters(description),
+                0x202 => LinuxNote::X86ExtendedState(description),
+                ty => LinuxNote::Other { ty, description },
+            }),
+            _ => match self.bsd() {
+                Some(note) => DecodedNote::Bsd(note),
+                None => DecodedNote::Unknown(self.clone()),
+            },
+        }
+    }
+}
@@ -0,0 +1,543 @@
+use core::fmt;
+
+use super::{
+    Error, Elf64, Class, Encoding, Abi, Type, Machine, Index, SectionIndex, SectionType,
+    SectionFlags, ProgramType, ProgramFlags, SymbolBinding, SymbolType, DynamicTag, Lossy,
+};
+
+// Renders the whole file like `readelf -a -W`: the file header, the section headers,
+// the program headers, the dynamic section and the symbol tables. A part that fails
+// to parse is a line with the error, the rest of the summary is still rendered.
+#[derive(Clone)]
+pub struct Summary<'e, 'a> {
+    elf: &'e Elf64<'a>,
+}
+
+impl<'a> Elf64<'a> {
+    pub fn summary(&self) -> Summary<'_, 'a> {
+        Summary { elf: self }
+    }
+}
+
+impl<'e, 'a> fmt::Display for Summary<'e, 'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.header(f)?;
+        let parts: [Part<'e, 'a>; 4] = [
+            Summary::sections,
+            Summary::programs,
+            Summary::dynamic,
+            Summary::symbols,
+        ];
+        for part in parts.iter() {
+            writeln!(f)?;
+            // the error of the parser is in the output, the error of the formatter stops it
+            match part(self, f) {
+                Ok(()) => (),
+                Err(Failure::Parse(error)) => writeln!(f, "  <error: {}>", error)?,
+                Err(Failure::Format(error)) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+}
+
+type Part<'e, 'a> = fn(&Summary<'e, 'a>, &mut fmt::Formatter<'_>) -> Result<(), Failure>;
+
+// `Arguments` ignore the width, the padding follows the written characters
+fn pad(f: &mut fmt::Formatter<'_>, width: usize, args: fmt::Arguments<'_>) -> fmt::Result {
+    let mut counter = Counter { f, count: 0 };
+    fmt::Write::write_fmt(&mut counter, args)?;
+    for _ in counter.count..width {
+        counter.f.write_str(" ")?;
+    }
+    Ok(())
+}
+
+struct Counter<'f, 'g> {
+    f: &'f mut fmt::Formatter<'g>,
+    count: usize,
+}
+
+impl<'f, 'g> fmt::Write for Counter<'f, 'g> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.count += s.chars().count();
+        self.f.write_str(s)
+    }
+}
+
+enum Failure {
+    Parse(Error),
+    Format(fmt::Error),
+}
+
+impl From<Error> for Failure {
+    fn from(v: Error) -> Self {
+        Failure::Parse(v)
+    }
+}
+
+impl From<fmt::Error> for Failure {
+    fn from(v: fmt::Error) -> Self {
+        Failure::Format(v)
+    }
+}
+
+impl<'e, 'a> Summary<'e, 'a> {
+    fn header(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elf = self.elf;
+        writeln!(f, "ELF Header:")?;
+        f.write_str("  Magic:  ")?;
+        for byte in &elf.identifier() {
+            write!(f, " {:02x}", byte)?;
+        }
+        writeln!(f)?;
+        let class = match elf.class() {
+            Class::_32 => "ELF32",
+            Class::_64 => "ELF64",
+            Class::Unknown(_) => "<unknown>",
+        };
+        writeln!(f, "  {:<34} {}", "Class:", class)?;
+        let encoding = match elf.encoding() {
+            Encoding::Little => "2's complement, little endian",
+            Encoding::Big => "2's complement, big endian",
+        };
+        writeln!(f, "  {:<34} {}", "Data:", encoding)?;
+        writeln!(f, "  {:<34} {}", "Version:", elf.version())?;
+        write!(f, "  {:<34} ", "OS/ABI:")?;
+        abi(f, &elf.abi())?;
+        writeln!(f)?;
+        writeln!(f, "  {:<34} {}", "ABI Version:", elf.abi_version())?;
+        write!(f, "  {:<34} ", "Type:")?;
+        ty(f, &elf.ty())?;
+        writeln!(f)?;
+        write!(f, "  {:<34} ", "Machine:")?;
+        machine(f, &elf.machine())?;
+        writeln!(f)?;
+        writeln!(f, "  {:<34} 0x{:x}", "Entry point address:", elf.entry())?;
+        writeln!(f, "  {:<34} 0x{:x}", "Flags:", elf.flags())?;
+        writeln!(
+            f,
+            "  {:<34} {}",
+            "Number of program headers:",
+            elf.program_number()
+        )?;
+        writeln!(
+            f,
+            "  {:<34} {}",
+            "Number of section headers:",
+            elf.section_number()
+        )
+    }
+
+    fn sections(&self, f: &mut fmt::Formatter<'_>) -> Result<(), Failure> {
+        let elf = self.elf;
+        if elf.section_number() == 0 {
+            writeln!(f, "There are no sections in this file.")?;
+            return Ok(());
+        }
+        writeln!(f, "Section Headers:")?;
+        writeln!(
+            f,
+            "  [Nr] {:<17} {:<15} {:<16} {:<6} {:<6} ES Flg Lk Inf Al",
+            "Name", "Type", "Address", "Off", "Size"
+        )?;
+        for index in 0..elf.section_number() {
+            let header = elf.section_table.pick(index)?;
+            let name = elf
+                .section_info(SectionIndex(index))?
+                .map_or(&[][..], |info| info.name);
+            write!(f, "  [{:>2}] ", index)?;
+            pad(f, 17, format_args!("{}", Lossy(name)))?;
+            f.write_str(" ")?;
+            section_type(f, &header.ty)?;
+            write!(
+                f,
+                " {:016x} {:06x} {:06x} {:02x} ",
+                header.address, header.offset, header.size, header.number_of_entries
+            )?;
+            section_flags(f, header.flags)?;
+            let link = match header.link {
+                Index::Regular(link) => link.0,
+                _ => 0,
+            };
+            writeln!(
+                f,
+                " {:>2} {:>3} {:>2}",
+                link, header.info, header.address_alignment
+            )?;
+        }
+        writeln!(f, "Key to Flags:")?;
+        writeln!(
+            f,
+            "  W (write), A (alloc), X (execute), M (merge), S (strings), I (info),"
+        )?;
+        writeln!(
+            f,
+            "  L (link order), O (extra OS processing required), G (group), T (TLS),"
+        )?;
+        writeln!(f, "  C (compressed)")?;
+        Ok(())
+    }
+
+    fn programs(&self, f: &mut fmt::Formatter<'_>) -> Result<(), Failure> {
+        let elf = self.elf;
+        if elf.program_number() == 0 {
+            writeln!(f, "There are no program headers in this file.")?;
+            return Ok(());
+        }
+        writeln!(f, "Program Headers:")?;
+        writeln!(
+            f,
+            "  {:<14} {:<8} {:<18} {:<18} {:<8} {:<8} Flg Align",
+            "Type", "Offset", "VirtAddr", "PhysAddr", "FileSiz", "MemSiz"
+        )?;
+        for index in 0..elf.program_number() {
+            let header = elf.program_table.pick(index)?;
+            f.write_str("  ")?;
+            program_type(f, &header.ty)?;
+            write!(
+                f,
+                " 0x{:06x} 0x{:016x} 0x{:016x} 0x{:06x} 0x{:06x} ",
+                header.file_offset,
+                header.virtual_address,
+                header.physical_address,
+                header.file_size,
+                header.memory_size
+            )?;
+            let flag = |flag, c| if header.flags.contains(flag) { c } else { ' ' };
+            writeln!(
+                f,
+                "{}{}{} 0x{:x}",
+                flag(ProgramFlags::READ, 'R'),
+                flag(ProgramFlags::WRITE, 'W'),
+                flag(ProgramFlags::EXECUTE, 'E'),
+                header.address_alignment
+            )?;
+        }
+        Ok(())
+    }
+
+    fn dynamic(&self, f: &mut fmt::Formatter<'_>) -> Result<(), Failure> {
+        let source = match self.elf.dynamic_source()? {
+            Some(source) => source,
+            None => {
+                writeln!(f, "There is no dynamic section in this file.")?;
+                return Ok(());
+            }
+        };
+        writeln!(f, "Dynamic section:")?;
+        writeln!(f, "  {:<18} {:<20} Name/Value", "Tag", "Type")?;
+        for entry in source.entries() {
+            let entry = entry?;
+            write!(f, "  0x{:016x} ", u64::from(entry.tag.clone()))?;
+            match dynamic_tag(&entry.tag) {
+                Some(name) => pad(f, 20, format_args!("({})", name))?,
+                None => write!(f, "{:<20}", "<unknown>")?,
+            }
+            match source.entry_string(&entry) {
+                Ok(Some(string)) => {
+                    let kind = match entry.tag {
+                        DynamicTag::Needed => "Shared library",
+                        DynamicTag::Soname => "Library soname",
+                        DynamicTag::Rpath => "Library rpath",
+                        DynamicTag::Runpath => "Library runpath",
+                        _ => "Audit library",
+                    };
+                    writeln!(f, " {}: [{}]", kind, Lossy(string))?;
+                }
+                _ => writeln!(f, " 0x{:x}", entry.value)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn symbols(&self, f: &mut fmt::Formatter<'_>) -> Result<(), Failure> {
+        let elf = self.elf;
+        let mut first = true;
+        for index in 0..elf.section_number() {
+            let header = elf.section_table.pick(index)?;
+            if header.ty != SectionType::SymbolTable && header.ty != SectionType::DynamicSymbolTable
+            {
+                continue;
+            }
+            let name = elf
+                .section_info(SectionIndex(index))?
+                .map_or(&[][..], |info| info.name);
+            let source = elf.symbol_source_at(index)?;
+            if !first {
+                writeln!(f)?;
+            }
+            first = false;
+            writeln!(
+                f,
+                "Symbol table '{}' contains {} entries:",
+                Lossy(name),
+                source.number
+            )?;
+            writeln!(
+                f,
+                "{:>6}: {:<16} {:>5} {:<7} {:<6} {:<8} {:>3} Name",
+                "Num", "Value", "Size", "Type", "Bind", "Vis", "Ndx"
+            )?;
+            for number in 0..source.number {
+                let symbol = source.get(number)?;
+                let entry = &symbol.entry;
+                write!(f, "{:>6}: {:016x} {:>5} ", number, entry.value, entry.size)?;
+                symbol_type(f, &entry.info.ty)?;
+                f.write_str(" ")?;
+                symbol_binding(f, &entry.info.binding)?;
+                let visibility = match entry.reserved & 0x3 {
+                    0 => "DEFAULT",
+                    1 => "INTERNAL",
+                    2 => "HIDDEN",
+                    _ => "PROTECTED",
+                };
+                write!(f, " {:<8} ", visibility)?;
+                match &entry.section_index {
+                    Index::Undefined => write!(f, "{:>3}", "UND")?,
+                    Index::AbsoluteValue => write!(f, "{:>3}", "ABS")?,
+                    Index::Common => write!(f, "{:>3}", "COM")?,
+                    Index::Extended => write!(f, "{:>3}", "XIX")?,
+                    Index::Regular(section) => write!(f, "{:>3}", section.0)?,
+                    Index::ProcessorSecific(_) | Index::EnvironmentSpecific(_) => {
+                        write!(f, "{:>3}", "RSV")?
+                    }
+                }
+                writeln!(f, " {}", symbol.name_lossy())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn abi(f: &mut fmt::Formatter<'_>, abi: &Abi) -> fmt::Result {
+    let name = match abi {
+        Abi::SystemV => "UNIX - System V",
+        Abi::HpUx => "UNIX - HP-UX",
+        Abi::NetBSD => "UNIX - NetBSD",
+        Abi::Linux => "UNIX - GNU",
+        Abi::Solaris => "UNIX - Solaris",
+        Abi::Aix => "UNIX - AIX",
+        Abi::Irix => "UNIX - IRIX",
+        Abi::FreeBSD => "UNIX - FreeBSD",
+        Abi::OpenBSD => "UNIX - OpenBSD",
+        Abi::OpenVMS => "VMS - OpenVMS",
+        Abi::Standalone => "Standalone App",
+        Abi::Unknown(t) => return write!(f, "<unknown: {:x}>", t),
+    };
+    f.write_str(name)
+}
+
+fn ty(f: &mut fmt::Formatter<'_>, ty: &Type) -> fmt::Result {
+    let name = match ty {
+        Type::None => "NONE (None)",
+        Type::Relocatable => "REL (Relocatable file)",
+        Type::Executable => "EXEC (Executable file)",
+        Type::SharedObject => "DYN (Shared object file)",
+        Type::Core => "CORE (Core file)",
+        Type::OsSpecific(t) => return write!(f, "OS Specific: ({:x})", t),
+        Type::ProcessorSpecific(t) => return write!(f, "Processor Specific: ({:x})", t),
+        Type::Unknown(t) => return write!(f, "<unknown>: {:x}", t),
+    };
+    f.write_str(name)
+}
+
+fn machine(f: &mut fmt::Formatter<'_>, machine: &Machine) -> fmt::Result {
+    let name = match machine {
+        Machine::None => "None",
+        Machine::Sparc => "Sparc",
+        Machine::X86 => "Intel 80386",
+        Machine::Mips => "MIPS R3000",
+        Machine::PowerPC => "PowerPC",
+        Machine::PowerPC64 => "PowerPC64",
+        Machine::Arm => "ARM",
+        Machine::SuperH => "Renesas / SuperH SH",
+        Machine::Ia64 => "Intel IA-64",
+        Machine::X86_64 => "Advanced Micro Devices X86-64",
+        Machine::AArch64 => "AArch64",
+        Machine::RiscV => "RISC-V",
+        Machine::Bpf => "Linux BPF",
+        Machine::Unknown(t) => return write!(f, "<unknown>: 0x{:x}", t),
+    };
+    f.write_str(name)
+}
+
+// 15 columns
+fn section_type(f: &mut fmt::Formatter<'_>, ty: &SectionType) -> fmt::Result {
+    let name = match ty {
+        SectionType::Null => "NULL",
+        SectionType::ProgramBits => "PROGBITS",
+        SectionType::SymbolTable => "SYMTAB",
+        SectionType::StringTable => "STRTAB",
+        SectionType::Rela => "RELA",
+        SectionType::Hash => "HASH",
+        SectionType::Dynamic => "DYNAMIC",
+        SectionType::Note => "NOTE",
+        SectionType::NoBits => "NOBITS",
+        SectionType::Rel => "REL",
+        SectionType::Shlib => "SHLIB",
+        SectionType::DynamicSymbolTable => "DYNSYM",
+        SectionType::OsSpecific(t) | SectionType::Unknown(t) => match t {
+            0x0e => "INIT_ARRAY",
+            0x0f => "FINI_ARRAY",
+            0x10 => "PREINIT_ARRAY",
+            0x11 => "GROUP",
+            0x12 => "SYMTAB SECTION INDICES",
+            0x13 => "RELR",
+            0x6ffffff5 => "GNU_ATTRIBUTES",
+            0x6ffffff6 => "GNU_HASH",
+            0x6ffffffd => "VERDEF",
+            0x6ffffffe => "VERNEED",
+            0x6fffffff => "VERSYM",
+            t => return pad(f, 15, format_args!("0x{:x}", t)),
+        },
+        SectionType::ProcessorSprcific(t) => match t {
+            0x70000001 => "X86_64_UNWIND",
+            0x70000003 => "ARM_ATTRIBUTES",
+            t => return pad(f, 15, format_args!("LOPROC+0x{:x}", t - 0x70000000)),
+        },
+    };
+    write!(f, "{:<15}", name)
+}
+
+// 3 columns
+fn section_flags(f: &mut fmt::Formatter<'_>, flags: SectionFlags) -> fmt::Result {
+    let letters = [
+        (SectionFlags::WRITE, 'W'),
+        (SectionFlags::ALLOC, 'A'),
+        (SectionFlags::EXECINSTR, 'X'),
+        (SectionFlags::MERGE, 'M'),
+        (SectionFlags::STRINGS, 'S'),
+        (SectionFlags::INFO_LINK, 'I'),
+        (SectionFlags::LINK_ORDER, 'L'),
+        (SectionFlags::OS_NONCONFORMING, 'O'),
+        (SectionFlags::GROUP, 'G'),
+        (SectionFlags::TLS, 'T'),
+        (SectionFlags::COMPRESSED, 'C'),
+    ];
+    let mut written = 0;
+    for (flag, letter) in letters.iter() {
+        if flags.contains(*flag) {
+            write!(f, "{}", letter)?;
+            written += 1;
+        }
+    }
+    for _ in written..3 {
+        f.write_str(" ")?;
+    }
+    Ok(())
+}
+
+// 14 columns
+fn program_type(f: &mut fmt::Formatter<'_>, ty: &ProgramType) -> fmt::Result {
+    let name = match ty {
+        ProgramType::Null => "NULL",
+        ProgramType::Load => "LOAD",
+        ProgramType::Dynamic => "DYNAMIC",
+        ProgramType::Interpreter => "INTERP",
+        ProgramType::Note => "NOTE",
+        ProgramType::Shlib => "SHLIB",
+        ProgramType::ProgramHeaderTable => "PHDR",
+        ProgramType::Tls => "TLS",
+        ProgramType::GnuEhFrame => "GNU_EH_FRAME",
+        ProgramType::GnuStack => "GNU_STACK",
+        ProgramType::GnuRelro => "GNU_RELRO",
+        ProgramType::GnuProperty => "GNU_PROPERTY",
+        ProgramType::OsSpecific(t)
+        | ProgramType::ProcessorSprcific(t)
+        | ProgramType::Unknown(t) => return pad(f, 14, format_args!("0x{:x}", t)),
+    };
+    write!(f, "{:<14}", name)
+}
+
+fn dynamic_tag(tag: &DynamicTag) -> Option<&'static str> {
+    let name = match tag {
+        DynamicTag::Null => "NULL",
+        DynamicTag::Needed => "NEEDED",
+        DynamicTag::PltRelocationSize => "PLTRELSZ",
+        DynamicTag::PltGot => "PLTGOT",
+        DynamicTag::Hash => "HASH",
+        DynamicTag::StringTable => "STRTAB",
+        DynamicTag::SymbolTable => "SYMTAB",
+        DynamicTag::Rela => "RELA",
+        DynamicTag::RelaSize => "RELASZ",
+        DynamicTag::RelaEntrySize => "RELAENT",
+        DynamicTag::StringTableSize => "STRSZ",
+        DynamicTag::SymbolEntrySize => "SYMENT",
+        DynamicTag::Init => "INIT",
+        DynamicTag::Fini => "FINI",
+        DynamicTag::Soname => "SONAME",
+        DynamicTag::Rpath => "RPATH",
+        DynamicTag::Symbolic => "SYMBOLIC",
+        DynamicTag::Rel => "REL",
+        DynamicTag::RelSize => "RELSZ",
+        DynamicTag::RelEntrySize => "RELENT",
+        DynamicTag::PltRelocation => "PLTREL",
+        DynamicTag::Debug => "DEBUG",
+        DynamicTag::TextRelocation => "TEXTREL",
+        DynamicTag::JumpRelocation => "JMPREL",
+        DynamicTag::BindNow => "BIND_NOW",
+        DynamicTag::InitArray => "INIT_ARRAY",
+        DynamicTag::FiniArray => "FINI_ARRAY",
+        DynamicTag::InitArraySize => "INIT_ARRAYSZ",
+        DynamicTag::FiniArraySize => "FINI_ARRAYSZ",
+        DynamicTag::Runpath => "RUNPATH",
+        DynamicTag::Flags => "FLAGS",
+        DynamicTag::PreinitArray => "PREINIT_ARRAY",
+        DynamicTag::PreinitArraySize => "PREINIT_ARRAYSZ",
+        DynamicTag::SymbolTableIndex => "SYMTAB_SHNDX",
+        DynamicTag::RelrSize => "RELRSZ",
+        DynamicTag::Relr => "RELR",
+        DynamicTag::RelrEntrySize => "RELRENT",
+        DynamicTag::Flags1 => "FLAGS_1",
+        DynamicTag::GnuHash => "GNU_HASH",
+        DynamicTag::Versym => "VERSYM",
+        DynamicTag::Verneed => "VERNEED",
+        DynamicTag::VerneedNumber => "VERNEEDNUM",
+        DynamicTag::Verdef => "VERDEF",
+        DynamicTag::VerdefNumber => "VERDEFNUM",
+        DynamicTag::Audit => "AUDIT",
+        DynamicTag::DependencyAudit => "DEPAUDIT",
+        DynamicTag::OsSpecific(t) => match t {
+            0x6ffffff9 => "RELACOUNT",
+            0x6ffffffa => "RELCOUNT",
+            _ => return None,
+        },
+        DynamicTag::ProcessorSpecific(_) | DynamicTag::Unknown(_) => return None,
+    };
+    Some(name)
+}
+
+// 7 columns
+fn symbol_type(f: &mut fmt::Formatter<'_>, ty: &SymbolType) -> fmt::Result {
+    let name = match ty {
+        SymbolType::Nothing => "NOTYPE",
+        SymbolType::Object => "OBJECT",
+        SymbolType::Function => "FUNC",
+        SymbolType::Section => "SECTION",
+        SymbolType::File => "FILE",
+        SymbolType::OsSpecific(0) => "IFUNC",
+        SymbolType::OsSpecific(t) => return pad(f, 7, format_args!("OS+{}", t)),
+        SymbolType::ProcessorSpecific(t) => return pad(f, 7, format_args!("PROC+{}", t)),
+        SymbolType::Unknown(t) => match t {
+            5 => "COMMON",
+            6 => "TLS",
+            t => return pad(f, 7, format_args!("<{}>", t)),
+        },
+    };
+    write!(f, "{:<7}", name)
+}
+
+// 6 columns
+fn symbol_binding(f: &mut fmt::Formatter<'_>, binding: &SymbolBinding) -> fmt::Result {
+    let name = match binding {
+        SymbolBinding::Local => "LOCAL",
+        SymbolBinding::Global => "GLOBAL",
+        SymbolBinding::Weak => "WEAK",
+        SymbolBinding::OsSpecific(0) => "UNIQUE",
+        SymbolBinding::OsSpecific(t) => return pad(f, 6, format_args!("OS+{}", t)),
+        SymbolBinding::ProcessorSpecific(t) => return pad(f, 6, format_args!("PROC+{}", t)),
+        SymbolBinding::Unknown(t) => return pad(f, 6, format_args!("<{}>", t)),
+    };
+    write!(f, "{:<6}", name)
+}
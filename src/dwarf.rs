@@ -0,0 +1,44 @@
+use alloc::{borrow::Cow, rc::Rc};
+use gimli::{Dwarf, EndianRcSlice, RunTimeEndian, SectionId};
+
+use super::{Error, Encoding, Elf64, SectionData};
+
+impl<'a> Elf64<'a> {
+    // The content of the DWARF section, decompressed if it is `SHF_COMPRESSED` or if it
+    // is the old style `.zdebug_*` section; empty if the file has no such section.
+    // This is the loader `gimli::Dwarf::load` expects.
+    pub fn dwarf_section(&self, id: SectionId) -> Result<Cow<'a, [u8]>, Error> {
+        let name = id.name().as_bytes();
+        if let Some(section) = self.section_by_name(name)? {
+            return section.data.decompressed();
+        }
+        let mut zname = [0; 32];
+        let zname = match name.strip_prefix(b".debug_") {
+            Some(suffix) if suffix.len() + 8 <= zname.len() => {
+                zname[..8].clone_from_slice(b".zdebug_");
+                zname[8..(8 + suffix.len())].clone_from_slice(suffix);
+                &zname[..(8 + suffix.len())]
+            }
+            _ => return Ok(Cow::Borrowed(&[])),
+        };
+        match self.section_by_name(zname)?.map(|section| section.data) {
+            Some(data @ SectionData::Compressed { .. })
+            | Some(data @ SectionData::NoBits { .. }) => data.decompressed(),
+            // without "ZLIB" and the size
+            Some(_) => Err(Error::CorruptedCompression),
+            None => Ok(Cow::Borrowed(&[])),
+        }
+    }
+
+    // All DWARF sections of the file, ready for `addr2line::Context::from_dwarf`
+    pub fn dwarf(&self) -> Result<Dwarf<EndianRcSlice<RunTimeEndian>>, Error> {
+        let endian = match self.encoding() {
+            Encoding::Little => RunTimeEndian::Little,
+            Encoding::Big => RunTimeEndian::Big,
+        };
+        Dwarf::load(|id| {
+            let data: Rc<[u8]> = self.dwarf_section(id)?.into();
+            Ok(EndianRcSlice::new(data, endian))
+        })
+    }
+}
@@ -0,0 +1,539 @@
+use core::fmt;
+
+use super::{
+    Error, Address, Offset, SectionIndex, Class, Encoding, Entry, Table, Elf64, Elf32, SectionType,
+    SectionData, ProgramType, StringTable, Index,
+};
+use super::elf32::DynamicEntry32;
+use super::common::file_slice;
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DynamicTag {
+    Null,
+    Needed,
+    PltRelocationSize,
+    PltGot,
+    Hash,
+    StringTable,
+    SymbolTable,
+    Rela,
+    RelaSize,
+    RelaEntrySize,
+    StringTableSize,
+    SymbolEntrySize,
+    Init,
+    Fini,
+    Soname,
+    Rpath,
+    Symbolic,
+    Rel,
+    RelSize,
+    RelEntrySize,
+    // `DT_REL` or `DT_RELA`, the format of `DT_JMPREL`
+    PltRelocation,
+    Debug,
+    TextRelocation,
+    JumpRelocation,
+    BindNow,
+    InitArray,
+    FiniArray,
+    InitArraySize,
+    FiniArraySize,
+    Runpath,
+    Flags,
+    PreinitArray,
+    PreinitArraySize,
+    SymbolTableIndex,
+    RelrSize,
+    Relr,
+    RelrEntrySize,
+    // `DT_FLAGS_1`
+    Flags1,
+    // `DT_GNU_HASH`
+    GnuHash,
+    // `DT_VERSYM`, `.gnu.version`
+    Versym,
+    // `DT_VERNEED` and `DT_VERNEEDNUM`, `.gnu.version_r`
+    Verneed,
+    VerneedNumber,
+    // `DT_VERDEF` and `DT_VERDEFNUM`, `.gnu.version_d`
+    Verdef,
+    VerdefNumber,
+    // `DT_AUDIT` and `DT_DEPAUDIT`, the auditing libraries, strings
+    Audit,
+    DependencyAudit,
+    OsSpecific(u64),
+    ProcessorSpecific(u64),
+    Unknown(u64),
+}
+
+impl From<u64> for DynamicTag {
+    fn from(v: u64) -> Self {
+        match v {
+            0 => DynamicTag::Null,
+            1 => DynamicTag::Needed,
+            2 => DynamicTag::PltRelocationSize,
+            3 => DynamicTag::PltGot,
+            4 => DynamicTag::Hash,
+            5 => DynamicTag::StringTable,
+            6 => DynamicTag::SymbolTable,
+            7 => DynamicTag::Rela,
+            8 => DynamicTag::RelaSize,
+            9 => DynamicTag::RelaEntrySize,
+            10 => DynamicTag::StringTableSize,
+            11 => DynamicTag::SymbolEntrySize,
+            12 => DynamicTag::Init,
+            13 => DynamicTag::Fini,
+            14 => DynamicTag::Soname,
+            15 => DynamicTag::Rpath,
+            16 => DynamicTag::Symbolic,
+            17 => DynamicTag::Rel,
+            18 => DynamicTag::RelSize,
+            19 => DynamicTag::RelEntrySize,
+            20 => DynamicTag::PltRelocation,
+            21 => DynamicTag::Debug,
+            22 => DynamicTag::TextRelocation,
+            23 => DynamicTag::JumpRelocation,
+            24 => DynamicTag::BindNow,
+            25 => DynamicTag::InitArray,
+            26 => DynamicTag::FiniArray,
+            27 => DynamicTag::InitArraySize,
+            28 => DynamicTag::FiniArraySize,
+            29 => DynamicTag::Runpath,
+            30 => DynamicTag::Flags,
+            32 => DynamicTag::PreinitArray,
+            33 => DynamicTag::PreinitArraySize,
+            34 => DynamicTag::SymbolTableIndex,
+            35 => DynamicTag::RelrSize,
+            36 => DynamicTag::Relr,
+            37 => DynamicTag::RelrEntrySize,
+            0x6ffffffb => DynamicTag::Flags1,
+            0x6ffffef5 => DynamicTag::GnuHash,
+            0x6ffffff0 => DynamicTag::Versym,
+            0x6ffffffe => DynamicTag::Verneed,
+            0x6fffffff => DynamicTag::VerneedNumber,
+            0x6ffffffc => DynamicTag::Verdef,
+            0x6ffffffd => DynamicTag::VerdefNumber,
+            0x6ffffefc => DynamicTag::Audit,
+            0x6ffffefb => DynamicTag::DependencyAudit,
+            t @ 0x60000000..=0x6fffffff => DynamicTag::OsSpecific(t),
+            t @ 0x70000000..=0x7fffffff => DynamicTag::ProcessorSpecific(t),
+            t => DynamicTag::Unknown(t),
+        }
+    }
+}
+
+impl From<DynamicTag> for u64 {
+    fn from(v: DynamicTag) -> Self {
+        match v {
+            DynamicTag::Null => 0,
+            DynamicTag::Needed => 1,
+            DynamicTag::PltRelocationSize => 2,
+            DynamicTag::PltGot => 3,
+            DynamicTag::Hash => 4,
+            DynamicTag::StringTable => 5,
+            DynamicTag::SymbolTable => 6,
+            DynamicTag::Rela => 7,
+            DynamicTag::RelaSize => 8,
+            DynamicTag::RelaEntrySize => 9,
+            DynamicTag::StringTableSize => 10,
+            DynamicTag::SymbolEntrySize => 11,
+            DynamicTag::Init => 12,
+            DynamicTag::Fini => 13,
+            DynamicTag::Soname => 14,
+            DynamicTag::Rpath => 15,
+            DynamicTag::Symbolic => 16,
+            DynamicTag::Rel => 17,
+            DynamicTag::RelSize => 18,
+            DynamicTag::RelEntrySize => 19,
+            DynamicTag::PltRelocation => 20,
+            DynamicTag::Debug => 21,
+            DynamicTag::TextRelocation => 22,
+            DynamicTag::JumpRelocation => 23,
+            DynamicTag::BindNow => 24,
+            DynamicTag::InitArray => 25,
+            DynamicTag::FiniArray => 26,
+            DynamicTag::InitArraySize => 27,
+            DynamicTag::FiniArraySize => 28,
+            DynamicTag::Runpath => 29,
+            DynamicTag::Flags => 30,
+            DynamicTag::PreinitArray => 32,
+            DynamicTag::PreinitArraySize => 33,
+            DynamicTag::SymbolTableIndex => 34,
+            DynamicTag::RelrSize => 35,
+            DynamicTag::Relr => 36,
+            DynamicTag::RelrEntrySize => 37,
+            DynamicTag::Flags1 => 0x6ffffffb,
+            DynamicTag::GnuHash => 0x6ffffef5,
+            DynamicTag::Versym => 0x6ffffff0,
+            DynamicTag::Verneed => 0x6ffffffe,
+            DynamicTag::VerneedNumber => 0x6fffffff,
+            DynamicTag::Verdef => 0x6ffffffc,
+            DynamicTag::VerdefNumber => 0x6ffffffd,
+            DynamicTag::Audit => 0x6ffffefc,
+            DynamicTag::DependencyAudit => 0x6ffffefb,
+            DynamicTag::OsSpecific(t) => t,
+            DynamicTag::ProcessorSpecific(t) => t,
+            DynamicTag::Unknown(t) => t,
+        }
+    }
+}
+
+impl DynamicTag {
+    // the value is an offset in the dynamic string table
+    pub fn is_string(&self) -> bool {
+        matches!(
+            self,
+            DynamicTag::Needed
+                | DynamicTag::Soname
+                | DynamicTag::Rpath
+                | DynamicTag::Runpath
+                | DynamicTag::Audit
+                | DynamicTag::DependencyAudit
+        )
+    }
+}
+
+bitflags! {
+    // `DT_FLAGS`, `DF_*`
+    pub struct DynamicFlags: u64 {
+        const ORIGIN = 0x00000001;
+        const SYMBOLIC = 0x00000002;
+        const TEXTREL = 0x00000004;
+        const BIND_NOW = 0x00000008;
+        const STATIC_TLS = 0x00000010;
+    }
+}
+
+bitflags! {
+    // `DT_FLAGS_1`, `DF_1_*`, what `ldd` and `elfdump` show
+    pub struct DynamicFlags1: u64 {
+        const NOW = 0x00000001;
+        const GLOBAL = 0x00000002;
+        const GROUP = 0x00000004;
+        const NODELETE = 0x00000008;
+        const LOADFLTR = 0x00000010;
+        const INITFIRST = 0x00000020;
+        const NOOPEN = 0x00000040;
+        const ORIGIN = 0x00000080;
+        const DIRECT = 0x00000100;
+        const TRANS = 0x00000200;
+        const INTERPOSE = 0x00000400;
+        const NODEFLIB = 0x00000800;
+        const NODUMP = 0x00001000;
+        const CONFALT = 0x00002000;
+        const ENDFILTEE = 0x00004000;
+        const DISPRELDNE = 0x00008000;
+        const DISPRELPND = 0x00010000;
+        const NODIRECT = 0x00020000;
+        const IGNMULDEF = 0x00040000;
+        const NOKSYMS = 0x00080000;
+        const NOHDR = 0x00100000;
+        const EDITED = 0x00200000;
+        const NORELOC = 0x00400000;
+        const SYMINTPOSE = 0x00800000;
+        const GLOBAUDIT = 0x01000000;
+        const SINGLETON = 0x02000000;
+        const STUB = 0x04000000;
+        const PIE = 0x08000000;
+    }
+}
+
+// `Elf64_Dyn`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DynamicEntry {
+    pub tag: DynamicTag,
+    pub value: u64,
+}
+
+impl Entry for DynamicEntry {
+    type Error = Error;
+
+    const SIZE: usize = 0x10;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        Ok(DynamicEntry {
+            tag: read_int!(&slice[0x00..], &encoding, u64).into(),
+            value: read_int!(&slice[0x08..], &encoding, u64),
+        })
+    }
+}
+
+// The entries of `SHT_DYNAMIC`, or of `PT_DYNAMIC`, and the strings they refer to
+#[derive(Clone)]
+pub struct DynamicTable<'a> {
+    table: DynamicEntries<'a>,
+    strings: StringTable<'a>,
+}
+
+// `Elf64_Dyn`, or `Elf32_Dyn` widened to it
+#[derive(Clone)]
+enum DynamicEntries<'a> {
+    Elf64(Table<'a, DynamicEntry>),
+    Elf32(Table<'a, DynamicEntry32>),
+}
+
+impl<'a> DynamicEntries<'a> {
+    fn len(&self) -> usize {
+        match self {
+            DynamicEntries::Elf64(table) => table.len(),
+            DynamicEntries::Elf32(table) => table.len(),
+        }
+    }
+
+    fn pick(&self, index: usize) -> Result<DynamicEntry, Error> {
+        match self {
+            DynamicEntries::Elf64(table) => table.pick(index),
+            DynamicEntries::Elf32(table) => table.pick(index).map(|entry| entry.0),
+        }
+    }
+}
+
+impl<'a> DynamicTable<'a> {
+    // the entries of `PT_DYNAMIC` of the class, the strings are empty until
+    // they are found at `DT_STRTAB`
+    pub(crate) fn of_segment(slice: &'a [u8], encoding: Encoding, class: Class) -> Self {
+        let table = match class {
+            Class::_32 => DynamicEntries::Elf32(Table::new(slice, encoding)),
+            _ => DynamicEntries::Elf64(Table::new(slice, encoding)),
+        };
+        DynamicTable {
+            table,
+            strings: StringTable::new(&[]),
+        }
+    }
+
+    // entries up to `DT_NULL`
+    pub fn entries(&self) -> impl Iterator<Item = Result<DynamicEntry, Error>> + 'a {
+        let table = self.table.clone();
+        (0..self.table.len())
+            .map(move |index| table.pick(index))
+            .take_while(|entry| {
+                !matches!(
+                    entry,
+                    Ok(DynamicEntry {
+                        tag: DynamicTag::Null,
+                        ..
+                    })
+                )
+            })
+    }
+
+    // the string at the value in the dynamic string table
+    pub fn string(&self, value: u64) -> Result<&'a [u8], Error> {
+        self.strings.pick_at(Offset(value))
+    }
+
+    // the string of the entry, `None` if the tag is not of a string
+    pub fn entry_string(&self, entry: &DynamicEntry) -> Result<Option<&'a [u8]>, Error> {
+        if entry.tag.is_string() {
+            self.string(entry.value).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    // the value of the first entry with the tag
+    pub fn value(&self, tag: DynamicTag) -> Result<Option<u64>, Error> {
+        for entry in self.entries() {
+            let entry = entry?;
+            if entry.tag == tag {
+                return Ok(Some(entry.value));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> fmt::Debug for DynamicTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("DynamicTable");
+        match &self.table {
+            DynamicEntries::Elf64(table) => s.field("table", table),
+            DynamicEntries::Elf32(table) => s.field("table", table),
+        };
+        s.field("strings", &self.strings).finish()
+    }
+}
+
+impl<'a> Elf64<'a> {
+    pub(crate) fn dynamic_source(&self) -> Result<Option<DynamicTable<'a>>, Error> {
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            if header.ty != SectionType::Dynamic {
+                continue;
+            }
+            let slice = file_slice(self.raw, header.offset, header.size)?;
+            let strings = match header.link {
+                Index::Regular(link) => match self.section(link)?.map(|s| s.data) {
+                    Some(SectionData::StringTable(strings)) => strings,
+                    _ => return Err(Error::UnexpectedSectionType),
+                },
+                _ => return Err(Error::UnexpectedSectionType),
+            };
+            return Ok(Some(DynamicTable {
+                table: DynamicEntries::Elf64(Table::with_entry_size(
+                    slice,
+                    self.encoding(),
+                    header.number_of_entries,
+                )),
+                strings,
+            }));
+        }
+        self.dynamic_segment_source()
+    }
+
+    // `PT_DYNAMIC` of a file without section headers, the strings are
+    // at `DT_STRTAB` in a `PT_LOAD`
+    fn dynamic_segment_source(&self) -> Result<Option<DynamicTable<'a>>, Error> {
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            if header.ty != ProgramType::Dynamic {
+                continue;
+            }
+            let slice = file_slice(self.raw, header.file_offset, header.file_size)?;
+            let mut source = DynamicTable::of_segment(slice, self.encoding(), Class::_64);
+            let (mut address, mut size) = (None, None);
+            for entry in source.entries() {
+                let entry = entry?;
+                match entry.tag {
+                    DynamicTag::StringTable => address = Some(entry.value),
+                    DynamicTag::StringTableSize => size = Some(entry.value),
+                    _ => (),
+                }
+            }
+            log_debug!("dynamic: no SHT_DYNAMIC, the entries of program {}", index);
+            if let (Some(address), Some(size)) = (address, size) {
+                source.strings = StringTable::new(self.virtual_slice(Address(address), size)?)
+                    .limited(self.limits.string_length);
+            } else {
+                log_debug!("dynamic: no DT_STRTAB or DT_STRSZ, the strings are empty");
+            }
+            return Ok(Some(source));
+        }
+        Ok(None)
+    }
+
+    // `SHT_DYNAMIC` with the string table of its link, or `PT_DYNAMIC`
+    // of a file without section headers
+    pub fn dynamic(&self) -> Result<Option<DynamicTable<'a>>, Error> {
+        self.dynamic_source()
+    }
+
+    // `DT_SONAME`
+    pub fn soname(&self) -> Result<Option<&'a [u8]>, Error> {
+        self.dynamic_string(DynamicTag::Soname)
+    }
+
+    // `DT_RPATH`, the runtime linker ignores it if there is `DT_RUNPATH`
+    pub fn rpath(&self) -> Result<Option<&'a [u8]>, Error> {
+        self.dynamic_string(DynamicTag::Rpath)
+    }
+
+    // `DT_RUNPATH`
+    pub fn runpath(&self) -> Result<Option<&'a [u8]>, Error> {
+        self.dynamic_string(DynamicTag::Runpath)
+    }
+
+    fn dynamic_string(&self, tag: DynamicTag) -> Result<Option<&'a [u8]>, Error> {
+        let source = match self.dynamic_source()? {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+        match source.value(tag)? {
+            Some(value) => source.string(value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // `DT_FLAGS`, the bits this crate does not know are dropped
+    pub fn dynamic_flags(&self) -> Result<Option<DynamicFlags>, Error> {
+        let source = match self.dynamic_source()? {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+        Ok(source
+            .value(DynamicTag::Flags)?
+            .map(DynamicFlags::from_bits_truncate))
+    }
+
+    // `DT_FLAGS_1`, the bits this crate does not know are dropped
+    pub fn dynamic_flags_1(&self) -> Result<Option<DynamicFlags1>, Error> {
+        let source = match self.dynamic_source()? {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+        Ok(source
+            .value(DynamicTag::Flags1)?
+            .map(DynamicFlags1::from_bits_truncate))
+    }
+
+    // `DT_NEEDED` in the order of the dynamic section
+    pub fn needed(&self) -> Result<impl Iterator<Item = Result<&'a [u8], Error>> + 'a, Error> {
+        let source = self.dynamic_source()?;
+        Ok(source.into_iter().flat_map(|source| {
+            let strings = source.clone();
+            source.entries().filter_map(move |entry| match entry {
+                Ok(DynamicEntry {
+                    tag: DynamicTag::Needed,
+                    value,
+                }) => Some(strings.string(value)),
+                Ok(_) => None,
+                Err(error) => Some(Err(error)),
+            })
+        }))
+    }
+}
+
+impl<'a> Elf32<'a> {
+    // `SHT_DYNAMIC` with the string table of its link, or `PT_DYNAMIC` of a file
+    // without section headers with the strings at `DT_STRTAB`, the same way as of
+    // `Elf64::dynamic`
+    pub fn dynamic(&self) -> Result<Option<DynamicTable<'a>>, Error> {
+        for index in 0..self.section_number() {
+            let info = match self.section_info(SectionIndex(index))? {
+                Some(info) if info.header.ty == SectionType::Dynamic => info,
+                _ => continue,
+            };
+            let slice = self.section_slice(SectionIndex(index))?.unwrap_or(&[]);
+            let strings = match info.header.link {
+                Index::Regular(link) => match self.section_info(link)? {
+                    Some(strings) if strings.header.ty == SectionType::StringTable => {
+                        StringTable::new(self.section_slice(link)?.unwrap_or(&[]))
+                    }
+                    _ => return Err(Error::UnexpectedSectionType),
+                },
+                _ => return Err(Error::UnexpectedSectionType),
+            };
+            return Ok(Some(DynamicTable {
+                table: DynamicEntries::Elf32(Table::with_entry_size(
+                    slice,
+                    self.encoding(),
+                    info.header.number_of_entries,
+                )),
+                strings,
+            }));
+        }
+        for index in 0..self.program_number() {
+            match self.program_info(index)? {
+                Some(header) if header.ty == ProgramType::Dynamic => (),
+                _ => continue,
+            }
+            let slice = self.program_slice(index)?.unwrap_or(&[]);
+            let mut source = DynamicTable::of_segment(slice, self.encoding(), Class::_32);
+            if let (Some(address), Some(size)) = (
+                source.value(DynamicTag::StringTable)?,
+                source.value(DynamicTag::StringTableSize)?,
+            ) {
+                source.strings = StringTable::new(self.virtual_slice(Address(address), size)?);
+            }
+            return Ok(Some(source));
+        }
+        Ok(None)
+    }
+}
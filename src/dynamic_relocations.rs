@@ -0,0 +1,180 @@
+use super::{
+    Error, Address, Elf64, Entry, Table, WordTable, RelEntry, RelaEntry, Relocation, DynamicTag,
+};
+use super::rel_rela::next_relative;
+
+// The table of the dynamic section a relocation is from
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DynamicRelocationTable {
+    // `DT_RELR`
+    Relr,
+    // `DT_REL`
+    Rel,
+    // `DT_RELA`
+    Rela,
+    // `DT_JMPREL`, `Rel` or `Rela` by `DT_PLTREL`, the ones the lazy binding may defer
+    Plt,
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Rel,
+    Rela,
+    Relr,
+}
+
+// The relocations the runtime linker processes, found by the dynamic tags only,
+// the order is of glibc, `DT_RELR` first, then `DT_REL` and `DT_RELA`, each followed
+// by `DT_JMPREL` of its format
+#[derive(Clone)]
+pub struct DynamicRelocations<'a> {
+    elf: Elf64<'a>,
+    tables: [Option<(DynamicRelocationTable, Format, &'a [u8])>; 4],
+    table: usize,
+    entry: usize,
+    // the state of `DT_RELR`
+    bit: u32,
+    base: Address,
+}
+
+impl<'a> Iterator for DynamicRelocations<'a> {
+    type Item = Result<(DynamicRelocationTable, Relocation), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.table < self.tables.len() {
+            let (table, format, slice) = match self.tables[self.table] {
+                Some(table) => table,
+                None => {
+                    self.table += 1;
+                    continue;
+                }
+            };
+            let index = self.entry;
+            let relocation = match format {
+                Format::Rel if index < slice.len() / RelEntry::SIZE => {
+                    self.entry += 1;
+                    Some(
+                        Table::new(slice, self.elf.encoding())
+                            .pick(index)
+                            .map(Relocation::Rel),
+                    )
+                }
+                Format::Rela if index < slice.len() / RelaEntry::SIZE => {
+                    self.entry += 1;
+                    Some(
+                        Table::new(slice, self.elf.encoding())
+                            .pick(index)
+                            .map(Relocation::Rela),
+                    )
+                }
+                Format::Relr => {
+                    let words = WordTable::<u64>::new(slice, self.elf.encoding());
+                    next_relative(&words, &mut self.entry, &mut self.bit, &mut self.base)
+                        .map(|address| Ok(Relocation::Relative(address)))
+                }
+                _ => None,
+            };
+            match relocation {
+                Some(relocation) => return Some(relocation.map(|r| (table, r))),
+                None => {
+                    self.table += 1;
+                    self.entry = 0;
+                    self.bit = 0;
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The relocations of `DT_RELR`, `DT_REL`, `DT_RELA` and `DT_JMPREL` in the `PT_LOAD`
+    // segments, without section headers, nothing for a file without a dynamic section
+    pub fn dynamic_relocations(&self) -> Result<DynamicRelocations<'a>, Error> {
+        let mut relocations = DynamicRelocations {
+            elf: self.clone(),
+            tables: [None; 4],
+            table: 0,
+            entry: 0,
+            bit: 0,
+            base: Address(0),
+        };
+        let source = match self.dynamic_source()? {
+            Some(source) => source,
+            None => return Ok(relocations),
+        };
+        let mut values = [None; 9];
+        for entry in source.entries() {
+            let entry = entry?;
+            let slot = match entry.tag {
+                DynamicTag::Relr => 0,
+                DynamicTag::RelrSize => 1,
+                DynamicTag::Rel => 2,
+                DynamicTag::RelSize => 3,
+                DynamicTag::Rela => 4,
+                DynamicTag::RelaSize => 5,
+                DynamicTag::JumpRelocation => 6,
+                DynamicTag::PltRelocationSize => 7,
+                DynamicTag::PltRelocation => 8,
+                _ => continue,
+            };
+            values[slot] = Some(entry.value);
+        }
+        let range = |address: usize| match (values[address], values[address + 1]) {
+            (Some(address), Some(size)) if size != 0 => Some((address, size)),
+            (Some(_), None) => {
+                log_debug!("dynamic relocations: a table without its size, skipped");
+                None
+            }
+            _ => None,
+        };
+        let (relr, mut rel, mut rela) = (range(0), range(2), range(4));
+        let plt = match (range(6), values[8].map(DynamicTag::from)) {
+            (Some(plt), Some(DynamicTag::Rel)) => Some((plt, Format::Rel)),
+            (Some(plt), Some(DynamicTag::Rela)) => Some((plt, Format::Rela)),
+            (Some(_), _) => {
+                log_debug!("dynamic relocations: DT_JMPREL without a valid DT_PLTREL, skipped");
+                None
+            }
+            _ => None,
+        };
+        // old linkers have `DT_JMPREL` at the end of `DT_REL` or `DT_RELA`, the way
+        // glibc does it the table ends where `DT_JMPREL` starts
+        if let Some(((start, size), format)) = plt {
+            let table = match format {
+                Format::Rel => &mut rel,
+                _ => &mut rela,
+            };
+            if let Some((address, table_size)) = table {
+                let end = address.checked_add(*table_size);
+                if *table_size >= size && end == start.checked_add(size) {
+                    *table_size -= size;
+                }
+            }
+        }
+        let slice = |(address, size): (u64, u64)| self.virtual_slice(Address(address), size);
+        if let Some(relr) = relr {
+            relocations.tables[0] =
+                Some((DynamicRelocationTable::Relr, Format::Relr, slice(relr)?));
+        }
+        if let Some(rel) = rel.filter(|(_, size)| *size != 0) {
+            relocations.tables[1] = Some((DynamicRelocationTable::Rel, Format::Rel, slice(rel)?));
+        }
+        if let Some(rela) = rela.filter(|(_, size)| *size != 0) {
+            relocations.tables[2] =
+                Some((DynamicRelocationTable::Rela, Format::Rela, slice(rela)?));
+        }
+        if let Some((plt, format)) = plt {
+            let plt = Some((DynamicRelocationTable::Plt, format, slice(plt)?));
+            // after the table of its format
+            match format {
+                Format::Rel => {
+                    relocations.tables[3] = relocations.tables[2].take();
+                    relocations.tables[2] = plt;
+                }
+                _ => relocations.tables[3] = plt,
+            }
+        }
+        Ok(relocations)
+    }
+}
@@ -0,0 +1,561 @@
+use super::{Error, Address, Encoding, Elf64, ProgramData, SectionData};
+
+// `DW_EH_PE_*`, the format in the low bits, the base in the high ones
+const DW_EH_PE_OMIT: u8 = 0xff;
+const DW_EH_PE_INDIRECT: u8 = 0x80;
+const DW_EH_PE_ABSPTR: u8 = 0x00;
+const DW_EH_PE_ULEB128: u8 = 0x01;
+const DW_EH_PE_UDATA2: u8 = 0x02;
+const DW_EH_PE_UDATA4: u8 = 0x03;
+const DW_EH_PE_UDATA8: u8 = 0x04;
+const DW_EH_PE_SLEB128: u8 = 0x09;
+const DW_EH_PE_SDATA2: u8 = 0x0a;
+const DW_EH_PE_SDATA4: u8 = 0x0b;
+const DW_EH_PE_SDATA8: u8 = 0x0c;
+const DW_EH_PE_PCREL: u8 = 0x10;
+const DW_EH_PE_DATAREL: u8 = 0x30;
+
+// An encoded pointer of the augmentation, the personality routine or the LSDA
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Pointer {
+    Direct(Address),
+    // `DW_EH_PE_indirect`, the address of the place that holds the pointer,
+    // e.g. of `DW.ref.__gxx_personality_v0`
+    Indirect(Address),
+}
+
+// The bytes of the unwind data from the address, the relative pointers are relative to
+#[derive(Clone)]
+struct Reader<'a> {
+    data: &'a [u8],
+    address: Address,
+    position: usize,
+    encoding: Encoding,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, size: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .position
+            .checked_add(size)
+            .ok_or(Error::ArithmeticOverflow)?;
+        let slice = self
+            .data
+            .get(self.position..end)
+            .ok_or(Error::SliceTooShort)?;
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(read_int!(self.bytes(2)?, &self.encoding, u16))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(read_int!(self.bytes(4)?, &self.encoding, u32))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(read_int!(self.bytes(8)?, &self.encoding, u64))
+    }
+
+    fn uleb128(&mut self) -> Result<u64, Error> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= u64::from(byte & 0x7f) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn sleb128(&mut self) -> Result<i64, Error> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= i64::from(byte & 0x7f) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Ok(value);
+            }
+        }
+    }
+
+    // the CIE id, or the CIE pointer of a FDE
+    fn id(&mut self, long: bool) -> Result<u64, Error> {
+        if long {
+            self.u64()
+        } else {
+            self.u32().map(u64::from)
+        }
+    }
+
+    fn place(&self) -> Address {
+        self.address.wrapping_add(self.position as u64)
+    }
+
+    // `None` for `DW_EH_PE_omit`, the data relative base is of `.eh_frame_hdr`,
+    // the text and the function relative ones are not supported
+    fn pointer(&mut self, format: u8, data: Option<Address>) -> Result<Option<Pointer>, Error> {
+        if format == DW_EH_PE_OMIT {
+            return Ok(None);
+        }
+        let place = self.place();
+        let value = match format & 0x0f {
+            DW_EH_PE_ABSPTR | DW_EH_PE_UDATA8 | DW_EH_PE_SDATA8 => self.u64()?,
+            DW_EH_PE_ULEB128 => self.uleb128()?,
+            DW_EH_PE_UDATA2 => u64::from(self.u16()?),
+            DW_EH_PE_UDATA4 => u64::from(self.u32()?),
+            DW_EH_PE_SLEB128 => self.sleb128()? as u64,
+            DW_EH_PE_SDATA2 => self.u16()? as i16 as u64,
+            DW_EH_PE_SDATA4 => self.u32()? as i32 as u64,
+            _ => return Err(Error::UnsupportedPointerEncoding(format)),
+        };
+        let base = match format & 0x70 {
+            0 => 0,
+            DW_EH_PE_PCREL => place.0,
+            DW_EH_PE_DATAREL => match data {
+                Some(base) => base.0,
+                None => return Err(Error::UnsupportedPointerEncoding(format)),
+            },
+            _ => return Err(Error::UnsupportedPointerEncoding(format)),
+        };
+        let address = Address(base.wrapping_add(value));
+        Ok(Some(if format & DW_EH_PE_INDIRECT != 0 {
+            Pointer::Indirect(address)
+        } else {
+            Pointer::Direct(address)
+        }))
+    }
+
+    fn direct(&mut self, format: u8, data: Option<Address>) -> Result<Address, Error> {
+        match self.pointer(format, data)? {
+            Some(Pointer::Direct(address)) => Ok(address),
+            _ => Err(Error::UnsupportedPointerEncoding(format)),
+        }
+    }
+}
+
+// the size of a fixed size format of the binary search table
+fn fixed_size(format: u8) -> Option<usize> {
+    match format & 0x0f {
+        DW_EH_PE_UDATA2 | DW_EH_PE_SDATA2 => Some(2),
+        DW_EH_PE_UDATA4 | DW_EH_PE_SDATA4 => Some(4),
+        DW_EH_PE_ABSPTR | DW_EH_PE_UDATA8 | DW_EH_PE_SDATA8 => Some(8),
+        _ => None,
+    }
+}
+
+// `.eh_frame_hdr`, of `PT_GNU_EH_FRAME`, the pointer to `.eh_frame` and the table
+// of the FDEs sorted by the initial location the unwinder searches
+#[derive(Clone)]
+pub struct EhFrameHeader<'a> {
+    pub version: u8,
+    // `eh_frame_ptr`
+    pub eh_frame: Address,
+    reader: Reader<'a>,
+    // the start of the table, zero without it
+    table: usize,
+    table_format: u8,
+    number: usize,
+}
+
+impl<'a> EhFrameHeader<'a> {
+    // the bytes of the section at its address
+    pub fn new(data: &'a [u8], address: Address, encoding: Encoding) -> Result<Self, Error> {
+        let mut reader = Reader {
+            data,
+            address,
+            position: 0,
+            encoding,
+        };
+        let version = reader.u8()?;
+        let eh_frame_format = reader.u8()?;
+        let count_format = reader.u8()?;
+        let table_format = reader.u8()?;
+        let eh_frame = reader.direct(eh_frame_format, Some(address))?;
+        let mut number = 0;
+        if table_format != DW_EH_PE_OMIT && fixed_size(table_format).is_some() {
+            if let Some(Pointer::Direct(count)) = reader.pointer(count_format, Some(address))? {
+                number = count.0 as usize;
+            }
+        }
+        Ok(EhFrameHeader {
+            version,
+            eh_frame,
+            table: reader.position,
+            reader,
+            table_format,
+            number,
+        })
+    }
+
+    // the number of the entries of the table, zero without one
+    pub fn len(&self) -> usize {
+        self.number
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.number == 0
+    }
+
+    // The initial location and the address of the FDE of the entry
+    pub fn entry(&self, index: usize) -> Result<(Address, Address), Error> {
+        if index >= self.number {
+            return Err(Error::IndexOutOfBounds);
+        }
+        let size = fixed_size(self.table_format).ok_or(Error::NotPresent)?;
+        let offset = index
+            .checked_mul(size * 2)
+            .and_then(|offset| offset.checked_add(self.table))
+            .ok_or(Error::ArithmeticOverflow)?;
+        let mut reader = Reader {
+            position: offset,
+            ..self.reader.clone()
+        };
+        let base = Some(self.reader.address);
+        let location = reader.direct(self.table_format, base)?;
+        let fde = reader.direct(self.table_format, base)?;
+        Ok((location, fde))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = Result<(Address, Address), Error>> + '_ {
+        (0..self.number).map(move |index| self.entry(index))
+    }
+
+    // The address of the FDE that may cover the address, of the last entry that
+    // starts at it or before, the range of the FDE tells if it does
+    pub fn search(&self, address: Address) -> Result<Option<Address>, Error> {
+        let (mut low, mut high) = (0, self.number);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.entry(middle)?.0 <= address {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        match low {
+            0 => Ok(None),
+            index => self.entry(index - 1).map(|(_, fde)| Some(fde)),
+        }
+    }
+}
+
+// A Common Information Entry
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cie<'a> {
+    pub address: Address,
+    pub version: u8,
+    pub augmentation: &'a [u8],
+    pub code_alignment: u64,
+    pub data_alignment: i64,
+    pub return_address_register: u64,
+    // `R` of the augmentation, the format of the addresses of the FDEs
+    pub fde_format: u8,
+    // `L`
+    pub lsda_format: Option<u8>,
+    // `P`
+    pub personality: Option<Pointer>,
+    // `S`
+    pub signal_frame: bool,
+    pub instructions: &'a [u8],
+}
+
+// A Frame Description Entry
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Fde<'a> {
+    pub address: Address,
+    pub cie: Cie<'a>,
+    pub initial_location: Address,
+    pub address_range: u64,
+    pub lsda: Option<Pointer>,
+    pub instructions: &'a [u8],
+}
+
+impl<'a> Fde<'a> {
+    pub fn contains(&self, address: Address) -> bool {
+        match address.checked_sub(self.initial_location) {
+            Some(offset) => offset < self.address_range,
+            None => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CfiEntry<'a> {
+    Cie(Cie<'a>),
+    Fde(Fde<'a>),
+}
+
+// `.eh_frame`, the CIEs and the FDEs one after another up to a zero length
+#[derive(Clone)]
+pub struct EhFrame<'a> {
+    reader: Reader<'a>,
+}
+
+impl<'a> EhFrame<'a> {
+    pub fn new(data: &'a [u8], address: Address, encoding: Encoding) -> Self {
+        EhFrame {
+            reader: Reader {
+                data,
+                address,
+                position: 0,
+                encoding,
+            },
+        }
+    }
+
+    pub fn entries(&self) -> CfiEntries<'a> {
+        CfiEntries {
+            frame: self.clone(),
+            position: 0,
+        }
+    }
+
+    // the entry at the address, the one of `EhFrameHeader`, `None` at the terminator
+    pub fn entry_at(&self, address: Address) -> Result<Option<CfiEntry<'a>>, Error> {
+        let position = address
+            .checked_sub(self.reader.address)
+            .ok_or(Error::SliceTooShort)?;
+        self.entry(position as usize)
+            .map(|entry| entry.map(|(entry, _)| entry))
+    }
+
+    // the FDE at the address
+    pub fn fde_at(&self, address: Address) -> Result<Fde<'a>, Error> {
+        match self.entry_at(address)? {
+            Some(CfiEntry::Fde(fde)) => Ok(fde),
+            _ => Err(Error::NotPresent),
+        }
+    }
+
+    // the content of the entry at the position, its end and if it is of the 64 bit format
+    fn record(&self, position: usize) -> Result<Option<(Reader<'a>, usize, bool)>, Error> {
+        let mut reader = Reader {
+            position,
+            ..self.reader.clone()
+        };
+        let (length, long) = match reader.u32()? {
+            0 => return Ok(None),
+            0xffffffff => (reader.u64()?, true),
+            length => (u64::from(length), false),
+        };
+        let end = (length as usize)
+            .checked_add(reader.position)
+            .ok_or(Error::ArithmeticOverflow)?;
+        if end > reader.data.len() {
+            return Err(Error::SliceTooShort);
+        }
+        let mut content = reader.clone();
+        content.data = &reader.data[..end];
+        Ok(Some((content, end, long)))
+    }
+
+    fn entry(&self, position: usize) -> Result<Option<(CfiEntry<'a>, usize)>, Error> {
+        let (mut reader, end, long) = match self.record(position)? {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        let id_place = reader.place();
+        let id = reader.id(long)?;
+        if id == 0 {
+            return Ok(Some((CfiEntry::Cie(self.cie(reader, position)?), end)));
+        }
+        let cie_address = id_place.0.wrapping_sub(id);
+        let cie_position = cie_address.wrapping_sub(self.reader.address.0) as usize;
+        let cie = match self.record(cie_position)? {
+            Some((mut cie_reader, _, long)) => match cie_reader.id(long)? {
+                0 => self.cie(cie_reader, cie_position)?,
+                _ => return Err(Error::NotPresent),
+            },
+            None => return Err(Error::NotPresent),
+        };
+        let initial_location = reader.direct(cie.fde_format, None)?;
+        // the range has the format without the base
+        let address_range = match reader.pointer(cie.fde_format & 0x0f, None)? {
+            Some(Pointer::Direct(range)) => range.0,
+            _ => return Err(Error::UnsupportedPointerEncoding(cie.fde_format)),
+        };
+        let mut lsda = None;
+        if cie.augmentation.first() == Some(&b'z') {
+            let length = reader.uleb128()? as usize;
+            let end = reader
+                .position
+                .checked_add(length)
+                .ok_or(Error::ArithmeticOverflow)?;
+            if let Some(format) = cie.lsda_format {
+                lsda = reader.pointer(format, None)?;
+            }
+            reader.position = end;
+        }
+        let instructions = reader
+            .data
+            .get(reader.position..)
+            .ok_or(Error::SliceTooShort)?;
+        Ok(Some((
+            CfiEntry::Fde(Fde {
+                address: self.reader.address.wrapping_add(position as u64),
+                cie,
+                initial_location,
+                address_range,
+                lsda,
+                instructions,
+            }),
+            end,
+        )))
+    }
+
+    fn cie(&self, mut reader: Reader<'a>, position: usize) -> Result<Cie<'a>, Error> {
+        let version = reader.u8()?;
+        let rest = reader
+            .data
+            .get(reader.position..)
+            .ok_or(Error::SliceTooShort)?;
+        let length = rest
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or(Error::UnterminatedString)?;
+        let augmentation = reader.bytes(length)?;
+        reader.u8()?;
+        let code_alignment = reader.uleb128()?;
+        let data_alignment = reader.sleb128()?;
+        let return_address_register = if version == 1 {
+            u64::from(reader.u8()?)
+        } else {
+            reader.uleb128()?
+        };
+        let mut cie = Cie {
+            address: self.reader.address.wrapping_add(position as u64),
+            version,
+            augmentation,
+            code_alignment,
+            data_alignment,
+            return_address_register,
+            fde_format: DW_EH_PE_ABSPTR,
+            lsda_format: None,
+            personality: None,
+            signal_frame: false,
+            instructions: &[],
+        };
+        if augmentation.first() == Some(&b'z') {
+            let length = reader.uleb128()? as usize;
+            let end = reader
+                .position
+                .checked_add(length)
+                .ok_or(Error::ArithmeticOverflow)?;
+            for letter in &augmentation[1..] {
+                match letter {
+                    b'R' => cie.fde_format = reader.u8()?,
+                    b'L' => cie.lsda_format = Some(reader.u8()?),
+                    b'P' => {
+                        let format = reader.u8()?;
+                        cie.personality = reader.pointer(format, None)?;
+                    }
+                    b'S' => cie.signal_frame = true,
+                    // the rest is not known, the length skips it
+                    _ => break,
+                }
+            }
+            reader.position = end;
+        }
+        cie.instructions = reader
+            .data
+            .get(reader.position..)
+            .ok_or(Error::SliceTooShort)?;
+        Ok(cie)
+    }
+}
+
+// The entries of `EhFrame::entries`, the iteration ends at an error
+#[derive(Clone)]
+pub struct CfiEntries<'a> {
+    frame: EhFrame<'a>,
+    position: usize,
+}
+
+impl<'a> Iterator for CfiEntries<'a> {
+    type Item = Result<CfiEntry<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.frame.reader.data.len() {
+            return None;
+        }
+        match self.frame.entry(self.position) {
+            Ok(Some((entry, end))) => {
+                self.position = end;
+                Some(Ok(entry))
+            }
+            Ok(None) => {
+                self.position = self.frame.reader.data.len();
+                None
+            }
+            Err(error) => {
+                self.position = self.frame.reader.data.len();
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // `PT_GNU_EH_FRAME`, or `.eh_frame_hdr` of a file without program headers
+    pub fn eh_frame_header(&self) -> Result<Option<EhFrameHeader<'a>>, Error> {
+        for index in 0..self.program_number() {
+            if let Some(ProgramData::EhFrameHeader { data, address }) =
+                self.program(index)?.map(|p| p.data)
+            {
+                return EhFrameHeader::new(data, address, self.encoding()).map(Some);
+            }
+        }
+        match self.program_bits(b".eh_frame_hdr")? {
+            Some((data, address)) => EhFrameHeader::new(data, address, self.encoding()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // `.eh_frame`, or the place `.eh_frame_hdr` points to up to the end of the file
+    // part of its segment, the terminator ends it there
+    pub fn eh_frame(&self) -> Result<Option<EhFrame<'a>>, Error> {
+        if let Some((data, address)) = self.program_bits(b".eh_frame")? {
+            return Ok(Some(EhFrame::new(data, address, self.encoding())));
+        }
+        let address = match self.eh_frame_header()? {
+            Some(header) => header.eh_frame,
+            None => return Ok(None),
+        };
+        let header = match self.segment_containing(address)? {
+            Some((_, header)) => header,
+            None => return Ok(None),
+        };
+        let end = header.virtual_address.saturating_add(header.file_size);
+        let size = end.0.saturating_sub(address.0);
+        let data = self.read_virtual(address, size)?;
+        Ok(Some(EhFrame::new(data, address, self.encoding())))
+    }
+
+    fn program_bits(&self, name: &[u8]) -> Result<Option<(&'a [u8], Address)>, Error> {
+        match self.section_by_name(name)? {
+            Some(section) => match section.data {
+                SectionData::ProgramBits(data) => Ok(Some((data, section.address))),
+                _ => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,583 @@
+use super::{
+    Error, UnexpectedSize, Address, Offset, SectionIndex, Encoding, Entry, Table, Class, Abi, Type,
+    Machine, Index, Header, ProgramHeader, ProgramType, ProgramFlags, Program, SectionHeader,
+    SectionFlags, SectionType, SectionInfo, SymbolEntry, Symbol, RelEntry, RelaEntry, Relocation,
+    DynamicEntry, StringTable, program_data,
+};
+use super::common::file_slice;
+use super::header::Identifier;
+use super::fragments::Source;
+
+// `Elf32_Phdr`, widened to the common header
+#[derive(Clone)]
+struct ProgramHeader32(ProgramHeader);
+
+impl Entry for ProgramHeader32 {
+    type Error = Error;
+
+    const SIZE: usize = 0x20;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let word = |offset: usize| read_int!(&slice[offset..], &encoding, u32) as u64;
+        Ok(ProgramHeader32(ProgramHeader {
+            ty: read_int!(&slice[0x00..], &encoding, u32).into(),
+            file_offset: Offset(word(0x04)),
+            virtual_address: Address(word(0x08)),
+            physical_address: Address(word(0x0c)),
+            file_size: word(0x10),
+            memory_size: word(0x14),
+            flags: ProgramFlags::from_bits_truncate(read_int!(&slice[0x18..], &encoding, u32)),
+            address_alignment: word(0x1c),
+        }))
+    }
+}
+
+// `Elf32_Shdr`
+#[derive(Clone)]
+struct SectionHeader32(SectionHeader);
+
+impl Entry for SectionHeader32 {
+    type Error = Error;
+
+    const SIZE: usize = 0x28;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let word = |offset: usize| read_int!(&slice[offset..], &encoding, u32);
+        Ok(SectionHeader32(SectionHeader {
+            name: word(0x00),
+            ty: word(0x04).into(),
+            flags: SectionFlags::from_bits_truncate(u64::from(word(0x08))),
+            address: Address(word(0x0c) as u64),
+            offset: Offset(word(0x10) as u64),
+            size: word(0x14) as u64,
+            link: word(0x18).into(),
+            info: word(0x1c),
+            address_alignment: word(0x20) as u64,
+            number_of_entries: word(0x24) as u64,
+        }))
+    }
+}
+
+// `Elf32_Sym`, the fields are in another order than of `Elf64_Sym`
+#[derive(Clone)]
+struct SymbolEntry32(SymbolEntry);
+
+impl Entry for SymbolEntry32 {
+    type Error = Error;
+
+    const SIZE: usize = 0x10;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        Ok(SymbolEntry32(SymbolEntry {
+            name: read_int!(&slice[0x00..], &encoding, u32),
+            value: Address(read_int!(&slice[0x04..], &encoding, u32) as u64),
+            size: read_int!(&slice[0x08..], &encoding, u32) as u64,
+            info: slice[0x0c].into(),
+            reserved: slice[0x0d],
+            section_index: read_int!(&slice[0x0e..], &encoding, u16).into(),
+        }))
+    }
+}
+
+// `Elf32_Rel`, the symbol is in the upper 24 bits of `r_info`, the type in the lower 8
+#[derive(Clone)]
+struct RelEntry32(RelEntry);
+
+impl Entry for RelEntry32 {
+    type Error = Error;
+
+    const SIZE: usize = 0x08;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let info = read_int!(&slice[0x04..], &encoding, u32);
+        Ok(RelEntry32(RelEntry {
+            address: Address(read_int!(&slice[0x00..], &encoding, u32) as u64),
+            symbol_index: (info >> 8).into(),
+            relocation_type: info & 0xff,
+        }))
+    }
+}
+
+// `Elf32_Rela`
+#[derive(Clone)]
+struct RelaEntry32(RelaEntry);
+
+impl Entry for RelaEntry32 {
+    type Error = Error;
+
+    const SIZE: usize = 0x0c;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let info = read_int!(&slice[0x04..], &encoding, u32);
+        Ok(RelaEntry32(RelaEntry {
+            address: Address(read_int!(&slice[0x00..], &encoding, u32) as u64),
+            symbol_index: (info >> 8).into(),
+            relocation_type: info & 0xff,
+            addend: read_int!(&slice[0x08..], &encoding, i32) as i64,
+        }))
+    }
+}
+
+// `Elf32_Dyn`, the tag is signed, but the tags in use are positive
+#[derive(Clone)]
+pub(crate) struct DynamicEntry32(pub DynamicEntry);
+
+impl Entry for DynamicEntry32 {
+    type Error = Error;
+
+    const SIZE: usize = 0x08;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        Ok(DynamicEntry32(DynamicEntry {
+            tag: u64::from(read_int!(&slice[0x00..], &encoding, u32)).into(),
+            value: u64::from(read_int!(&slice[0x04..], &encoding, u32)),
+        }))
+    }
+}
+
+// `Elf32_Ehdr`
+fn header(slice: &[u8]) -> Result<Header, Error> {
+    const SIZE: usize = 0x34;
+
+    if slice.len() < SIZE {
+        return Err(Error::SliceTooShort);
+    }
+
+    let identifier = Identifier::new(&slice[0x00..0x10])?;
+    if identifier.class != Class::_32 {
+        return Err(Error::UnexpectedClass(identifier.class));
+    }
+    let encoding = identifier.encoding.clone();
+    let half = |offset: usize| read_int!(&slice[offset..], &encoding, u16);
+    let word = |offset: usize| read_int!(&slice[offset..], &encoding, u32);
+    if half(0x28) as usize != SIZE {
+        return Err(Error::UnexpectedSize(UnexpectedSize::Header));
+    }
+    let program_header_number = half(0x2c);
+    if program_header_number != 0 && half(0x2a) as usize != ProgramHeader32::SIZE {
+        return Err(Error::UnexpectedSize(UnexpectedSize::ProgramHeader));
+    }
+    let section_header_number = half(0x30);
+    if (section_header_number != 0 || word(0x20) != 0)
+        && half(0x2e) as usize != SectionHeader32::SIZE
+    {
+        return Err(Error::UnexpectedSize(UnexpectedSize::SectionHeader));
+    }
+    Ok(Header {
+        ty: half(0x10).into(),
+        machine: half(0x12).into(),
+        format_version: word(0x14),
+        entry: Address(word(0x18) as u64),
+        program_headers_offset: Offset(word(0x1c) as u64),
+        section_headers_offset: Offset(word(0x20) as u64),
+        flags: word(0x24),
+        program_header_size: half(0x2a),
+        section_header_size: half(0x2e),
+        program_header_number,
+        section_header_number,
+        section_names: half(0x32).into(),
+        identifier,
+    })
+}
+
+// A 32 bits file, e.g. of i686 or ARM, the headers, the symbols and the relocations
+// are widened to the types of `Elf64`, the content is borrowed the same way
+#[derive(Clone)]
+pub struct Elf32<'a> {
+    raw: Source<'a>,
+    header: Header,
+    program_table: Table<'a, ProgramHeader32>,
+    section_table: Table<'a, SectionHeader32>,
+    names: Option<StringTable<'a>>,
+}
+
+impl<'a> Elf32<'a> {
+    pub fn new(raw: &'a [u8]) -> Result<Self, Error> {
+        let header = header(raw)?;
+        let raw = Source::Contiguous(raw);
+        let encoding = header.identifier.encoding.clone();
+        let table = |offset: Offset, number: u16, size: usize| match number {
+            0 => Ok(&[][..]),
+            number => file_slice(raw, offset, (number as usize * size) as u64),
+        };
+        let program_table = Table::new(
+            table(
+                header.program_headers_offset,
+                header.program_header_number,
+                ProgramHeader32::SIZE,
+            )?,
+            encoding.clone(),
+        );
+        let section_table = Table::<SectionHeader32>::new(
+            table(
+                header.section_headers_offset,
+                header.section_header_number,
+                SectionHeader32::SIZE,
+            )?,
+            encoding,
+        );
+        let names = match header.section_names {
+            Index::Regular(index) if index.0 < header.section_header_number as usize => {
+                let names = section_table.pick(index.0)?.0;
+                match names.ty {
+                    SectionType::StringTable => {
+                        Some(StringTable::new(file_slice(raw, names.offset, names.size)?))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        Ok(Elf32 {
+            raw,
+            header,
+            program_table,
+            section_table,
+            names,
+        })
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.header.identifier.encoding.clone()
+    }
+
+    pub fn abi(&self) -> Abi {
+        self.header.identifier.abi.clone()
+    }
+
+    pub fn ty(&self) -> Type {
+        self.header.ty.clone()
+    }
+
+    pub fn machine(&self) -> Machine {
+        self.header.machine.clone()
+    }
+
+    pub fn entry(&self) -> Address {
+        self.header.entry
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.header.flags
+    }
+
+    pub fn program_number(&self) -> usize {
+        self.header.program_header_number as usize
+    }
+
+    pub fn program_info(&self, index: usize) -> Result<Option<ProgramHeader>, Error> {
+        if index >= self.program_number() {
+            return Ok(None);
+        }
+        self.program_table.pick(index).map(|header| Some(header.0))
+    }
+
+    // the bytes of the segment in the file
+    pub fn program_slice(&self, index: usize) -> Result<Option<&'a [u8]>, Error> {
+        match self.program_info(index)? {
+            Some(header) if header.file_size == 0 => Ok(Some(&[])),
+            Some(header) => file_slice(self.raw, header.file_offset, header.file_size).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // The segment with its content by the type, the same as of `Elf64::program`
+    pub fn program(&self, index: usize) -> Result<Option<Program<'a>>, Error> {
+        let header = match self.program_info(index)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let slice = self.program_slice(index)?.unwrap_or(&[]);
+        Ok(Some(Program {
+            data: program_data(&header, index, slice, self.encoding(), Class::_32, None),
+            flags: header.flags,
+            file_size: header.file_size,
+            memory_size: header.memory_size,
+            address_alignment: header.address_alignment,
+        }))
+    }
+
+    // The bytes of the file a `PT_LOAD` maps at the address, the range must be
+    // in the file part of a single segment
+    pub(crate) fn virtual_slice(&self, address: Address, size: u64) -> Result<&'a [u8], Error> {
+        let end = address.checked_add(size).ok_or(Error::ArithmeticOverflow)?;
+        for index in 0..self.program_number() {
+            let header = match self.program_info(index)? {
+                Some(header) if header.ty == ProgramType::Load => header,
+                _ => continue,
+            };
+            let start = header.virtual_address;
+            if start <= address && end.0 <= start.0.saturating_add(header.file_size) {
+                let offset = header.file_offset.0 + (address.0 - start.0);
+                return file_slice(self.raw, Offset(offset), size);
+            }
+        }
+        Err(Error::NotPresent)
+    }
+
+    pub fn section_number(&self) -> usize {
+        self.header.section_header_number as usize
+    }
+
+    pub fn section_info(&self, index: SectionIndex) -> Result<Option<SectionInfo<'a>>, Error> {
+        if index.0 >= self.section_number() {
+            return Ok(None);
+        }
+        let header = self.section_table.pick(index.0)?.0;
+        let name = match &self.names {
+            Some(table) => table.pick(header.name as usize)?,
+            None => &[],
+        };
+        Ok(Some(SectionInfo { header, name }))
+    }
+
+    // the bytes of the section in the file, empty for `SHT_NOBITS`
+    pub fn section_slice(&self, index: SectionIndex) -> Result<Option<&'a [u8]>, Error> {
+        match self.section_info(index)? {
+            Some(info) if matches!(info.header.ty, SectionType::Null | SectionType::NoBits) => {
+                Ok(Some(&[]))
+            }
+            Some(info) => file_slice(self.raw, info.header.offset, info.header.size).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // The section by its name, the first one
+    pub fn section_by_name(&self, name: &[u8]) -> Result<Option<SectionInfo<'a>>, Error> {
+        for index in 0..self.section_number() {
+            match self.section_info(SectionIndex(index))? {
+                Some(info) if info.name == name => return Ok(Some(info)),
+                _ => (),
+            }
+        }
+        Ok(None)
+    }
+
+    // The symbols of `SHT_SYMTAB` or `SHT_DYNSYM` with the index, with the null symbol
+    pub fn symbols(
+        &self,
+        index: SectionIndex,
+    ) -> Result<impl Iterator<Item = Result<Symbol<'a>, Error>> + 'a, Error> {
+        let info = self.section_info(index)?.ok_or(Error::IndexOutOfBounds)?;
+        match info.header.ty {
+            SectionType::SymbolTable | SectionType::DynamicSymbolTable => (),
+            _ => return Err(Error::UnexpectedSectionType),
+        }
+        let strings = match info.header.link {
+            Index::Regular(link) => match self.section_info(link)? {
+                Some(strings) if strings.header.ty == SectionType::StringTable => {
+                    StringTable::new(self.section_slice(link)?.unwrap_or(&[]))
+                }
+                _ => return Err(Error::UnexpectedSectionType),
+            },
+            _ => return Err(Error::UnexpectedSectionType),
+        };
+        let slice = self.section_slice(index)?.unwrap_or(&[]);
+        let table = Table::<SymbolEntry32>::with_entry_size(
+            slice,
+            self.encoding(),
+            info.header.number_of_entries,
+        );
+        Ok((0..table.len()).map(move |index| {
+            let entry = table.pick(index)?.0;
+            Ok(Symbol {
+                name: strings.pick(entry.name as usize)?,
+                entry,
+            })
+        }))
+    }
+
+    // The entries of `SHT_REL` or `SHT_RELA` with the index
+    pub fn relocations(
+        &self,
+        index: SectionIndex,
+    ) -> Result<impl Iterator<Item = Result<Relocation, Error>> + 'a, Error> {
+        let info = self.section_info(index)?.ok_or(Error::IndexOutOfBounds)?;
+        let rela = match info.header.ty {
+            SectionType::Rel => false,
+            SectionType::Rela => true,
+            _ => return Err(Error::UnexpectedSectionType),
+        };
+        let slice = self.section_slice(index)?.unwrap_or(&[]);
+        let entry_size = info.header.number_of_entries;
+        let (rel_table, rela_table) = (
+            Table::<RelEntry32>::with_entry_size(slice, self.encoding(), entry_size),
+            Table::<RelaEntry32>::with_entry_size(slice, self.encoding(), entry_size),
+        );
+        let number = if rela {
+            rela_table.len()
+        } else {
+            rel_table.len()
+        };
+        Ok((0..number).map(move |index| {
+            if rela {
+                rela_table
+                    .pick(index)
+                    .map(|entry| Relocation::Rela(entry.0))
+            } else {
+                rel_table.pick(index).map(|entry| Relocation::Rel(entry.0))
+            }
+        }))
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{Elf32, SectionHeader32};
+    use crate::{
+        Encoding, Entry, Index, SectionIndex, ProgramData, DynamicTag, DynamicEntry, SymbolEntry,
+    };
+
+    fn half(file: &mut Vec<u8>, value: u16) {
+        file.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn word(file: &mut Vec<u8>, value: u32) {
+        file.extend_from_slice(&value.to_le_bytes());
+    }
+
+    // `.dynstr`, `.dynamic`, `.note`, `.symtab` of a stride of 0x18 and `.strtab`,
+    // `PT_NOTE` and `PT_DYNAMIC`
+    fn object() -> Vec<u8> {
+        let names = b"\0.shstrtab\0.dynstr\0.dynamic\0.note\0.symtab\0.strtab\0";
+        let mut dynamic = Vec::new();
+        for (tag, value) in [(1, 1), (0, 0)] {
+            word(&mut dynamic, tag);
+            word(&mut dynamic, value);
+        }
+        let mut note = Vec::new();
+        for value in [4, 4, 3] {
+            word(&mut note, value);
+        }
+        note.extend_from_slice(b"GNU\0\x01\x02\x03\x04");
+        let mut symbols = Vec::new();
+        for (name, value) in [(0, 0), (1, 0x1000)] {
+            for field in [name, value, 4, 0x0012_0001, 0, 0] {
+                word(&mut symbols, field);
+            }
+        }
+        // with the name, the type, the link and `sh_entsize`
+        let contents: [(&[u8], u32, u32, u32, u32); 6] = [
+            (names, 1, 3, 0, 0),
+            (b"\0libc.so.6\0", 11, 3, 0, 0),
+            (&dynamic, 19, 6, 2, 8),
+            (&note, 28, 7, 0, 0),
+            (&symbols, 34, 2, 6, 0x18),
+            (b"\0f\0", 42, 3, 0, 0),
+        ];
+
+        let mut file = b"\x7fELF\x01\x01\x01\0\0\0\0\0\0\0\0\0".to_vec();
+        let mut offsets = Vec::new();
+        let mut offset = 0x34 + 2 * 0x20;
+        for (content, ..) in &contents {
+            offsets.push(offset);
+            offset += (content.len() as u32 + 3) & !3;
+        }
+        for value in [2, 3] {
+            half(&mut file, value);
+        }
+        for value in [1, 0, 0x34, offset, 0] {
+            word(&mut file, value);
+        }
+        for value in [0x34, 0x20, 2, 0x28, 7, 1] {
+            half(&mut file, value);
+        }
+        for (ty, section) in [(4, 3), (2, 2)] {
+            let (content, ..) = contents[section];
+            let offset = offsets[section];
+            for value in [ty, offset, offset, offset, content.len() as u32, 0, 4, 4] {
+                word(&mut file, value);
+            }
+        }
+        for (content, ..) in &contents {
+            file.extend_from_slice(content);
+            file.resize((file.len() + 3) & !3, 0);
+        }
+        file.resize(file.len() + SectionHeader32::SIZE, 0);
+        for (i, (content, name, ty, link, entry_size)) in contents.iter().enumerate() {
+            let size = content.len() as u32;
+            for value in [*name, *ty, 0, 0, offsets[i], size, *link, 0, 4, *entry_size] {
+                word(&mut file, value);
+            }
+        }
+        file
+    }
+
+    #[test]
+    fn link_of_32_bits() {
+        let mut header = [0; 0x28];
+        header[0x18..0x1c].copy_from_slice(&0x0001_2345u32.to_le_bytes());
+        let header = SectionHeader32::new(&header, Encoding::Little).unwrap().0;
+        assert_eq!(header.link, Index::Regular(SectionIndex(0x0001_2345)));
+    }
+
+    #[test]
+    fn programs_notes_and_dynamic() {
+        let raw = object();
+        let elf = Elf32::new(&raw).unwrap();
+        let program = elf.program(0).unwrap().unwrap();
+        assert!(matches!(program.data, ProgramData::Note(_)));
+        let program = elf.program(1).unwrap().unwrap();
+        let table = match program.data {
+            ProgramData::Dynamic { table, .. } => table,
+            _ => panic!("not the dynamic segment"),
+        };
+        assert_eq!(table.value(DynamicTag::Needed), Ok(Some(1)));
+        assert!(elf.program(2).unwrap().is_none());
+
+        let notes = elf.notes().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].name, b"GNU");
+        assert_eq!(notes[0].description, [1, 2, 3, 4]);
+
+        let dynamic = elf.dynamic().unwrap().unwrap();
+        let entries = dynamic.entries().collect::<Result<Vec<_>, _>>().unwrap();
+        let needed = DynamicEntry {
+            tag: DynamicTag::Needed,
+            value: 1,
+        };
+        assert_eq!(dynamic.entry_string(&needed), Ok(Some(&b"libc.so.6"[..])));
+        assert_eq!(entries, [needed]);
+    }
+
+    #[test]
+    fn symbols_of_a_larger_entry_size() {
+        let raw = object();
+        let elf = Elf32::new(&raw).unwrap();
+        let symbols = elf
+            .symbols(SectionIndex(5))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let names = symbols.iter().map(|symbol| symbol.name).collect::<Vec<_>>();
+        assert_eq!(names, [&b""[..], b"f"]);
+        let SymbolEntry { value, size, .. } = symbols[1].entry.clone();
+        assert_eq!((value.0, size), (0x1000, 4));
+    }
+}
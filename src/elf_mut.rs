@@ -0,0 +1,187 @@
+use super::{
+    Error, SectionIndex, SymbolIndex, Address, Offset, Encoding, Elf64, Entry, Encode, SectionType,
+    SectionFlags, SectionHeader, SymbolEntry, ProgramHeader, ProgramType,
+};
+use super::common::to_usize;
+
+// A view of the file that changes single fields in place, the layout stays
+// the same, so nothing moves and nothing grows. Everything is written
+// in the encoding of the file, `Patcher` is for the changes that need to reallocate.
+pub struct Elf64Mut<'a> {
+    raw: &'a mut [u8],
+    encoding: Encoding,
+}
+
+impl<'a> Elf64Mut<'a> {
+    pub fn new(raw: &'a mut [u8]) -> Result<Self, Error> {
+        let encoding = Elf64::new(raw)?.encoding();
+        Ok(Elf64Mut { raw, encoding })
+    }
+
+    pub fn elf(&self) -> Result<Elf64<'_>, Error> {
+        Elf64::new(self.raw)
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        self.raw
+    }
+
+    pub fn into_inner(self) -> &'a mut [u8] {
+        self.raw
+    }
+
+    pub fn set_section_header(
+        &mut self,
+        index: SectionIndex,
+        header: &SectionHeader,
+    ) -> Result<(), Error> {
+        let offset = {
+            let elf = self.elf()?;
+            if index.0 >= elf.section_number() {
+                return Err(Error::IndexOutOfBounds);
+            }
+            elf.header.section_headers_offset + (index.0 * SectionHeader::SIZE) as u64
+        };
+        self.encode(offset, header)
+    }
+
+    pub fn set_section_flags(
+        &mut self,
+        index: SectionIndex,
+        flags: SectionFlags,
+    ) -> Result<(), Error> {
+        let header = self.section_header(index)?;
+        self.set_section_header(index, &SectionHeader { flags, ..header })
+    }
+
+    pub fn set_program_header(
+        &mut self,
+        index: usize,
+        header: &ProgramHeader,
+    ) -> Result<(), Error> {
+        let offset = {
+            let elf = self.elf()?;
+            if index >= elf.program_number() {
+                return Err(Error::IndexOutOfBounds);
+            }
+            elf.header.program_headers_offset + (index * ProgramHeader::SIZE) as u64
+        };
+        self.encode(offset, header)
+    }
+
+    // the symbol of `SHT_SYMTAB` or `SHT_DYNSYM` at the index `table`
+    pub fn set_symbol(
+        &mut self,
+        table: SectionIndex,
+        index: SymbolIndex,
+        entry: &SymbolEntry,
+    ) -> Result<(), Error> {
+        let offset = self.symbol_offset(table, index)?;
+        self.encode(offset, entry)
+    }
+
+    pub fn set_symbol_value(
+        &mut self,
+        table: SectionIndex,
+        index: SymbolIndex,
+        value: Address,
+    ) -> Result<(), Error> {
+        let offset = self.symbol_offset(table, index)?;
+        self.write_u64(offset + 0x08, value.0)
+    }
+
+    pub fn set_symbol_size(
+        &mut self,
+        table: SectionIndex,
+        index: SymbolIndex,
+        size: u64,
+    ) -> Result<(), Error> {
+        let offset = self.symbol_offset(table, index)?;
+        self.write_u64(offset + 0x10, size)
+    }
+
+    // the entry at the index of `SHT_DYNAMIC`, or of `PT_DYNAMIC` if there are
+    // no section headers
+    pub fn set_dynamic_entry(&mut self, index: usize, tag: u64, value: u64) -> Result<(), Error> {
+        let (offset, size) = self.dynamic_location()?.ok_or(Error::NotPresent)?;
+        if index >= size as usize / 0x10 {
+            return Err(Error::IndexOutOfBounds);
+        }
+        let offset = offset + (index * 0x10) as u64;
+        self.write_u64(offset, tag)?;
+        self.write_u64(offset + 0x08, value)
+    }
+
+    // Replaces the description of the note at the index of `Elf64::notes`,
+    // the new one must be of the same size, so the table stays the same
+    pub fn set_note_description(&mut self, index: usize, description: &[u8]) -> Result<(), Error> {
+        let (start, size) = {
+            let elf = self.elf()?;
+            let note = elf.notes().nth(index).ok_or(Error::IndexOutOfBounds)??;
+            let start = note.description.as_ptr() as usize - self.raw.as_ptr() as usize;
+            (start, note.description.len())
+        };
+        if description.len() != size {
+            return Err(Error::SizeMismatch(size));
+        }
+        self.raw[start..(start + size)].clone_from_slice(description);
+        Ok(())
+    }
+
+    fn section_header(&self, index: SectionIndex) -> Result<SectionHeader, Error> {
+        let elf = self.elf()?;
+        if index.0 >= elf.section_number() {
+            return Err(Error::IndexOutOfBounds);
+        }
+        elf.section_table.pick(index.0)
+    }
+
+    fn symbol_offset(&self, table: SectionIndex, index: SymbolIndex) -> Result<Offset, Error> {
+        let header = self.section_header(table)?;
+        match header.ty {
+            SectionType::SymbolTable | SectionType::DynamicSymbolTable => (),
+            _ => return Err(Error::UnexpectedSectionType),
+        }
+        if index.0 >= header.size as usize / SymbolEntry::SIZE {
+            return Err(Error::IndexOutOfBounds);
+        }
+        Ok(header.offset + (index.0 * SymbolEntry::SIZE) as u64)
+    }
+
+    fn dynamic_location(&self) -> Result<Option<(Offset, u64)>, Error> {
+        let elf = self.elf()?;
+        for index in 0..elf.section_number() {
+            let header = elf.section_table.pick(index)?;
+            if header.ty == SectionType::Dynamic {
+                return Ok(Some((header.offset, header.size)));
+            }
+        }
+        for index in 0..elf.program_number() {
+            let header = elf.program_table.pick(index)?;
+            if header.ty == ProgramType::Dynamic {
+                return Ok(Some((header.file_offset, header.file_size)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn encode<E>(&mut self, offset: Offset, entry: &E) -> Result<(), Error>
+    where
+        E: Encode<Error = Error>,
+    {
+        let encoding = self.encoding.clone();
+        entry.encode(self.slice_mut(offset, E::SIZE)?, encoding)
+    }
+
+    fn write_u64(&mut self, offset: Offset, value: u64) -> Result<(), Error> {
+        let encoding = self.encoding.clone();
+        write_int!(self.slice_mut(offset, 8)?, &encoding, u64, value);
+        Ok(())
+    }
+
+    fn slice_mut(&mut self, offset: Offset, size: usize) -> Result<&mut [u8], Error> {
+        let start = to_usize(offset.0)?;
+        let end = start.checked_add(size).ok_or(Error::ArithmeticOverflow)?;
+        self.raw.get_mut(start..end).ok_or(Error::SliceTooShort)
+    }
+}
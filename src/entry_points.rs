@@ -0,0 +1,268 @@
+use alloc::vec::Vec;
+
+use super::{
+    Error, SectionIndex, Address, Elf64, WordTable, Type, Index, SectionType, SectionData, Symbol,
+    SymbolType, Relocation, DynamicTag,
+};
+
+// `SHT_INIT_ARRAY`
+const SHT_INIT_ARRAY: u32 = 0xe;
+// `SHT_FINI_ARRAY`
+const SHT_FINI_ARRAY: u32 = 0xf;
+// `SHT_PREINIT_ARRAY`
+const SHT_PREINIT_ARRAY: u32 = 0x10;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EntryPointKind {
+    // `e_entry`
+    Entry,
+    // `DT_INIT`
+    Init,
+    // `DT_FINI`
+    Fini,
+    // an element of `SHT_PREINIT_ARRAY`
+    PreinitArray,
+    // an element of `SHT_INIT_ARRAY`
+    InitArray,
+    // an element of `SHT_FINI_ARRAY`
+    FiniArray,
+    // an element of the legacy `.ctors`, it runs from the last to the first
+    Ctors,
+    // an element of the legacy `.dtors`
+    Dtors,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntryPoint<'a> {
+    pub kind: EntryPointKind,
+    // an element of an array of a relocatable file is usually zero,
+    // the relocation fills it
+    pub address: Address,
+    // the function at the address, or the target of the relocation
+    pub symbol: Option<Symbol<'a>>,
+}
+
+impl<'a> Elf64<'a> {
+    // Where the control comes from the outside: `e_entry`, `DT_INIT`, `DT_FINI`,
+    // the elements of the init and fini arrays and of `.ctors` and `.dtors`
+    // without the sentinels, in the order of the file. Without section headers
+    // the arrays are found by `DT_INIT_ARRAY` and the like, and there are no symbols.
+    pub fn entry_points(&self) -> Result<Vec<EntryPoint<'a>>, Error> {
+        let mut points = Vec::new();
+        if self.entry() != Address(0) {
+            points.push(EntryPoint {
+                kind: EntryPointKind::Entry,
+                address: self.entry(),
+                symbol: self.entry_symbol()?,
+            });
+        }
+
+        // the arrays by address and size, only if there are no sections to find them
+        let mut arrays = [
+            (EntryPointKind::PreinitArray, None, None),
+            (EntryPointKind::InitArray, None, None),
+            (EntryPointKind::FiniArray, None, None),
+        ];
+        if let Some(dynamic) = self.dynamic_source()? {
+            for entry in dynamic.entries() {
+                let entry = entry?;
+                let kind = match entry.tag {
+                    DynamicTag::Init => EntryPointKind::Init,
+                    DynamicTag::Fini => EntryPointKind::Fini,
+                    DynamicTag::PreinitArray => {
+                        arrays[0].1 = Some(entry.value);
+                        continue;
+                    }
+                    DynamicTag::PreinitArraySize => {
+                        arrays[0].2 = Some(entry.value);
+                        continue;
+                    }
+                    DynamicTag::InitArray => {
+                        arrays[1].1 = Some(entry.value);
+                        continue;
+                    }
+                    DynamicTag::InitArraySize => {
+                        arrays[1].2 = Some(entry.value);
+                        continue;
+                    }
+                    DynamicTag::FiniArray => {
+                        arrays[2].1 = Some(entry.value);
+                        continue;
+                    }
+                    DynamicTag::FiniArraySize => {
+                        arrays[2].2 = Some(entry.value);
+                        continue;
+                    }
+                    _ => continue,
+                };
+                points.push(EntryPoint {
+                    kind,
+                    address: Address(entry.value),
+                    symbol: self.function_at(Address(entry.value))?,
+                });
+            }
+        }
+        if self.section_number() == 0 {
+            for (kind, address, size) in arrays.iter().cloned() {
+                let slice = match (address, size) {
+                    (Some(address), Some(size)) => self.virtual_slice(Address(address), size)?,
+                    _ => continue,
+                };
+                for address in WordTable::<u64>::new(slice, self.encoding()).iter() {
+                    points.push(EntryPoint {
+                        kind,
+                        address: Address(address),
+                        symbol: None,
+                    });
+                }
+            }
+        }
+
+        for section in 0..self.section_number() {
+            let kind = match self.section_table.pick(section)?.ty {
+                SectionType::Unknown(SHT_PREINIT_ARRAY) => EntryPointKind::PreinitArray,
+                SectionType::Unknown(SHT_INIT_ARRAY) => EntryPointKind::InitArray,
+                SectionType::Unknown(SHT_FINI_ARRAY) => EntryPointKind::FiniArray,
+                SectionType::ProgramBits => {
+                    match self.section(SectionIndex(section))?.map(|s| s.name) {
+                        Some(b".ctors") => EntryPointKind::Ctors,
+                        Some(b".dtors") => EntryPointKind::Dtors,
+                        _ => continue,
+                    }
+                }
+                _ => continue,
+            };
+            self.array_entry_points(section, kind, &mut points)?;
+        }
+        Ok(points)
+    }
+
+    // The constructors in the order they run: `SHT_PREINIT_ARRAY`, `.ctors`
+    // from the last one, run by `DT_INIT`, and `SHT_INIT_ARRAY`
+    pub fn constructors(&self) -> Result<Vec<EntryPoint<'a>>, Error> {
+        let points = self.entry_points()?;
+        let of_kind = |kind| points.iter().filter(move |point| point.kind == kind);
+        Ok(of_kind(EntryPointKind::PreinitArray)
+            .chain(of_kind(EntryPointKind::Ctors).rev())
+            .chain(of_kind(EntryPointKind::InitArray))
+            .cloned()
+            .collect())
+    }
+
+    // The destructors in the order they run: `SHT_FINI_ARRAY` from the last one
+    // and `.dtors`, run by `DT_FINI`
+    pub fn destructors(&self) -> Result<Vec<EntryPoint<'a>>, Error> {
+        let points = self.entry_points()?;
+        let of_kind = |kind| points.iter().filter(move |point| point.kind == kind);
+        Ok(of_kind(EntryPointKind::FiniArray)
+            .rev()
+            .chain(of_kind(EntryPointKind::Dtors))
+            .cloned()
+            .collect())
+    }
+
+    // What a loader calls to initialize the file, the way glibc does: `DT_PREINIT_ARRAY`
+    // of the executable, `DT_INIT` and `DT_INIT_ARRAY`. The `.ctors` are not here,
+    // `DT_INIT` runs them.
+    pub fn initializers(&self) -> Result<Vec<EntryPoint<'a>>, Error> {
+        let points = self.entry_points()?;
+        let of_kind = |kind| points.iter().filter(move |point| point.kind == kind);
+        Ok(of_kind(EntryPointKind::PreinitArray)
+            .chain(of_kind(EntryPointKind::Init))
+            .chain(of_kind(EntryPointKind::InitArray))
+            .cloned()
+            .collect())
+    }
+
+    // What a loader calls to finalize the file, the way glibc does: `DT_FINI_ARRAY`
+    // from the last one and `DT_FINI`, it runs the `.dtors`
+    pub fn finalizers(&self) -> Result<Vec<EntryPoint<'a>>, Error> {
+        let points = self.entry_points()?;
+        let of_kind = |kind| points.iter().filter(move |point| point.kind == kind);
+        Ok(of_kind(EntryPointKind::FiniArray)
+            .rev()
+            .chain(of_kind(EntryPointKind::Fini))
+            .cloned()
+            .collect())
+    }
+
+    fn array_entry_points(
+        &self,
+        section: usize,
+        kind: EntryPointKind,
+        points: &mut Vec<EntryPoint<'a>>,
+    ) -> Result<(), Error> {
+        let slice = match self.section(SectionIndex(section))?.map(|s| s.data) {
+            Some(SectionData::Unknown { slice, .. }) => slice,
+            Some(SectionData::ProgramBits(slice)) => slice,
+            _ => return Ok(()),
+        };
+        let relocatable = self.ty() == Type::Relocatable;
+        let legacy = kind == EntryPointKind::Ctors || kind == EntryPointKind::Dtors;
+        let words = WordTable::<u64>::new(slice, self.encoding());
+        for (index, address) in words.iter().enumerate() {
+            let offset = index * 8;
+            // `.ctors` and `.dtors` of a linked file start with -1 and end with 0
+            if legacy && (address == u64::MAX || (address == 0 && !relocatable)) {
+                continue;
+            }
+            let symbol = if relocatable {
+                self.relocation_target(section, offset as u64, address)?
+            } else {
+                self.function_at(Address(address))?
+            };
+            points.push(EntryPoint {
+                kind,
+                address: Address(address),
+                symbol,
+            });
+        }
+        Ok(())
+    }
+
+    // The function the relocation at the offset in the section of a relocatable file
+    // refers to, `implicit` is the addend of `SHT_REL`, the content at the offset
+    fn relocation_target(
+        &self,
+        section: usize,
+        offset: u64,
+        implicit: u64,
+    ) -> Result<Option<Symbol<'a>>, Error> {
+        for relocation in self.relocations_for_section(SectionIndex(section))? {
+            let (relocation_section, relocation) = relocation?;
+            let (symbol_index, addend) = match relocation {
+                Relocation::Rela(entry) if entry.address == Address(offset) => {
+                    (entry.symbol_index, entry.addend as u64)
+                }
+                Relocation::Rel(entry) if entry.address == Address(offset) => {
+                    (entry.symbol_index, implicit)
+                }
+                _ => continue,
+            };
+            let symbols = match self.section_table.pick(relocation_section)?.link {
+                Index::Regular(link) => self.symbol_source_at(link.0)?,
+                _ => return Ok(None),
+            };
+            let symbol = symbols.get(symbol_index.0)?;
+            return match symbol.entry.info.ty {
+                SymbolType::Function => Ok(Some(symbol)),
+                // the function is at the addend in the section
+                SymbolType::Section => {
+                    for index in 1..symbols.number {
+                        let candidate = symbols.get(index)?;
+                        let entry = &candidate.entry;
+                        if entry.info.ty == SymbolType::Function
+                            && entry.section_index == symbol.entry.section_index
+                            && entry.value == Address(addend)
+                        {
+                            return Ok(Some(candidate));
+                        }
+                    }
+                    Ok(None)
+                }
+                _ => Ok(None),
+            };
+        }
+        Ok(None)
+    }
+}
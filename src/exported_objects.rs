@@ -0,0 +1,102 @@
+use alloc::vec::Vec;
+
+use super::{Error, Elf64, Offset, Symbol, SymbolBinding, SymbolType, SectionType, Index};
+use super::version::{VERSYM_HIDDEN, VER_NDX_GLOBAL};
+use super::common::file_slice;
+
+// What a variable holds before the program runs
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InitialValue<'a> {
+    // `st_size` bytes of the file
+    Bytes(&'a [u8]),
+    // of `SHT_NOBITS`, `.bss` and `.tbss`, zeros of the size
+    Zeros(u64),
+    // `SHN_ABS`, or a section the symbol is not in
+    Unknown,
+}
+
+// A defined `STT_OBJECT` of `.dynsym` other modules may refer to
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExportedObject<'a> {
+    pub symbol: Symbol<'a>,
+    // of `SHT_GNU_verdef`, `None` for an unversioned symbol
+    pub version: Option<&'a [u8]>,
+    // a hidden version is not the default for the name
+    pub hidden: bool,
+    pub initial: InitialValue<'a>,
+}
+
+impl<'a> Elf64<'a> {
+    // The exported variables with the bytes the loader maps for them, in the order
+    // of `.dynsym`. The relocations may change the bytes at run time, e.g.
+    // of a pointer.
+    pub fn exported_objects(&self) -> Result<Vec<ExportedObject<'a>>, Error> {
+        let mut objects = Vec::new();
+        let symbols = match self.symbol_source(SectionType::DynamicSymbolTable)? {
+            Some(symbols) => symbols,
+            None => return Ok(objects),
+        };
+        let versions = self.versions()?;
+        for index in 1..symbols.number {
+            let symbol = symbols.get(index)?;
+            let entry = &symbol.entry;
+            if entry.info.ty != SymbolType::Object || !entry.is_defined() {
+                continue;
+            }
+            match entry.info.binding {
+                SymbolBinding::Global | SymbolBinding::Weak | SymbolBinding::OsSpecific(0) => (),
+                _ => continue,
+            }
+            // hidden and internal visibility are not exported
+            if matches!(entry.reserved & 0x3, 1 | 2) {
+                continue;
+            }
+            let (version, hidden) = match versions.versym(index)? {
+                Some(versym) => {
+                    let ndx = versym & !VERSYM_HIDDEN;
+                    let version = match ndx {
+                        0 => continue,
+                        VER_NDX_GLOBAL => None,
+                        ndx => versions.definition(ndx)?,
+                    };
+                    (version, versym & VERSYM_HIDDEN != 0)
+                }
+                None => (None, false),
+            };
+            // the linker defines an absolute symbol of the name of each version
+            if entry.section_index == Index::AbsoluteValue && version == Some(symbol.name) {
+                continue;
+            }
+            let initial = match &entry.section_index {
+                Index::Regular(section) => self.initial_value(section.0, &symbol)?,
+                _ => InitialValue::Unknown,
+            };
+            objects.push(ExportedObject {
+                symbol,
+                version,
+                hidden,
+                initial,
+            });
+        }
+        Ok(objects)
+    }
+
+    fn initial_value(
+        &self,
+        section: usize,
+        symbol: &Symbol<'a>,
+    ) -> Result<InitialValue<'a>, Error> {
+        let header = self.section_table.pick(section)?;
+        let entry = &symbol.entry;
+        let start = match entry.value.0.checked_sub(header.address.0) {
+            Some(start) if start.saturating_add(entry.size) <= header.size => start,
+            _ => return Ok(InitialValue::Unknown),
+        };
+        if header.ty == SectionType::NoBits {
+            return Ok(InitialValue::Zeros(entry.size));
+        }
+        let offset = Offset(header.offset.0.wrapping_add(start));
+        let bytes = file_slice(self.raw, offset, entry.size)?;
+        Ok(InitialValue::Bytes(bytes))
+    }
+}
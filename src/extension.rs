@@ -0,0 +1,156 @@
+use super::{
+    Error, SectionIndex, Machine, Elf64, Section, SectionData, Relocation, NoteEntry, DecodedNote,
+    Notes,
+};
+
+// Decodes the sections of the codes the crate does not know, e.g. of a proprietary
+// toolchain, `None` if the decoder does not know the section either
+pub trait SectionDecoder<'a, T> {
+    fn decode(&self, elf: &Elf64<'a>, section: &Section<'a>) -> Option<Result<T, Error>>;
+}
+
+// Names the relocation types the crate does not know
+pub trait RelocationDecoder<T> {
+    fn decode(&self, machine: &Machine, ty: u32) -> Option<T>;
+}
+
+// Decodes the notes of an owner, e.g. the provenance or the signature
+// of an organization, `None` if the decoder does not know the type
+pub trait NoteDecoder<'a, T> {
+    fn decode(&self, note: &NoteEntry<'a>) -> Option<Result<T, Error>>;
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExtendedNote<'a, T> {
+    Decoded(DecodedNote<'a>),
+    Custom(T),
+}
+
+// The decoders to ask one after another, the first one that knows wins,
+// `T` is the type of the user, e.g. an enum of everything the decoders return
+pub struct Registry<'r, 'a, T> {
+    sections: &'r [&'r dyn SectionDecoder<'a, T>],
+    relocations: &'r [&'r dyn RelocationDecoder<T>],
+    // by the owner
+    notes: &'r [(&'r [u8], &'r dyn NoteDecoder<'a, T>)],
+}
+
+impl<'r, 'a, T> Clone for Registry<'r, 'a, T> {
+    fn clone(&self) -> Self {
+        Registry {
+            sections: self.sections,
+            relocations: self.relocations,
+            notes: self.notes,
+        }
+    }
+}
+
+impl<'r, 'a, T> Default for Registry<'r, 'a, T> {
+    fn default() -> Self {
+        Registry {
+            sections: &[],
+            relocations: &[],
+            notes: &[],
+        }
+    }
+}
+
+impl<'r, 'a, T> Registry<'r, 'a, T> {
+    pub fn with_sections(self, sections: &'r [&'r dyn SectionDecoder<'a, T>]) -> Self {
+        Registry { sections, ..self }
+    }
+
+    pub fn with_relocations(self, relocations: &'r [&'r dyn RelocationDecoder<T>]) -> Self {
+        Registry {
+            relocations,
+            ..self
+        }
+    }
+
+    pub fn with_notes(self, notes: &'r [(&'r [u8], &'r dyn NoteDecoder<'a, T>)]) -> Self {
+        Registry { notes, ..self }
+    }
+
+    pub fn relocation(&self, machine: &Machine, ty: u32) -> Option<T> {
+        self.relocations
+            .iter()
+            .find_map(|decoder| decoder.decode(machine, ty))
+    }
+
+    // the decoders of the owner go first, `NoteEntry::decode` if none of them
+    // knows the note
+    pub fn note(&self, note: &NoteEntry<'a>) -> Result<ExtendedNote<'a, T>, Error> {
+        let custom = self
+            .notes
+            .iter()
+            .filter(|(owner, _)| *owner == note.name)
+            .find_map(|(_, decoder)| decoder.decode(note));
+        match custom {
+            Some(custom) => custom.map(ExtendedNote::Custom),
+            None => Ok(ExtendedNote::Decoded(note.decode())),
+        }
+    }
+}
+
+// The notes of `Elf64::notes` decoded by the registry
+#[derive(Clone)]
+pub struct ExtendedNotes<'r, 'a, T> {
+    notes: Notes<'a>,
+    registry: Registry<'r, 'a, T>,
+}
+
+impl<'r, 'a, T> Iterator for ExtendedNotes<'r, 'a, T> {
+    type Item = Result<ExtendedNote<'a, T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let note = self.notes.next()?;
+        Some(note.and_then(|note| self.registry.note(&note)))
+    }
+}
+
+impl<'a> Notes<'a> {
+    pub fn with_registry<'r, T>(self, registry: Registry<'r, 'a, T>) -> ExtendedNotes<'r, 'a, T> {
+        ExtendedNotes {
+            notes: self,
+            registry,
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The section decoded by the registry if it is `SectionData::OsSpecific`,
+    // `SectionData::ProcessorSprcific` or `SectionData::Unknown`, the others
+    // have their types already. `None` if no decoder knows the section.
+    pub fn decode_section<T>(
+        &self,
+        index: SectionIndex,
+        registry: &Registry<'_, 'a, T>,
+    ) -> Result<Option<T>, Error> {
+        let section = match self.section(index)? {
+            Some(section) => section,
+            None => return Ok(None),
+        };
+        match section.data {
+            SectionData::OsSpecific { .. }
+            | SectionData::ProcessorSprcific { .. }
+            | SectionData::Unknown { .. } => (),
+            _ => return Ok(None),
+        }
+        registry
+            .sections
+            .iter()
+            .find_map(|decoder| decoder.decode(self, &section))
+            .transpose()
+    }
+
+    // The type of a relocation named by the registry, `None` for `SHT_RELR`
+    // and if no decoder knows the type
+    pub fn decode_relocation<T>(
+        &self,
+        relocation: &Relocation,
+        registry: &Registry<'_, 'a, T>,
+    ) -> Option<T> {
+        let ty = relocation.relocation_type()?;
+        registry.relocation(&self.machine(), ty)
+    }
+}
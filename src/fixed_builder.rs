@@ -0,0 +1,503 @@
+use core::array;
+
+use super::{
+    Error, Address, Offset, SectionIndex, Encoding, Entry, Encode, Class, Abi, Type, Machine,
+    Header, Index, SectionType, SectionFlags, SectionHeader, ProgramType, ProgramFlags,
+    ProgramHeader, SymbolEntry, SymbolInfo, SymbolBinding, LayoutOptions, LayoutSection,
+    LayoutSegment, Placement,
+};
+use super::header::Identifier;
+use super::layout::{place, place_unloaded, section_headers_offset};
+
+struct FixedSection<'a> {
+    name: &'a [u8],
+    ty: SectionType,
+    flags: SectionFlags,
+    address_alignment: u64,
+    link: Index,
+    info: u32,
+    number_of_entries: u64,
+    data: &'a [u8],
+    // of `SHT_NOBITS`, there is no data
+    size: u64,
+}
+
+struct FixedSymbol<'a> {
+    name: &'a [u8],
+    info: SymbolInfo,
+    section_index: Index,
+    // relative to the section, as of `ElfBuilder`
+    value: u64,
+    size: u64,
+}
+
+struct FixedProgram {
+    ty: ProgramType,
+    flags: ProgramFlags,
+    section: Option<SectionIndex>,
+}
+
+// `ElfBuilder` without allocation, the tables have room for `SECTIONS`, `SYMBOLS`
+// and `PROGRAMS` entries, an entry more is `Error::CapacityExceeded`. The content
+// and the names are borrowed, `build` writes the file into the buffer of the caller.
+// The added sections get the same indices and places as of `ElfBuilder`, the names
+// are not merged.
+pub struct FixedBuilder<'a, const SECTIONS: usize, const SYMBOLS: usize, const PROGRAMS: usize> {
+    encoding: Encoding,
+    abi: Abi,
+    ty: Type,
+    machine: Machine,
+    flags: u32,
+    entry: Option<(SectionIndex, u64)>,
+    options: LayoutOptions,
+    sections: [Option<FixedSection<'a>>; SECTIONS],
+    section_number: usize,
+    symbols: [Option<FixedSymbol<'a>>; SYMBOLS],
+    symbol_number: usize,
+    programs: [Option<FixedProgram>; PROGRAMS],
+    program_number: usize,
+}
+
+// the entries the builder added, in the order of the table
+fn added<T>(table: &[Option<T>]) -> impl Iterator<Item = &T> {
+    table.iter().map_while(Option::as_ref)
+}
+
+// the size of a string table of the names after the empty one, none merged
+fn strings_size<'a>(names: impl Iterator<Item = &'a [u8]>) -> usize {
+    names.fold(1, |size, name| size + name.len() + 1)
+}
+
+// writes the name at `offset` in the table, the offset of the next name
+fn put_string(table: &mut [u8], offset: usize, name: &[u8]) -> usize {
+    table[offset..(offset + name.len())].clone_from_slice(name);
+    offset + name.len() + 1
+}
+
+impl<'a, const SECTIONS: usize, const SYMBOLS: usize, const PROGRAMS: usize>
+    FixedBuilder<'a, SECTIONS, SYMBOLS, PROGRAMS>
+{
+    pub fn new(encoding: Encoding, ty: Type, machine: Machine) -> Self {
+        FixedBuilder {
+            encoding,
+            abi: Abi::SystemV,
+            ty,
+            machine,
+            flags: 0,
+            entry: None,
+            options: LayoutOptions::default(),
+            sections: array::from_fn(|_| None),
+            section_number: 0,
+            symbols: array::from_fn(|_| None),
+            symbol_number: 0,
+            programs: array::from_fn(|_| None),
+            program_number: 0,
+        }
+    }
+
+    pub fn set_abi(&mut self, abi: Abi) -> &mut Self {
+        self.abi = abi;
+        self
+    }
+
+    // `e_flags`
+    pub fn set_flags(&mut self, flags: u32) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn set_layout_options(&mut self, options: LayoutOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    pub fn set_entry(&mut self, section: SectionIndex, offset: u64) -> &mut Self {
+        self.entry = Some((section, offset));
+        self
+    }
+
+    pub fn add_section(
+        &mut self,
+        name: &'a [u8],
+        ty: SectionType,
+        flags: SectionFlags,
+        address_alignment: u64,
+        data: &'a [u8],
+    ) -> Result<SectionIndex, Error> {
+        let slot = self
+            .sections
+            .get_mut(self.section_number)
+            .ok_or(Error::CapacityExceeded)?;
+        *slot = Some(FixedSection {
+            name,
+            ty,
+            flags,
+            address_alignment,
+            link: Index::Undefined,
+            info: 0,
+            number_of_entries: 0,
+            data,
+            size: data.len() as u64,
+        });
+        self.section_number += 1;
+        Ok(SectionIndex(self.section_number))
+    }
+
+    // `SHT_NOBITS`, e.g. `.bss`
+    pub fn add_no_bits(
+        &mut self,
+        name: &'a [u8],
+        flags: SectionFlags,
+        address_alignment: u64,
+        size: u64,
+    ) -> Result<SectionIndex, Error> {
+        let index = self.add_section(name, SectionType::NoBits, flags, address_alignment, &[])?;
+        self.section_mut(index)?.size = size;
+        Ok(index)
+    }
+
+    // `sh_link` of an added section
+    pub fn set_link(&mut self, section: SectionIndex, link: SectionIndex) -> Result<(), Error> {
+        self.section_mut(section)?.link = Index::Regular(link);
+        Ok(())
+    }
+
+    // `sh_info` of an added section
+    pub fn set_info(&mut self, section: SectionIndex, info: u32) -> Result<(), Error> {
+        self.section_mut(section)?.info = info;
+        Ok(())
+    }
+
+    // `sh_entsize` of an added section
+    pub fn set_entry_size(&mut self, section: SectionIndex, size: u64) -> Result<(), Error> {
+        self.section_mut(section)?.number_of_entries = size;
+        Ok(())
+    }
+
+    fn section_mut(&mut self, section: SectionIndex) -> Result<&mut FixedSection<'a>, Error> {
+        section
+            .0
+            .checked_sub(1)
+            .and_then(move |index| self.sections.get_mut(index))
+            .and_then(Option::as_mut)
+            .ok_or(Error::IndexOutOfBounds)
+    }
+
+    // the index `.symtab` gets, only valid once every section is added
+    pub fn symbol_table(&self) -> SectionIndex {
+        SectionIndex(self.section_number + 1)
+    }
+
+    pub fn add_symbol(
+        &mut self,
+        name: &'a [u8],
+        info: SymbolInfo,
+        section_index: Index,
+        value: u64,
+        size: u64,
+    ) -> Result<&mut Self, Error> {
+        let slot = self
+            .symbols
+            .get_mut(self.symbol_number)
+            .ok_or(Error::CapacityExceeded)?;
+        *slot = Some(FixedSymbol {
+            name,
+            info,
+            section_index,
+            value,
+            size,
+        });
+        self.symbol_number += 1;
+        Ok(self)
+    }
+
+    // a program header that is not `PT_LOAD`, spanning the section if there is one
+    pub fn add_program(
+        &mut self,
+        ty: ProgramType,
+        flags: ProgramFlags,
+        section: Option<SectionIndex>,
+    ) -> Result<&mut Self, Error> {
+        let slot = self
+            .programs
+            .get_mut(self.program_number)
+            .ok_or(Error::CapacityExceeded)?;
+        *slot = Some(FixedProgram { ty, flags, section });
+        self.program_number += 1;
+        Ok(self)
+    }
+
+    // The `.symtab`, `.strtab` and `.shstrtab` the builder adds, not loaded,
+    // they follow the added sections
+    fn tables(&self) -> [Option<LayoutSection>; 3] {
+        let table = |size: usize, address_alignment| LayoutSection {
+            size: size as u64,
+            address_alignment,
+            flags: SectionFlags::empty(),
+            no_bits: false,
+        };
+        let names = added(&self.sections).map(|section| section.name);
+        let (symbols, strings, names) = if self.symbol_number == 0 {
+            (None, None, strings_size(names.chain([&b".shstrtab"[..]])))
+        } else {
+            let symbols = (self.symbol_number + 1) * SymbolEntry::SIZE;
+            let strings = strings_size(added(&self.symbols).map(|symbol| symbol.name));
+            let tables = [&b".symtab"[..], b".strtab", b".shstrtab"];
+            let names = strings_size(names.chain(tables));
+            (Some(table(symbols, 8)), Some(table(strings, 1)), names)
+        };
+        [symbols, strings, Some(table(names, 1))]
+    }
+
+    // Lays the sections out and writes the file to the beginning of the buffer,
+    // the size of the file, `Error::SliceTooShort` if it does not fit
+    pub fn build(&self, buffer: &mut [u8]) -> Result<usize, Error> {
+        let loaded = self.ty != Type::Relocatable;
+
+        let mut layout_sections: [LayoutSection; SECTIONS] = array::from_fn(|_| LayoutSection {
+            size: 0,
+            address_alignment: 0,
+            flags: SectionFlags::empty(),
+            no_bits: false,
+        });
+        for (layout, section) in layout_sections.iter_mut().zip(added(&self.sections)) {
+            *layout = LayoutSection {
+                size: section.size,
+                address_alignment: section.address_alignment,
+                flags: if loaded {
+                    section.flags
+                } else {
+                    section.flags - SectionFlags::ALLOC
+                },
+                no_bits: section.ty == SectionType::NoBits,
+            };
+        }
+        let options = LayoutOptions {
+            extra_program_headers: if loaded { self.program_number } else { 0 },
+            ..self.options.clone()
+        };
+        let mut placements: [Placement; SECTIONS] = array::from_fn(|_| Placement {
+            offset: Offset(0),
+            address: Address(0),
+        });
+        let mut segments = [
+            LayoutSegment::empty(),
+            LayoutSegment::empty(),
+            LayoutSegment::empty(),
+        ];
+        let number = self.section_number;
+        let (load_number, mut end) = place(
+            &layout_sections[..number],
+            &options,
+            &mut placements[..number],
+            &mut segments,
+        );
+        let tables = self.tables();
+        let mut table_offsets = [Offset(0); 3];
+        for (offset, table) in table_offsets.iter_mut().zip(&tables) {
+            if let Some(table) = table {
+                *offset = place_unloaded(end, table);
+                end = *offset + table.size;
+            }
+        }
+        let section_headers_offset = section_headers_offset(end);
+        let table_number = tables.iter().flatten().count();
+        let section_number = number + table_number + 1;
+        let size = section_headers_offset.0 as usize + section_number * SectionHeader::SIZE;
+        let raw = buffer.get_mut(..size).ok_or(Error::SliceTooShort)?;
+        raw.iter_mut().for_each(|byte| *byte = 0);
+
+        let address_of = |index: SectionIndex| -> Result<Address, Error> {
+            index
+                .0
+                .checked_sub(1)
+                .filter(|index| *index < number)
+                .map(|index| placements[index].address)
+                .ok_or(Error::IndexOutOfBounds)
+        };
+
+        let program_number = if loaded {
+            self.program_number + load_number
+        } else {
+            0
+        };
+        if loaded {
+            let programs = added(&self.programs).map(|program| {
+                let (file_offset, virtual_address, size, address_alignment) = match program.section
+                {
+                    Some(index) => {
+                        let section = index
+                            .0
+                            .checked_sub(1)
+                            .and_then(|index| self.sections.get(index))
+                            .and_then(Option::as_ref)
+                            .ok_or(Error::IndexOutOfBounds)?;
+                        let placement = &placements[index.0 - 1];
+                        (
+                            placement.offset,
+                            placement.address,
+                            section.size,
+                            section.address_alignment.max(1),
+                        )
+                    }
+                    None => (Offset(0), Address(0), 0, 0x10),
+                };
+                Ok(ProgramHeader {
+                    ty: program.ty.clone(),
+                    flags: program.flags,
+                    file_offset,
+                    virtual_address,
+                    physical_address: virtual_address,
+                    file_size: size,
+                    memory_size: size,
+                    address_alignment,
+                })
+            });
+            let loads = segments[..load_number].iter().map(|segment| {
+                Ok(ProgramHeader {
+                    ty: ProgramType::Load,
+                    flags: segment.flags,
+                    file_offset: segment.file_offset,
+                    virtual_address: segment.virtual_address,
+                    physical_address: segment.virtual_address,
+                    file_size: segment.file_size,
+                    memory_size: segment.memory_size,
+                    address_alignment: segment.address_alignment,
+                })
+            });
+            let start = Header::SIZE;
+            for (index, program) in programs.chain(loads).enumerate() {
+                let offset = start + index * ProgramHeader::SIZE;
+                program?.encode(&mut raw[offset..], self.encoding.clone())?;
+            }
+        }
+
+        for (section, placement) in added(&self.sections).zip(&placements) {
+            let offset = placement.offset.0 as usize;
+            raw[offset..(offset + section.data.len())].clone_from_slice(section.data);
+        }
+
+        if tables[0].is_some() {
+            // the local symbols go first, the order is kept otherwise
+            let symbols = table_offsets[0].0 as usize;
+            let strings = table_offsets[1].0 as usize;
+            let locals = added(&self.symbols).filter(|s| s.info.binding == SymbolBinding::Local);
+            let others = added(&self.symbols).filter(|s| s.info.binding != SymbolBinding::Local);
+            let mut name = 1;
+            for (index, symbol) in locals.chain(others).enumerate() {
+                let value = match &symbol.section_index {
+                    Index::Regular(index) if loaded => {
+                        address_of(*index)?.wrapping_add(symbol.value)
+                    }
+                    _ => Address(symbol.value),
+                };
+                let entry = SymbolEntry {
+                    name: name as u32,
+                    info: symbol.info.clone(),
+                    reserved: 0,
+                    section_index: symbol.section_index.clone(),
+                    value,
+                    size: symbol.size,
+                };
+                let offset = symbols + (index + 1) * SymbolEntry::SIZE;
+                entry.encode(&mut raw[offset..], self.encoding.clone())?;
+                name = put_string(&mut raw[strings..], name, symbol.name);
+            }
+        }
+
+        let entry = match self.entry {
+            Some((index, offset)) if loaded => address_of(index)?.wrapping_add(offset),
+            _ => Address(0),
+        };
+        let header = Header {
+            identifier: Identifier {
+                class: Class::_64,
+                encoding: self.encoding.clone(),
+                version: 1,
+                abi: self.abi.clone(),
+                abi_version: 0,
+                padding: [0; 7],
+            },
+            ty: self.ty.clone(),
+            machine: self.machine.clone(),
+            format_version: 1,
+            entry,
+            program_headers_offset: if program_number == 0 {
+                Offset(0)
+            } else {
+                Offset(Header::SIZE as u64)
+            },
+            section_headers_offset,
+            flags: self.flags,
+            program_header_size: ProgramHeader::SIZE as u16,
+            section_header_size: SectionHeader::SIZE as u16,
+            program_header_number: program_number as u16,
+            section_header_number: section_number as u16,
+            section_names: Index::Regular(SectionIndex(section_number - 1)),
+        };
+        header.encode(raw)?;
+
+        let names = table_offsets[2].0 as usize;
+        let first_global = added(&self.symbols)
+            .filter(|s| s.info.binding == SymbolBinding::Local)
+            .count()
+            + 1;
+        let symbol_table = self.symbol_table();
+        let builder_tables = [
+            (&b".symtab"[..], SectionType::SymbolTable, 8),
+            (b".strtab", SectionType::StringTable, 1),
+            (b".shstrtab", SectionType::StringTable, 1),
+        ];
+        let added_headers = added(&self.sections)
+            .zip(&placements)
+            .map(|(section, placement)| {
+                let header = SectionHeader {
+                    name: 0,
+                    ty: section.ty.clone(),
+                    flags: section.flags,
+                    address: placement.address,
+                    offset: placement.offset,
+                    size: section.size,
+                    link: section.link.clone(),
+                    info: section.info,
+                    address_alignment: section.address_alignment,
+                    number_of_entries: section.number_of_entries,
+                };
+                (section.name, header)
+            });
+        let table_headers = builder_tables
+            .iter()
+            .zip(tables.iter().zip(&table_offsets))
+            .filter_map(|((name, ty, alignment), (table, offset))| {
+                let table = table.as_ref()?;
+                let symbols = *ty == SectionType::SymbolTable;
+                let header = SectionHeader {
+                    name: 0,
+                    ty: ty.clone(),
+                    flags: SectionFlags::empty(),
+                    address: Address(0),
+                    offset: *offset,
+                    size: table.size,
+                    link: if symbols {
+                        Index::Regular(SectionIndex(symbol_table.0 + 1))
+                    } else {
+                        Index::Undefined
+                    },
+                    info: if symbols { first_global as u32 } else { 0 },
+                    address_alignment: *alignment,
+                    number_of_entries: if symbols { SymbolEntry::SIZE as u64 } else { 0 },
+                };
+                Some((*name, header))
+            });
+        let start = section_headers_offset.0 as usize;
+        let mut name = 1;
+        for (index, (section_name, mut header)) in added_headers.chain(table_headers).enumerate() {
+            header.name = name as u32;
+            name = put_string(&mut raw[names..], name, section_name);
+            let offset = start + (index + 1) * SectionHeader::SIZE;
+            header.encode(&mut raw[offset..], self.encoding.clone())?;
+        }
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,142 @@
+use alloc::vec::Vec;
+
+use super::{Error, Elf64};
+
+// glibc functions that have a `__*_chk` variant
+const FORTIFIABLE: &[&[u8]] = &[
+    b"confstr",
+    b"dprintf",
+    b"explicit_bzero",
+    b"fdelt",
+    b"fgets",
+    b"fgets_unlocked",
+    b"fgetws",
+    b"fgetws_unlocked",
+    b"fprintf",
+    b"fread",
+    b"fread_unlocked",
+    b"fwprintf",
+    b"getcwd",
+    b"getdomainname",
+    b"getgroups",
+    b"gethostname",
+    b"getlogin_r",
+    b"gets",
+    b"getwd",
+    b"longjmp",
+    b"mbsnrtowcs",
+    b"mbsrtowcs",
+    b"mbstowcs",
+    b"memcpy",
+    b"memmove",
+    b"mempcpy",
+    b"memset",
+    b"poll",
+    b"ppoll",
+    b"pread",
+    b"pread64",
+    b"printf",
+    b"ptsname_r",
+    b"read",
+    b"readlink",
+    b"readlinkat",
+    b"realpath",
+    b"recv",
+    b"recvfrom",
+    b"snprintf",
+    b"sprintf",
+    b"stpcpy",
+    b"stpncpy",
+    b"strcat",
+    b"strcpy",
+    b"strncat",
+    b"strncpy",
+    b"swprintf",
+    b"syslog",
+    b"ttyname_r",
+    b"vasprintf",
+    b"vdprintf",
+    b"vfprintf",
+    b"vfwprintf",
+    b"vprintf",
+    b"vsnprintf",
+    b"vsprintf",
+    b"vswprintf",
+    b"vsyslog",
+    b"vwprintf",
+    b"wcpcpy",
+    b"wcpncpy",
+    b"wcrtomb",
+    b"wcscat",
+    b"wcscpy",
+    b"wcsncat",
+    b"wcsncpy",
+    b"wcsnrtombs",
+    b"wcsrtombs",
+    b"wcstombs",
+    b"wctomb",
+    b"wmemcpy",
+    b"wmemmove",
+    b"wmempcpy",
+    b"wmemset",
+    b"wprintf",
+];
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FortifyReport<'a> {
+    // imported `__*_chk` functions, named without the prefix and the suffix
+    pub fortified: Vec<&'a [u8]>,
+    // imported functions that have a fortified variant the file does not import
+    pub unfortified: Vec<&'a [u8]>,
+}
+
+impl<'a> FortifyReport<'a> {
+    pub fn is_fortified(&self) -> bool {
+        !self.fortified.is_empty()
+    }
+}
+
+// `memcpy@GLIBC_2.14` in `.symtab` is `memcpy`
+fn unversioned(name: &[u8]) -> &[u8] {
+    match name.iter().position(|&c| c == b'@') {
+        Some(position) => &name[..position],
+        None => name,
+    }
+}
+
+fn checked(name: &[u8]) -> Option<&[u8]> {
+    if name.len() > 6 && name.starts_with(b"__") && name.ends_with(b"_chk") {
+        Some(&name[2..(name.len() - 4)])
+    } else {
+        None
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Which functions the program calls through the `_FORTIFY_SOURCE` checks
+    pub fn fortify(&self) -> Result<FortifyReport<'a>, Error> {
+        let mut imports = Vec::new();
+        for symbol in self.merged_symbols()? {
+            let symbol = symbol?;
+            if symbol.entry.is_defined() || symbol.name.is_empty() {
+                continue;
+            }
+            let name = unversioned(symbol.name);
+            if !imports.contains(&name) {
+                imports.push(name);
+            }
+        }
+
+        let mut report = FortifyReport::default();
+        for &name in &imports {
+            match checked(name) {
+                Some(function) => report.fortified.push(function),
+                None if FORTIFIABLE.contains(&name) => report.unfortified.push(name),
+                None => (),
+            }
+        }
+        let fortified = report.fortified.clone();
+        report.unfortified.retain(|name| !fortified.contains(name));
+        Ok(report)
+    }
+}
@@ -0,0 +1,183 @@
+use super::{Error, Offset, Elf64};
+use super::common::to_usize;
+
+// The bytes of the file, all of them, or the fragments that were captured
+#[derive(Clone, Copy)]
+pub(crate) enum Source<'a> {
+    Contiguous(&'a [u8]),
+    // ordered, do not overlap
+    Fragments(&'a [(Offset, &'a [u8])]),
+}
+
+impl<'a> Source<'a> {
+    // the end of the last fragment
+    pub fn len(&self) -> usize {
+        match self {
+            Source::Contiguous(raw) => raw.len(),
+            Source::Fragments(fragments) => fragments
+                .last()
+                .map(|(offset, data)| offset.0 as usize + data.len())
+                .unwrap_or(0),
+        }
+    }
+
+    #[cfg(any(feature = "alloc", feature = "object"))]
+    pub fn contiguous(&self) -> Option<&'a [u8]> {
+        match self {
+            Source::Contiguous(raw) => Some(raw),
+            Source::Fragments(_) => None,
+        }
+    }
+
+    // `raw[start..end]`, or `raw[start..]` up to the end of the fragment,
+    // a range that is not in a single fragment is `Error::NotCaptured`
+    pub fn get(&self, start: usize, end: Option<usize>) -> Result<&'a [u8], Error> {
+        let fragments = match self {
+            Source::Contiguous(raw) => {
+                let slice = match end {
+                    Some(end) => raw.get(start..end),
+                    None => raw.get(start..),
+                };
+                return slice.ok_or(Error::SliceTooShort);
+            }
+            Source::Fragments(fragments) => fragments,
+        };
+        if end
+            .map(|end| end > self.len())
+            .unwrap_or(start > self.len())
+        {
+            return Err(Error::SliceTooShort);
+        }
+        let not_captured = Error::NotCaptured(Offset(start as u64));
+        let (offset, data) = fragments
+            .iter()
+            .rev()
+            .find(|(offset, _)| offset.0 as usize <= start)
+            .ok_or_else(|| not_captured.clone())?;
+        let data = &data[(start - offset.0 as usize).min(data.len())..];
+        match end {
+            Some(end) => data.get(..(end - start)).ok_or(not_captured),
+            None if data.is_empty() && start != self.len() => Err(not_captured),
+            None => Ok(data),
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Parses the fragments of a partially captured image, e.g. pages read from
+    // a debugger, without copying. The fragments are ordered by the offset and
+    // do not overlap, a read that is not in a single fragment fails with
+    // `Error::NotCaptured`, so the adjacent fragments better be merged.
+    pub fn from_fragments(fragments: &'a [(Offset, &'a [u8])]) -> Result<Self, Error> {
+        let mut end = 0;
+        for (offset, data) in fragments {
+            let start = to_usize(offset.0)?;
+            if start < end {
+                return Err(Error::OverlappingFragments);
+            }
+            end = start
+                .checked_add(data.len())
+                .ok_or(Error::ArithmeticOverflow)?;
+        }
+        Elf64::with_source(Source::Fragments(fragments))
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use core::convert::TryInto;
+
+    use alloc::vec::Vec;
+
+    use crate::{
+        Elf64, Encoding, Offset, SectionIndex, SectionData, ProgramHeader, SectionHeader, Entry,
+    };
+    use crate::tests::executable;
+
+    // past `u32::MAX`, the offsets of a huge core dump
+    const BASE: u64 = 0x1_0000_1000;
+
+    fn add(raw: &mut [u8], at: usize, value: u64) {
+        let field = &mut raw[at..at + 8];
+        let value = u64::from_le_bytes(field.try_into().unwrap()) + value;
+        field.copy_from_slice(&value.to_le_bytes());
+    }
+
+    // The headers at the start, the whole file again at `BASE`, every offset
+    // in the headers points to the copy, `.bss` is larger than 4 GiB
+    fn moved(raw: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let elf = Elf64::new(raw).unwrap();
+        let (programs, sections) = (
+            elf.header.program_headers_offset.0 as usize,
+            elf.header.section_headers_offset.0 as usize,
+        );
+        let mut moved = raw.to_vec();
+        add(&mut moved, 0x28, BASE);
+        for index in 0..elf.program_number() {
+            add(
+                &mut moved,
+                programs + index * ProgramHeader::SIZE + 0x08,
+                BASE,
+            );
+        }
+        for index in 0..elf.section_number() {
+            add(
+                &mut moved,
+                sections + index * SectionHeader::SIZE + 0x18,
+                BASE,
+            );
+        }
+        let bss = (0..elf.section_number())
+            .find(|&index| elf.section_info(SectionIndex(index)).unwrap().unwrap().name == b".bss")
+            .unwrap();
+        add(
+            &mut moved,
+            sections + bss * SectionHeader::SIZE + 0x20,
+            BASE,
+        );
+        let head = moved[..programs + elf.program_number() * ProgramHeader::SIZE].to_vec();
+        (head, moved)
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn offsets_above_4_gib() {
+        let raw = executable(Encoding::Little);
+        let (head, moved) = moved(&raw);
+        let fragments = [(Offset(0), &head[..]), (Offset(BASE), &moved[..])];
+        let elf = Elf64::from_fragments(&fragments).unwrap();
+        let original = Elf64::new(&raw).unwrap();
+        assert_eq!(elf.section_number(), original.section_number());
+        for index in 1..elf.section_number() {
+            let info = elf.section_info(SectionIndex(index)).unwrap().unwrap();
+            let expected = original.section_info(SectionIndex(index)).unwrap().unwrap();
+            assert_eq!(info.name, expected.name);
+            assert!(info.header.offset.0 > u64::from(u32::MAX));
+            elf.section(SectionIndex(index)).unwrap();
+        }
+        let bss = elf.section_by_name(b".bss").unwrap().unwrap();
+        assert!(matches!(bss.data, SectionData::NoBits { size } if size == BASE + 0x40));
+        let notes = elf.notes().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].name, b"GNU");
+        assert_eq!(notes[0].description, [1, 2, 3, 4]);
+        let needed = elf.needed().unwrap().collect::<Result<Vec<_>, _>>();
+        assert_eq!(needed.unwrap(), [b"libc.so.6"]);
+        let symbols = elf.symbols().unwrap().unwrap();
+        let names = symbols.map(|symbol| symbol.unwrap().name);
+        assert_eq!(names.collect::<Vec<_>>(), [&b"_start"[..], b"undefined"]);
+        for index in 0..elf.program_number() {
+            elf.program(index).unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "32")]
+    fn offsets_above_4_gib() {
+        let raw = executable(Encoding::Little);
+        let (head, moved) = moved(&raw);
+        let fragments = [(Offset(0), &head[..]), (Offset(BASE), &moved[..])];
+        let error = Elf64::from_fragments(&fragments).unwrap_err();
+        assert_eq!(error, crate::Error::ArithmeticOverflow);
+    }
+}
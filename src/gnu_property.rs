@@ -0,0 +1,139 @@
+use super::{Error, Encoding, Elf64, NoteEntry};
+
+bitflags! {
+    // `GNU_PROPERTY_X86_FEATURE_1_AND`, the features of CET every object was built for
+    pub struct X86Feature1: u32 {
+        const IBT = 0b00000001;
+        const SHSTK = 0b00000010;
+    }
+}
+
+bitflags! {
+    // `GNU_PROPERTY_AARCH64_FEATURE_1_AND`
+    pub struct AArch64Feature1: u32 {
+        const BTI = 0b00000001;
+        const PAC = 0b00000010;
+    }
+}
+
+bitflags! {
+    // `GNU_PROPERTY_X86_ISA_1_NEEDED`, the levels of the x86-64 psABI
+    pub struct X86IsaLevel: u32 {
+        const BASELINE = 0b00000001;
+        const V2 = 0b00000010;
+        const V3 = 0b00000100;
+        const V4 = 0b00001000;
+    }
+}
+
+// An element of `NT_GNU_PROPERTY_TYPE_0`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GnuProperty<'a> {
+    // `GNU_PROPERTY_STACK_SIZE`
+    StackSize(u64),
+    // `GNU_PROPERTY_NO_COPY_ON_PROTECTED`
+    NoCopyOnProtected,
+    X86Feature1(X86Feature1),
+    AArch64Feature1(AArch64Feature1),
+    X86IsaNeeded(X86IsaLevel),
+    Other { ty: u32, data: &'a [u8] },
+}
+
+// The properties of `NT_GNU_PROPERTY_TYPE_0`, `pr_type`, `pr_datasz` and the data
+// aligned to eight bytes, a truncated property is the last item
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GnuProperties<'a> {
+    slice: &'a [u8],
+    encoding: Encoding,
+}
+
+impl<'a> GnuProperties<'a> {
+    pub fn new(slice: &'a [u8], encoding: Encoding) -> Self {
+        GnuProperties { slice, encoding }
+    }
+
+    fn read(&self) -> Result<(GnuProperty<'a>, usize), Error> {
+        if self.slice.len() < 8 {
+            return Err(Error::SliceTooShort);
+        }
+        let ty = read_int!(self.slice, &self.encoding, u32);
+        let size = read_int!(&self.slice[4..], &self.encoding, u32) as usize;
+        let data = self
+            .slice
+            .get(8..)
+            .and_then(|rest| rest.get(..size))
+            .ok_or(Error::SliceTooShort)?;
+        let word = || match data.len() {
+            4 => Ok(read_int!(data, &self.encoding, u32)),
+            _ => Err(Error::SliceTooShort),
+        };
+        let property = match ty {
+            1 if data.len() == 8 => GnuProperty::StackSize(read_int!(data, &self.encoding, u64)),
+            2 => GnuProperty::NoCopyOnProtected,
+            0xc0000002 => GnuProperty::X86Feature1(X86Feature1::from_bits_truncate(word()?)),
+            0xc0000000 => {
+                GnuProperty::AArch64Feature1(AArch64Feature1::from_bits_truncate(word()?))
+            }
+            0xc0008002 => GnuProperty::X86IsaNeeded(X86IsaLevel::from_bits_truncate(word()?)),
+            ty => GnuProperty::Other { ty, data },
+        };
+        let next = (8 + size + 7) & !7;
+        Ok((property, next.min(self.slice.len())))
+    }
+}
+
+impl<'a> Iterator for GnuProperties<'a> {
+    type Item = Result<GnuProperty<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.slice.is_empty() {
+            return None;
+        }
+        match self.read() {
+            Ok((property, next)) => {
+                self.slice = &self.slice[next..];
+                Some(Ok(property))
+            }
+            Err(error) => {
+                self.slice = &[];
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl<'a> NoteEntry<'a> {
+    // `NT_GNU_PROPERTY_TYPE_0` of the `GNU` owner
+    pub fn gnu_properties(&self) -> Option<GnuProperties<'a>> {
+        match (self.name, self.ty) {
+            (b"GNU", 5) => Some(GnuProperties::new(
+                self.description,
+                self.encoding().clone(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // `NT_GNU_BUILD_ID`, the first one
+    pub fn build_id(&self) -> Result<Option<&'a [u8]>, Error> {
+        for note in self.notes() {
+            let note = note?;
+            if note.name == b"GNU" && note.ty == 3 {
+                return Ok(Some(note.description));
+            }
+        }
+        Ok(None)
+    }
+
+    // `NT_GNU_PROPERTY_TYPE_0`, in `PT_GNU_PROPERTY` or in `.note.gnu.property`
+    pub fn gnu_properties(&self) -> Result<Option<GnuProperties<'a>>, Error> {
+        for note in self.notes() {
+            if let Some(properties) = note?.gnu_properties() {
+                return Ok(Some(properties));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,140 @@
+use alloc::vec::Vec;
+
+use super::{
+    Error, Address, Elf64, Symbol, SymbolType, ProgramFlags, LoadedImage, DynamicRelocationTable,
+};
+
+// The code of a module loaded in the process, e.g. an executable mapping of
+// `/proc/<pid>/maps`, the entries of the GOT may point there
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleRange<'n> {
+    pub name: &'n [u8],
+    pub address: Address,
+    pub size: u64,
+}
+
+impl<'n> ModuleRange<'n> {
+    pub fn contains(&self, address: Address) -> bool {
+        address
+            .0
+            .checked_sub(self.address.0)
+            .is_some_and(|offset| offset < self.size)
+    }
+}
+
+// An entry of the GOT that holds the address of an imported function, the place of
+// `R_*_JUMP_SLOT`, or of `R_*_GLOB_DAT` against a function
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GotSlot<'a> {
+    pub table: DynamicRelocationTable,
+    // the virtual address, without the load bias
+    pub address: Address,
+    pub symbol: Symbol<'a>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HookedSlot<'a> {
+    pub slot: GotSlot<'a>,
+    // the run time address read from the slot in the image
+    pub target: Address,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GotHookReport<'a> {
+    // the slots read from the image
+    pub checked: usize,
+    // point outside the code of every known module and of the file itself
+    pub hooked: Vec<HookedSlot<'a>>,
+    // the image does not have the slot
+    pub unavailable: Vec<GotSlot<'a>>,
+}
+
+impl<'a> GotHookReport<'a> {
+    pub fn is_clean(&self) -> bool {
+        self.hooked.is_empty()
+    }
+}
+
+// `STT_GNU_IFUNC`, the slot holds the function the resolver returned
+const INDIRECT_FUNCTION: SymbolType = SymbolType::OsSpecific(0);
+
+impl<'a> Elf64<'a> {
+    // The entries of the GOT filled with the addresses of functions, found by
+    // the dynamic relocations and `DT_SYMTAB`, without section headers
+    pub fn got_slots(&self) -> Result<Vec<GotSlot<'a>>, Error> {
+        let machine = self.machine();
+        let mut slots = Vec::new();
+        for relocation in self.dynamic_relocations()? {
+            let (table, relocation) = relocation?;
+            let any = match relocation.any() {
+                Some(any) if any.symbol_index.0 != 0 => any,
+                _ => continue,
+            };
+            let kind = any.kind(&machine);
+            if !kind.is_jump_slot() && !kind.is_global_data() {
+                continue;
+            }
+            let symbol = self.dynamic_symbol(any.symbol_index.0)?;
+            let function = matches!(
+                symbol.entry.info.ty,
+                SymbolType::Function | INDIRECT_FUNCTION
+            );
+            // `R_*_GLOB_DAT` of an object points to data
+            if kind.is_jump_slot() || function {
+                slots.push(GotSlot {
+                    table,
+                    address: any.address,
+                    symbol,
+                });
+            }
+        }
+        Ok(slots)
+    }
+
+    // Reads the slots of `got_slots` from the image and reports the ones that point
+    // neither in the code of the modules nor in an executable `PT_LOAD` of the file,
+    // the lazy binding leaves the address of the PLT of the file there.
+    // An undefined weak function is zero, it is not reported.
+    pub fn got_hooks(
+        &self,
+        image: &LoadedImage<'_>,
+        modules: &[ModuleRange<'_>],
+    ) -> Result<GotHookReport<'a>, Error> {
+        let bias = image.bias();
+        let encoding = self.encoding();
+        let mut code = Vec::new();
+        for header in self.load_segments()? {
+            let header = header?;
+            if header.flags.contains(ProgramFlags::EXECUTE) {
+                code.push(ModuleRange {
+                    name: &[],
+                    address: bias.wrapping_add(header.virtual_address.0),
+                    size: header.memory_size,
+                });
+            }
+        }
+
+        let mut report = GotHookReport::default();
+        for slot in self.got_slots()? {
+            let target = match image.word(bias.wrapping_add(slot.address.0), &encoding) {
+                Some(target) => Address(target),
+                None => {
+                    report.unavailable.push(slot);
+                    continue;
+                }
+            };
+            report.checked += 1;
+            if target.0 == 0 {
+                continue;
+            }
+            let known = modules
+                .iter()
+                .chain(code.iter())
+                .any(|module| module.contains(target));
+            if !known {
+                report.hooked.push(HookedSlot { slot, target });
+            }
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,160 @@
+use super::{
+    Error, Elf64, SectionIndex, SectionType, SectionHeader, SectionData, ProgramData, Symbol,
+    NoteEntry, NoteTable,
+};
+use super::common::{file_slice, to_usize};
+
+// The ids are plain indices of the parts of a file, for a caller that cannot keep
+// a borrow between two calls, a C library, a script, a GUI, a lookup by an id borrows
+// the bytes of the file only. A section by its index in the section header table.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct SectionId(pub u32);
+
+// A symbol by the section of its table, `.symtab` or `.dynsym`, and its index there
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[repr(C)]
+pub struct SymbolId {
+    pub table: SectionId,
+    pub index: u32,
+}
+
+// A note by its table, a `PT_NOTE` or a `SHT_NOTE`, and its offset in the table
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[repr(C)]
+pub struct NoteId {
+    // the index of the program header if the file has them, of the section if not,
+    // the way `Elf64::notes` chooses
+    pub table: u32,
+    pub offset: u64,
+}
+
+fn index(id: u32) -> usize {
+    id as usize
+}
+
+impl<'a> Elf64<'a> {
+    pub fn section_id_by_name(&self, name: &[u8]) -> Result<Option<SectionId>, Error> {
+        for index in 0..self.section_number() {
+            match self.section_info(SectionIndex(index))? {
+                Some(info) if info.name == name => return Ok(Some(SectionId(index as u32))),
+                _ => (),
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn section_header_by_id(&self, id: SectionId) -> Result<SectionHeader, Error> {
+        self.section_table.pick(index(id.0))
+    }
+
+    // empty if the file has no section names
+    pub fn section_name_by_id(&self, id: SectionId) -> Result<&'a [u8], Error> {
+        self.section_info(SectionIndex(index(id.0)))?
+            .map(|info| info.name)
+            .ok_or(Error::IndexOutOfBounds)
+    }
+
+    // The bytes of the section in the file, empty for `SHT_NOBITS`
+    pub fn section_bytes_by_id(&self, id: SectionId) -> Result<&'a [u8], Error> {
+        let header = self.section_header_by_id(id)?;
+        match header.ty {
+            SectionType::Null | SectionType::NoBits => Ok(&[]),
+            _ => file_slice(self.raw, header.offset, header.size),
+        }
+    }
+
+    // The number of the entries of the symbol table, with the null symbol,
+    // `Error::UnexpectedSectionType` if the section is not a symbol table
+    pub fn symbol_count(&self, table: SectionId) -> Result<u32, Error> {
+        let source = self.symbol_source_at(index(table.0))?;
+        Ok(source.number as u32)
+    }
+
+    pub fn symbol_by_id(&self, id: SymbolId) -> Result<Symbol<'a>, Error> {
+        self.symbol_source_at(index(id.table.0))?
+            .get(index(id.index))
+    }
+
+    // The first symbol of the name, of `.symtab` and then of `.dynsym`
+    pub fn symbol_id_by_name(&self, name: &[u8]) -> Result<Option<SymbolId>, Error> {
+        for ty in [SectionType::SymbolTable, SectionType::DynamicSymbolTable] {
+            for table in 0..self.section_number() {
+                if self.section_table.pick(table)?.ty != ty {
+                    continue;
+                }
+                let source = self.symbol_source_at(table)?;
+                for index in 1..source.number {
+                    if source.get(index)?.name == name {
+                        return Ok(Some(SymbolId {
+                            table: SectionId(table as u32),
+                            index: index as u32,
+                        }));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // `None` if the table is not of notes, `Error::IndexOutOfBounds` past the last one
+    fn note_table(&self, table: usize) -> Result<Option<NoteTable<'a>>, Error> {
+        if self.program_number() != 0 {
+            match self.program(table)?.map(|p| p.data) {
+                Some(ProgramData::Note(notes)) => Ok(Some(notes)),
+                Some(_) => Ok(None),
+                None => Err(Error::IndexOutOfBounds),
+            }
+        } else {
+            match self.section(SectionIndex(table))?.map(|s| s.data) {
+                Some(SectionData::Note(notes)) => Ok(Some(notes)),
+                Some(_) => Ok(None),
+                None => Err(Error::IndexOutOfBounds),
+            }
+        }
+    }
+
+    fn note_tables(&self) -> usize {
+        if self.program_number() != 0 {
+            self.program_number()
+        } else {
+            self.section_number()
+        }
+    }
+
+    // The note after the one of the id, the first note for `None`, the notes of
+    // `Elf64::notes` in the same order, a corrupted note is an error
+    pub fn next_note_id(&self, after: Option<NoteId>) -> Result<Option<NoteId>, Error> {
+        let (mut table, mut position) = match after {
+            Some(id) => {
+                let notes = self
+                    .note_table(index(id.table))?
+                    .ok_or(Error::UnexpectedSectionType)?;
+                let mut position = to_usize(id.offset)?;
+                notes.next(&mut position)?;
+                (index(id.table), position)
+            }
+            None => (0, 0),
+        };
+        while table < self.note_tables() {
+            if let Some(notes) = self.note_table(table)? {
+                if position < notes.as_raw().len() {
+                    return Ok(Some(NoteId {
+                        table: table as u32,
+                        offset: position as u64,
+                    }));
+                }
+            }
+            table += 1;
+            position = 0;
+        }
+        Ok(None)
+    }
+
+    pub fn note_by_id(&self, id: NoteId) -> Result<NoteEntry<'a>, Error> {
+        let notes = self
+            .note_table(index(id.table))?
+            .ok_or(Error::UnexpectedSectionType)?;
+        notes.next(&mut to_usize(id.offset)?)
+    }
+}
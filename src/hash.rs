@@ -0,0 +1,299 @@
+use core::fmt;
+
+use super::{
+    Error, SectionIndex, Encoding, Elf64, Index, Table, WordTable, StringTable, SymbolEntry,
+    Symbol, SectionType, SectionData,
+};
+
+// `SHT_GNU_HASH`
+pub(crate) const SHT_GNU_HASH: u32 = 0x6ffffff6;
+
+// the hash of `.hash`
+pub fn elf_hash(name: &[u8]) -> u32 {
+    let mut h = 0u32;
+    for &c in name {
+        h = (h << 4).wrapping_add(c as u32);
+        let g = h & 0xf0000000;
+        if g != 0 {
+            h ^= g >> 24;
+        }
+        h &= !g;
+    }
+    h
+}
+
+// the hash of `.gnu.hash`, the one of Bernstein
+pub fn gnu_hash(name: &[u8]) -> u32 {
+    name.iter()
+        .fold(5381u32, |h, &c| h.wrapping_mul(33).wrapping_add(c as u32))
+}
+
+// the words of the tables follow each other, the counts are from the file
+fn word_index(start: usize, index: usize) -> Result<usize, Error> {
+    start.checked_add(index).ok_or(Error::ArithmeticOverflow)
+}
+
+// the symbol is the defined one of the name, every version of it matches
+fn matches(
+    symbols: &Table<'_, SymbolEntry>,
+    names: &StringTable<'_>,
+    index: usize,
+    name: &[u8],
+) -> Result<Option<SymbolEntry>, Error> {
+    let entry = symbols.pick(index)?;
+    if entry.section_index != Index::Undefined && names.pick(entry.name as usize)? == name {
+        Ok(Some(entry))
+    } else {
+        Ok(None)
+    }
+}
+
+// The SysV `.hash` section, `nbucket`, `nchain`, the buckets and the chains,
+// all are 32 bit words
+#[derive(Clone)]
+pub struct HashTable<'a> {
+    slice: &'a [u8],
+    encoding: Encoding,
+}
+
+impl<'a> HashTable<'a> {
+    pub fn new(slice: &'a [u8], encoding: Encoding) -> Self {
+        HashTable { slice, encoding }
+    }
+
+    fn word(&self, index: usize) -> Result<u32, Error> {
+        WordTable::<u32>::new(self.slice, self.encoding.clone())
+            .get(index)
+            .ok_or(Error::SliceTooShort)
+    }
+
+    pub fn bucket_number(&self) -> Result<usize, Error> {
+        self.word(0).map(|n| n as usize)
+    }
+
+    // the same as the number of symbols in the linked symbol table
+    pub fn chain_number(&self) -> Result<usize, Error> {
+        self.word(1).map(|n| n as usize)
+    }
+
+    // the first symbol index of the bucket
+    pub fn bucket(&self, index: usize) -> Result<u32, Error> {
+        if index >= self.bucket_number()? {
+            return Err(Error::IndexOutOfBounds);
+        }
+        self.word(2 + index)
+    }
+
+    // the next symbol index with the same bucket, zero ends the chain
+    pub fn chain(&self, index: usize) -> Result<u32, Error> {
+        if index >= self.chain_number()? {
+            return Err(Error::IndexOutOfBounds);
+        }
+        self.word(word_index(2 + self.bucket_number()?, index)?)
+    }
+
+    // The defined symbol of the name in the linked symbol table, by its index,
+    // the chain of the bucket of the hash is all that is read
+    pub fn lookup(
+        &self,
+        name: &[u8],
+        symbols: &Table<'_, SymbolEntry>,
+        names: &StringTable<'_>,
+    ) -> Result<Option<(usize, SymbolEntry)>, Error> {
+        let bucket_number = self.bucket_number()?;
+        if bucket_number == 0 {
+            return Ok(None);
+        }
+        let mut index = self.bucket(elf_hash(name) as usize % bucket_number)? as usize;
+        // a corrupted chain may loop, it is not longer than the table
+        for _ in 0..self.chain_number()? {
+            if index == 0 {
+                break;
+            }
+            if let Some(entry) = matches(symbols, names, index, name)? {
+                return Ok(Some((index, entry)));
+            }
+            index = self.chain(index)? as usize;
+        }
+        Ok(None)
+    }
+
+    pub fn as_raw(&self) -> &'a [u8] {
+        self.slice
+    }
+}
+
+impl<'a> fmt::Debug for HashTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashTable")
+            .field("bucket_number", &self.bucket_number().ok())
+            .field("chain_number", &self.chain_number().ok())
+            .finish()
+    }
+}
+
+// The `.gnu.hash` section, `nbuckets`, `symoffset`, `bloom_size` and `bloom_shift`,
+// the 64 bit words of the bloom filter, the buckets and the chains. The symbols
+// below `symoffset` are not in the table, the chains are of the rest of them,
+// the lowest bit of a chain value ends the chain.
+#[derive(Clone)]
+pub struct GnuHashTable<'a> {
+    slice: &'a [u8],
+    encoding: Encoding,
+}
+
+impl<'a> GnuHashTable<'a> {
+    pub fn new(slice: &'a [u8], encoding: Encoding) -> Self {
+        GnuHashTable { slice, encoding }
+    }
+
+    fn word(&self, index: usize) -> Result<u32, Error> {
+        WordTable::<u32>::new(self.slice, self.encoding.clone())
+            .get(index)
+            .ok_or(Error::SliceTooShort)
+    }
+
+    pub fn bucket_number(&self) -> Result<usize, Error> {
+        self.word(0).map(|n| n as usize)
+    }
+
+    // the index of the first symbol in the table
+    pub fn symbol_offset(&self) -> Result<usize, Error> {
+        self.word(1).map(|n| n as usize)
+    }
+
+    pub fn bloom_size(&self) -> Result<usize, Error> {
+        self.word(2).map(|n| n as usize)
+    }
+
+    pub fn bloom_shift(&self) -> Result<u32, Error> {
+        self.word(3)
+    }
+
+    pub fn bloom(&self, index: usize) -> Result<u64, Error> {
+        if index >= self.bloom_size()? {
+            return Err(Error::IndexOutOfBounds);
+        }
+        WordTable::<u64>::new(self.slice.get(0x10..).unwrap_or(&[]), self.encoding.clone())
+            .get(index)
+            .ok_or(Error::SliceTooShort)
+    }
+
+    // in 32 bit words from the start
+    fn buckets_start(&self) -> Result<usize, Error> {
+        self.bloom_size()?
+            .checked_mul(2)
+            .and_then(|words| words.checked_add(4))
+            .ok_or(Error::ArithmeticOverflow)
+    }
+
+    // the first symbol index of the bucket, zero is an empty bucket
+    pub fn bucket(&self, index: usize) -> Result<u32, Error> {
+        if index >= self.bucket_number()? {
+            return Err(Error::IndexOutOfBounds);
+        }
+        self.word(word_index(self.buckets_start()?, index)?)
+    }
+
+    // the hash of the symbol of the index with the lowest bit set for the last
+    // symbol of the chain, only for the symbols from `symbol_offset`
+    pub fn chain(&self, symbol: usize) -> Result<u32, Error> {
+        let index = symbol
+            .checked_sub(self.symbol_offset()?)
+            .ok_or(Error::IndexOutOfBounds)?;
+        let chains = word_index(self.buckets_start()?, self.bucket_number()?)?;
+        self.word(word_index(chains, index)?)
+    }
+
+    // The defined symbol of the name in the linked symbol table, by its index,
+    // the bloom filter rejects most of the names that are not there
+    pub fn lookup(
+        &self,
+        name: &[u8],
+        symbols: &Table<'_, SymbolEntry>,
+        names: &StringTable<'_>,
+    ) -> Result<Option<(usize, SymbolEntry)>, Error> {
+        let (bucket_number, bloom_size) = (self.bucket_number()?, self.bloom_size()?);
+        if bucket_number == 0 || bloom_size == 0 {
+            return Ok(None);
+        }
+        let hash = gnu_hash(name);
+        let word = self.bloom((hash as usize / 64) % bloom_size)?;
+        let shift = self.bloom_shift()?;
+        let mask = (1u64 << (hash % 64)) | (1u64 << (hash.wrapping_shr(shift) % 64));
+        if word & mask != mask {
+            return Ok(None);
+        }
+        let mut index = self.bucket(hash as usize % bucket_number)? as usize;
+        if index == 0 || index < self.symbol_offset()? {
+            return Ok(None);
+        }
+        loop {
+            let chain = self.chain(index)?;
+            if chain | 1 == hash | 1 {
+                if let Some(entry) = matches(symbols, names, index, name)? {
+                    return Ok(Some((index, entry)));
+                }
+            }
+            if chain & 1 != 0 {
+                return Ok(None);
+            }
+            index += 1;
+        }
+    }
+
+    pub fn as_raw(&self) -> &'a [u8] {
+        self.slice
+    }
+}
+
+impl<'a> fmt::Debug for GnuHashTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GnuHashTable")
+            .field("bucket_number", &self.bucket_number().ok())
+            .field("symbol_offset", &self.symbol_offset().ok())
+            .field("bloom_size", &self.bloom_size().ok())
+            .finish()
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The defined symbol of the name in `.dynsym` by `.gnu.hash`, or `.hash` if there
+    // is no `.gnu.hash`, the way the runtime linker finds it. `None` if there is none
+    // of the tables, the versions are not checked.
+    pub fn lookup_dynamic_symbol(&self, name: &[u8]) -> Result<Option<Symbol<'a>>, Error> {
+        let mut hash = None;
+        for index in 0..self.section_number() {
+            let section = match self.section(SectionIndex(index))? {
+                Some(section) => section,
+                None => continue,
+            };
+            match section.data {
+                SectionData::GnuHash(_) => {
+                    hash = Some(section);
+                    break;
+                }
+                SectionData::Hash(_) if hash.is_none() => hash = Some(section),
+                _ => (),
+            }
+        }
+        let hash = match hash {
+            Some(hash) => hash,
+            None => return Ok(None),
+        };
+        let link = match hash.link {
+            Index::Regular(link) => link,
+            _ => return Err(Error::UnexpectedSectionType),
+        };
+        if self.section_table.pick(link.0)?.ty != SectionType::DynamicSymbolTable {
+            return Err(Error::UnexpectedSectionType);
+        }
+        let source = self.symbol_source_at(link.0)?;
+        let found = match hash.data {
+            SectionData::GnuHash(table) => table.lookup(name, &source.table, &source.names)?,
+            SectionData::Hash(table) => table.lookup(name, &source.table, &source.names)?,
+            _ => None,
+        };
+        found.map(|(index, _)| source.get(index)).transpose()
+    }
+}
@@ -0,0 +1,34 @@
+// Lets the user choose the digest, e.g. SHA-1 for build ids compatible with GNU ld,
+// the crate has no dependency on any cryptography.
+pub trait Hasher {
+    type Output: AsRef<[u8]>;
+
+    fn update(&mut self, data: &[u8]);
+
+    fn finish(self) -> Self::Output;
+}
+
+// FNV-1a, 64 bits, good only for identification, not for integrity
+#[derive(Clone)]
+pub struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    type Output = [u8; 8];
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x00000100000001b3);
+        }
+    }
+
+    fn finish(self) -> Self::Output {
+        self.0.to_be_bytes()
+    }
+}
@@ -1,8 +1,14 @@
 use core::{convert::TryFrom, fmt};
 
-use super::{Error, UnexpectedSize, Address, Offset, Index, SectionHeader, ProgramHeader, Entry, Table};
+use super::{
+    Error, UnexpectedSize, Address, Offset, Index, SectionIndex, SectionHeader, ProgramHeader,
+    Entry, Table,
+};
+use super::common::{file_slice, file_tail, to_usize};
+use super::fragments::Source;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Class {
     _32,
     _64,
@@ -19,7 +25,18 @@ impl From<u8> for Class {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl From<Class> for u8 {
+    fn from(v: Class) -> Self {
+        match v {
+            Class::_32 => 1,
+            Class::_64 => 2,
+            Class::Unknown(t) => t,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Encoding {
     Little,
     Big,
@@ -37,7 +54,17 @@ impl TryFrom<u8> for Encoding {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl From<Encoding> for u8 {
+    fn from(v: Encoding) -> Self {
+        match v {
+            Encoding::Little => 1,
+            Encoding::Big => 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Abi {
     SystemV,
     HpUx,
@@ -72,13 +99,35 @@ impl From<u8> for Abi {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl From<Abi> for u8 {
+    fn from(v: Abi) -> Self {
+        match v {
+            Abi::SystemV => 0x00,
+            Abi::HpUx => 0x01,
+            Abi::NetBSD => 0x02,
+            Abi::Linux => 0x03,
+            Abi::Solaris => 0x06,
+            Abi::Aix => 0x07,
+            Abi::Irix => 0x08,
+            Abi::FreeBSD => 0x09,
+            Abi::OpenBSD => 0x0c,
+            Abi::OpenVMS => 0x0d,
+            Abi::Standalone => 0xff,
+            Abi::Unknown(t) => t,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Identifier {
     pub class: Class,
     pub encoding: Encoding,
     pub version: u8,
     pub abi: Abi,
     pub abi_version: u8,
+    // `EI_PAD`, zeros by the standard, some tools stash a mark there
+    pub padding: [u8; 7],
 }
 
 impl Identifier {
@@ -88,17 +137,35 @@ impl Identifier {
         if !(slice[0x00] == 0x7f && slice[0x01..0x04].eq(b"ELF")) {
             return Err(Error::WrongMagicNumber);
         };
+        let mut padding = [0; 7];
+        padding.clone_from_slice(&slice[0x09..0x10]);
         Ok(Identifier {
             class: slice[0x04].into(),
             encoding: slice[0x05].try_into().map_err(Error::UnknownEncoding)?,
             version: slice[0x06],
             abi: slice[0x07].into(),
             abi_version: slice[0x08],
+            padding,
         })
     }
+
+    // `e_ident` as it is in the file
+    pub fn bytes(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[0x00] = 0x7f;
+        bytes[0x01..0x04].clone_from_slice(b"ELF");
+        bytes[0x04] = self.class.clone().into();
+        bytes[0x05] = self.encoding.clone().into();
+        bytes[0x06] = self.version;
+        bytes[0x07] = self.abi.clone().into();
+        bytes[0x08] = self.abi_version;
+        bytes[0x09..0x10].clone_from_slice(&self.padding);
+        bytes
+    }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Type {
     None,
     Relocatable,
@@ -125,18 +192,36 @@ impl From<u16> for Type {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl From<Type> for u16 {
+    fn from(v: Type) -> Self {
+        match v {
+            Type::None => 0x0000,
+            Type::Relocatable => 0x0001,
+            Type::Executable => 0x0002,
+            Type::SharedObject => 0x0003,
+            Type::Core => 0x0004,
+            Type::OsSpecific(t) => 0xfe00 | (t as u16),
+            Type::ProcessorSpecific(t) => 0xff00 | (t as u16),
+            Type::Unknown(t) => t,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Machine {
     None,
     Sparc,
     X86,
     Mips,
     PowerPC,
+    PowerPC64,
     Arm,
     SuperH,
     Ia64,
     X86_64,
     AArch64,
+    RiscV,
     Bpf,
     Unknown(u16),
 }
@@ -149,18 +234,94 @@ impl From<u16> for Machine {
             0x0003 => Machine::X86,
             0x0008 => Machine::Mips,
             0x0014 => Machine::PowerPC,
+            0x0015 => Machine::PowerPC64,
             0x0028 => Machine::Arm,
             0x002a => Machine::SuperH,
             0x0032 => Machine::Ia64,
             0x003e => Machine::X86_64,
             0x00b7 => Machine::AArch64,
+            0x00f3 => Machine::RiscV,
             0x00f7 => Machine::Bpf,
             t => Machine::Unknown(t),
         }
     }
 }
 
-#[derive(Clone, Eq, PartialEq)]
+impl From<Machine> for u16 {
+    fn from(v: Machine) -> Self {
+        match v {
+            Machine::None => 0x0000,
+            Machine::Sparc => 0x0002,
+            Machine::X86 => 0x0003,
+            Machine::Mips => 0x0008,
+            Machine::PowerPC => 0x0014,
+            Machine::PowerPC64 => 0x0015,
+            Machine::Arm => 0x0028,
+            Machine::SuperH => 0x002a,
+            Machine::Ia64 => 0x0032,
+            Machine::X86_64 => 0x003e,
+            Machine::AArch64 => 0x00b7,
+            Machine::RiscV => 0x00f3,
+            Machine::Bpf => 0x00f7,
+            Machine::Unknown(t) => t,
+        }
+    }
+}
+
+// The sizes of the pages the kernels of the machine are built with, and the
+// alignment of the segments the linker gives by default
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct PageSizes {
+    // in increasing order, the first is the usual one, never empty, it is
+    // only made by `Machine::page_sizes`
+    sizes: &'static [u64],
+    // `-z max-page-size` of GNU ld, a segment of this alignment loads with
+    // a page of any of `sizes`
+    pub max_page_size: u64,
+}
+
+const K: u64 = 0x400;
+
+impl PageSizes {
+    pub fn sizes(&self) -> &'static [u64] {
+        self.sizes
+    }
+
+    pub fn smallest(&self) -> u64 {
+        self.sizes[0]
+    }
+
+    pub fn largest(&self) -> u64 {
+        self.sizes[self.sizes.len() - 1]
+    }
+}
+
+impl Machine {
+    // `None` for a machine of no conventional page size, e.g. BPF, or an unknown one.
+    // AArch64 kernels take 4, 16 or 64 KiB pages, Apple processors and some Android
+    // devices use 16 KiB, enterprise distributions 64 KiB.
+    pub fn page_sizes(&self) -> Option<PageSizes> {
+        let (sizes, max_page_size): (&'static [u64], u64) = match self {
+            Machine::X86 | Machine::X86_64 => (&[4 * K], 4 * K),
+            Machine::AArch64 => (&[4 * K, 16 * K, 64 * K], 64 * K),
+            Machine::PowerPC | Machine::PowerPC64 => (&[4 * K, 64 * K], 64 * K),
+            Machine::Mips => (&[4 * K, 16 * K, 64 * K], 64 * K),
+            Machine::Ia64 => (&[4 * K, 8 * K, 16 * K, 64 * K], 64 * K),
+            Machine::SuperH => (&[4 * K, 8 * K, 16 * K, 64 * K], 64 * K),
+            Machine::Arm => (&[4 * K], 64 * K),
+            Machine::RiscV => (&[4 * K], 4 * K),
+            Machine::Sparc => (&[4 * K], 64 * K),
+            Machine::None | Machine::Bpf | Machine::Unknown(_) => return None,
+        };
+        Some(PageSizes {
+            sizes,
+            max_page_size,
+        })
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Header {
     pub identifier: Identifier,
     pub ty: Type,
@@ -170,6 +331,10 @@ pub struct Header {
     pub program_headers_offset: Offset,
     pub section_headers_offset: Offset,
     pub flags: u32,
+    // `e_phentsize` and `e_shentsize`, the size of an entry is checked only if there
+    // is a table, an object file of GNU as has zero program header size
+    pub program_header_size: u16,
+    pub section_header_size: u16,
     pub program_header_number: u16,
     pub section_header_number: u16,
     pub section_names: Index,
@@ -188,11 +353,22 @@ impl fmt::Debug for Header {
             .field("format_version", &self.format_version)
             .field("entry", &format_args!("0x{:08x}", self.entry))
             .field("flags", &self.flags)
+            .field("program_header_size", &self.program_header_size)
+            .field("section_header_size", &self.section_header_size)
             .field("section_names", &self.section_names)
             .finish()
     }
 }
 
+// `sh_link` of the section zero for `e_shstrndx` of `SHN_XINDEX`, the index is
+// of a section past `SHN_LORESERVE`, it is not a reserved one
+pub(crate) fn extended_names(first: &SectionHeader) -> Index {
+    match u32::from(first.link.clone()) {
+        0 => Index::Undefined,
+        index => Index::Regular(SectionIndex(index as usize)),
+    }
+}
+
 impl Header {
     pub const SIZE: usize = 0x40;
 
@@ -205,10 +381,20 @@ impl Header {
         if read_int!(&slice[0x34..], &identifier.encoding, u16) as usize != Self::SIZE {
             return Err(Error::UnexpectedSize(UnexpectedSize::Header));
         };
-        if read_int!(&slice[0x36..], &identifier.encoding, u16) as usize != ProgramHeader::SIZE {
+        // relocatable files have no program headers and may leave the entry size zero
+        let program_header_number = read_int!(&slice[0x38..], &identifier.encoding, u16);
+        let program_header_size = read_int!(&slice[0x36..], &identifier.encoding, u16);
+        if program_header_number != 0 && program_header_size as usize != ProgramHeader::SIZE {
             return Err(Error::UnexpectedSize(UnexpectedSize::ProgramHeader));
         };
-        if read_int!(&slice[0x3a..], &identifier.encoding, u16) as usize != SectionHeader::SIZE {
+        // so do core files for the section headers, a zero number with an offset
+        // is the extended numbering and needs the size
+        let section_header_number = read_int!(&slice[0x3c..], &identifier.encoding, u16);
+        let section_header_offset = read_int!(&slice[0x28..], &identifier.encoding, u64);
+        let section_header_size = read_int!(&slice[0x3a..], &identifier.encoding, u16);
+        if (section_header_number != 0 || section_header_offset != 0)
+            && section_header_size as usize != SectionHeader::SIZE
+        {
             return Err(Error::UnexpectedSize(UnexpectedSize::SectionHeader));
         };
         let encoding = identifier.encoding.clone();
@@ -217,35 +403,178 @@ impl Header {
             ty: read_int!(&slice[0x10..], &encoding, u16).into(),
             machine: read_int!(&slice[0x12..], &encoding, u16).into(),
             format_version: read_int!(&slice[0x14..], &encoding, u32),
-            entry: read_int!(&slice[0x18..], &encoding, u64),
-            program_headers_offset: read_int!(&slice[0x20..], &encoding, u64),
-            section_headers_offset: read_int!(&slice[0x28..], &encoding, u64),
+            entry: Address(read_int!(&slice[0x18..], &encoding, u64)),
+            program_headers_offset: Offset(read_int!(&slice[0x20..], &encoding, u64)),
+            section_headers_offset: Offset(read_int!(&slice[0x28..], &encoding, u64)),
             flags: read_int!(&slice[0x30..], &encoding, u32),
-            program_header_number: read_int!(&slice[0x38..], &encoding, u16),
-            section_header_number: read_int!(&slice[0x3c..], &encoding, u16),
+            program_header_size,
+            section_header_size,
+            program_header_number,
+            section_header_number,
             section_names: read_int!(&slice[0x3e..], &encoding, u16).into(),
         })
     }
 
+    pub fn encode(&self, slice: &mut [u8]) -> Result<(), Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let encoding = self.identifier.encoding.clone();
+        slice[0x00..0x10].clone_from_slice(&self.identifier.bytes());
+        write_int!(&mut slice[0x10..], &encoding, u16, self.ty.clone().into());
+        write_int!(
+            &mut slice[0x12..],
+            &encoding,
+            u16,
+            self.machine.clone().into()
+        );
+        write_int!(&mut slice[0x14..], &encoding, u32, self.format_version);
+        write_int!(&mut slice[0x18..], &encoding, u64, self.entry.0);
+        write_int!(
+            &mut slice[0x20..],
+            &encoding,
+            u64,
+            self.program_headers_offset.0
+        );
+        write_int!(
+            &mut slice[0x28..],
+            &encoding,
+            u64,
+            self.section_headers_offset.0
+        );
+        write_int!(&mut slice[0x30..], &encoding, u32, self.flags);
+        write_int!(&mut slice[0x34..], &encoding, u16, Self::SIZE as u16);
+        write_int!(&mut slice[0x36..], &encoding, u16, self.program_header_size);
+        write_int!(
+            &mut slice[0x38..],
+            &encoding,
+            u16,
+            self.program_header_number
+        );
+        write_int!(&mut slice[0x3a..], &encoding, u16, self.section_header_size);
+        write_int!(
+            &mut slice[0x3c..],
+            &encoding,
+            u16,
+            self.section_header_number
+        );
+        write_int!(
+            &mut slice[0x3e..],
+            &encoding,
+            u16,
+            self.section_names.clone().into()
+        );
+        Ok(())
+    }
+
+    // the section zero holds what does not fit in the header, the extended numbering
+    fn first_section(&self, raw: Source<'_>) -> Option<SectionHeader> {
+        if self.section_headers_offset == Offset(0) {
+            return None;
+        }
+        let slice =
+            file_slice(raw, self.section_headers_offset, SectionHeader::SIZE as u64).ok()?;
+        SectionHeader::new(slice, self.identifier.encoding.clone()).ok()
+    }
+
+    // `e_phnum`, or `sh_info` of the section zero if it is `PN_XNUM`
+    pub(crate) fn program_count(&self, raw: Source<'_>) -> usize {
+        match self.program_header_number {
+            PN_XNUM => self
+                .first_section(raw)
+                .map_or(PN_XNUM as usize, |section| section.info as usize),
+            number => number as usize,
+        }
+    }
+
+    // `e_shnum`, or `sh_size` of the section zero if it is zero and there is a table
+    pub(crate) fn section_count(&self, raw: Source<'_>) -> Result<usize, Error> {
+        match self.section_header_number {
+            0 => match self.first_section(raw) {
+                Some(section) => to_usize(section.size),
+                None => Ok(0),
+            },
+            number => Ok(number as usize),
+        }
+    }
+
+    // `e_shstrndx`, or `sh_link` of the section zero if it is `SHN_XINDEX`
+    pub(crate) fn section_names_index(&self, raw: Source<'_>) -> Index {
+        match self.section_names {
+            Index::Extended => self
+                .first_section(raw)
+                .map_or(Index::Undefined, |section| extended_names(&section)),
+            ref index => index.clone(),
+        }
+    }
+
     pub fn program_header_table<'a>(
         &self,
-        raw: &'a [u8],
+        raw: Source<'a>,
     ) -> Result<Table<'a, ProgramHeader>, Error> {
-        let start = self.program_headers_offset as usize;
-        if raw.len() < start {
-            return Err(Error::SliceTooShort);
-        }
-        Ok(Table::new(&raw[start..], self.identifier.encoding.clone()))
+        // `e_phoff` of a file without program headers is usually zero, not a table
+        let number = self.program_count(raw);
+        let slice = match number {
+            0 => &[],
+            number => table_slice(
+                raw,
+                self.program_headers_offset,
+                number,
+                ProgramHeader::SIZE,
+            )?,
+        };
+        Ok(Table::new(slice, self.identifier.encoding.clone()).with_len(number))
     }
 
     pub fn section_header_table<'a>(
         &self,
-        raw: &'a [u8],
+        raw: Source<'a>,
     ) -> Result<Table<'a, SectionHeader>, Error> {
-        let start = self.section_headers_offset as usize;
-        if raw.len() < start {
-            return Err(Error::SliceTooShort);
+        let number = self.section_count(raw)?;
+        let slice = match number {
+            0 => &[],
+            number => table_slice(
+                raw,
+                self.section_headers_offset,
+                number,
+                SectionHeader::SIZE,
+            )?,
+        };
+        Ok(Table::new(slice, self.identifier.encoding.clone()).with_len(number))
+    }
+}
+
+// `e_phnum` of a file with more program headers than it holds
+pub(crate) const PN_XNUM: u16 = 0xffff;
+
+// the whole table, or what there is of a truncated one, the entries
+// past the end of the file fail when picked
+fn table_slice(
+    raw: Source<'_>,
+    offset: Offset,
+    number: usize,
+    size: usize,
+) -> Result<&[u8], Error> {
+    let size = number.checked_mul(size).ok_or(Error::ArithmeticOverflow)?;
+    match file_slice(raw, offset, size as u64) {
+        Err(Error::SliceTooShort) => file_tail(raw, offset),
+        slice => slice,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Machine;
+
+    #[test]
+    fn page_sizes_are_not_empty() {
+        for code in 0..=u16::MAX {
+            if let Some(sizes) = Machine::from(code).page_sizes() {
+                assert!(!sizes.sizes().is_empty());
+                assert!(sizes.smallest() <= sizes.largest());
+                assert!(sizes.largest() <= sizes.max_page_size);
+            }
         }
-        Ok(Table::new(&raw[start..], self.identifier.encoding.clone()))
     }
 }
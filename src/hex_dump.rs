@@ -0,0 +1,49 @@
+use core::fmt;
+
+use super::Address;
+
+// Renders bytes like `readelf -x`, sixteen bytes a line: the address,
+// four groups of four bytes in hex and the printable characters.
+#[derive(Clone)]
+pub struct HexDump<'a> {
+    data: &'a [u8],
+    address: Address,
+}
+
+impl<'a> HexDump<'a> {
+    // the address of the first byte, the virtual address or the file offset
+    pub fn new(data: &'a [u8], address: Address) -> Self {
+        HexDump { data, address }
+    }
+}
+
+impl<'a> fmt::Display for HexDump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (line, chunk) in self.data.chunks(16).enumerate() {
+            write!(
+                f,
+                "  0x{:08x} ",
+                self.address.wrapping_add((line as u64) * 16)
+            )?;
+            for i in 0..16 {
+                match chunk.get(i) {
+                    Some(byte) => write!(f, "{:02x}", byte)?,
+                    None => f.write_str("  ")?,
+                }
+                if i % 4 == 3 {
+                    f.write_str(" ")?;
+                }
+            }
+            for &byte in chunk {
+                let c = if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use super::{
+    Error, Address, Elf64, Entry, Machine, Capability, SoftwareCapabilities, X86Capabilities,
+    SunwSection, SunwDynamicTag, GnuProperty, X86Feature1, AArch64Feature1, X86IsaLevel,
+};
+
+bitflags! {
+    // Where the report of `hw_capabilities` comes from
+    pub struct CapabilitySources: u32 {
+        // `SHT_SUNW_cap`
+        const SUNW_SECTION = 0b00000001;
+        // `DT_SUNW_CAP`, of a file without the section
+        const SUNW_DYNAMIC = 0b00000010;
+        // `NT_GNU_PROPERTY_TYPE_0`
+        const GNU_PROPERTY = 0b00000100;
+    }
+}
+
+// What the object needs of the processor, the group of `SHT_SUNW_cap` that is of
+// the whole object and `NT_GNU_PROPERTY_TYPE_0`, or what a target has to compare with
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HwCapabilities<'a> {
+    pub machine: Machine,
+    // `CA_SUNW_HW_1`, `CA_SUNW_HW_2` and `CA_SUNW_HW_3`, the bits depend on the machine
+    pub hardware: [u64; 3],
+    pub software: SoftwareCapabilities,
+    // `CA_SUNW_PLAT` and `CA_SUNW_MACH`, the object runs only there
+    pub platform: Option<&'a [u8]>,
+    pub machine_name: Option<&'a [u8]>,
+    // `GNU_PROPERTY_X86_ISA_1_NEEDED`
+    pub x86_isa: X86IsaLevel,
+    // the object works with these enabled, they are not requirements
+    pub x86_features: X86Feature1,
+    pub aarch64_features: AArch64Feature1,
+    pub sources: CapabilitySources,
+}
+
+// What the target lacks of the requirements, `HwCapabilities::missing`
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MissingCapabilities {
+    pub machine: bool,
+    pub hardware: [u64; 3],
+    pub x86_isa: X86IsaLevel,
+    pub platform: bool,
+    pub machine_name: bool,
+}
+
+impl MissingCapabilities {
+    pub fn is_empty(&self) -> bool {
+        *self == MissingCapabilities::default()
+    }
+}
+
+impl Default for X86IsaLevel {
+    fn default() -> Self {
+        X86IsaLevel::empty()
+    }
+}
+
+impl<'a> HwCapabilities<'a> {
+    // Nothing required, to fill with what a target has
+    pub fn new(machine: Machine) -> Self {
+        HwCapabilities {
+            machine,
+            hardware: [0; 3],
+            software: SoftwareCapabilities::empty(),
+            platform: None,
+            machine_name: None,
+            x86_isa: X86IsaLevel::empty(),
+            x86_features: X86Feature1::empty(),
+            aarch64_features: AArch64Feature1::empty(),
+            sources: CapabilitySources::empty(),
+        }
+    }
+
+    // `CA_SUNW_HW_1` of `EM_386` and `EM_AMD64`
+    pub fn x86(&self) -> Option<X86Capabilities> {
+        match self.machine {
+            Machine::X86_64 | Machine::X86 => {
+                Some(X86Capabilities::from_bits_truncate(self.hardware[0]))
+            }
+            _ => None,
+        }
+    }
+
+    // The requirements of the object the target does not meet, a platform or
+    // a machine name the target does not give is missing
+    pub fn missing(&self, target: &HwCapabilities) -> MissingCapabilities {
+        let mut hardware = [0; 3];
+        for (missing, (needed, present)) in hardware
+            .iter_mut()
+            .zip(self.hardware.iter().zip(target.hardware.iter()))
+        {
+            *missing = needed & !present;
+        }
+        let name = |needed: Option<&[u8]>, present: Option<&[u8]>| match needed {
+            Some(needed) => present != Some(needed),
+            None => false,
+        };
+        MissingCapabilities {
+            machine: self.machine != target.machine,
+            hardware,
+            x86_isa: self.x86_isa - target.x86_isa,
+            platform: name(self.platform, target.platform),
+            machine_name: name(self.machine_name, target.machine_name),
+        }
+    }
+
+    fn add(&mut self, capability: Capability, string: impl Fn(u32) -> Result<&'a [u8], Error>) {
+        match capability {
+            Capability::Hardware1(value) => self.hardware[0] |= value,
+            Capability::Hardware2(value) => self.hardware[1] |= value,
+            Capability::Hardware3(value) => self.hardware[2] |= value,
+            Capability::Software1(value) => self.software |= value,
+            Capability::Platform(offset) => self.platform = string(offset).ok(),
+            Capability::Machine(offset) => self.machine_name = string(offset).ok(),
+            _ => (),
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The requirements of the object on the processor, the first group of
+    // `SHT_SUNW_cap`, or of `DT_SUNW_CAP` if the file has no such section, the later
+    // groups are of the symbols that have several variants. The properties of GNU
+    // are the same for the whole object.
+    pub fn hw_capabilities(&self) -> Result<HwCapabilities<'a>, Error> {
+        let mut report = HwCapabilities::new(self.machine());
+
+        if let Some(SunwSection::Capabilities {
+            table,
+            number,
+            strings,
+        }) = self.sunw_capabilities()?
+        {
+            report.sources |= CapabilitySources::SUNW_SECTION;
+            for index in 0..number {
+                let capability = table.pick(index)?;
+                if capability == Capability::Null {
+                    break;
+                }
+                report.add(capability, |offset| match &strings {
+                    Some(strings) => strings.pick(offset as usize),
+                    None => Err(Error::NotPresent),
+                });
+            }
+        } else if let Some(address) = self.sunw_capabilities_address()? {
+            report.sources |= CapabilitySources::SUNW_DYNAMIC;
+            // the strings are of `DT_STRTAB`
+            let source = self.dynamic_source()?;
+            let size = Capability::SIZE as u64;
+            for index in 0u64.. {
+                let address = Address(address.0.wrapping_add(index.wrapping_mul(size)));
+                let slice = self.virtual_slice(address, size)?;
+                let capability = Capability::new(slice, self.encoding())?;
+                if capability == Capability::Null {
+                    break;
+                }
+                report.add(capability, |offset| match &source {
+                    Some(source) => source.string(u64::from(offset)),
+                    None => Err(Error::NotPresent),
+                });
+            }
+        }
+
+        if let Some(properties) = self.gnu_properties()? {
+            report.sources |= CapabilitySources::GNU_PROPERTY;
+            for property in properties {
+                match property? {
+                    GnuProperty::X86IsaNeeded(level) => report.x86_isa |= level,
+                    GnuProperty::X86Feature1(features) => report.x86_features |= features,
+                    GnuProperty::AArch64Feature1(features) => report.aarch64_features |= features,
+                    _ => (),
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn sunw_capabilities_address(&self) -> Result<Option<Address>, Error> {
+        for entry in self.sunw_dynamic()? {
+            let entry = entry?;
+            if entry.tag == SunwDynamicTag::Capabilities {
+                return Ok(Some(Address(entry.value)));
+            }
+        }
+        Ok(None)
+    }
+}
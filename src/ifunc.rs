@@ -0,0 +1,217 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use super::{Error, Address, Encoding, Elf64, Symbol, SymbolType, SymbolIndex};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IrelativeRelocation {
+    // the relocation section and the index of the entry in it
+    pub section: usize,
+    pub index: usize,
+    // where the loader stores the value returned by the resolver
+    pub address: Address,
+    pub resolver: Address,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IndirectFunctions<'a> {
+    pub relocations: Vec<IrelativeRelocation>,
+    // `STT_GNU_IFUNC` symbols, the value of the symbol is the resolver
+    pub symbols: Vec<Symbol<'a>>,
+}
+
+impl<'a> IndirectFunctions<'a> {
+    pub fn is_empty(&self) -> bool {
+        self.relocations.is_empty() && self.symbols.is_empty()
+    }
+}
+
+// The memory of the process the file is loaded in, to read what the resolvers
+// returned, the file is loaded at `bias`, zero for `ET_EXEC`
+#[derive(Clone)]
+pub enum LoadedImage<'m> {
+    // the memory from `address` on, e.g. what a debugger read from the process
+    Memory {
+        data: &'m [u8],
+        address: Address,
+        bias: Address,
+    },
+    // the `PT_LOAD` segments of a core file
+    Core {
+        core: Elf64<'m>,
+        bias: Address,
+    },
+}
+
+impl<'m> LoadedImage<'m> {
+    pub fn bias(&self) -> Address {
+        match self {
+            LoadedImage::Memory { bias, .. } | LoadedImage::Core { bias, .. } => *bias,
+        }
+    }
+
+    // the bytes at the run time address, `None` if the image does not have all of them
+    pub(crate) fn bytes(&self, address: Address, size: u64) -> Option<&'m [u8]> {
+        match self {
+            LoadedImage::Memory {
+                data,
+                address: start,
+                ..
+            } => {
+                let offset = usize::try_from(address.0.checked_sub(start.0)?).ok()?;
+                data.get(offset..)?.get(..usize::try_from(size).ok()?)
+            }
+            LoadedImage::Core { core, .. } => core.read_virtual(address, size).ok(),
+        }
+    }
+
+    // the word at the run time address, `None` if the image does not have it
+    pub(crate) fn word(&self, address: Address, encoding: &Encoding) -> Option<u64> {
+        let slice = self.bytes(address, 8)?;
+        Some(read_int!(slice, encoding, u64))
+    }
+}
+
+// A `STT_GNU_IFUNC` symbol, what a profile shows as the symbol runs in the resolver
+// only once, the calls go to the function the resolver returned
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IndirectSymbol<'a> {
+    pub symbol: Symbol<'a>,
+    // the value of the symbol
+    pub resolver: Address,
+    // the word the loader stores the result of the resolver in, of an `IRELATIVE`
+    // relocation or of a dynamic relocation against the symbol
+    pub slot: Option<Address>,
+    // the run time address read from the slot in the image
+    pub target: Option<Address>,
+    // the function of the file at the target, e.g. `__memcpy_avx_unaligned`,
+    // `None` if the target is in another file
+    pub target_symbol: Option<Symbol<'a>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Symbolization<'a> {
+    Symbol(Symbol<'a>),
+    // the address is in the resolver of the symbol
+    Indirect(IndirectSymbol<'a>),
+}
+
+// `STT_GNU_IFUNC`
+const INDIRECT_FUNCTION: SymbolType = SymbolType::OsSpecific(0);
+
+impl<'a> Elf64<'a> {
+    // The resolvers the loader runs before the program starts
+    pub fn indirect_functions(&self) -> Result<IndirectFunctions<'a>, Error> {
+        let mut relocations = Vec::new();
+        let machine = self.machine();
+        self.visit_rela(|section, _, index, entry| {
+            if entry.kind(&machine).is_indirect_relative() {
+                relocations.push(IrelativeRelocation {
+                    section,
+                    index,
+                    address: entry.address,
+                    resolver: Address(entry.addend as u64),
+                });
+            }
+            Ok(())
+        })?;
+
+        let mut symbols = Vec::new();
+        for symbol in self.merged_symbols()? {
+            let symbol = symbol?;
+            if symbol.entry.info.ty == INDIRECT_FUNCTION && symbol.entry.is_defined() {
+                symbols.push(symbol);
+            }
+        }
+
+        Ok(IndirectFunctions {
+            relocations,
+            symbols,
+        })
+    }
+    // What `symbolize` finds, except an address in the resolver of a `STT_GNU_IFUNC`
+    // symbol is the indirect symbol, with the resolved target if there is the image
+    pub fn symbolize_indirect(
+        &self,
+        address: Address,
+        image: Option<&LoadedImage<'_>>,
+    ) -> Result<Option<Symbolization<'a>>, Error> {
+        for symbol in self.merged_symbols()? {
+            let symbol = symbol?;
+            let entry = &symbol.entry;
+            if entry.info.ty != INDIRECT_FUNCTION || !entry.is_defined() {
+                continue;
+            }
+            let end = entry.value.saturating_add(entry.size.max(1));
+            if (entry.value..end).contains(&address) {
+                let indirect = self.indirect_symbol(symbol, image)?;
+                return Ok(Some(Symbolization::Indirect(indirect)));
+            }
+        }
+        Ok(self.symbolize(address)?.map(Symbolization::Symbol))
+    }
+
+    // The resolver of the `STT_GNU_IFUNC` symbol, the slot of its result found
+    // in the dynamic relocations and the target read from the image
+    pub fn indirect_symbol(
+        &self,
+        symbol: Symbol<'a>,
+        image: Option<&LoadedImage<'_>>,
+    ) -> Result<IndirectSymbol<'a>, Error> {
+        if symbol.entry.info.ty != INDIRECT_FUNCTION {
+            return Err(Error::UnexpectedSymbolType);
+        }
+        let (encoding, machine) = (self.encoding(), self.machine());
+        let resolver = symbol.entry.value;
+        let dynamic_symbols = self.dynamic_symbol_table()?;
+        let mut slot = None;
+        for relocation in self.dynamic_relocations()? {
+            let relocation = match relocation?.1.any() {
+                Some(relocation) => relocation,
+                None => continue,
+            };
+            let found = if relocation.kind(&machine).is_indirect_relative() {
+                let addend = match relocation.addend {
+                    Some(addend) => addend as u64,
+                    None => match self.read_virtual(relocation.address, 8) {
+                        Ok(slice) => read_int!(slice, &encoding, u64),
+                        Err(_) => continue,
+                    },
+                };
+                addend == resolver.0
+            } else {
+                match (&dynamic_symbols, relocation.symbol_index) {
+                    (_, SymbolIndex(0)) | (None, _) => false,
+                    (Some(table), SymbolIndex(index)) => {
+                        let target = table.get(index)?;
+                        target.name == symbol.name && target.entry.value == resolver
+                    }
+                }
+            };
+            if found {
+                slot = Some(relocation.address);
+                break;
+            }
+        }
+        let target = match (slot, image) {
+            (Some(slot), Some(image)) => image
+                .word(image.bias().wrapping_add(slot.0), &encoding)
+                .map(Address),
+            _ => None,
+        };
+        let target_symbol = match (target, image) {
+            (Some(target), Some(image)) => match target.0.checked_sub(image.bias().0) {
+                Some(address) => self.symbolize(Address(address))?,
+                None => None,
+            },
+            _ => None,
+        };
+        Ok(IndirectSymbol {
+            symbol,
+            resolver,
+            slot,
+            target,
+            target_symbol,
+        })
+    }
+}
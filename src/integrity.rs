@@ -0,0 +1,147 @@
+use alloc::vec::Vec;
+
+use super::{Error, Address, Elf64, Entry, DynamicTag, ProgramType, ProgramFlags, LoadedImage};
+use super::dynamic::DynamicEntry;
+use super::common::file_slice;
+
+// the part of the image read at once, up to the end of the page, a core file splits
+// a segment at the pages `mprotect` changed, e.g. of `PT_GNU_RELRO`
+const CHUNK: u64 = 0x1000;
+
+// the place of a dynamic relocation is a word
+const PLACE: u64 = 8;
+
+// Run time addresses of a `PT_LOAD`, with the permissions of the segment
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImageRange {
+    pub program: usize,
+    pub flags: ProgramFlags,
+    pub address: Address,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IntegrityReport {
+    // the bytes of the file found in the image
+    pub compared: u64,
+    // differ from the file in the places of the dynamic relocations, as expected
+    pub relocated: Vec<ImageRange>,
+    // differ from the file elsewhere, e.g. a hook written over the code, of a writable
+    // segment it is also the data the program changed
+    pub modified: Vec<ImageRange>,
+    pub unavailable: Vec<ImageRange>,
+}
+
+impl IntegrityReport {
+    // nothing but the relocations changed the segments that are not writable
+    pub fn is_intact(&self) -> bool {
+        self.modified
+            .iter()
+            .all(|range| range.flags.contains(ProgramFlags::WRITE))
+    }
+}
+
+// the bytes one after another make one range
+fn push(ranges: &mut Vec<ImageRange>, range: ImageRange) {
+    if let Some(last) = ranges.last_mut() {
+        if last.program == range.program && last.address.wrapping_add(last.size) == range.address {
+            last.size += range.size;
+            return;
+        }
+    }
+    ranges.push(range);
+}
+
+impl<'a> Elf64<'a> {
+    // Compares the file part of each `PT_LOAD` with the image the file is loaded in, the
+    // bytes the dynamic relocations write to and the values of `PT_DYNAMIC` are expected
+    // to differ, the rest is not.
+    // The part past `p_filesz`, `.bss`, is not in the file, it is not compared.
+    pub fn compare_image(&self, image: &LoadedImage<'_>) -> Result<IntegrityReport, Error> {
+        let bias = image.bias();
+        let mut places = Vec::new();
+        for relocation in self.dynamic_relocations()? {
+            let (_, relocation) = relocation?;
+            // `R_*_NONE` writes nothing
+            if relocation.relocation_type() != Some(0) {
+                places.push(relocation.address().0);
+            }
+        }
+        // the loader of glibc adds the bias to the addresses of `PT_DYNAMIC` in place,
+        // and writes `DT_DEBUG`, the values of the entries are expected to change
+        for program in 0..self.program_number() {
+            let header = self.program_table.pick(program)?;
+            if header.ty != ProgramType::Dynamic {
+                continue;
+            }
+            let slice = file_slice(self.raw, header.file_offset, header.file_size)?;
+            for index in 0..(slice.len() / DynamicEntry::SIZE) {
+                let entry = header
+                    .virtual_address
+                    .wrapping_add((index * DynamicEntry::SIZE) as u64);
+                places.push(entry.0.wrapping_add(8));
+            }
+        }
+        // and the words of `DT_PLTGOT` after the first, its link map and its resolver
+        if let Some(source) = self.dynamic_source()? {
+            for entry in source.entries() {
+                let entry = entry?;
+                if entry.tag == DynamicTag::PltGot {
+                    places.push(entry.value.wrapping_add(PLACE));
+                    places.push(entry.value.wrapping_add(PLACE * 2));
+                }
+            }
+        }
+        places.sort_unstable();
+        places.dedup();
+        let relocated = |address: u64| match places.binary_search(&address) {
+            Ok(_) => true,
+            Err(0) => false,
+            Err(index) => address - places[index - 1] < PLACE,
+        };
+
+        let mut report = IntegrityReport::default();
+        for program in 0..self.program_number() {
+            let header = self.program_table.pick(program)?;
+            if header.ty != ProgramType::Load || header.file_size == 0 {
+                continue;
+            }
+            let file = file_slice(self.raw, header.file_offset, header.file_size)?;
+            let mut done = 0;
+            while done < file.len() {
+                let virtual_address = header.virtual_address.wrapping_add(done as u64);
+                let address = bias.wrapping_add(virtual_address.0);
+                let size = ((CHUNK - address.0 % CHUNK) as usize).min(file.len() - done);
+                let range = |address: Address, size: u64| ImageRange {
+                    program,
+                    flags: header.flags,
+                    address,
+                    size,
+                };
+                let actual = match image.bytes(address, size as u64) {
+                    Some(actual) => actual,
+                    None => {
+                        push(&mut report.unavailable, range(address, size as u64));
+                        done += size;
+                        continue;
+                    }
+                };
+                report.compared += size as u64;
+                let expected = &file[done..(done + size)];
+                for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                    if expected == actual {
+                        continue;
+                    }
+                    let byte = range(address.wrapping_add(index as u64), 1);
+                    if relocated(virtual_address.0.wrapping_add(index as u64)) {
+                        push(&mut report.relocated, byte);
+                    } else {
+                        push(&mut report.modified, byte);
+                    }
+                }
+                done += size;
+            }
+        }
+        Ok(report)
+    }
+}
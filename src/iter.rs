@@ -0,0 +1,255 @@
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+use super::{Error, SectionIndex, SectionFlags, Elf64, Program, ProgramHeader, Section};
+
+// The iterators yield a `Result` for each item, an error of one item
+// does not end the iteration, the next one is read anyway
+#[derive(Clone)]
+pub struct Programs<'a> {
+    elf: Elf64<'a>,
+    position: usize,
+}
+
+impl<'a> Iterator for Programs<'a> {
+    type Item = Result<Program<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.position;
+        self.position += 1;
+        self.elf.program(index).transpose()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.elf.program_number().saturating_sub(self.position);
+        (remaining, Some(remaining))
+    }
+}
+
+#[derive(Clone)]
+pub struct ProgramHeaders<'a> {
+    elf: Elf64<'a>,
+    position: usize,
+}
+
+impl<'a> Iterator for ProgramHeaders<'a> {
+    type Item = Result<ProgramHeader, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.position;
+        self.position += 1;
+        self.elf.program_info(index).transpose()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.elf.program_number().saturating_sub(self.position);
+        (remaining, Some(remaining))
+    }
+}
+
+// with the index, it is what the links and the symbols refer to
+#[derive(Clone)]
+pub struct Sections<'a> {
+    elf: Elf64<'a>,
+    position: usize,
+}
+
+impl<'a> Iterator for Sections<'a> {
+    type Item = Result<(usize, Section<'a>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.position;
+        self.position += 1;
+        self.elf
+            .section(SectionIndex(index))
+            .map(|section| section.map(|section| (index, section)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.elf.section_number().saturating_sub(self.position);
+        (remaining, Some(remaining))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Order {
+    Offset,
+    Address,
+}
+
+// The sections in the order of the key, the index breaks the ties. With `alloc`
+// the keys are read and sorted on the first call of `next`, without it each call
+// reads all the section headers to select the next one. A header that cannot be
+// read is the last item.
+#[derive(Clone)]
+pub struct OrderedSections<'a> {
+    elf: Elf64<'a>,
+    order: Order,
+    #[cfg(not(feature = "alloc"))]
+    last: Option<(u64, usize)>,
+    #[cfg(feature = "alloc")]
+    sorted: Option<vec::IntoIter<(u64, usize)>>,
+    failed: bool,
+}
+
+impl<'a> OrderedSections<'a> {
+    fn key(&self, index: usize) -> Result<Option<(u64, usize)>, Error> {
+        let header = self.elf.section_table.pick(index)?;
+        Ok(match self.order {
+            Order::Offset => Some((header.offset.0, index)),
+            Order::Address if header.flags.contains(SectionFlags::ALLOC) => {
+                Some((header.address.0, index))
+            }
+            Order::Address => None,
+        })
+    }
+
+    #[cfg(feature = "alloc")]
+    fn select(&mut self) -> Result<Option<usize>, Error> {
+        if self.sorted.is_none() {
+            let mut keys = Vec::new();
+            for index in 0..self.elf.section_number() {
+                keys.extend(self.key(index)?);
+            }
+            keys.sort_unstable();
+            self.sorted = Some(keys.into_iter());
+        }
+        Ok(self
+            .sorted
+            .as_mut()
+            .and_then(Iterator::next)
+            .map(|(_, index)| index))
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn select(&mut self) -> Result<Option<usize>, Error> {
+        let mut next: Option<(u64, usize)> = None;
+        for index in 0..self.elf.section_number() {
+            let key = match self.key(index)? {
+                Some(key) => key,
+                None => continue,
+            };
+            let before = match next {
+                Some(next) => key < next,
+                None => true,
+            };
+            if Some(key) > self.last && before {
+                next = Some(key);
+            }
+        }
+        if next.is_some() {
+            self.last = next;
+        }
+        Ok(next.map(|(_, index)| index))
+    }
+}
+
+impl<'a> Iterator for OrderedSections<'a> {
+    type Item = Result<(usize, Section<'a>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let index = match self.select() {
+            Ok(index) => index?,
+            Err(error) => {
+                self.failed = true;
+                return Some(Err(error));
+            }
+        };
+        self.elf
+            .section(SectionIndex(index))
+            .map(|section| section.map(|section| (index, section)))
+            .transpose()
+    }
+}
+
+impl<'a> Elf64<'a> {
+    pub fn programs(&self) -> Programs<'a> {
+        Programs {
+            elf: self.clone(),
+            position: 0,
+        }
+    }
+
+    // the headers only, like `program_info`
+    pub fn program_headers(&self) -> ProgramHeaders<'a> {
+        ProgramHeaders {
+            elf: self.clone(),
+            position: 0,
+        }
+    }
+
+    pub fn sections(&self) -> Sections<'a> {
+        Sections {
+            elf: self.clone(),
+            position: 0,
+        }
+    }
+
+    // every section by `sh_offset`, `SHT_NOBITS` as well
+    pub fn sections_by_offset(&self) -> OrderedSections<'a> {
+        OrderedSections {
+            elf: self.clone(),
+            order: Order::Offset,
+            #[cfg(not(feature = "alloc"))]
+            last: None,
+            #[cfg(feature = "alloc")]
+            sorted: None,
+            failed: false,
+        }
+    }
+
+    // the `SHF_ALLOC` sections by `sh_addr`, the rest have no address
+    pub fn sections_by_vaddr(&self) -> OrderedSections<'a> {
+        OrderedSections {
+            elf: self.clone(),
+            order: Order::Address,
+            #[cfg(not(feature = "alloc"))]
+            last: None,
+            #[cfg(feature = "alloc")]
+            sorted: None,
+            failed: false,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{Elf64, Encoding, SectionFlags};
+
+    #[test]
+    fn ordered_sections() {
+        let raw = crate::tests::executable(Encoding::Little);
+        let elf = Elf64::new(&raw).unwrap();
+        let headers = (0..elf.section_number())
+            .map(|index| (index, elf.section_table.pick(index).unwrap()))
+            .collect::<Vec<_>>();
+
+        let mut by_offset = headers
+            .iter()
+            .map(|(index, header)| (header.offset.0, *index))
+            .collect::<Vec<_>>();
+        by_offset.sort();
+        let mut by_address = headers
+            .iter()
+            .filter(|(_, header)| header.flags.contains(SectionFlags::ALLOC))
+            .map(|(index, header)| (header.address.0, *index))
+            .collect::<Vec<_>>();
+        by_address.sort();
+
+        let indices = |sections: crate::OrderedSections| {
+            sections
+                .map(|section| section.unwrap().0)
+                .collect::<Vec<_>>()
+        };
+        let expected =
+            |keys: Vec<(u64, usize)>| keys.into_iter().map(|(_, index)| index).collect::<Vec<_>>();
+        assert_eq!(indices(elf.sections_by_offset()), expected(by_offset));
+        assert_eq!(indices(elf.sections_by_vaddr()), expected(by_address));
+    }
+}
@@ -0,0 +1,221 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use super::{Address, Offset, SectionFlags, ProgramFlags, Header, ProgramHeader, Entry};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LayoutSection {
+    pub size: u64,
+    pub address_alignment: u64,
+    pub flags: SectionFlags,
+    pub no_bits: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LayoutOptions {
+    pub base_address: Address,
+    pub page_size: u64,
+    // program headers that are not `PT_LOAD`, but still need space, e.g. `PT_INTERP`
+    pub extra_program_headers: usize,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        LayoutOptions {
+            base_address: Address(0x400000),
+            page_size: 0x1000,
+            extra_program_headers: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Placement {
+    pub offset: Offset,
+    // zero for sections that are not loaded
+    pub address: Address,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LayoutSegment {
+    pub flags: ProgramFlags,
+    pub file_offset: Offset,
+    pub virtual_address: Address,
+    pub file_size: u64,
+    pub memory_size: u64,
+    pub address_alignment: u64,
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Layout {
+    pub program_headers_offset: Offset,
+    pub program_header_number: usize,
+    // placements are in the same order as the input sections
+    pub placements: Vec<Placement>,
+    pub segments: Vec<LayoutSegment>,
+    pub section_headers_offset: Offset,
+}
+
+fn align_up(x: u64, alignment: u64) -> u64 {
+    x.div_ceil(alignment.max(1)) * alignment.max(1)
+}
+
+// the group of the permissions of a loaded section, `None` for the others
+fn group_of(section: &LayoutSection) -> Option<usize> {
+    if !section.flags.contains(SectionFlags::ALLOC) {
+        None
+    } else if section.flags.contains(SectionFlags::WRITE) {
+        Some(2)
+    } else if section.flags.contains(SectionFlags::EXECINSTR) {
+        Some(1)
+    } else {
+        Some(0)
+    }
+}
+
+// `Layout::new` into the storage of the caller, `placements` is as long as `sections`,
+// for `FixedBuilder` that does not allocate. The number of the segments and the end of
+// the placed content, it is not aligned.
+pub(crate) fn place(
+    sections: &[LayoutSection],
+    options: &LayoutOptions,
+    placements: &mut [Placement],
+    segments: &mut [LayoutSegment; 3],
+) -> (usize, Offset) {
+    let page = options.page_size.max(1);
+    let groups_flags = [
+        ProgramFlags::READ,
+        ProgramFlags::READ | ProgramFlags::EXECUTE,
+        ProgramFlags::READ | ProgramFlags::WRITE,
+    ];
+
+    let load_number = (0..3)
+        .filter(|group| sections.iter().any(|s| group_of(s) == Some(*group)))
+        .count();
+    let program_header_number = load_number + options.extra_program_headers;
+    let headers_size = (Header::SIZE + program_header_number * ProgramHeader::SIZE) as u64;
+
+    let mut segment_number = 0;
+    let mut offset = Offset(headers_size);
+    let mut address_end = options.base_address;
+    for (group, flags) in groups_flags.iter().enumerate() {
+        if !sections.iter().any(|s| group_of(s) == Some(group)) {
+            continue;
+        }
+        // the input order, except the sections without file content go last
+        let members = [false, true].iter().flat_map(|no_bits| {
+            sections
+                .iter()
+                .enumerate()
+                .filter(move |(_, s)| group_of(s) == Some(group) && s.no_bits == *no_bits)
+        });
+
+        let (segment_offset, segment_address, mut address) = if segment_number == 0 {
+            let base = options.base_address;
+            (Offset(0), base, base + headers_size)
+        } else {
+            let address = Address(align_up(address_end.0, page) + offset.0 % page);
+            (offset, address, address)
+        };
+        let mut file_end = offset;
+
+        for (index, section) in members {
+            let delta = align_up(address.0, section.address_alignment) - address.0;
+            address += delta;
+            if !section.no_bits {
+                offset += delta;
+            }
+            placements[index] = Placement { offset, address };
+            address += section.size;
+            if !section.no_bits {
+                offset += section.size;
+                file_end = offset;
+            }
+        }
+
+        segments[segment_number] = LayoutSegment {
+            flags: *flags,
+            file_offset: segment_offset,
+            virtual_address: segment_address,
+            file_size: file_end - segment_offset,
+            memory_size: address - segment_address,
+            address_alignment: page,
+        };
+        segment_number += 1;
+        address_end = address;
+    }
+
+    for (index, section) in sections.iter().enumerate() {
+        if group_of(section).is_some() {
+            continue;
+        }
+        offset = place_unloaded(offset, section);
+        placements[index] = Placement {
+            offset,
+            address: Address(0),
+        };
+        if !section.no_bits {
+            offset += section.size;
+        }
+    }
+
+    (segment_number, offset)
+}
+
+// the offset of a section that is not loaded, after the content up to `offset`
+pub(crate) fn place_unloaded(offset: Offset, section: &LayoutSection) -> Offset {
+    if section.no_bits {
+        offset
+    } else {
+        Offset(align_up(offset.0, section.address_alignment))
+    }
+}
+
+pub(crate) fn section_headers_offset(end: Offset) -> Offset {
+    Offset(align_up(end.0, 8))
+}
+
+impl LayoutSegment {
+    pub(crate) fn empty() -> Self {
+        LayoutSegment {
+            flags: ProgramFlags::empty(),
+            file_offset: Offset(0),
+            virtual_address: Address(0),
+            file_size: 0,
+            memory_size: 0,
+            address_alignment: 0,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Layout {
+    // Loaded sections are grouped by permissions into read-only, executable and writable
+    // segments, in this order, keeping the input order inside a group, except the sections
+    // without file content go last. The first segment also maps the headers.
+    // The file offset and the virtual address are congruent modulo the page size.
+    pub fn new(sections: &[LayoutSection], options: &LayoutOptions) -> Self {
+        let mut placements = sections
+            .iter()
+            .map(|_| Placement {
+                offset: Offset(0),
+                address: Address(0),
+            })
+            .collect::<Vec<_>>();
+        let mut segments = [
+            LayoutSegment::empty(),
+            LayoutSegment::empty(),
+            LayoutSegment::empty(),
+        ];
+        let (load_number, end) = place(sections, options, &mut placements, &mut segments);
+
+        Layout {
+            program_headers_offset: Offset(Header::SIZE as u64),
+            program_header_number: load_number + options.extra_program_headers,
+            placements,
+            segments: segments[..load_number].to_vec(),
+            section_headers_offset: section_headers_offset(end),
+        }
+    }
+}
@@ -4,6 +4,12 @@
 #[macro_use]
 extern crate bitflags;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
 macro_rules! read_int {
     ($slice:expr, $encoding:expr, $ty:ty) => {{
         let mut a = [0; core::mem::size_of::<$ty>()];
@@ -15,76 +21,551 @@ macro_rules! read_int {
     }};
 }
 
+macro_rules! write_int {
+    ($slice:expr, $encoding:expr, $ty:ty, $value:expr) => {{
+        let v: $ty = $value;
+        let a = match $encoding {
+            &Encoding::Little => v.to_le_bytes(),
+            &Encoding::Big => v.to_be_bytes(),
+        };
+        $slice[..core::mem::size_of::<$ty>()].clone_from_slice(&a);
+    }};
+}
+
+// events of the `log` feature, why a file parses the way it does,
+// the arguments are not evaluated without the feature
+macro_rules! log_debug {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
+
+macro_rules! log_trace {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::trace!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
+
+// a span of the `tracing` feature around an expensive operation, the fields
+// after `;` are not known yet, `record_span!` sets them at the end
+#[cfg(feature = "tracing")]
+macro_rules! enter_span {
+    ($name:literal $(, $field:ident = $value:expr)* $(; $($later:ident),*)?) => {
+        tracing::debug_span!(
+            $name,
+            $($field = $value,)*
+            $($($later = tracing::field::Empty,)*)?
+        )
+        .entered()
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! enter_span {
+    ($name:literal $(, $field:ident = $value:expr)* $(; $($later:ident),*)?) => {{
+        $(let _ = $value;)*
+        core::marker::PhantomData::<()>
+    }};
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! record_span {
+    ($span:expr, $field:ident, $value:expr) => {
+        $span.record(stringify!($field), $value);
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! record_span {
+    ($span:expr, $field:ident, $value:expr) => {{
+        let _ = (&$span, $value);
+    }};
+}
+
+use core::str;
+
 mod common;
-pub use self::common::{Address, Offset, Error, UnexpectedSize};
+pub use self::common::{Address, Offset, SectionIndex, SymbolIndex, Error, UnexpectedSize};
+use self::common::{file_slice, file_tail};
 
 mod header;
 use self::header::Header;
-pub use self::header::{Class, Encoding, Abi, Type, Machine};
+use self::hash::SHT_GNU_HASH;
+pub use self::header::{Class, Encoding, Abi, Type, Machine, PageSizes};
 
 mod section;
-use self::section::SectionHeader;
-pub use self::section::{Index, SectionType, SectionFlags};
+pub use self::section::{Index, SectionType, SectionFlags, SectionHeader};
 
 mod program;
-use self::program::{ProgramType, ProgramHeader};
-pub use self::program::ProgramFlags;
+pub use self::program::{ProgramType, ProgramFlags, ProgramHeader};
 
 mod symbol;
-pub use self::symbol::{SymbolBinding, SymbolType, SymbolInfo, SymbolEntry};
+pub use self::symbol::{SymbolBinding, SymbolType, SymbolInfo, SymbolEntry, Symbol};
+
+mod symbolication;
+pub use self::symbolication::MergedSymbols;
+
+mod ppc64;
+pub use self::ppc64::{FunctionDescriptor, Opd};
+
+mod arm_exidx;
+pub use self::arm_exidx::{UnwindInstructions, ExtabEntry, Unwind, ExidxEntry, ExidxEntries};
+
+mod eh_frame;
+pub use self::eh_frame::{Pointer, EhFrameHeader, Cie, Fde, CfiEntry, EhFrame, CfiEntries};
+
+mod cheri;
+pub use self::cheri::{CheriPlatform, CheriAbi, Cheri, CapabilityRelocation};
+
+mod extension;
+pub use self::extension::{
+    SectionDecoder, RelocationDecoder, NoteDecoder, ExtendedNote, Registry, ExtendedNotes,
+};
+
+mod symbols;
+pub use self::symbols::{Symbols, SymbolTable};
+
+mod dynamic;
+pub use self::dynamic::{DynamicTag, DynamicFlags, DynamicFlags1, DynamicEntry, DynamicTable};
+
+mod version;
+pub use self::version::{
+    Versions, VersionIndex, VersionDefinition, VersionRequirement, SymbolVersion,
+    VersionDefinitions, VersionRequirements,
+};
 
 mod rel_rela;
-pub use self::rel_rela::{RelEntry, RelaEntry};
+pub use self::rel_rela::{RelEntry, RelaEntry, Relocation, AnyRelocation, SectionRelocations};
+
+mod relocation_kind;
+pub use self::relocation_kind::{RelocationKind, X86_64Relocation, AArch64Relocation, RiscVRelocation};
+
+mod relocation_formula;
+pub use self::relocation_formula::{Operand, Term, Formula, FormulaInputs};
+
+mod dynamic_relocations;
+pub use self::dynamic_relocations::{DynamicRelocationTable, DynamicRelocations};
+
+mod nul;
 
 mod string_note;
-pub use self::string_note::{StringTable, NoteEntry, NoteTable};
+pub use self::string_note::{StringTable, Strings, NoteEntry, NoteTable, NoteEntries, Lossy};
+#[cfg(feature = "alloc")]
+pub use self::string_note::IndexedNotes;
+
+mod merge_strings;
+pub use self::merge_strings::{MergeString, MergeStrings};
+
+mod bsd_note;
+pub use self::bsd_note::{BsdNote, NetBsdPaxFlags, FreeBsdFeatureFlags};
+
+mod sunw;
+pub use self::sunw::{
+    SoftwareCapabilities, X86Capabilities, SyminfoFlags, Capability, BoundTo, SyminfoEntry,
+    MoveEntry, SunwSection, FeatureFlags, PositionFlags, SunwDynamicTag, SunwDynamicEntry,
+};
+
+mod hash;
+pub use self::hash::{HashTable, GnuHashTable, elf_hash, gnu_hash};
+
+mod triage;
+pub use self::triage::{Triage, TriageFlags};
+
+mod compression;
+pub use self::compression::{CompressionType, CompressionHeader};
+#[cfg(feature = "compression")]
+pub use self::compression::decompress;
+use self::compression::legacy_header;
+
+mod tls;
+pub use self::tls::{TlsTemplate, SymbolAddress};
+
+mod gnu_property;
+pub use self::gnu_property::{GnuProperty, GnuProperties, X86Feature1, AArch64Feature1, X86IsaLevel};
+
+mod hwcaps;
+pub use self::hwcaps::{HwCapabilities, CapabilitySources, MissingCapabilities};
+
+mod decoded_note;
+pub use self::decoded_note::{DecodedNote, GnuNote, GnuAbiOs, CoreNote, LinuxNote};
+
+mod core_note;
+pub use self::core_note::{
+    ProcessInfo, ProcessStatus, TimeValue, Registers, X86_64Registers, AArch64Registers,
+    MappedFile, MappedFiles, AuxiliaryEntries,
+};
+
+mod c_library;
+pub use self::c_library::{CLibrary, MaximumVersions, compare_versions};
+
+mod loader;
+pub use self::loader::{LoadSection, LoaderMemory, Loaded, apply_relocation};
+
+mod relocate;
+pub use self::relocate::{MappedImage, apply_dynamic_relocation};
+
+mod coverage;
+pub use self::coverage::{Structure, CoverageRange, Coverage, UnclaimedRegion};
+
+mod warning;
+pub use self::warning::{Origin, Warning, Warnings};
+
+mod code;
+pub use self::code::{CodeRange, CodeConsumer};
+
+#[cfg(feature = "capstone")]
+mod capstone_adapter;
+
+#[cfg(feature = "dwarf")]
+mod dwarf;
+
+#[cfg(feature = "object")]
+mod object_adapter;
+
+mod iter;
+pub use self::iter::{Programs, ProgramHeaders, Sections, OrderedSections};
+
+mod segments;
+pub use self::segments::{Notes, GnuStack, GnuRelro, RelroCoverage};
+
+mod auxv;
+pub use self::auxv::{AuxiliaryType, AuxiliaryEntry};
+
+mod capability;
+pub use self::capability::Capabilities;
+
+mod fragments;
+use self::fragments::Source;
+
+#[cfg(feature = "alloc")]
+mod prefetch;
+#[cfg(feature = "alloc")]
+pub use self::prefetch::Operation;
+
+mod name_recovery;
 
 mod table;
-pub use self::table::{Entry, Table};
+pub use self::table::{Entry, Encode, Table, Word, WordTable};
+#[cfg(feature = "alloc")]
+pub use self::table::encode_entries;
+
+mod hasher;
+pub use self::hasher::{Hasher, Fnv1a};
+
+mod manifest;
+pub use self::manifest::{SegmentDigest, SegmentDigests, Verification, verify_image};
+
+#[cfg(feature = "rayon")]
+mod parallel;
+
+#[cfg(feature = "alloc")]
+mod cache;
+#[cfg(feature = "alloc")]
+pub use self::cache::{CachedTable, CachedStringTable, IndexedStringTable};
+
+mod debug;
+
+#[cfg(feature = "fmt")]
+mod hex_dump;
+#[cfg(feature = "fmt")]
+pub use self::hex_dump::HexDump;
+
+#[cfg(feature = "fmt")]
+mod display;
+#[cfg(feature = "fmt")]
+pub use self::display::Summary;
+
+#[cfg(feature = "alloc")]
+mod string_builder;
+#[cfg(feature = "alloc")]
+pub use self::string_builder::StringTableBuilder;
+
+mod layout;
+pub use self::layout::{LayoutOptions, LayoutSection, LayoutSegment, Placement};
+#[cfg(feature = "alloc")]
+pub use self::layout::Layout;
+
+#[cfg(feature = "alloc")]
+mod builder;
+#[cfg(feature = "alloc")]
+pub use self::builder::ElfBuilder;
+
+mod fixed_builder;
+pub use self::fixed_builder::FixedBuilder;
+
+#[cfg(feature = "alloc")]
+mod write;
+
+#[cfg(feature = "alloc")]
+mod roundtrip;
+#[cfg(feature = "alloc")]
+pub use self::roundtrip::{Difference, round_trip};
+
+#[cfg(feature = "alloc")]
+mod search_path;
+#[cfg(feature = "alloc")]
+pub use self::search_path::SearchPathTokens;
+
+#[cfg(feature = "alloc")]
+mod needed_usage;
+#[cfg(feature = "alloc")]
+pub use self::needed_usage::NeededUsage;
+
+#[cfg(feature = "alloc")]
+mod co_load;
+#[cfg(feature = "alloc")]
+pub use self::co_load::{Image, LoadCommand, ProcessImage};
+
+#[cfg(feature = "alloc")]
+mod relocation_statistics;
+#[cfg(feature = "alloc")]
+pub use self::relocation_statistics::{RelocationCount, RelocationStatistics};
+
+#[cfg(feature = "alloc")]
+mod linkage;
+#[cfg(feature = "alloc")]
+pub use self::linkage::{Linkage, LinkedObject, Definition};
+
+#[cfg(feature = "alloc")]
+mod ifunc;
+#[cfg(feature = "alloc")]
+pub use self::ifunc::{
+    IrelativeRelocation, IndirectFunctions, LoadedImage, IndirectSymbol, Symbolization,
+};
+
+#[cfg(feature = "alloc")]
+mod integrity;
+#[cfg(feature = "alloc")]
+pub use self::integrity::{ImageRange, IntegrityReport};
+
+#[cfg(feature = "alloc")]
+mod got_hooks;
+#[cfg(feature = "alloc")]
+pub use self::got_hooks::{ModuleRange, GotSlot, HookedSlot, GotHookReport};
+
+#[cfg(feature = "alloc")]
+mod copy_relocation;
+#[cfg(feature = "alloc")]
+pub use self::copy_relocation::CopyRelocation;
+
+#[cfg(feature = "alloc")]
+mod exported_objects;
+#[cfg(feature = "alloc")]
+pub use self::exported_objects::{InitialValue, ExportedObject};
+
+#[cfg(feature = "alloc")]
+mod canonical;
+#[cfg(feature = "alloc")]
+pub use self::canonical::{
+    CanonicalSection, CanonicalValue, CanonicalDynamic, CanonicalIndex, CanonicalSymbol,
+    CanonicalDump,
+};
+
+#[cfg(feature = "alloc")]
+mod relro;
+#[cfg(feature = "alloc")]
+pub use self::relro::{RelroObjectKind, RelroObject, RelroObjects};
+
+#[cfg(feature = "alloc")]
+mod fortify;
+#[cfg(feature = "alloc")]
+pub use self::fortify::FortifyReport;
+
+#[cfg(feature = "alloc")]
+mod lint;
+#[cfg(feature = "alloc")]
+pub use self::lint::Diagnostic;
+
+#[cfg(feature = "alloc")]
+mod comdat;
+#[cfg(feature = "alloc")]
+pub use self::comdat::{Group, ComdatSelection};
+
+#[cfg(feature = "alloc")]
+mod resolution;
+#[cfg(feature = "alloc")]
+pub use self::resolution::{Candidate, Conflict, SymbolResolution};
+
+#[cfg(feature = "alloc")]
+mod common_allocation;
+#[cfg(feature = "alloc")]
+pub use self::common_allocation::{CommonSymbol, CommonAllocation};
+
+#[cfg(feature = "alloc")]
+mod entry_points;
+#[cfg(feature = "alloc")]
+pub use self::entry_points::{EntryPointKind, EntryPoint};
+
+#[cfg(feature = "alloc")]
+mod version_script;
+#[cfg(feature = "alloc")]
+pub use self::version_script::{VersionNode, VersionScript};
+
+#[cfg(feature = "alloc")]
+mod symbol_consistency;
+#[cfg(feature = "alloc")]
+pub use self::symbol_consistency::SymbolMismatch;
+
+#[cfg(feature = "alloc")]
+mod byte_order;
+#[cfg(feature = "alloc")]
+pub use self::byte_order::{Unconverted, UnconvertedPart, ByteOrderConversion};
+
+#[cfg(feature = "alloc")]
+mod validation;
+#[cfg(feature = "alloc")]
+pub use self::validation::{Violation, ValidationReport};
+
+mod elf_mut;
+pub use self::elf_mut::Elf64Mut;
+
+mod elf32;
+pub use self::elf32::Elf32;
+
+mod read_at;
+pub use self::read_at::{ReadAt, Elf64Reader};
+
+mod handle;
+pub use self::handle::{SectionId, SymbolId, NoteId};
+
+mod archive;
+pub use self::archive::{FileKind, ArchiveMember, Archive, ArchiveMembers, probe};
+
+#[cfg(feature = "std")]
+mod symbol_table_export;
+#[cfg(feature = "std")]
+pub use self::symbol_table_export::TableFormat;
+
+mod limits;
+pub use self::limits::{Limits, Limit};
+
+mod prefix;
+pub use self::prefix::Availability;
+
+#[cfg(feature = "alloc")]
+mod patcher;
+#[cfg(feature = "alloc")]
+pub use self::patcher::{Patcher, ConversionNote};
 
 #[derive(Clone)]
 pub struct Elf64<'a> {
-    raw: &'a [u8],
+    raw: Source<'a>,
     header: Header,
     program_table: Table<'a, ProgramHeader>,
+    // the numbers of the entries are of the header or of the section zero
     section_table: Table<'a, SectionHeader>,
     names: Option<StringTable<'a>>,
+    limits: Limits,
 }
 
 impl<'a> Elf64<'a> {
     pub fn new(raw: &'a [u8]) -> Result<Self, Error> {
+        Elf64::with_source(Source::Contiguous(raw))
+    }
+
+    fn with_source(raw: Source<'a>) -> Result<Self, Error> {
+        Elf64::header_only(raw)?
+            .with_program_table()?
+            .with_section_table()?
+            .with_section_names()
+    }
+
+    // The steps of `new` one by one, for a view of some of the tables only, e.g. on
+    // a target that cannot afford to read the section headers. `from_header` parses
+    // the header, a table that is not attached is empty, `program_number` or
+    // `section_number` is zero, and the queries that need it go without it.
+    pub fn from_header(raw: &'a [u8]) -> Result<Self, Error> {
+        Elf64::header_only(Source::Contiguous(raw))
+    }
+
+    fn header_only(raw: Source<'a>) -> Result<Self, Error> {
+        let mut header = Elf64::file_header(raw)?;
+        if let Machine::Unknown(code) = header.machine {
+            log_debug!("unknown machine 0x{:x}", code);
+        }
+        let encoding = header.identifier.encoding.clone();
+        header.program_header_number = 0;
+        header.section_header_number = 0;
+        Ok(Elf64 {
+            raw,
+            header,
+            program_table: Table::new(&[], encoding.clone()),
+            section_table: Table::new(&[], encoding),
+            names: None,
+            limits: Limits::default(),
+        })
+    }
+
+    fn file_header(raw: Source<'a>) -> Result<Header, Error> {
         if raw.len() < Header::SIZE {
             return Err(Error::SliceTooShort);
         }
+        Header::new(file_slice(raw, Offset(0), Header::SIZE as u64)?)
+    }
 
-        let header = Header::new(&raw[0..Header::SIZE])?;
-        let program_table = header.program_header_table(raw)?;
+    pub fn with_program_table(mut self) -> Result<Self, Error> {
+        let header = Elf64::file_header(self.raw)?;
+        self.program_table = header.program_header_table(self.raw)?;
+        self.header.program_header_number = header.program_header_number;
+        Ok(self)
+    }
+
+    pub fn with_section_table(mut self) -> Result<Self, Error> {
+        let header = Elf64::file_header(self.raw)?;
+        self.section_table = header.section_header_table(self.raw)?;
+        self.header.section_header_number = header.section_header_number;
+        Ok(self)
+    }
 
-        let section_table = header.section_header_table(raw)?;
-        let names = match header.section_names {
+    // `e_shstrndx`, `Error::NotPresent` if the file has sections, but the table
+    // is not attached
+    pub fn with_section_names(mut self) -> Result<Self, Error> {
+        let raw = self.raw;
+        if self.section_number() == 0 && Elf64::file_header(raw)?.section_count(raw)? != 0 {
+            return Err(Error::NotPresent);
+        }
+        self.names = match self.section_names() {
             Index::Regular(i) => {
-                let names_section = section_table.pick(i as usize)?;
+                let names_section = self.section_table.pick(i.0)?;
                 match names_section.ty {
-                    SectionType::StringTable => {
-                        let start = names_section.offset as usize;
-                        if raw.len() < start {
-                            return Err(Error::SliceTooShort);
+                    SectionType::StringTable => match file_tail(raw, names_section.offset) {
+                        Ok(slice) => {
+                            Some(StringTable::new(slice).limited(self.limits.string_length))
                         }
-                        Some(StringTable::new(&raw[start..]))
+                        // parsed again once they are captured
+                        Err(Error::NotCaptured(_)) => {
+                            log_debug!("section names: not captured");
+                            None
+                        }
+                        Err(error) => return Err(error),
+                    },
+                    ty => {
+                        log_debug!(
+                            "section names: e_shstrndx {} is {:?}, not a string table",
+                            i,
+                            ty
+                        );
+                        None
                     }
-                    _ => None,
                 }
             }
-            _ => None,
+            Index::Undefined => None,
+            ref index => {
+                log_debug!("section names: e_shstrndx is {:?}", index);
+                None
+            }
         };
-
-        Ok(Elf64 {
-            raw,
-            header,
-            program_table,
-            section_table,
-            names,
-        })
+        Ok(self)
     }
 
     pub fn class(&self) -> Class {
@@ -107,6 +588,25 @@ impl<'a> Elf64<'a> {
         self.header.identifier.abi_version
     }
 
+    // `e_ident` with the padding, the bytes the file starts with
+    pub fn identifier(&self) -> [u8; 16] {
+        self.header.identifier.bytes()
+    }
+
+    pub fn is_little_endian(&self) -> bool {
+        self.header.identifier.encoding == Encoding::Little
+    }
+
+    // `ELFCLASS64`, `Elf64` parses a file of another class by the layout of this one
+    pub fn is_64bit(&self) -> bool {
+        self.header.identifier.class == Class::_64
+    }
+
+    // by the value of `EI_OSABI`, so `Abi::Unknown` of a known value matches as well
+    pub fn osabi_matches(&self, abi: Abi) -> bool {
+        u8::from(self.abi()) == u8::from(abi)
+    }
+
     pub fn ty(&self) -> Type {
         self.header.ty.clone()
     }
@@ -127,107 +627,162 @@ impl<'a> Elf64<'a> {
         self.header.flags
     }
 
+    // zero for a relocatable file, `program` is `None` for any index then
     pub fn program_number(&self) -> usize {
-        self.header.program_header_number as usize
+        self.program_table.len()
     }
 
+    // The program header only, the content is not sliced nor checked
+    pub fn program_info(&self, index: usize) -> Result<Option<ProgramHeader>, Error> {
+        if index >= self.program_number() {
+            return Ok(None);
+        }
+        self.program_table.pick(index).map(Some)
+    }
+
+    // `None` only if the index is out of range
     pub fn program(&self, index: usize) -> Result<Option<Program<'a>>, Error> {
+        if index >= self.program_number() {
+            return Ok(None);
+        }
         let program_header = self.program_table.pick(index)?;
         let encoding = self.encoding();
 
-        let slice = if self.raw.len() < program_header.file_offset as usize {
-            return Err(Error::SliceTooShort);
+        // nothing in the file, e.g. `PT_GNU_STACK`, the offset does not matter
+        let slice = if program_header.file_size == 0 {
+            &[]
         } else {
-            &self.raw[(program_header.file_offset as usize)..]
+            file_slice(
+                self.raw,
+                program_header.file_offset,
+                program_header.file_size,
+            )?
         };
-        if slice.len() < program_header.file_size as usize {
-            return Err(Error::SliceTooShort);
-        }
-        let slice = &slice[..(program_header.file_size as usize)];
 
-        let data = match program_header.ty {
-            ProgramType::Null => None,
-            ProgramType::Load => Some(ProgramData::Load {
-                data: slice,
-                address: program_header.virtual_address,
-            }),
-            // TODO:
-            ProgramType::Dynamic => None,
-            ProgramType::Interpreter => Some(ProgramData::Interpreter(slice)),
-            ProgramType::Note => Some(ProgramData::Note(NoteTable::new(slice, encoding))),
-            ProgramType::Shlib => None,
-            ProgramType::ProgramHeaderTable => None,
-            ProgramType::OsSpecific(code) => Some(ProgramData::OsSpecific {
-                code,
-                data: slice,
-                address: program_header.virtual_address,
-            }),
-            ProgramType::ProcessorSprcific(code) => Some(ProgramData::ProcessorSprcific {
-                code,
-                data: slice,
-                address: program_header.virtual_address,
-            }),
-            ProgramType::Unknown(code) => Some(ProgramData::Unknown {
-                code,
-                data: slice,
-                address: program_header.virtual_address,
-            }),
-        };
+        let data = program_data(
+            &program_header,
+            index,
+            slice,
+            encoding,
+            Class::_64,
+            self.limits.note_size,
+        );
 
-        Ok(data.map(|d| Program {
-            data: d,
+        Ok(Some(Program {
+            data,
             flags: program_header.flags,
+            file_size: program_header.file_size,
             memory_size: program_header.memory_size,
             address_alignment: program_header.address_alignment,
         }))
     }
 
+    // `e_shstrndx`, or `sh_link` of the section zero, the header keeps `SHN_XINDEX`
+    pub(crate) fn section_names(&self) -> Index {
+        self.header.section_names_index(self.raw)
+    }
+
     pub fn section_number(&self) -> usize {
-        self.header.section_header_number as usize
+        self.section_table.len()
+    }
+
+    // The section header and the name, the content is not sliced nor checked
+    pub fn section_info(&self, index: SectionIndex) -> Result<Option<SectionInfo<'a>>, Error> {
+        let index = index.0;
+        if index >= self.section_number() {
+            return Ok(None);
+        }
+        let header = self.section_table.pick(index)?;
+        let name = match &self.names {
+            Some(table) => table.pick(header.name as usize)?,
+            None => &[],
+        };
+        Ok(Some(SectionInfo { header, name }))
+    }
+
+    // The first section with the name, e.g. `.text` or `.debug_info`,
+    // `None` without section names
+    pub fn section_by_name(&self, name: &[u8]) -> Result<Option<Section<'a>>, Error> {
+        if self.names.is_none() {
+            return Ok(None);
+        }
+        for index in 0..self.section_number() {
+            match self.section_info(SectionIndex(index))? {
+                Some(info) if info.name == name => return self.section(SectionIndex(index)),
+                _ => (),
+            }
+        }
+        Ok(None)
     }
 
-    pub fn section(&self, index: usize) -> Result<Option<Section<'a>>, Error> {
+    // `None` only if the index is out of range
+    pub fn section(&self, index: SectionIndex) -> Result<Option<Section<'a>>, Error> {
+        let index = index.0;
+        if index >= self.section_number() {
+            return Ok(None);
+        }
         let section_header = self.section_table.pick(index)?;
         let encoding = self.encoding();
 
-        let start = section_header.offset as usize;
-        let end = start + (section_header.size as usize);
-        if self.raw.len() < end || start > end {
-            return Err(Error::SliceTooShort);
-        }
-        let slice = &self.raw[start..end];
+        // these have nothing in the file, the offset and the size do not matter
+        let slice = match section_header.ty {
+            SectionType::Null | SectionType::NoBits => &[],
+            _ if section_header.size == 0 => &[],
+            _ => file_slice(self.raw, section_header.offset, section_header.size)?,
+        };
 
+        let entry_size = section_header.number_of_entries;
+        // `sh_info` of a relocation section is 32 bits wide, and from `SHN_LORESERVE`
+        // on it is a section of the file if there are so many
+        let apply_to_section = match section_header.info {
+            info if (0xff00..self.section_number()).contains(&(info as usize)) => {
+                Index::Regular(SectionIndex(info as usize))
+            }
+            info => info.into(),
+        };
         let data = match section_header.ty {
-            SectionType::Null => None,
-            SectionType::ProgramBits => Some(SectionData::ProgramBits(slice)),
-            SectionType::SymbolTable => Some(SectionData::SymbolTable {
-                table: Table::new(slice, encoding),
+            SectionType::Null => SectionData::Null,
+            SectionType::ProgramBits => SectionData::ProgramBits(slice),
+            SectionType::SymbolTable => SectionData::SymbolTable {
+                table: Table::with_entry_size(slice, encoding, entry_size),
                 number_of_locals: section_header.info as usize,
-            }),
-            SectionType::StringTable => Some(SectionData::StringTable(StringTable::new(slice))),
-            SectionType::Rela => Some(SectionData::Rela {
-                table: Table::new(slice, encoding),
-                apply_to_section: (section_header.info as u16).into(),
-            }),
-            // TODO:
-            SectionType::Hash => None,
-            SectionType::Dynamic => None,
-            SectionType::Note => Some(SectionData::Note(NoteTable::new(slice, encoding))),
-            SectionType::NoBits => None,
-            SectionType::Rel => Some(SectionData::Rel {
-                table: Table::new(slice, encoding),
-                apply_to_section: (section_header.info as u16).into(),
-            }),
-            SectionType::Shlib => None,
-            SectionType::DynamicSymbolTable => Some(SectionData::DynamicSymbolTable {
-                table: Table::new(slice, encoding),
+            },
+            SectionType::StringTable => {
+                SectionData::StringTable(StringTable::new(slice).limited(self.limits.string_length))
+            }
+            SectionType::Rela => SectionData::Rela {
+                table: Table::with_entry_size(slice, encoding, entry_size),
+                apply_to_section,
+            },
+            SectionType::Hash => SectionData::Hash(HashTable::new(slice, encoding)),
+            SectionType::Dynamic => {
+                SectionData::Dynamic(Table::with_entry_size(slice, encoding, entry_size))
+            }
+            SectionType::Note => SectionData::Note(
+                NoteTable::with_alignment(slice, encoding, section_header.address_alignment)
+                    .limited(self.limits.note_size),
+            ),
+            SectionType::NoBits => SectionData::NoBits {
+                size: section_header.size,
+            },
+            SectionType::Rel => SectionData::Rel {
+                table: Table::with_entry_size(slice, encoding, entry_size),
+                apply_to_section,
+            },
+            SectionType::Shlib => SectionData::Shlib(slice),
+            SectionType::DynamicSymbolTable => SectionData::DynamicSymbolTable {
+                table: Table::with_entry_size(slice, encoding, entry_size),
                 number_of_locals: section_header.info as usize,
-            }),
-            SectionType::OsSpecific(code) => Some(SectionData::OsSpecific { code, slice }),
-            SectionType::ProcessorSprcific(code) => {
-                Some(SectionData::ProcessorSprcific { code, slice })
+            },
+            SectionType::OsSpecific(SHT_GNU_HASH) => {
+                SectionData::GnuHash(GnuHashTable::new(slice, encoding))
+            }
+            SectionType::OsSpecific(code) => SectionData::OsSpecific { code, slice },
+            SectionType::ProcessorSprcific(code) => SectionData::ProcessorSprcific { code, slice },
+            SectionType::Unknown(code) => {
+                log_trace!("section {} has unknown type 0x{:x}", index, code);
+                SectionData::Unknown { code, slice }
             }
-            SectionType::Unknown(code) => Some(SectionData::Unknown { code, slice }),
         };
 
         let name = match &self.names {
@@ -235,7 +790,30 @@ impl<'a> Elf64<'a> {
             None => &[],
         };
 
-        Ok(data.map(|data| Section {
+        // the content of any type may be compressed but `SHT_NOBITS`
+        let data = if section_header.ty == SectionType::NoBits {
+            data
+        } else if section_header.flags.contains(SectionFlags::COMPRESSED) {
+            let header = CompressionHeader::new(slice, self.encoding())?;
+            SectionData::Compressed {
+                ty: header.ty,
+                uncompressed_size: header.size,
+                alignment: header.alignment,
+                payload: &slice[CompressionHeader::SIZE..],
+            }
+        } else {
+            match (name.starts_with(b".zdebug"), legacy_header(slice)) {
+                (true, Some(header)) => SectionData::Compressed {
+                    ty: header.ty,
+                    uncompressed_size: header.size,
+                    alignment: header.alignment,
+                    payload: &slice[12..],
+                },
+                _ => data,
+            }
+        };
+
+        Ok(Some(Section {
             data,
             name,
             flags: section_header.flags,
@@ -246,6 +824,88 @@ impl<'a> Elf64<'a> {
     }
 }
 
+// The content of a segment by its type, the same for both classes, `slice` is
+// the part in the file
+pub(crate) fn program_data<'a>(
+    header: &ProgramHeader,
+    index: usize,
+    slice: &'a [u8],
+    encoding: Encoding,
+    class: Class,
+    note_size: Option<u32>,
+) -> ProgramData<'a> {
+    match header.ty {
+        ProgramType::Null => ProgramData::Null,
+        ProgramType::Load if slice.is_empty() => ProgramData::ZeroFilled {
+            address: header.virtual_address,
+        },
+        ProgramType::Load => ProgramData::Load {
+            data: slice,
+            address: header.virtual_address,
+        },
+        ProgramType::Dynamic => ProgramData::Dynamic {
+            table: DynamicTable::of_segment(slice, encoding, class),
+            address: header.virtual_address,
+        },
+        ProgramType::Interpreter => ProgramData::Interpreter(slice),
+        ProgramType::Note => ProgramData::Note(
+            NoteTable::with_alignment(slice, encoding, header.address_alignment).limited(note_size),
+        ),
+        ProgramType::Shlib => ProgramData::Shlib {
+            data: slice,
+            address: header.virtual_address,
+        },
+        ProgramType::ProgramHeaderTable => ProgramData::ProgramHeaderTable {
+            data: slice,
+            address: header.virtual_address,
+        },
+        ProgramType::Tls => ProgramData::Tls {
+            data: slice,
+            template: TlsTemplate {
+                program: index,
+                address: header.virtual_address,
+                file_size: header.file_size,
+                memory_size: header.memory_size,
+                alignment: header.address_alignment,
+            },
+        },
+        ProgramType::GnuEhFrame => ProgramData::EhFrameHeader {
+            data: slice,
+            address: header.virtual_address,
+        },
+        ProgramType::GnuStack => ProgramData::GnuStack(GnuStack {
+            flags: header.flags,
+            size: Some(header.memory_size).filter(|size| *size != 0),
+        }),
+        ProgramType::GnuRelro => ProgramData::GnuRelro(GnuRelro {
+            program: index,
+            address: header.virtual_address,
+            size: header.memory_size,
+        }),
+        ProgramType::GnuProperty => ProgramData::GnuProperty(
+            NoteTable::with_alignment(slice, encoding, header.address_alignment).limited(note_size),
+        ),
+        ProgramType::OsSpecific(code) => ProgramData::OsSpecific {
+            code,
+            data: slice,
+            address: header.virtual_address,
+        },
+        ProgramType::ProcessorSprcific(code) => ProgramData::ProcessorSprcific {
+            code,
+            data: slice,
+            address: header.virtual_address,
+        },
+        ProgramType::Unknown(code) => {
+            log_trace!("program {} has unknown type 0x{:x}", index, code);
+            ProgramData::Unknown {
+                code,
+                data: slice,
+                address: header.virtual_address,
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum ProgramData<'a> {
     Null,
@@ -253,8 +913,41 @@ pub enum ProgramData<'a> {
         data: &'a [u8],
         address: Address,
     },
+    // a `PT_LOAD` with nothing in the file, `memory_size` bytes of zeros in memory
+    ZeroFilled {
+        address: Address,
+    },
+    // the entries, without the strings, `Elf64::dynamic` finds them
+    Dynamic {
+        table: DynamicTable<'a>,
+        address: Address,
+    },
     Interpreter(&'a [u8]),
     Note(NoteTable<'a>),
+    // reserved, the semantics is unspecified
+    Shlib {
+        data: &'a [u8],
+        address: Address,
+    },
+    // the program header table itself, where it is in the file and in memory
+    ProgramHeaderTable {
+        data: &'a [u8],
+        address: Address,
+    },
+    // `data` is the initialized part of the template, `.tdata`
+    Tls {
+        data: &'a [u8],
+        template: TlsTemplate,
+    },
+    // `.eh_frame_hdr`, the table of the unwinder to find the FDE of an address
+    EhFrameHeader {
+        data: &'a [u8],
+        address: Address,
+    },
+    GnuStack(GnuStack),
+    GnuRelro(GnuRelro),
+    // the note `NT_GNU_PROPERTY_TYPE_0`, usually inside a `PT_NOTE` as well
+    GnuProperty(NoteTable<'a>),
     OsSpecific {
         code: u32,
         data: &'a [u8],
@@ -276,6 +969,7 @@ pub enum ProgramData<'a> {
 pub struct Program<'a> {
     pub data: ProgramData<'a>,
     pub flags: ProgramFlags,
+    pub file_size: u64,
     pub memory_size: u64,
     pub address_alignment: u64,
 }
@@ -289,15 +983,25 @@ pub enum SectionData<'a> {
         number_of_locals: usize,
     },
     StringTable(StringTable<'a>),
+    Hash(HashTable<'a>),
+    // `SHT_GNU_HASH`
+    GnuHash(GnuHashTable<'a>),
+    // the `Elf64_Dyn` entries, `Elf64::dynamic` has them with the strings
+    Dynamic(Table<'a, DynamicEntry>),
     Rela {
         table: Table<'a, RelaEntry>,
         apply_to_section: Index,
     },
     Note(NoteTable<'a>),
+    // occupies no space in the file, `size` bytes in memory
+    NoBits {
+        size: u64,
+    },
     Rel {
         table: Table<'a, RelEntry>,
         apply_to_section: Index,
     },
+    Shlib(&'a [u8]),
     DynamicSymbolTable {
         table: Table<'a, SymbolEntry>,
         number_of_locals: usize,
@@ -314,6 +1018,19 @@ pub enum SectionData<'a> {
         code: u32,
         slice: &'a [u8],
     },
+    // `SHF_COMPRESSED` behind `Elf64_Chdr`, or a `.zdebug_*` section, of any type
+    Compressed {
+        ty: CompressionType,
+        uncompressed_size: u64,
+        alignment: u64,
+        payload: &'a [u8],
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SectionInfo<'a> {
+    pub header: SectionHeader,
+    pub name: &'a [u8],
 }
 
 #[derive(Clone)]
@@ -325,3 +1042,338 @@ pub struct Section<'a> {
     pub address_alignment: u64,
     pub link: Index,
 }
+
+impl<'a> Section<'a> {
+    pub fn name_str(&self) -> Result<&'a str, str::Utf8Error> {
+        str::from_utf8(self.name)
+    }
+
+    pub fn name_lossy(&self) -> Lossy<'a> {
+        Lossy(self.name)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{
+        Elf64, ElfBuilder, Encoding, Type, Machine, SectionIndex, SectionType, SectionFlags,
+        SectionData, ProgramType, ProgramFlags, ProgramData, ProgramHeader, SectionHeader, Index,
+        SymbolInfo, SymbolBinding, SymbolType, GnuStack, Entry, DynamicEntry, DynamicTag,
+        round_trip,
+    };
+
+    // `.text`, `.bss` in a segment of its own, `PT_GNU_STACK` and an empty `.comment`
+    fn with_nothing_in_the_file() -> (Vec<u8>, SectionIndex) {
+        let mut builder = ElfBuilder::new(Encoding::Little, Type::Executable, Machine::X86_64);
+        let text = builder.add_section(
+            b".text",
+            SectionType::ProgramBits,
+            SectionFlags::ALLOC | SectionFlags::EXECINSTR,
+            16,
+            vec![0xc3; 16],
+        );
+        builder.add_no_bits(b".bss", SectionFlags::ALLOC | SectionFlags::WRITE, 8, 0x40);
+        let comment = builder.add_section(
+            b".comment",
+            SectionType::ProgramBits,
+            SectionFlags::empty(),
+            1,
+            Vec::new(),
+        );
+        builder.add_program(
+            ProgramType::GnuStack,
+            ProgramFlags::READ | ProgramFlags::WRITE,
+            None,
+        );
+        builder.set_entry(text, 0);
+        (builder.build().unwrap(), comment)
+    }
+
+    #[test]
+    fn zero_filled_segment() {
+        let (raw, _) = with_nothing_in_the_file();
+        let elf = Elf64::new(&raw).unwrap();
+        let bss = elf.section_by_name(b".bss").unwrap().unwrap().address;
+        let program = (0..elf.program_number())
+            .map(|index| elf.program(index).unwrap().unwrap())
+            .find(|program| program.file_size == 0 && program.memory_size != 0)
+            .unwrap();
+        assert!(matches!(program.data, ProgramData::ZeroFilled { address } if address == bss));
+        assert_eq!(program.memory_size, 0x40);
+    }
+
+    #[test]
+    fn offsets_of_nothing_in_the_file() {
+        let (mut raw, comment) = with_nothing_in_the_file();
+        let elf = Elf64::new(&raw).unwrap();
+        let stack = (0..elf.program_number())
+            .find(|&index| elf.program_info(index).unwrap().unwrap().ty == ProgramType::GnuStack)
+            .unwrap();
+        let program = elf.header.program_headers_offset.0 as usize + stack * ProgramHeader::SIZE;
+        let section =
+            elf.header.section_headers_offset.0 as usize + comment.0 * SectionHeader::SIZE;
+        // the offsets past the end of the file are not read
+        raw[program + 0x08..program + 0x10].copy_from_slice(&u64::MAX.to_le_bytes());
+        raw[section + 0x18..section + 0x20].copy_from_slice(&u64::MAX.to_le_bytes());
+        let elf = Elf64::new(&raw).unwrap();
+        let program = elf.program(stack).unwrap().unwrap();
+        let flags = ProgramFlags::READ | ProgramFlags::WRITE;
+        assert!(matches!(
+            program.data,
+            ProgramData::GnuStack(GnuStack { flags: f, size: None }) if f == flags
+        ));
+        let section = elf.section(comment).unwrap().unwrap();
+        assert!(matches!(section.data, SectionData::ProgramBits(data) if data.is_empty()));
+    }
+
+    // `number` sections of no content and two relocation sections after them,
+    // in a file of the extended numbering, the names are the last section, so
+    // `e_shstrndx` is `SHN_XINDEX` too
+    pub(crate) fn with_extended_numbering(number: usize, infos: [u32; 2]) -> Vec<u8> {
+        let mut builder = ElfBuilder::new(Encoding::Little, Type::Relocatable, Machine::X86_64);
+        for _ in 0..number {
+            builder.add_section(
+                b".a",
+                SectionType::ProgramBits,
+                SectionFlags::empty(),
+                1,
+                Vec::new(),
+            );
+        }
+        for info in infos {
+            let rela = builder.add_section(
+                b".rela.a",
+                SectionType::Rela,
+                SectionFlags::INFO_LINK,
+                8,
+                Vec::new(),
+            );
+            builder.set_info(rela, info).unwrap();
+        }
+        let mut raw = builder.build().unwrap();
+        // the builder writes the number and the index of the names as they are, the
+        // table is at the end of the file
+        let table = Elf64::new(&raw).unwrap().header.section_headers_offset.0 as usize;
+        let count = (raw.len() - table) / SectionHeader::SIZE;
+        raw[0x3c..0x3e].copy_from_slice(&0u16.to_le_bytes());
+        raw[0x3e..0x40].copy_from_slice(&0xffffu16.to_le_bytes());
+        raw[(table + 0x20)..(table + 0x28)].copy_from_slice(&(count as u64).to_le_bytes());
+        raw[(table + 0x28)..(table + 0x2c)].copy_from_slice(&(count as u32 - 1).to_le_bytes());
+        raw
+    }
+
+    #[test]
+    fn dynamic_entries() {
+        for encoding in [Encoding::Little, Encoding::Big] {
+            let raw = executable(encoding);
+            let elf = Elf64::new(&raw).unwrap();
+            let section = elf.section_by_name(b".dynamic").unwrap().unwrap();
+            let needed = DynamicEntry {
+                tag: DynamicTag::Needed,
+                value: 1,
+            };
+            match section.data {
+                SectionData::Dynamic(table) => assert_eq!(table.pick(0), Ok(needed)),
+                _ => panic!("not the dynamic section"),
+            }
+            let table = elf
+                .programs()
+                .find_map(|program| match program.unwrap().data {
+                    ProgramData::Dynamic { table, .. } => Some(table),
+                    _ => None,
+                })
+                .unwrap();
+            assert_eq!(table.entries().count(), 3);
+            assert_eq!(table.value(DynamicTag::StringTableSize), Ok(Some(11)));
+        }
+    }
+
+    #[test]
+    fn relocations_of_sections_above_reserved() {
+        let raw = with_extended_numbering(0xff00, [0xff00, 0x1_0005]);
+        let elf = Elf64::new(&raw).unwrap();
+        assert_eq!(elf.section_number(), 0xff04);
+        for (index, target) in [(0xff01, 0xff00), (0xff02, 0x1_0005)] {
+            let section = elf.section(SectionIndex(index)).unwrap().unwrap();
+            assert!(matches!(
+                section.data,
+                SectionData::Rela { apply_to_section: Index::Regular(SectionIndex(i)), .. }
+                    if i == target
+            ));
+        }
+    }
+
+    // an executable with a dynamic section, a note, relocations and symbols, the
+    // truncated and garbled copies of it must not make any query panic
+    pub(crate) fn executable(encoding: Encoding) -> Vec<u8> {
+        let int = |value: u64| match encoding {
+            Encoding::Little => value.to_le_bytes(),
+            Encoding::Big => value.to_be_bytes(),
+        };
+        let mut builder = ElfBuilder::new(encoding.clone(), Type::Executable, Machine::X86_64);
+        let text = builder.add_section(
+            b".text",
+            SectionType::ProgramBits,
+            SectionFlags::ALLOC | SectionFlags::EXECINSTR,
+            16,
+            vec![0xc3; 32],
+        );
+        builder.add_no_bits(b".bss", SectionFlags::ALLOC | SectionFlags::WRITE, 8, 64);
+        let mut note = Vec::new();
+        for word in [4u32, 4, 3] {
+            note.extend_from_slice(&int(u64::from(word))[..4]);
+        }
+        note.extend_from_slice(b"GNU\0\x01\x02\x03\x04");
+        let note = builder.add_section(
+            b".note.gnu.build-id",
+            SectionType::Note,
+            SectionFlags::ALLOC,
+            4,
+            note,
+        );
+        let strings = builder.add_section(
+            b".dynstr",
+            SectionType::StringTable,
+            SectionFlags::ALLOC,
+            1,
+            b"\0libc.so.6\0".to_vec(),
+        );
+        let mut entries = Vec::new();
+        for (tag, value) in [(1, 1), (5, 0), (10, 11), (0, 0)] {
+            entries.extend_from_slice(&int(tag));
+            entries.extend_from_slice(&int(value));
+        }
+        let dynamic = builder.add_section(
+            b".dynamic",
+            SectionType::Dynamic,
+            SectionFlags::ALLOC | SectionFlags::WRITE,
+            8,
+            entries,
+        );
+        builder.set_link(dynamic, strings).unwrap();
+        builder.set_entry_size(dynamic, 16).unwrap();
+        let mut relocations = Vec::new();
+        for word in [8, (1 << 32) | 1, 0] {
+            relocations.extend_from_slice(&int(word));
+        }
+        let rela = builder.add_section(
+            b".rela.text",
+            SectionType::Rela,
+            SectionFlags::empty(),
+            8,
+            relocations,
+        );
+        builder.set_link(rela, builder.symbol_table()).unwrap();
+        builder.set_info(rela, text.0 as u32).unwrap();
+        builder.set_entry_size(rela, 24).unwrap();
+        let info = SymbolInfo {
+            binding: SymbolBinding::Global,
+            ty: SymbolType::Function,
+        };
+        builder.add_symbol(b"_start", info.clone(), Index::Regular(text), 0, 32);
+        builder.add_symbol(b"undefined", info, Index::Undefined, 0, 0);
+        builder.add_program(ProgramType::Note, ProgramFlags::READ, Some(note));
+        builder.add_program(
+            ProgramType::Dynamic,
+            ProgramFlags::READ | ProgramFlags::WRITE,
+            Some(dynamic),
+        );
+        builder.set_entry(text, 0);
+        builder.build().unwrap()
+    }
+
+    // the queries that read the tables, the results do not matter
+    fn query(raw: &[u8]) {
+        let elf = match Elf64::new(raw) {
+            Ok(elf) => elf,
+            Err(_) => return,
+        };
+        for index in 0..elf.program_number() {
+            let _ = elf.program(index);
+        }
+        for index in 0..elf.section_number() {
+            let _ = elf.section(SectionIndex(index));
+        }
+        if let Ok(Some(symbols)) = elf.symbols() {
+            symbols.take(0x100).for_each(drop);
+        }
+        if let Ok(Some(symbols)) = elf.dynamic_symbols() {
+            symbols.take(0x100).for_each(drop);
+        }
+        if let Ok(needed) = elf.needed() {
+            needed.take(0x100).for_each(drop);
+        }
+        elf.notes().take(0x100).for_each(drop);
+        elf.relocations().take(0x100).for_each(drop);
+        elf.unclaimed_regions().take(0x100).for_each(drop);
+        let _ = elf.versions();
+        let _ = elf.canonical_dump();
+        let _ = elf.lint();
+        let _ = elf.validate();
+        let _ = elf.to_opposite_byte_order();
+        let _ = round_trip(raw);
+    }
+
+    // xorshift, the same bytes on every run
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        for encoding in [Encoding::Little, Encoding::Big] {
+            let raw = executable(encoding);
+            // the tables the queries read are there in the whole file
+            let elf = Elf64::new(&raw).unwrap();
+            let needed = elf.needed().unwrap().collect::<Result<Vec<_>, _>>();
+            assert_eq!(needed.unwrap(), [b"libc.so.6"]);
+            assert_eq!(elf.notes().filter(Result::is_ok).count(), 1);
+            assert_eq!(elf.relocations().filter(Result::is_ok).count(), 1);
+            assert_eq!(elf.symbols().unwrap().unwrap().count(), 2);
+            for length in 0..raw.len() {
+                query(&raw[..length]);
+            }
+        }
+    }
+
+    #[test]
+    fn garbled() {
+        let mut random = Random(0x1234_5678_9abc_def1);
+        for encoding in [Encoding::Little, Encoding::Big] {
+            let raw = executable(encoding);
+            for _ in 0..0x1000 {
+                let mut garbled = raw.clone();
+                for _ in 0..1 + random.next() % 8 {
+                    let position = random.next() as usize % garbled.len();
+                    // an extreme value is more likely to reach an overflow
+                    garbled[position] = match random.next() % 3 {
+                        0 => 0xff,
+                        1 => 0,
+                        _ => random.next() as u8,
+                    };
+                }
+                query(&garbled);
+            }
+        }
+    }
+
+    #[test]
+    fn garbage() {
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..0x100 {
+            let mut raw = (0..0x200).map(|_| random.next() as u8).collect::<Vec<_>>();
+            // the identifier of a 64-bit file, else nothing past it is read
+            raw[..7].copy_from_slice(b"\x7fELF\x02\x01\x01");
+            query(&raw);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use super::{Error, Elf64};
+
+// Bounds of the work on a file from an untrusted source, `None` is no bound,
+// the default. A file past a bound is `Error::LimitExceeded` where the parser
+// reaches it, the same file fails the same way every time.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Limits {
+    // the number of the section headers, of the extended numbering as well
+    pub sections: Option<u32>,
+    // the entries of the table one iteration of `Symbols` reads
+    pub symbols: Option<u32>,
+    // `n_descsz` of a note
+    pub note_size: Option<u32>,
+    // a string of a string table, without the terminator, a longer one
+    // is not searched to its end
+    pub string_length: Option<u32>,
+}
+
+// The bound of `Limits` the file is past
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Limit {
+    Sections,
+    Symbols,
+    NoteSize,
+    StringLength,
+}
+
+impl<'a> Elf64<'a> {
+    // Applies the bounds to everything parsed from here on, the number of
+    // the sections is checked at once
+    pub fn with_limits(mut self, limits: Limits) -> Result<Self, Error> {
+        if let Some(sections) = limits.sections {
+            if self.section_number() > sections as usize {
+                return Err(Error::LimitExceeded(Limit::Sections));
+            }
+        }
+        self.limits = limits;
+        self.names = self.names.map(|names| names.limited(limits.string_length));
+        Ok(self)
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+}
@@ -0,0 +1,245 @@
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+use super::{Error, Elf64, Symbol, SymbolBinding, SymbolType, SectionType, Index};
+use super::version::{VERSYM_HIDDEN, VER_NDX_GLOBAL};
+
+#[derive(Clone)]
+pub struct LinkedObject<'a> {
+    pub soname: &'a [u8],
+    pub elf: Elf64<'a>,
+    pub needed: Vec<&'a [u8]>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Definition<'a> {
+    pub object: usize,
+    // index in `.dynsym` of the object
+    pub index: usize,
+    pub symbol: Symbol<'a>,
+    pub version: Option<&'a [u8]>,
+}
+
+#[derive(Clone, Default)]
+pub struct Linkage<'a> {
+    objects: Vec<LinkedObject<'a>>,
+    dynamic_weak: bool,
+}
+
+impl<'a> Linkage<'a> {
+    pub fn new() -> Self {
+        Linkage {
+            objects: Vec::new(),
+            dynamic_weak: false,
+        }
+    }
+
+    // The loader takes the first definition in the search order, weak or not.
+    // With `LD_DYNAMIC_WEAK` a strong definition later in the order wins over a weak one.
+    pub fn set_dynamic_weak(&mut self, dynamic_weak: bool) {
+        self.dynamic_weak = dynamic_weak;
+    }
+
+    // The object is registered by its `DT_SONAME`, `Error::NotPresent` if it has none.
+    pub fn register(&mut self, elf: Elf64<'a>) -> Result<usize, Error> {
+        let soname = elf.soname()?.ok_or(Error::NotPresent)?;
+        self.register_as(soname, elf)
+    }
+
+    // The main executable usually has no `DT_SONAME`, the caller names it.
+    // Registering the same name again replaces the object and keeps its index.
+    pub fn register_as(&mut self, soname: &'a [u8], elf: Elf64<'a>) -> Result<usize, Error> {
+        let needed = elf.needed()?.collect::<Result<Vec<_>, _>>()?;
+        let object = LinkedObject {
+            soname,
+            elf,
+            needed,
+        };
+        match self.find(soname) {
+            Some(index) => {
+                self.objects[index] = object;
+                Ok(index)
+            }
+            None => {
+                self.objects.push(object);
+                Ok(self.objects.len() - 1)
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn object(&self, index: usize) -> Option<&LinkedObject<'a>> {
+        self.objects.get(index)
+    }
+
+    pub fn find(&self, soname: &[u8]) -> Option<usize> {
+        self.objects.iter().position(|o| o.soname == soname)
+    }
+
+    // Edges of the needed-library graph, `(object, dependency)`, in the order of `DT_NEEDED`
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges = Vec::new();
+        for (index, object) in self.objects.iter().enumerate() {
+            for name in &object.needed {
+                if let Some(dependency) = self.find(name) {
+                    edges.push((index, dependency));
+                }
+            }
+        }
+        edges
+    }
+
+    // The order in which the loader searches objects for a symbol: the root first,
+    // then its dependencies breadth-first, each object only once.
+    pub fn search_order(&self, root: usize) -> Result<Vec<usize>, Error> {
+        self.breadth_first(root).map(|(order, _)| order)
+    }
+
+    // Names needed by the root, directly or not, that are not registered
+    pub fn missing(&self, root: usize) -> Result<Vec<&'a [u8]>, Error> {
+        self.breadth_first(root).map(|(_, missing)| missing)
+    }
+
+    fn breadth_first(&self, root: usize) -> Result<(Vec<usize>, Vec<&'a [u8]>), Error> {
+        if root >= self.objects.len() {
+            return Err(Error::IndexOutOfBounds);
+        }
+
+        let mut visited = vec![false; self.objects.len()];
+        let mut order = Vec::new();
+        let mut missing = Vec::new();
+        let mut queue = VecDeque::new();
+        visited[root] = true;
+        queue.push_back(root);
+        while let Some(index) = queue.pop_front() {
+            order.push(index);
+            for &name in &self.objects[index].needed {
+                match self.find(name) {
+                    Some(dependency) if !visited[dependency] => {
+                        visited[dependency] = true;
+                        queue.push_back(dependency);
+                    }
+                    Some(_) => (),
+                    None if !missing.contains(&name) => missing.push(name),
+                    None => (),
+                }
+            }
+        }
+        Ok((order, missing))
+    }
+
+    // Which object provides the symbol for the process that starts with the root.
+    // A versioned reference binds to the definition of that version or to an unversioned
+    // one, an unversioned reference binds to the default version.
+    pub fn resolve(
+        &self,
+        root: usize,
+        name: &[u8],
+        version: Option<&[u8]>,
+    ) -> Result<Option<Definition<'a>>, Error> {
+        let mut weak = None;
+        for object in self.search_order(root)? {
+            let definition = match self.lookup(object, name, version)? {
+                Some(definition) => definition,
+                None => continue,
+            };
+            if !self.dynamic_weak || definition.symbol.entry.info.binding != SymbolBinding::Weak {
+                return Ok(Some(definition));
+            }
+            if weak.is_none() {
+                weak = Some(definition);
+            }
+        }
+        Ok(weak)
+    }
+
+    // Resolves the symbol with the index in `.dynsym` of the object with the version
+    // it requires, in the scope of the root.
+    pub fn resolve_import(
+        &self,
+        root: usize,
+        object: usize,
+        index: usize,
+    ) -> Result<Option<Definition<'a>>, Error> {
+        let elf = &self.objects.get(object).ok_or(Error::IndexOutOfBounds)?.elf;
+        let symbols = elf
+            .symbol_source(SectionType::DynamicSymbolTable)?
+            .ok_or(Error::NotPresent)?;
+        if index >= symbols.number {
+            return Err(Error::IndexOutOfBounds);
+        }
+        let symbol = symbols.get(index)?;
+        let versions = elf.versions()?;
+        let version = match versions.versym(index)? {
+            Some(versym) => versions
+                .requirement(versym & !VERSYM_HIDDEN)?
+                .map(|requirement| requirement.name),
+            None => None,
+        };
+        self.resolve(root, symbol.name, version)
+    }
+
+    fn lookup(
+        &self,
+        object: usize,
+        name: &[u8],
+        version: Option<&[u8]>,
+    ) -> Result<Option<Definition<'a>>, Error> {
+        let elf = &self.objects[object].elf;
+        let symbols = match elf.symbol_source(SectionType::DynamicSymbolTable)? {
+            Some(symbols) => symbols,
+            None => return Ok(None),
+        };
+        let versions = elf.versions()?;
+        for index in 1..symbols.number {
+            let symbol = symbols.get(index)?;
+            if symbol.name != name {
+                continue;
+            }
+            let entry = &symbol.entry;
+            match entry.info.binding {
+                SymbolBinding::Global | SymbolBinding::Weak | SymbolBinding::OsSpecific(0) => (),
+                _ => continue,
+            }
+            match entry.info.ty {
+                SymbolType::Section | SymbolType::File => continue,
+                _ => (),
+            }
+            // hidden and internal visibility are not exported
+            if entry.section_index == Index::Undefined || matches!(entry.reserved & 0x3, 1 | 2) {
+                continue;
+            }
+            let (matches, defined_version) = match versions.versym(index)? {
+                None => (true, None),
+                Some(versym) => {
+                    let hidden = versym & VERSYM_HIDDEN != 0;
+                    let ndx = versym & !VERSYM_HIDDEN;
+                    if ndx == 0 {
+                        continue;
+                    }
+                    let defined_version = versions.definition(ndx)?;
+                    let matches = match version {
+                        Some(version) => ndx == VER_NDX_GLOBAL || defined_version == Some(version),
+                        None => !hidden,
+                    };
+                    (matches, defined_version.filter(|_| ndx != VER_NDX_GLOBAL))
+                }
+            };
+            if matches {
+                return Ok(Some(Definition {
+                    object,
+                    index,
+                    symbol,
+                    version: defined_version,
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,170 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use super::{Error, Address, Offset, Elf64, Type, ProgramType, ProgramFlags, SectionType, SectionFlags};
+
+// `SHT_GROUP`, every group section of a relocatable file is named `.group`
+const SHT_GROUP: u32 = 0x11;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Diagnostic {
+    // an executable file has no `PT_LOAD`, there is nothing to run
+    ExecutableWithoutLoad,
+    // `PT_INTERP` is only meaningful for dynamically linked files
+    InterpreterWithoutDynamic,
+    // `DT_NEEDED` in an executable without an interpreter, nobody loads the libraries
+    NeededInStaticFile,
+    // the entry point is not in a `PT_LOAD` with `PF_X`
+    EntryOutsideExecutableSegment { entry: Address },
+    // a `PT_LOAD` the kernel and the dynamic loader reject with pages of the size,
+    // its alignment is not a multiple of it, or the offset and the address are not
+    // congruent modulo it, the smallest of `Machine::page_sizes` it fails, e.g.
+    // a library for AArch64 linked with `-z max-page-size=4096` on a 16 KiB system
+    UnsupportedPageSize { program: usize, page_size: u64 },
+    DuplicateSectionName { first: usize, second: usize },
+    // a section with an address and no alignment, the linker always sets the alignment
+    AddressWithoutAlignment { section: usize },
+    // a `SHT_NULL` section with a size, an offset, an address or flags
+    NullSectionWithData { section: usize },
+    // e.g. executable symbol table or writable section that is not loaded
+    FlagsContradictType { section: usize },
+}
+
+impl<'a> Elf64<'a> {
+    // Checks for contradictions that do not stop parsing, but the loader
+    // would reject or misbehave on.
+    pub fn lint(&self) -> Result<Vec<Diagnostic>, Error> {
+        let mut diagnostics = Vec::new();
+        self.lint_programs(&mut diagnostics)?;
+        self.lint_sections(&mut diagnostics)?;
+        Ok(diagnostics)
+    }
+
+    fn lint_programs(&self, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        let ty = self.ty();
+        let mut has_load = false;
+        let mut has_interpreter = false;
+        let mut has_dynamic = false;
+        let mut entry_is_executable = false;
+        let entry = self.entry();
+        let page_sizes = self.machine().page_sizes();
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            match header.ty {
+                ProgramType::Load => {
+                    has_load = true;
+                    let distance = header.virtual_address.0.wrapping_sub(header.file_offset.0);
+                    let unsupported = page_sizes.iter().flat_map(|p| p.sizes()).find(|&&size| {
+                        header.address_alignment % size != 0 || distance % size != 0
+                    });
+                    if let Some(&page_size) = unsupported {
+                        diagnostics.push(Diagnostic::UnsupportedPageSize {
+                            program: index,
+                            page_size,
+                        });
+                    }
+                    let start = header.virtual_address;
+                    let end = start.saturating_add(header.memory_size);
+                    if header.flags.contains(ProgramFlags::EXECUTE) && (start..end).contains(&entry)
+                    {
+                        entry_is_executable = true;
+                    }
+                }
+                ProgramType::Interpreter => has_interpreter = true,
+                ProgramType::Dynamic => has_dynamic = true,
+                _ => (),
+            }
+        }
+
+        if ty == Type::Executable && !has_load {
+            diagnostics.push(Diagnostic::ExecutableWithoutLoad);
+        }
+        if has_interpreter && !has_dynamic {
+            diagnostics.push(Diagnostic::InterpreterWithoutDynamic);
+        }
+        if ty == Type::Executable && !has_interpreter && self.needed()?.next().is_some() {
+            diagnostics.push(Diagnostic::NeededInStaticFile);
+        }
+        // shared libraries usually have no entry point
+        let has_entry = match ty {
+            Type::Executable => true,
+            Type::SharedObject => entry != Address(0),
+            _ => false,
+        };
+        if has_entry && has_load && !entry_is_executable {
+            diagnostics.push(Diagnostic::EntryOutsideExecutableSegment { entry });
+        }
+        Ok(())
+    }
+
+    fn lint_sections(&self, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        let mut names = BTreeMap::new();
+        for section in 0..self.section_number() {
+            let header = self.section_table.pick(section)?;
+
+            let name = match &self.names {
+                Some(names) => names.pick(header.name as usize)?,
+                None => &[],
+            };
+            if !name.is_empty() && header.ty != SectionType::Unknown(SHT_GROUP) {
+                if let Some(&first) = names.get(name) {
+                    diagnostics.push(Diagnostic::DuplicateSectionName {
+                        first,
+                        second: section,
+                    });
+                } else {
+                    names.insert(name, section);
+                }
+            }
+
+            if header.address != Address(0) && header.address_alignment == 0 {
+                diagnostics.push(Diagnostic::AddressWithoutAlignment { section });
+            }
+
+            // `sh_size` of the section zero is the number of the sections if
+            // `e_shnum` is zero, `sh_link` and `sh_info` are not checked
+            let extended = section == 0 && self.header.section_header_number == 0;
+            if header.ty == SectionType::Null
+                && ((header.size != 0 && !extended)
+                    || header.offset != Offset(0)
+                    || header.address != Address(0)
+                    || !header.flags.is_empty())
+            {
+                diagnostics.push(Diagnostic::NullSectionWithData { section });
+            }
+
+            let loaded = header.flags.contains(SectionFlags::ALLOC);
+            let needs_load = SectionFlags::WRITE | SectionFlags::EXECINSTR | SectionFlags::TLS;
+            let is_data = matches!(
+                header.ty,
+                SectionType::SymbolTable
+                    | SectionType::DynamicSymbolTable
+                    | SectionType::StringTable
+                    | SectionType::Rela
+                    | SectionType::Rel
+                    | SectionType::Hash
+                    | SectionType::Dynamic
+                    | SectionType::Note
+            );
+            if (!loaded && header.flags.intersects(needs_load))
+                || (is_data && header.flags.contains(SectionFlags::EXECINSTR))
+            {
+                diagnostics.push(Diagnostic::FlagsContradictType { section });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Elf64, Diagnostic};
+
+    #[test]
+    fn extended_numbering() {
+        let raw = crate::tests::with_extended_numbering(0xff00, [0, 0]);
+        let diagnostics = Elf64::new(&raw).unwrap().lint().unwrap();
+        assert!(!diagnostics
+            .iter()
+            .any(|d| matches!(d, Diagnostic::NullSectionWithData { .. })));
+    }
+}
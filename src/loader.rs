@@ -0,0 +1,452 @@
+use super::{
+    Error, Address, SymbolIndex, Encoding, Machine, Type, Elf64, Symbol, SymbolBinding,
+    SectionType, SectionFlags, Index, RelaEntry, Table, RelocationKind, Formula, FormulaInputs,
+};
+use super::symbolication::SymbolSource;
+use super::common::{file_slice, to_usize};
+use super::tls::{SymbolAddress, THREAD_LOCAL};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoadSection<'a> {
+    pub index: usize,
+    pub name: &'a [u8],
+    pub size: u64,
+    pub address_alignment: u64,
+    pub flags: SectionFlags,
+    // `None` for sections without file content, the loader fills them with zeros
+    pub content: Option<&'a [u8]>,
+}
+
+// The caller owns the memory the object is loaded to.
+pub trait LoaderMemory {
+    // Reserves at least `section.size` bytes aligned to `section.address_alignment`
+    // and returns the address the section will run at.
+    fn place(&mut self, section: &LoadSection<'_>) -> Result<Address, Error>;
+
+    // the address returned by `place` for the section index, `None` if it is not placed
+    fn address(&self, index: usize) -> Option<Address>;
+
+    // the memory reserved for the section index
+    fn slice(&mut self, index: usize) -> Option<&mut [u8]>;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Unsigned(u32),
+    Signed(u32),
+    Any(u32),
+}
+
+fn write_field(
+    encoding: &Encoding,
+    slice: &mut [u8],
+    offset: usize,
+    field: Field,
+    value: u64,
+) -> Result<(), Error> {
+    let (bits, fits) = match field {
+        Field::Unsigned(bits) => (bits, bits == 64 || value >> bits == 0),
+        Field::Signed(bits) => {
+            let v = value as i64;
+            (
+                bits,
+                bits == 64 || (v >> (bits - 1) == 0 || v >> (bits - 1) == -1),
+            )
+        }
+        Field::Any(bits) => (bits, true),
+    };
+    if !fits {
+        return Err(Error::ArithmeticOverflow);
+    }
+    let bytes = (bits / 8) as usize;
+    let slice = slice
+        .get_mut(offset..offset.checked_add(bytes).ok_or(Error::ArithmeticOverflow)?)
+        .ok_or(Error::SliceTooShort)?;
+    match bytes {
+        1 => slice[0] = value as u8,
+        2 => write_int!(slice, encoding, u16, value as u16),
+        4 => write_int!(slice, encoding, u32, value as u32),
+        _ => write_int!(slice, encoding, u64, value),
+    }
+    Ok(())
+}
+
+fn patch_instruction<F>(
+    encoding: &Encoding,
+    slice: &mut [u8],
+    offset: usize,
+    f: F,
+) -> Result<(), Error>
+where
+    F: FnOnce(u32) -> u32,
+{
+    let slice = slice
+        .get_mut(offset..offset.checked_add(4).ok_or(Error::ArithmeticOverflow)?)
+        .ok_or(Error::SliceTooShort)?;
+    let instruction = read_int!(slice, encoding, u32);
+    write_int!(slice, encoding, u32, f(instruction));
+    Ok(())
+}
+
+// the operands of the relocation calculation, named as in the ABI documents
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Operands {
+    // the value of the symbol
+    pub s: u64,
+    // the addend
+    pub a: i64,
+    // the address of the place being relocated
+    pub p: u64,
+}
+
+// Applies the relocation of a relocatable file to the content of its target section,
+// placed at `section_base`, the offset of the relocation is relative to the section.
+// The value of a `STT_TLS` symbol is its offset in the block of the module,
+// `SymbolAddress::ThreadLocal`, for `R_X86_64_DTPOFF64` and the others like it.
+pub fn apply_relocation(
+    section: &mut [u8],
+    relocation: &RelaEntry,
+    symbol_value: Address,
+    section_base: Address,
+    machine: &Machine,
+    encoding: &Encoding,
+) -> Result<(), Error> {
+    let offset = to_usize(relocation.address.0)?;
+    relocate(
+        machine,
+        encoding,
+        relocation.relocation_type,
+        section,
+        offset,
+        Operands {
+            s: symbol_value.0,
+            a: relocation.addend,
+            p: section_base.wrapping_add(relocation.address.0).0,
+        },
+    )
+}
+
+// Applies the relocation at the offset of the slice.
+// Relocations that need a GOT or a PLT are not supported, except the relaxable
+// `R_X86_64_GOTPCRELX` loads, which become direct address computations.
+pub(crate) fn relocate(
+    machine: &Machine,
+    encoding: &Encoding,
+    ty: u32,
+    slice: &mut [u8],
+    offset: usize,
+    operands: Operands,
+) -> Result<(), Error> {
+    let Operands { s, a, p } = operands;
+    // without a PLT `L` is the symbol itself
+    let inputs = FormulaInputs {
+        s: Some(s),
+        a: Some(a),
+        p: Some(p),
+        l: Some(s),
+        ..FormulaInputs::default()
+    };
+    let value = RelocationKind::new(machine, ty)
+        .formula()
+        .ok_or(Error::UnsupportedRelocation(ty))
+        .and_then(|formula| formula.evaluate(&inputs));
+    match (machine, ty) {
+        (Machine::X86_64, 0) => Ok(()),
+        (Machine::X86_64, 1) => write_field(encoding, slice, offset, Field::Any(64), value?),
+        (Machine::X86_64, 2) | (Machine::X86_64, 4) => {
+            write_field(encoding, slice, offset, Field::Signed(32), value?)
+        }
+        (Machine::X86_64, 10) => write_field(encoding, slice, offset, Field::Unsigned(32), value?),
+        (Machine::X86_64, 11) => write_field(encoding, slice, offset, Field::Signed(32), value?),
+        (Machine::X86_64, 12) => write_field(encoding, slice, offset, Field::Unsigned(16), value?),
+        (Machine::X86_64, 13) => write_field(encoding, slice, offset, Field::Signed(16), value?),
+        (Machine::X86_64, 14) => write_field(encoding, slice, offset, Field::Unsigned(8), value?),
+        (Machine::X86_64, 15) => write_field(encoding, slice, offset, Field::Signed(8), value?),
+        // the offset in the block of the module
+        (Machine::X86_64, 17) => write_field(encoding, slice, offset, Field::Any(64), value?),
+        (Machine::X86_64, 21) => write_field(encoding, slice, offset, Field::Signed(32), value?),
+        (Machine::X86_64, 24) => write_field(encoding, slice, offset, Field::Any(64), value?),
+        (Machine::X86_64, 41) | (Machine::X86_64, 42) => {
+            // `mov foo@GOTPCREL(%rip), %reg` is `lea foo(%rip), %reg`
+            match offset.checked_sub(2).and_then(|o| slice.get_mut(o)) {
+                Some(opcode) if *opcode == 0x8b => *opcode = 0x8d,
+                _ => return Err(Error::UnsupportedRelocation(ty)),
+            }
+            let relaxed = Formula::PC_RELATIVE.evaluate(&inputs)?;
+            write_field(encoding, slice, offset, Field::Signed(32), relaxed)
+        }
+        (Machine::AArch64, 0) | (Machine::AArch64, 256) => Ok(()),
+        (Machine::AArch64, 257) => write_field(encoding, slice, offset, Field::Any(64), value?),
+        (Machine::AArch64, 258) => {
+            let value = value?;
+            // either signed or unsigned 32 bit value
+            if !(-(1 << 31)..(1 << 32)).contains(&(value as i64)) {
+                return Err(Error::ArithmeticOverflow);
+            }
+            write_field(encoding, slice, offset, Field::Any(32), value)
+        }
+        (Machine::AArch64, 260) => write_field(encoding, slice, offset, Field::Any(64), value?),
+        (Machine::AArch64, 1029) => write_field(encoding, slice, offset, Field::Any(64), value?),
+        (Machine::AArch64, 261) => write_field(encoding, slice, offset, Field::Signed(32), value?),
+        (Machine::AArch64, 262) => write_field(encoding, slice, offset, Field::Signed(16), value?),
+        (Machine::AArch64, 275) => {
+            let pages = (value? as i64) >> 12;
+            if !(-(1 << 20)..(1 << 20)).contains(&pages) {
+                return Err(Error::ArithmeticOverflow);
+            }
+            let pages = pages as u32;
+            patch_instruction(encoding, slice, offset, |i| {
+                (i & 0x9f00001f) | ((pages & 0x3) << 29) | (((pages >> 2) & 0x7ffff) << 5)
+            })
+        }
+        (Machine::AArch64, 277) | (Machine::AArch64, 278) => {
+            let imm = (value? & 0xfff) as u32;
+            patch_instruction(encoding, slice, offset, |i| (i & 0xffc003ff) | (imm << 10))
+        }
+        (Machine::AArch64, 282) | (Machine::AArch64, 283) => {
+            let delta = value? as i64;
+            if delta & 0x3 != 0 || !(-(1 << 27)..(1 << 27)).contains(&delta) {
+                return Err(Error::ArithmeticOverflow);
+            }
+            let imm = ((delta >> 2) as u32) & 0x03ffffff;
+            patch_instruction(encoding, slice, offset, |i| (i & 0xfc000000) | imm)
+        }
+        (Machine::AArch64, 284)
+        | (Machine::AArch64, 285)
+        | (Machine::AArch64, 286)
+        | (Machine::AArch64, 299) => {
+            let shift = match ty {
+                284 => 1,
+                285 => 2,
+                286 => 3,
+                _ => 4,
+            };
+            let imm = ((value? & 0xfff) >> shift) as u32;
+            patch_instruction(encoding, slice, offset, |i| (i & 0xffc003ff) | (imm << 10))
+        }
+        (Machine::X86_64, _) | (Machine::AArch64, _) => Err(Error::UnsupportedRelocation(ty)),
+        _ => Err(Error::UnsupportedMachine),
+    }
+}
+
+// the run-time address of the defined symbol, `None` if it is undefined
+fn symbol_address<M>(memory: &M, symbol: &Symbol<'_>) -> Result<Option<Address>, Error>
+where
+    M: LoaderMemory + ?Sized,
+{
+    if symbol.entry.section_index == Index::Undefined {
+        return Ok(None);
+    }
+    symbol
+        .entry
+        .resolve(|index| memory.address(index))
+        .map(Some)
+}
+
+pub struct Loaded<'a, 'm, M>
+where
+    M: LoaderMemory,
+{
+    symbols: Option<SymbolSource<'a>>,
+    memory: &'m M,
+}
+
+impl<'a, 'm, M> Loaded<'a, 'm, M>
+where
+    M: LoaderMemory,
+{
+    // the run-time address of the global symbol defined by the object
+    pub fn symbol(&self, name: &[u8]) -> Result<Option<Address>, Error> {
+        for symbol in self.symbols() {
+            let (symbol_name, address) = symbol?;
+            if symbol_name == name {
+                return Ok(Some(address));
+            }
+        }
+        Ok(None)
+    }
+
+    // global and weak symbols defined by the object with their run-time addresses
+    pub fn symbols(&self) -> impl Iterator<Item = Result<(&'a [u8], Address), Error>> + '_ {
+        let number = self.symbols.as_ref().map(|s| s.number).unwrap_or(0);
+        (1..number).filter_map(move |index| {
+            let symbol = match self.symbols.as_ref()?.get(index) {
+                Ok(symbol) => symbol,
+                Err(error) => return Some(Err(error)),
+            };
+            match symbol.entry.info.binding {
+                SymbolBinding::Global | SymbolBinding::Weak => (),
+                _ => return None,
+            }
+            match symbol_address(self.memory, &symbol) {
+                Ok(Some(address)) => Some(Ok((symbol.name, address))),
+                Ok(None) => None,
+                Err(error) => Some(Err(error)),
+            }
+        })
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Loads the relocatable object: places every `SHF_ALLOC` section into the memory,
+    // copies its content and applies the relocations. Undefined symbols are asked
+    // from `external`, undefined weak symbols it does not know are zero.
+    pub fn load<'m, M, F>(
+        &self,
+        memory: &'m mut M,
+        mut external: F,
+    ) -> Result<Loaded<'a, 'm, M>, Error>
+    where
+        M: LoaderMemory,
+        F: FnMut(&[u8]) -> Option<Address>,
+    {
+        if self.ty() != Type::Relocatable {
+            return Err(Error::UnexpectedFileType);
+        }
+        let machine = self.machine();
+        match machine {
+            Machine::X86_64 | Machine::AArch64 => (),
+            _ => return Err(Error::UnsupportedMachine),
+        }
+        let encoding = self.encoding();
+        let span = enter_span!("load", sections = self.section_number(); bytes, relocations);
+        let (mut bytes, mut relocations) = (0, 0);
+
+        let mut symtab = None;
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            if header.ty == SectionType::SymbolTable {
+                symtab = Some(index);
+            }
+            if !header.flags.contains(SectionFlags::ALLOC) {
+                continue;
+            }
+            let content = match header.ty {
+                SectionType::NoBits => None,
+                _ => Some(file_slice(self.raw, header.offset, header.size)?),
+            };
+            let name = match &self.names {
+                Some(names) => names.pick(header.name as usize)?,
+                None => &[],
+            };
+            memory.place(&LoadSection {
+                index,
+                name,
+                size: header.size,
+                address_alignment: header.address_alignment,
+                flags: header.flags,
+                content,
+            })?;
+            let slice = memory.slice(index).ok_or(Error::NotPresent)?;
+            let slice = slice
+                .get_mut(..to_usize(header.size)?)
+                .ok_or(Error::SliceTooShort)?;
+            match content {
+                Some(content) => slice.clone_from_slice(content),
+                None => slice.iter_mut().for_each(|b| *b = 0),
+            }
+            bytes += header.size;
+        }
+
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            let target = header.info as usize;
+            match header.ty {
+                SectionType::Rela => (),
+                // both supported machines use explicit addends
+                SectionType::Rel if memory.address(target).is_some() => {
+                    return Err(Error::UnexpectedSectionType)
+                }
+                _ => continue,
+            }
+            let target_address = match memory.address(target) {
+                Some(address) => address,
+                None => continue,
+            };
+            let symbols = match header.link {
+                Index::Regular(link) => self.symbol_source_at(link.0)?,
+                _ => return Err(Error::UnexpectedSectionType),
+            };
+            let slice = file_slice(self.raw, header.offset, header.size)?;
+            let table = Table::<RelaEntry>::with_entry_size(
+                slice,
+                encoding.clone(),
+                header.number_of_entries,
+            );
+            for entry_index in 0..table.len() {
+                let entry = table.pick(entry_index)?;
+                let s = if entry.symbol_index == SymbolIndex(0) {
+                    Address(0)
+                } else {
+                    let symbol = symbols.get(entry.symbol_index.0)?;
+                    let thread_local = symbol.entry.info.ty == THREAD_LOCAL;
+                    match symbol_address(memory, &symbol)? {
+                        // not where the section is placed, the offset in the block
+                        Some(_) if thread_local => match self.symbol_address(&symbol.entry)? {
+                            Some(SymbolAddress::ThreadLocal(offset)) => Address(offset),
+                            _ => return Err(Error::UnexpectedSectionType),
+                        },
+                        Some(address) => address,
+                        None => match external(symbol.name) {
+                            Some(address) => address,
+                            None if symbol.entry.info.binding == SymbolBinding::Weak => Address(0),
+                            None => return Err(Error::UnresolvedSymbol),
+                        },
+                    }
+                };
+                let p = target_address.wrapping_add(entry.address.0);
+                let slice = memory.slice(target).ok_or(Error::NotPresent)?;
+                relocate(
+                    &machine,
+                    &encoding,
+                    entry.relocation_type,
+                    slice,
+                    to_usize(entry.address.0)?,
+                    Operands {
+                        s: s.0,
+                        a: entry.addend,
+                        p: p.0,
+                    },
+                )?;
+                relocations += 1;
+            }
+        }
+        record_span!(span, bytes, bytes);
+        record_span!(span, relocations, relocations);
+
+        Ok(Loaded {
+            symbols: symtab.map(|i| self.symbol_source_at(i)).transpose()?,
+            memory,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, Address, SymbolIndex, Encoding, Machine, RelaEntry};
+
+    use super::apply_relocation;
+
+    #[test]
+    fn relocation_at_the_end_of_the_address_space() {
+        let mut section = [0; 0x10];
+        // `R_X86_64_64` and `R_AARCH64_CALL26`, a field and an instruction
+        for (machine, relocation_type) in [(Machine::X86_64, 1), (Machine::AArch64, 283)] {
+            let relocation = RelaEntry {
+                address: Address(u64::MAX - 1),
+                symbol_index: SymbolIndex(1),
+                relocation_type,
+                addend: 0,
+            };
+            let result = apply_relocation(
+                &mut section,
+                &relocation,
+                Address(u64::MAX - 1),
+                Address(0),
+                &machine,
+                &Encoding::Little,
+            );
+            assert_eq!(result, Err(Error::ArithmeticOverflow));
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use core::convert::TryFrom;
+
+use super::{Error, Address, Elf64, ProgramType, ProgramFlags, Hasher};
+use super::common::file_slice;
+
+// The digest of the file part of a `PT_LOAD`, with what the loader needs
+// to put it at the same place with the same permissions
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SegmentDigest<D> {
+    // of the program header table
+    pub index: usize,
+    pub address: Address,
+    pub file_size: u64,
+    pub memory_size: u64,
+    pub flags: ProgramFlags,
+    pub digest: D,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Verification {
+    Valid,
+    // the entry of the manifest at the index has another digest
+    DigestMismatch(usize),
+    // the entry of the manifest at the index has another address, size or flags
+    LayoutMismatch(usize),
+    // the file has another number of loadable segments
+    CountMismatch { expected: usize, found: usize },
+}
+
+// The digests of `PT_LOAD` in the order of the program header table,
+// each one by a clone of the hasher, so a keyed hasher works as well
+#[derive(Clone)]
+pub struct SegmentDigests<'a, H> {
+    elf: Elf64<'a>,
+    hasher: H,
+    index: usize,
+}
+
+impl<'a, H> Iterator for SegmentDigests<'a, H>
+where
+    H: Hasher + Clone,
+{
+    type Item = Result<SegmentDigest<H::Output>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.elf.program_number() {
+            let index = self.index;
+            self.index += 1;
+            let header = match self.elf.program_table.pick(index) {
+                Ok(header) => header,
+                Err(error) => return Some(Err(error)),
+            };
+            if header.ty != ProgramType::Load {
+                continue;
+            }
+            let slice = match file_slice(self.elf.raw, header.file_offset, header.file_size) {
+                Ok(slice) => slice,
+                Err(error) => return Some(Err(error)),
+            };
+            let mut hasher = self.hasher.clone();
+            hasher.update(slice);
+            return Some(Ok(SegmentDigest {
+                index,
+                address: header.virtual_address,
+                file_size: header.file_size,
+                memory_size: header.memory_size,
+                flags: header.flags,
+                digest: hasher.finish(),
+            }));
+        }
+        None
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The manifest, collect it or write it somewhere, nothing is allocated
+    pub fn segment_digests<H>(&self, hasher: H) -> SegmentDigests<'a, H>
+    where
+        H: Hasher + Clone,
+    {
+        SegmentDigests {
+            elf: self.clone(),
+            hasher,
+            index: 0,
+        }
+    }
+
+    // Checks the file against the manifest, the first entry that differs is reported
+    pub fn verify_segments<H, D>(
+        &self,
+        manifest: &[SegmentDigest<D>],
+        hasher: H,
+    ) -> Result<Verification, Error>
+    where
+        H: Hasher + Clone,
+        D: AsRef<[u8]>,
+    {
+        let mut found = 0;
+        for digest in self.segment_digests(hasher) {
+            let digest = digest?;
+            if let Some(expected) = manifest.get(found) {
+                if expected.index != digest.index
+                    || expected.address != digest.address
+                    || expected.file_size != digest.file_size
+                    || expected.memory_size != digest.memory_size
+                    || expected.flags != digest.flags
+                {
+                    return Ok(Verification::LayoutMismatch(found));
+                }
+                if expected.digest.as_ref() != digest.digest.as_ref() {
+                    return Ok(Verification::DigestMismatch(found));
+                }
+            }
+            found += 1;
+        }
+        if found != manifest.len() {
+            return Ok(Verification::CountMismatch {
+                expected: manifest.len(),
+                found,
+            });
+        }
+        Ok(Verification::Valid)
+    }
+}
+
+// Checks the image in memory, `image` is mapped at `base`, against the manifest,
+// only the part of each segment that came from the file is compared, the loader
+// must check it before the relocations are applied and before the code runs
+pub fn verify_image<H, D>(
+    image: &[u8],
+    base: Address,
+    manifest: &[SegmentDigest<D>],
+    hasher: H,
+) -> Verification
+where
+    H: Hasher + Clone,
+    D: AsRef<[u8]>,
+{
+    for (index, expected) in manifest.iter().enumerate() {
+        let slice = expected.address.checked_sub(base).and_then(|start| {
+            let start = usize::try_from(start).ok()?;
+            let size = usize::try_from(expected.file_size).ok()?;
+            image.get(start..start.checked_add(size)?)
+        });
+        let slice = match slice {
+            Some(slice) => slice,
+            None => return Verification::LayoutMismatch(index),
+        };
+        let mut hasher = hasher.clone();
+        hasher.update(slice);
+        if hasher.finish().as_ref() != expected.digest.as_ref() {
+            return Verification::DigestMismatch(index);
+        }
+    }
+    Verification::Valid
+}
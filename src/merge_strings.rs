@@ -0,0 +1,159 @@
+use super::{Error, Elf64, Encoding, SectionIndex, SectionType, SectionFlags};
+use super::common::file_slice;
+use super::nul::find_nul;
+
+// A string of a `SHF_MERGE | SHF_STRINGS` section, e.g. `.rodata.str1.1`, or
+// `.rodata.str4.4` of `U"..."` literals, `bytes` is without the terminator
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MergeString<'a> {
+    // of the first character in the section, a multiple of the width
+    pub offset: usize,
+    pub bytes: &'a [u8],
+    width: usize,
+    encoding: Encoding,
+}
+
+impl<'a> MergeString<'a> {
+    // the size of a character, `sh_entsize`
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    // the number of the characters, without the terminator
+    pub fn len(&self) -> usize {
+        self.bytes.len() / self.width
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    // The characters in the encoding of the file, UTF-16 or UTF-32 code units
+    // for the wide strings, they are not decoded further
+    pub fn characters(&self) -> impl Iterator<Item = u64> + 'a {
+        let (width, encoding) = (self.width, self.encoding.clone());
+        self.bytes
+            .chunks_exact(width)
+            .map(move |character| character_at(character, width, &encoding))
+    }
+}
+
+fn character_at(slice: &[u8], width: usize, encoding: &Encoding) -> u64 {
+    match width {
+        1 => u64::from(slice[0]),
+        2 => u64::from(read_int!(slice, encoding, u16)),
+        4 => u64::from(read_int!(slice, encoding, u32)),
+        _ => read_int!(slice, encoding, u64),
+    }
+}
+
+// The strings of a `SHF_MERGE | SHF_STRINGS` section in order, one after the
+// terminator of the other. A string is a run of characters of `sh_entsize` bytes
+// up to a character of zeros, so a zero byte of a wide character ends nothing.
+// The iteration ends with `Error::UnterminatedString` at an unterminated tail.
+// The linker is free to merge the strings that are equal, or one that is the
+// tail of the other, these are the strings before it.
+#[derive(Clone)]
+pub struct MergeStrings<'a> {
+    slice: &'a [u8],
+    width: usize,
+    encoding: Encoding,
+    position: usize,
+}
+
+impl<'a> MergeStrings<'a> {
+    // `sh_entsize` zero is one, the way the assemblers treat it, another width
+    // but 1, 2, 4 and 8 is `Error::UnsupportedCharacterSize`
+    pub fn new(slice: &'a [u8], entry_size: u64, encoding: Encoding) -> Result<Self, Error> {
+        let width = match entry_size {
+            0 | 1 => 1,
+            2 | 4 | 8 => entry_size as usize,
+            _ => return Err(Error::UnsupportedCharacterSize(entry_size)),
+        };
+        Ok(MergeStrings {
+            slice,
+            width,
+            encoding,
+            position: 0,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn as_raw(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    // The string at the offset, e.g. the one a relocation against the section
+    // refers to, it may be the tail of a longer string.
+    // `Error::IndexOutOfBounds` if the offset is not at a character.
+    pub fn pick(&self, offset: usize) -> Result<MergeString<'a>, Error> {
+        if !offset.is_multiple_of(self.width) {
+            return Err(Error::IndexOutOfBounds);
+        }
+        let tail = self.slice.get(offset..).ok_or(Error::SliceTooShort)?;
+        let length = if self.width == 1 {
+            find_nul(tail)
+        } else {
+            tail.chunks_exact(self.width)
+                .position(|character| character.iter().all(|b| *b == 0))
+                .map(|position| position * self.width)
+        }
+        .ok_or(Error::UnterminatedString)?;
+        Ok(MergeString {
+            offset,
+            bytes: &tail[..length],
+            width: self.width,
+            encoding: self.encoding.clone(),
+        })
+    }
+}
+
+impl<'a> Iterator for MergeStrings<'a> {
+    type Item = Result<MergeString<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.slice.len() {
+            return None;
+        }
+        match self.pick(self.position) {
+            Ok(string) => {
+                self.position += string.bytes.len() + self.width;
+                Some(Ok(string))
+            }
+            Err(error) => {
+                self.position = self.slice.len();
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The strings of the section, `None` if the section is not `SHF_MERGE | SHF_STRINGS`
+    // or the index is out of range. A compressed section is `Error::UnexpectedSectionType`,
+    // its strings are those of `MergeStrings::new` over the content `decompress` gives.
+    pub fn merge_strings(&self, index: SectionIndex) -> Result<Option<MergeStrings<'a>>, Error> {
+        let (header, name) = match self.section_info(index)? {
+            Some(info) => (info.header, info.name),
+            None => return Ok(None),
+        };
+        if !header
+            .flags
+            .contains(SectionFlags::MERGE | SectionFlags::STRINGS)
+        {
+            return Ok(None);
+        }
+        // `.zdebug_str` of the old style of GNU is compressed too
+        if header.flags.contains(SectionFlags::COMPRESSED) || name.starts_with(b".zdebug") {
+            return Err(Error::UnexpectedSectionType);
+        }
+        let slice = match header.ty {
+            SectionType::NoBits => &[],
+            _ => file_slice(self.raw, header.offset, header.size)?,
+        };
+        MergeStrings::new(slice, header.number_of_entries, self.encoding()).map(Some)
+    }
+}
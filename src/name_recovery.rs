@@ -0,0 +1,54 @@
+use super::{Error, Elf64, SectionType, StringTable};
+use super::common::file_slice;
+
+impl<'a> Elf64<'a> {
+    // If `e_shstrndx` is `SHN_UNDEF`, out of range or not a string table, looks for
+    // a string table the names of the sections plausibly point into: at the start
+    // of a string that starts with a dot, for more than half of the named sections.
+    // A heuristic for damaged files, the index of the table it uses.
+    pub fn recover_section_names(&mut self) -> Result<Option<usize>, Error> {
+        if self.names.is_some() {
+            return Ok(None);
+        }
+
+        let mut best = None;
+        let mut best_score = 0;
+        let mut named = 0;
+        for index in 0..self.section_number() {
+            if self.section_table.pick(index)?.name != 0 {
+                named += 1;
+            }
+        }
+        for candidate in 0..self.section_number() {
+            let header = self.section_table.pick(candidate)?;
+            if header.ty != SectionType::StringTable {
+                continue;
+            }
+            let slice = match file_slice(self.raw, header.offset, header.size) {
+                Ok(slice) => slice,
+                Err(_) => continue,
+            };
+            let mut score = 0;
+            for index in 0..self.section_number() {
+                let name = self.section_table.pick(index)?.name as usize;
+                let starts_string = name == 0 || slice.get(name - 1) == Some(&0);
+                if name != 0 && starts_string && slice.get(name) == Some(&b'.') {
+                    score += 1;
+                }
+            }
+            if score > best_score {
+                best = Some((candidate, slice));
+                best_score = score;
+            }
+        }
+
+        match best {
+            Some((index, slice)) if best_score * 2 > named => {
+                log_debug!("section names: recovered from section {}", index);
+                self.names = Some(StringTable::new(slice).limited(self.limits.string_length));
+                Ok(Some(index))
+            }
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use alloc::vec::Vec;
+
+use super::{Error, Elf64, SectionType};
+use super::version::{VERSYM_HIDDEN, VER_NDX_GLOBAL};
+
+// A `DT_NEEDED` library and what the file imports from it by the versions
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NeededUsage<'a> {
+    pub library: &'a [u8],
+    // the library has an entry in `.gnu.version_r`
+    pub versioned: bool,
+    // the undefined symbols of `.dynsym` with a version of the library
+    pub symbols: usize,
+}
+
+impl<'a> NeededUsage<'a> {
+    // no symbol is imported by a version of the library, the file is over-linked,
+    // or the library does not version its symbols, only the library can tell
+    pub fn is_unused(&self) -> bool {
+        self.symbols == 0
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Every `DT_NEEDED` in the order of the dynamic section with the imports
+    // `.gnu.version_r` binds to it
+    pub fn needed_usage(&self) -> Result<Vec<NeededUsage<'a>>, Error> {
+        let versions = self.versions()?;
+        let mut usage = Vec::new();
+        for library in self.needed()? {
+            let library = library?;
+            let versioned = versions
+                .find_requirement(|_, requirement| requirement.file == library)?
+                .is_some();
+            usage.push(NeededUsage {
+                library,
+                versioned,
+                symbols: 0,
+            });
+        }
+        let symbols = match self.symbol_source(SectionType::DynamicSymbolTable)? {
+            Some(symbols) => symbols,
+            None => return Ok(usage),
+        };
+        for index in 1..symbols.number {
+            if symbols.get(index)?.entry.is_defined() {
+                continue;
+            }
+            let ndx = match versions.versym(index)? {
+                Some(versym) => versym & !VERSYM_HIDDEN,
+                None => break,
+            };
+            // local and global are not of a library
+            if ndx <= VER_NDX_GLOBAL {
+                continue;
+            }
+            if let Some(requirement) = versions.requirement(ndx)? {
+                usage
+                    .iter_mut()
+                    .filter(|usage| usage.library == requirement.file)
+                    .for_each(|usage| usage.symbols += 1);
+            }
+        }
+        Ok(usage)
+    }
+}
@@ -0,0 +1,27 @@
+use core::convert::TryInto;
+
+const WORD: usize = core::mem::size_of::<u64>();
+const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+// The position of the first zero byte, it checks a word at a time,
+// string tables are mostly long runs of non zero bytes.
+pub(crate) fn find_nul(slice: &[u8]) -> Option<usize> {
+    let mut chunks = slice.chunks_exact(WORD);
+    let mut offset = 0;
+    for chunk in &mut chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        // has a high bit set in each byte that was zero, and maybe above it,
+        // but the lowest set bit is exact
+        let zeros = word.wrapping_sub(LOW_BITS) & !word & HIGH_BITS;
+        if zeros != 0 {
+            return Some(offset + (zeros.trailing_zeros() / 8) as usize);
+        }
+        offset += WORD;
+    }
+    chunks
+        .remainder()
+        .iter()
+        .position(|&b| b == 0)
+        .map(|position| offset + position)
+}
@@ -0,0 +1,142 @@
+use object::{
+    read::elf::ElfFile64, Architecture, Endianness, ObjectKind, SectionKind, SymbolKind,
+    SymbolScope,
+};
+
+use super::{
+    Encoding, Machine, Type, Elf64, SectionHeader, SectionType, SectionFlags, SymbolInfo,
+    SymbolBinding, SymbolType, SymbolEntry,
+};
+
+// `SHT_GROUP`, `SHT_SYMTAB_SHNDX`, `SHT_RELR` and `SHT_CREL`
+const METADATA_TYPES: [u32; 4] = [0x11, 0x12, 0x13, 0x40000014];
+
+impl From<Encoding> for Endianness {
+    fn from(v: Encoding) -> Self {
+        match v {
+            Encoding::Little => Endianness::Little,
+            Encoding::Big => Endianness::Big,
+        }
+    }
+}
+
+// the file is 64 bit, so it is the 64 bit flavor of the architecture
+impl From<Machine> for Architecture {
+    fn from(v: Machine) -> Self {
+        match v {
+            Machine::Sparc => Architecture::Sparc64,
+            Machine::X86 => Architecture::I386,
+            Machine::Mips => Architecture::Mips64,
+            Machine::PowerPC | Machine::PowerPC64 => Architecture::PowerPc64,
+            Machine::Arm => Architecture::Arm,
+            Machine::SuperH => Architecture::SuperH,
+            Machine::Ia64 => Architecture::Ia64,
+            Machine::X86_64 => Architecture::X86_64,
+            Machine::AArch64 => Architecture::Aarch64,
+            Machine::RiscV => Architecture::Riscv64,
+            Machine::Bpf => Architecture::Bpf,
+            Machine::None | Machine::Unknown(_) => Architecture::Unknown,
+        }
+    }
+}
+
+impl From<Type> for ObjectKind {
+    fn from(v: Type) -> Self {
+        match v {
+            Type::Relocatable => ObjectKind::Relocatable,
+            Type::Executable => ObjectKind::Executable,
+            Type::SharedObject => ObjectKind::Dynamic,
+            Type::Core => ObjectKind::Core,
+            _ => ObjectKind::Unknown,
+        }
+    }
+}
+
+// the same classification `object` does for its own sections
+impl From<&SectionHeader> for SectionKind {
+    fn from(v: &SectionHeader) -> Self {
+        let flags = &v.flags;
+        match &v.ty {
+            SectionType::ProgramBits => {
+                if !flags.contains(SectionFlags::ALLOC) {
+                    if flags.contains(SectionFlags::STRINGS) {
+                        SectionKind::OtherString
+                    } else {
+                        SectionKind::Other
+                    }
+                } else if flags.contains(SectionFlags::EXECINSTR) {
+                    SectionKind::Text
+                } else if flags.contains(SectionFlags::TLS) {
+                    SectionKind::Tls
+                } else if flags.contains(SectionFlags::WRITE) {
+                    SectionKind::Data
+                } else if flags.contains(SectionFlags::STRINGS) {
+                    SectionKind::ReadOnlyString
+                } else {
+                    SectionKind::ReadOnlyData
+                }
+            }
+            SectionType::NoBits if flags.contains(SectionFlags::TLS) => {
+                SectionKind::UninitializedTls
+            }
+            SectionType::NoBits => SectionKind::UninitializedData,
+            SectionType::Note => SectionKind::Note,
+            SectionType::Null
+            | SectionType::SymbolTable
+            | SectionType::StringTable
+            | SectionType::Rela
+            | SectionType::Hash
+            | SectionType::Dynamic
+            | SectionType::Rel
+            | SectionType::DynamicSymbolTable => SectionKind::Metadata,
+            SectionType::OsSpecific(code) | SectionType::Unknown(code)
+                if METADATA_TYPES.contains(code) =>
+            {
+                SectionKind::Metadata
+            }
+            _ => SectionKind::Unknown,
+        }
+    }
+}
+
+impl From<SymbolInfo> for SymbolKind {
+    fn from(v: SymbolInfo) -> Self {
+        match v.ty {
+            SymbolType::Object => SymbolKind::Data,
+            SymbolType::Function => SymbolKind::Text,
+            SymbolType::Section => SymbolKind::Section,
+            SymbolType::File => SymbolKind::File,
+            // `STT_COMMON`
+            SymbolType::Unknown(5) => SymbolKind::Data,
+            // `STT_TLS`
+            SymbolType::Unknown(6) => SymbolKind::Tls,
+            // `STT_GNU_IFUNC`
+            SymbolType::OsSpecific(0) => SymbolKind::Text,
+            _ => SymbolKind::Unknown,
+        }
+    }
+}
+
+impl From<&SymbolEntry> for SymbolScope {
+    fn from(v: &SymbolEntry) -> Self {
+        if !v.is_defined() {
+            SymbolScope::Unknown
+        } else if v.info.binding == SymbolBinding::Local {
+            SymbolScope::Compilation
+        } else if v.reserved & 0x3 == 1 || v.reserved & 0x3 == 2 {
+            // internal or hidden
+            SymbolScope::Linkage
+        } else {
+            SymbolScope::Dynamic
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The same bytes parsed by `object`, for code written against
+    // the `object::read` traits, that cannot be implemented outside of it.
+    // It needs the whole file, parsing fragments fails as if the file was empty.
+    pub fn object_file(&self) -> object::Result<ElfFile64<'a, Endianness>> {
+        ElfFile64::parse(self.raw.contiguous().unwrap_or(&[]))
+    }
+}
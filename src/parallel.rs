@@ -0,0 +1,72 @@
+use alloc::vec::Vec;
+
+use rayon::iter::{self, Either};
+use rayon::prelude::*;
+
+use super::{Error, SectionIndex, Elf64, SectionType, SectionData, Symbol, Relocation};
+
+// `SHT_RELR`
+const SHT_RELR: u32 = 0x13;
+
+impl<'a> Elf64<'a> {
+    // The symbols of the first section of the given type, `SectionType::SymbolTable`
+    // or `SectionType::DynamicSymbolTable`, decoded by any thread, in the order
+    // of the table if collected
+    pub fn par_symbols(
+        &self,
+        ty: SectionType,
+    ) -> Result<Option<impl IndexedParallelIterator<Item = Result<Symbol<'a>, Error>> + 'a>, Error>
+    {
+        Ok(self.symbol_source(ty)?.map(|source| {
+            (0..source.number)
+                .into_par_iter()
+                .map(move |index| source.get(index))
+        }))
+    }
+
+    // The same entries `Elf64::relocations` yields, decoded by any thread,
+    // in the same order if collected. `SHT_RELR` is a chain, each of its sections
+    // is decoded by a single thread.
+    pub fn par_relocations(
+        &self,
+    ) -> Result<impl ParallelIterator<Item = Result<(usize, Relocation), Error>> + 'a, Error> {
+        let mut sections = Vec::new();
+        for index in 0..self.section_number() {
+            match self.section_table.pick(index)?.ty {
+                SectionType::Rel | SectionType::Rela | SectionType::Unknown(SHT_RELR) => {
+                    sections.push(index)
+                }
+                _ => (),
+            }
+        }
+        let elf = self.clone();
+        Ok(sections.into_par_iter().flat_map(move |index| {
+            let data = match elf.section(SectionIndex(index)) {
+                Ok(section) => section.map(|s| s.data),
+                Err(error) => return Either::Left(Either::Left(iter::once(Err(error)))),
+            };
+            match data {
+                Some(SectionData::Rel { table, .. }) => Either::Right(Either::Left(
+                    table
+                        .par_entries()
+                        .map(move |entry| entry.map(|entry| (index, Relocation::Rel(entry)))),
+                )),
+                Some(SectionData::Rela { table, .. }) => {
+                    Either::Right(Either::Right(table.par_entries().map(move |entry| {
+                        entry.map(|entry| (index, Relocation::Rela(entry)))
+                    })))
+                }
+                _ => {
+                    let relative = elf
+                        .relocations_from(index)
+                        .take_while(|relocation| match relocation {
+                            Ok((section, _)) => *section == index,
+                            Err(_) => false,
+                        })
+                        .collect::<Vec<_>>();
+                    Either::Left(Either::Right(relative.into_par_iter()))
+                }
+            }
+        }))
+    }
+}
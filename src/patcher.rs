@@ -0,0 +1,1939 @@
+use core::{cmp::Ordering, convert::TryFrom, ops::RangeInclusive};
+
+use alloc::{vec, vec::Vec};
+
+use super::{
+    Error, SectionIndex, SymbolIndex, Address, Offset, Encoding, Index, Elf64, Elf64Mut, Entry,
+    Encode, SectionType, SectionFlags, SectionHeader, SymbolEntry, SymbolBinding, RelEntry,
+    RelaEntry, StringTableBuilder, Type, Machine, ProgramHeader, ProgramType, ProgramFlags, Header,
+    Hasher, SectionData, WordTable, encode_entries,
+};
+use super::common::to_usize;
+use super::rel_rela::next_relative;
+use super::header::PN_XNUM;
+use super::symbols::SHT_SYMTAB_SHNDX;
+use super::version::SHT_GNU_VERSYM;
+
+// the largest `p_align` `add_segment` follows, 2 MiB, the huge page of x86-64
+// and the old `-z max-page-size` of it, the new segment is padded up to the alignment
+const MAX_SEGMENT_ALIGNMENT: u64 = 0x20_0000;
+
+// the first reserved section index, `e_shnum` and `e_shstrndx` from it on are
+// in the section zero
+const SHN_LORESERVE: usize = 0xff00;
+
+// the sections that refer the symbols by the index
+const SHT_RELA: u32 = 0x4;
+const SHT_REL: u32 = 0x9;
+const SHT_GROUP: u32 = 0x11;
+// the addresses of the relative relocations of `-z pack-relative-relocs`
+const SHT_RELR: u32 = 0x13;
+
+// `p_align` or `sh_addralign` from the file, zero and one are no alignment,
+// one that is not a power of two, or is over 2 MiB, is `Error::BadAlignment`
+fn checked_alignment(alignment: u64) -> Result<u64, Error> {
+    if alignment > MAX_SEGMENT_ALIGNMENT || (alignment > 1 && !alignment.is_power_of_two()) {
+        return Err(Error::BadAlignment(alignment));
+    }
+    Ok(alignment.max(1))
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConversionNote {
+    Rebased { delta: i64 },
+    PieFlagSet,
+    PieFlagCleared,
+    // the dynamic section has no `DT_FLAGS_1` and no spare slot to add it
+    NoRoomForFlags,
+    NoDynamicSection,
+    // the result can be loaded only as a library
+    NoInterpreter,
+    // the code is not patched and is likely to use absolute addresses
+    PositionDependentCode,
+}
+
+// a symbol inserted at the position, local or not, or the symbol removed
+#[derive(Clone, Copy)]
+enum SymbolEdit {
+    Insert(u32, bool),
+    Remove(u32),
+}
+
+impl SymbolEdit {
+    // the new index of the symbol, `None` for the removed one
+    fn map(self, index: u32) -> Option<u32> {
+        match self {
+            SymbolEdit::Insert(at, _) if index >= at => Some(index + 1),
+            SymbolEdit::Insert(..) => Some(index),
+            SymbolEdit::Remove(at) => match index.cmp(&at) {
+                Ordering::Less => Some(index),
+                Ordering::Equal => None,
+                Ordering::Greater => Some(index - 1),
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Patcher {
+    raw: Vec<u8>,
+    encoding: Encoding,
+}
+
+impl Patcher {
+    pub fn new(raw: Vec<u8>) -> Result<Self, Error> {
+        let encoding = Elf64::new(&raw)?.encoding();
+        Ok(Patcher { raw, encoding })
+    }
+
+    pub fn elf(&self) -> Result<Elf64<'_>, Error> {
+        Elf64::new(&self.raw)
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.raw
+    }
+
+    // for the changes in place
+    pub fn elf_mut(&mut self) -> Result<Elf64Mut<'_>, Error> {
+        Elf64Mut::new(&mut self.raw)
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.raw
+    }
+
+    // the name, the offset and the size of the `header` are ignored, the size is taken
+    // from the `header` only for `SectionType::NoBits`
+    pub fn add_section(
+        &mut self,
+        name: &[u8],
+        header: &SectionHeader,
+        content: &[u8],
+    ) -> Result<SectionIndex, Error> {
+        let names = match self.elf()?.section_names() {
+            Index::Regular(i) => i.0,
+            _ => return Err(Error::UnexpectedSectionType),
+        };
+        // before the name is added
+        checked_alignment(header.address_alignment)?;
+        let name = self.intern(names, name)?;
+
+        let (offset, size) = if header.ty == SectionType::NoBits {
+            (self.raw.len(), header.size)
+        } else {
+            let offset = self.append(content, header.address_alignment)?;
+            (offset, content.len() as u64)
+        };
+        let header = SectionHeader {
+            name,
+            offset: Offset(offset as u64),
+            size,
+            ..header.clone()
+        };
+        let mut encoded = [0; SectionHeader::SIZE];
+        header.encode(&mut encoded, self.encoding.clone())?;
+
+        let (table_offset, number) = {
+            let elf = self.elf()?;
+            let offset = elf.header.section_headers_offset.0 as usize;
+            (offset, elf.section_number())
+        };
+        let table_end = table_offset + number * SectionHeader::SIZE;
+        if self.raw.len() < table_end {
+            return Err(Error::SliceTooShort);
+        }
+        if self.raw.len() == table_end {
+            self.raw.extend_from_slice(&encoded);
+        } else {
+            let mut table = self.raw[table_offset..table_end].to_vec();
+            table.extend_from_slice(&encoded);
+            self.raw[table_offset..table_end]
+                .iter_mut()
+                .for_each(|b| *b = 0);
+            let table_offset = self.append(&table, 8)?;
+            self.write_u64(0x28, table_offset as u64);
+        }
+        self.write_section_number(number + 1, names)?;
+
+        Ok(SectionIndex(number))
+    }
+
+    // `e_shnum` and `e_shstrndx`, or zero and `SHN_XINDEX` with the values in `sh_size`
+    // and `sh_link` of the section zero, the way `Elf64::new` reads them
+    fn write_section_number(&mut self, number: usize, names: usize) -> Result<(), Error> {
+        let zero = self.section_header_offset(0)?;
+        if number >= SHN_LORESERVE {
+            self.write_u64(zero + 0x20, number as u64);
+            self.write_u16(0x3c, 0);
+        } else {
+            self.write_u16(0x3c, number as u16);
+        }
+        if names >= SHN_LORESERVE {
+            let names = u32::try_from(names).map_err(|_| Error::IndexOutOfBounds)?;
+            self.write_u32(zero + 0x28, names);
+            self.write_u16(0x3e, Index::Extended.into());
+        } else {
+            self.write_u16(0x3e, names as u16);
+        }
+        Ok(())
+    }
+
+    // rebuilds the section names table, so every section name is updated
+    pub fn rename_section(&mut self, index: SectionIndex, name: &[u8]) -> Result<(), Error> {
+        let index = index.0;
+        let (names_index, table, ids) = {
+            let elf = self.elf()?;
+            let names_index = match elf.section_names() {
+                Index::Regular(i) => i.0,
+                _ => return Err(Error::UnexpectedSectionType),
+            };
+            let names = elf.names.clone().ok_or(Error::UnexpectedSectionType)?;
+            if index >= elf.section_number() {
+                return Err(Error::IndexOutOfBounds);
+            }
+
+            let mut builder = StringTableBuilder::new();
+            let mut ids = Vec::with_capacity(elf.section_number());
+            for i in 0..elf.section_number() {
+                let id = if i == index {
+                    builder.add(name)
+                } else {
+                    let header = elf.section_table.pick(i)?;
+                    builder.add(names.pick(header.name as usize)?)
+                };
+                ids.push(id);
+            }
+            let (table, offsets) = builder.build();
+            let ids = ids.into_iter().map(|id| offsets[id]).collect::<Vec<_>>();
+            (names_index, table, ids)
+        };
+
+        self.set_section_content(names_index, &table)?;
+        for (i, name) in ids.into_iter().enumerate() {
+            let header_offset = self.section_header_offset(i)?;
+            self.write_u32(header_offset, name);
+        }
+
+        Ok(())
+    }
+
+    pub fn add_rela_section(
+        &mut self,
+        name: &[u8],
+        symtab: SectionIndex,
+        target: SectionIndex,
+        relocations: &[RelaEntry],
+    ) -> Result<SectionIndex, Error> {
+        let content = encode_entries(relocations, self.encoding.clone())?;
+        let header = Self::relocation_header(SectionType::Rela, RelaEntry::SIZE, symtab, target);
+        self.add_section(name, &header, &content)
+    }
+
+    pub fn add_rel_section(
+        &mut self,
+        name: &[u8],
+        symtab: SectionIndex,
+        target: SectionIndex,
+        relocations: &[RelEntry],
+    ) -> Result<SectionIndex, Error> {
+        let content = encode_entries(relocations, self.encoding.clone())?;
+        let header = Self::relocation_header(SectionType::Rel, RelEntry::SIZE, symtab, target);
+        self.add_section(name, &header, &content)
+    }
+
+    fn relocation_header(
+        ty: SectionType,
+        entry_size: usize,
+        symtab: SectionIndex,
+        target: SectionIndex,
+    ) -> SectionHeader {
+        SectionHeader {
+            name: 0,
+            ty,
+            flags: SectionFlags::INFO_LINK,
+            address: Address(0),
+            offset: Offset(0),
+            size: 0,
+            link: Index::Regular(symtab),
+            info: target.0 as u32,
+            address_alignment: 8,
+            number_of_entries: entry_size as u64,
+        }
+    }
+
+    pub fn add_symbol(
+        &mut self,
+        symtab: SectionIndex,
+        name: &[u8],
+        symbol: &SymbolEntry,
+    ) -> Result<SymbolIndex, Error> {
+        let symtab = symtab.0;
+        let (header, strings) = self.symbol_table(symtab)?;
+        let local = symbol.info.binding == SymbolBinding::Local;
+        let position = if local {
+            header.info as usize
+        } else {
+            header.size as usize / SymbolEntry::SIZE
+        };
+        // `sh_info` comes from the file, it may be past the end of the table
+        let at = position
+            .checked_mul(SymbolEntry::SIZE)
+            .filter(|at| *at as u64 <= header.size)
+            .ok_or(Error::IndexOutOfBounds)?;
+
+        let name = self.intern(strings, name)?;
+        // the content moves if the string table is before it
+        let header = self.section_header(symtab)?;
+
+        let mut encoded = [0; SymbolEntry::SIZE];
+        let entry = SymbolEntry {
+            name,
+            ..symbol.clone()
+        };
+        entry.encode(&mut encoded, self.encoding.clone())?;
+
+        let mut content = self.section_content(&header)?.to_vec();
+        content.splice(at..at, encoded.iter().cloned());
+
+        self.remap_symbols(symtab, SymbolEdit::Insert(position as u32, local))?;
+        self.set_section_content(symtab, &content)?;
+        if local {
+            let header_offset = self.section_header_offset(symtab)?;
+            self.write_u32(header_offset + 0x2c, header.info + 1);
+        }
+
+        Ok(SymbolIndex(position))
+    }
+
+    pub fn rename_symbol(
+        &mut self,
+        symtab: SectionIndex,
+        index: SymbolIndex,
+        name: &[u8],
+    ) -> Result<(), Error> {
+        let (symtab, index) = (symtab.0, index.0);
+        let (header, strings) = self.symbol_table(symtab)?;
+        if index >= header.size as usize / SymbolEntry::SIZE {
+            return Err(Error::IndexOutOfBounds);
+        }
+        // the table is in the file, it is checked before the name is added
+        self.section_content(&header)?;
+
+        let name = self.intern(strings, name)?;
+        let header = self.section_header(symtab)?;
+        self.write_u32(header.offset.0 as usize + index * SymbolEntry::SIZE, name);
+
+        Ok(())
+    }
+
+    // fails with `Error::SymbolInUse` if some relocation still refers the symbol
+    pub fn remove_symbol(&mut self, symtab: SectionIndex, index: SymbolIndex) -> Result<(), Error> {
+        let (symtab, index) = (symtab.0, index.0);
+        let (header, _) = self.symbol_table(symtab)?;
+        if index == 0 || index >= header.size as usize / SymbolEntry::SIZE {
+            return Err(Error::IndexOutOfBounds);
+        }
+
+        self.remap_symbols(symtab, SymbolEdit::Remove(index as u32))?;
+
+        let mut content = self.section_content(&header)?.to_vec();
+        let at = index * SymbolEntry::SIZE;
+        content.drain(at..(at + SymbolEntry::SIZE));
+        self.set_section_content(symtab, &content)?;
+        if index < header.info as usize {
+            let header_offset = self.section_header_offset(symtab)?;
+            self.write_u32(header_offset + 0x2c, header.info - 1);
+        }
+
+        Ok(())
+    }
+
+    // Appends a new `PT_LOAD` segment at the end of the file, in memory it is after the others,
+    // returns the address of the content.
+    // The program header table cannot grow in place, so it moves into the new segment too,
+    // `PT_PHDR` is updated accordingly. A `PT_LOAD` aligned to more than 2 MiB, or not to
+    // a power of two, is `Error::BadAlignment`. From `PN_XNUM` headers on the number is in
+    // the section zero, a file without sections cannot have more.
+    pub fn add_segment(&mut self, content: &[u8], flags: ProgramFlags) -> Result<Address, Error> {
+        let (program_number, table_offset, section_number) = {
+            let elf = self.elf()?;
+            let offset = to_usize(elf.header.program_headers_offset.0)?;
+            (elf.program_number(), offset, elf.section_number())
+        };
+        // from `PN_XNUM` on the number is in `sh_info` of the section zero
+        let new_number = program_number + 1;
+        if new_number >= PN_XNUM as usize
+            && (section_number == 0 || u32::try_from(new_number).is_err())
+        {
+            return Err(Error::CapacityExceeded);
+        }
+        let table_end = program_number
+            .checked_mul(ProgramHeader::SIZE)
+            .and_then(|size| size.checked_add(table_offset))
+            .ok_or(Error::ArithmeticOverflow)?;
+        let table = self
+            .raw
+            .get(table_offset..table_end)
+            .ok_or(Error::SliceTooShort)?
+            .to_vec();
+
+        let mut page = 0x1000;
+        let mut loaded = false;
+        let mut memory_end = Address(0);
+        for i in 0..program_number {
+            let header = self.elf()?.program_table.pick(i)?;
+            if header.ty != ProgramType::Load {
+                continue;
+            }
+            page = page.max(checked_alignment(header.address_alignment)?);
+            loaded = true;
+            let end = header
+                .virtual_address
+                .checked_add(header.memory_size)
+                .ok_or(Error::ArithmeticOverflow)?;
+            memory_end = memory_end.max(end);
+        }
+        if !loaded {
+            return Err(Error::NotPresent);
+        }
+
+        // the segment is at the end of the file, and at the first page after the others
+        // in memory, `p_vaddr` and `p_offset` are congruent modulo the page, that is all
+        // `PT_LOAD` needs, the file does not grow up to the addresses
+        let overflow = Error::ArithmeticOverflow;
+        let offset = (self.raw.len() as u64)
+            .checked_next_multiple_of(0x10)
+            .ok_or_else(|| overflow.clone())?;
+        let address = memory_end
+            .align_up(page)
+            .and_then(|page_start| page_start.checked_add(offset % page))
+            .ok_or_else(|| overflow.clone())?
+            .0;
+        let new_table_size = (table.len() + ProgramHeader::SIZE) as u64;
+        let content_offset = offset
+            .checked_add(new_table_size)
+            .and_then(|end| end.checked_next_multiple_of(0x10))
+            .ok_or_else(|| overflow.clone())?;
+        let size = content_offset
+            .checked_add(content.len() as u64)
+            .ok_or_else(|| overflow.clone())?
+            - offset;
+        address.checked_add(size).ok_or(overflow)?;
+        let (offset_, content_offset_) = (to_usize(offset)?, to_usize(content_offset)?);
+
+        let mut new_table = table;
+        let header = ProgramHeader {
+            ty: ProgramType::Load,
+            flags: flags | ProgramFlags::READ,
+            file_offset: Offset(offset),
+            virtual_address: Address(address),
+            physical_address: Address(address),
+            file_size: size,
+            memory_size: size,
+            address_alignment: page,
+        };
+        let mut encoded = [0; ProgramHeader::SIZE];
+        header.encode(&mut encoded, self.encoding.clone())?;
+        new_table.extend_from_slice(&encoded);
+
+        self.raw.resize(offset_, 0);
+        self.raw.extend_from_slice(&new_table);
+        self.raw.resize(content_offset_, 0);
+        self.raw.extend_from_slice(content);
+
+        self.write_u64(0x20, offset);
+        if new_number >= PN_XNUM as usize {
+            let zero = self.section_header_offset(0)?;
+            self.write_u32(zero + 0x2c, new_number as u32);
+            self.write_u16(0x38, PN_XNUM);
+        } else {
+            self.write_u16(0x38, new_number as u16);
+        }
+        for i in 0..program_number {
+            let entry = offset_ + i * ProgramHeader::SIZE;
+            if self.read_u32(entry)? == ProgramType::ProgramHeaderTable.into() {
+                self.write_u64(entry + 0x08, offset);
+                self.write_u64(entry + 0x10, address);
+                self.write_u64(entry + 0x18, address);
+                self.write_u64(entry + 0x20, new_table_size);
+                self.write_u64(entry + 0x28, new_table_size);
+            }
+        }
+
+        Ok(Address(address + (content_offset - offset)))
+    }
+
+    // Injects the `stub` into a new executable segment and makes it the entry point,
+    // a jump to the original entry point is appended to the stub, returns the original one.
+    pub fn redirect_entry(&mut self, stub: &[u8]) -> Result<Address, Error> {
+        let (machine, original) = {
+            let elf = self.elf()?;
+            (elf.machine(), elf.entry())
+        };
+
+        let mut content = stub.to_vec();
+        match machine {
+            Machine::X86_64 => content.extend_from_slice(&[0xe9, 0, 0, 0, 0]),
+            Machine::AArch64 => {
+                while !content.len().is_multiple_of(4) {
+                    content.push(0);
+                }
+                content.extend_from_slice(&[0, 0, 0, 0]);
+            }
+            _ => return Err(Error::UnsupportedMachine),
+        }
+
+        let address = self.add_segment(&content, ProgramFlags::EXECUTE)?;
+        let jump = address + (content.len() as u64) - 4;
+        let jump_offset = self.raw.len() - 4;
+        let distance = original.0.wrapping_sub(jump.0) as i64;
+        match machine {
+            Machine::X86_64 => {
+                let relative = distance - 4;
+                if relative < i32::MIN as i64 || relative > i32::MAX as i64 {
+                    return Err(Error::ArithmeticOverflow);
+                }
+                self.write_u32(jump_offset, relative as i32 as u32);
+            }
+            _ => {
+                // instructions are always little endian
+                if !(-0x8000000..0x8000000).contains(&distance) {
+                    return Err(Error::ArithmeticOverflow);
+                }
+                let code = 0x14000000 | (((distance / 4) as u32) & 0x03ffffff);
+                write_int!(&mut self.raw[jump_offset..], &Encoding::Little, u32, code);
+            }
+        }
+        self.write_u64(0x18, address.0);
+
+        Ok(original)
+    }
+
+    // Replaces the content of the section, the following content of the file shifts,
+    // by a multiple of the greatest alignment among the structures that follow,
+    // segments containing the section grow or shrink accordingly.
+    // A loaded section can change its size only if it is the last in its segment
+    // and there is room in the address space. The section zero has no content,
+    // an alignment of what follows that `add_segment` rejects is `Error::BadAlignment`.
+    pub fn replace_section(&mut self, index: SectionIndex, content: &[u8]) -> Result<(), Error> {
+        let index = index.0;
+        let (programs, sections, program_table, section_table) = {
+            let elf = self.elf()?;
+            let programs = (0..elf.program_number())
+                .map(|i| elf.program_table.pick(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            let sections = (0..elf.section_number())
+                .map(|i| elf.section_table.pick(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            let program_table = elf.header.program_headers_offset;
+            let section_table = elf.header.section_headers_offset;
+            (programs, sections, program_table, section_table)
+        };
+        // the tables are read, so they are in the file
+        let tables = [
+            (program_table, programs.len() * ProgramHeader::SIZE),
+            (section_table, sections.len() * SectionHeader::SIZE),
+        ];
+        let header = sections.get(index).ok_or(Error::IndexOutOfBounds)?.clone();
+        // the section zero is the header of the table, it has no content
+        if index == 0 || matches!(header.ty, SectionType::Null | SectionType::NoBits) {
+            return Err(Error::UnexpectedSectionType);
+        }
+
+        let overflow = Error::ArithmeticOverflow;
+        let start = header.offset;
+        let old_end = start
+            .checked_add(header.size)
+            .ok_or_else(|| overflow.clone())?;
+        let new_end = start
+            .checked_add(content.len() as u64)
+            .ok_or_else(|| overflow.clone())?;
+        if (self.raw.len() as u64) < old_end.0 {
+            return Err(Error::SliceTooShort);
+        }
+        // the content would overwrite a header table, it does not move if it is
+        // at the start of the section
+        let overlaps = tables.iter().any(|&(offset, size)| {
+            size != 0 && (offset < old_end || offset == start) && start < offset + size as u64
+        });
+        if overlaps {
+            return Err(Error::CannotGrowSection);
+        }
+        let mut ends = Vec::with_capacity(programs.len());
+        for program in &programs {
+            let file_end = program.file_offset.checked_add(program.file_size);
+            let memory_end = program.virtual_address.checked_add(program.memory_size);
+            match (file_end, memory_end) {
+                (Some(file_end), Some(memory_end)) => ends.push((file_end, memory_end)),
+                _ => return Err(overflow),
+            }
+        }
+
+        if header.flags.contains(SectionFlags::ALLOC) && new_end != old_end {
+            let address_end = header
+                .address
+                .checked_add(header.size)
+                .ok_or_else(|| overflow.clone())?;
+            for (program, &(_, segment_end)) in programs.iter().zip(&ends) {
+                let contains = program.ty == ProgramType::Load
+                    && (program.virtual_address..segment_end).contains(&header.address);
+                if !contains {
+                    continue;
+                }
+                let followed = sections.iter().enumerate().any(|(j, s)| {
+                    j != index
+                        && s.flags.contains(SectionFlags::ALLOC)
+                        && s.address >= address_end
+                        && s.address < segment_end
+                });
+                let grown_end = segment_end
+                    .saturating_add(content.len() as u64)
+                    .saturating_sub(header.size);
+                let collides = programs.iter().any(|p| {
+                    p.ty == ProgramType::Load
+                        && p.virtual_address >= segment_end
+                        && p.virtual_address < grown_end
+                });
+                if followed || collides {
+                    return Err(Error::CannotGrowSection);
+                }
+            }
+        }
+
+        let mut alignment = 1;
+        for program in &programs {
+            if program.file_offset >= old_end {
+                alignment = alignment.max(checked_alignment(program.address_alignment)?);
+            }
+        }
+        for (j, section) in sections.iter().enumerate() {
+            if j != index && section.offset >= old_end && section.size != 0 {
+                alignment = alignment.max(checked_alignment(section.address_alignment)?);
+            }
+        }
+        if program_table >= old_end || section_table >= old_end {
+            alignment = alignment.max(8);
+        }
+        // the alignment is bounded, so is the padding
+        let (shift, padding) = if new_end >= old_end {
+            let delta = new_end - old_end;
+            let shift = delta.div_ceil(alignment) * alignment;
+            (shift as i64, shift - delta)
+        } else {
+            let delta = old_end - new_end;
+            let shift = delta / alignment * alignment;
+            (-(shift as i64), delta - shift)
+        };
+
+        let map_start = |x: Offset| {
+            if x >= old_end && x != start {
+                x.wrapping_add(shift as u64)
+            } else {
+                x
+            }
+        };
+        let map_end = |x: Offset| match x.cmp(&old_end) {
+            Ordering::Less => x.min(new_end),
+            Ordering::Equal => new_end,
+            Ordering::Greater => x.wrapping_add(shift as u64),
+        };
+        // the new offset, the new size and the new memory size of each segment,
+        // computed before anything is written
+        let mut updates = Vec::with_capacity(programs.len());
+        for (program, &(file_end, _)) in programs.iter().zip(&ends) {
+            let file_offset = map_start(program.file_offset);
+            let file_size = map_end(file_end)
+                .checked_sub(file_offset)
+                .ok_or_else(|| overflow.clone())?;
+            let memory_size = if file_size != program.file_size {
+                let memory_size = program
+                    .memory_size
+                    .checked_add(file_size)
+                    .ok_or_else(|| overflow.clone())?;
+                Some(memory_size.saturating_sub(program.file_size))
+            } else {
+                None
+            };
+            updates.push((file_offset, file_size, memory_size));
+        }
+
+        let (start_, old_end_) = (to_usize(start.0)?, to_usize(old_end.0)?);
+        let length = (self.raw.len() - (old_end_ - start_))
+            .checked_add(content.len())
+            .and_then(|length| length.checked_add(to_usize(padding).ok()?))
+            .ok_or(overflow)?;
+        let mut raw = Vec::with_capacity(length);
+        raw.extend_from_slice(&self.raw[..start_]);
+        raw.extend_from_slice(content);
+        raw.resize(raw.len() + padding as usize, 0);
+        raw.extend_from_slice(&self.raw[old_end_..]);
+        self.raw = raw;
+
+        let program_table = map_start(program_table);
+        let section_table = map_start(section_table);
+        self.write_u64(0x20, program_table.0);
+        self.write_u64(0x28, section_table.0);
+        for (i, (file_offset, file_size, memory_size)) in updates.into_iter().enumerate() {
+            let offset = program_table.0 as usize + i * ProgramHeader::SIZE;
+            self.write_u64(offset + 0x08, file_offset.0);
+            self.write_u64(offset + 0x20, file_size);
+            if let Some(memory_size) = memory_size {
+                self.write_u64(offset + 0x28, memory_size);
+            }
+        }
+        for (j, section) in sections.iter().enumerate() {
+            let offset = section_table.0 as usize + j * SectionHeader::SIZE;
+            if j == index {
+                self.write_u64(offset + 0x20, content.len() as u64);
+            } else {
+                self.write_u64(offset + 0x18, map_start(section.offset).0);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Removes the gaps between the structures of the file, returns how many bytes are saved.
+    // Each block of the file that a header, a segment or a section claims moves towards
+    // the beginning by a multiple of its alignment, so segments stay congruent to their
+    // addresses, overlapping blocks move together.
+    pub fn compact(&mut self) -> Result<usize, Error> {
+        let overflow = Error::ArithmeticOverflow;
+        let mut blocks = Vec::new();
+        let (program_table, section_table, program_number, section_number) = {
+            let elf = self.elf()?;
+            let program_table = elf.header.program_headers_offset.0;
+            let section_table = elf.header.section_headers_offset.0;
+            let program_number = elf.program_number();
+            let section_number = elf.section_number();
+
+            // the offsets and the sizes come from the file
+            let end = |start: u64, size: u64| start.checked_add(size).ok_or(overflow.clone());
+            blocks.push((0, Header::SIZE as u64, 1));
+            if program_number != 0 {
+                let size = (program_number * ProgramHeader::SIZE) as u64;
+                blocks.push((program_table, end(program_table, size)?, 8));
+            }
+            if section_number != 0 {
+                let size = (section_number * SectionHeader::SIZE) as u64;
+                blocks.push((section_table, end(section_table, size)?, 8));
+            }
+            for i in 0..program_number {
+                let header = elf.program_table.pick(i)?;
+                if header.file_size != 0 {
+                    let end = end(header.file_offset.0, header.file_size)?;
+                    let alignment = checked_alignment(header.address_alignment)?;
+                    blocks.push((header.file_offset.0, end, alignment));
+                }
+            }
+            for i in 0..section_number {
+                let header = elf.section_table.pick(i)?;
+                if header.ty != SectionType::NoBits && header.size != 0 {
+                    let end = end(header.offset.0, header.size)?;
+                    let alignment = checked_alignment(header.address_alignment)?;
+                    blocks.push((header.offset.0, end, alignment));
+                }
+            }
+            (program_table, section_table, program_number, section_number)
+        };
+        if blocks
+            .iter()
+            .any(|&(_, end, _)| end > self.raw.len() as u64)
+        {
+            return Err(Error::SliceTooShort);
+        }
+
+        blocks.sort();
+        let mut merged = Vec::<(u64, u64, u64, u64)>::with_capacity(blocks.len());
+        for (start, end, alignment) in blocks {
+            match merged.last_mut() {
+                Some(last) if start < last.1 => {
+                    last.1 = last.1.max(end);
+                    last.2 = last.2.max(alignment);
+                }
+                _ => merged.push((start, end, alignment, 0)),
+            }
+        }
+        let mut cursor = 0;
+        for block in &mut merged {
+            let (start, end, alignment, _) = *block;
+            // the alignment is at most 2 MiB, and the block is not past the end of the file
+            let new = cursor + (start % alignment + alignment - cursor % alignment) % alignment;
+            block.3 = new;
+            cursor = new + (end - start);
+        }
+
+        // offsets out of any block, e.g. of empty sections, move like the preceding block
+        let map = |offset: u64| {
+            let block = merged.iter().rev().find(|block| block.0 <= offset);
+            match block {
+                Some(&(start, end, _, new)) => new + offset.min(end) - start,
+                None => offset,
+            }
+        };
+
+        let mut raw = Vec::with_capacity(cursor as usize);
+        for &(start, end, _, new) in &merged {
+            raw.resize(new as usize, 0);
+            raw.extend_from_slice(&self.raw[(start as usize)..(end as usize)]);
+        }
+        let saved = self.raw.len() - raw.len();
+        self.raw = raw;
+
+        let program_table = map(program_table);
+        let section_table = map(section_table);
+        self.write_u64(0x20, program_table);
+        self.write_u64(0x28, section_table);
+        for i in 0..program_number {
+            let offset = program_table as usize + i * ProgramHeader::SIZE + 0x08;
+            let value = self.read_u64(offset)?;
+            self.write_u64(offset, map(value));
+        }
+        for i in 0..section_number {
+            let offset = section_table as usize + i * SectionHeader::SIZE + 0x18;
+            let value = self.read_u64(offset)?;
+            self.write_u64(offset, map(value));
+        }
+
+        Ok(saved)
+    }
+
+    // Converts an executable to a shared object (PIE) and back, applies what can be applied
+    // and returns what has been done and what needs attention.
+    pub fn convert_type(&mut self, ty: Type) -> Result<Vec<ConversionNote>, Error> {
+        const DT_FLAGS_1: u64 = 0x6ffffffb;
+        const DF_1_PIE: u64 = 0x08000000;
+
+        let (current, programs) = {
+            let elf = self.elf()?;
+            let programs = (0..elf.program_number())
+                .map(|i| elf.program_table.pick(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            (elf.ty(), programs)
+        };
+        let to_shared = match (current, &ty) {
+            (Type::Executable, Type::SharedObject) => true,
+            (Type::SharedObject, Type::Executable) => false,
+            _ => return Err(Error::UnexpectedFileType),
+        };
+
+        let mut notes = Vec::new();
+        if !programs.iter().any(|p| p.ty == ProgramType::Interpreter) {
+            notes.push(ConversionNote::NoInterpreter);
+        }
+
+        let base = programs
+            .iter()
+            .filter(|p| p.ty == ProgramType::Load)
+            .map(|p| p.virtual_address)
+            .min()
+            .unwrap_or(Address(0));
+        let delta = match (to_shared, base) {
+            (true, base) if base != Address(0) => -(base.0 as i64),
+            (false, Address(0)) => 0x400000,
+            _ => 0,
+        };
+        if delta != 0 {
+            self.rebase(delta)?;
+            notes.push(ConversionNote::Rebased { delta });
+        }
+        if to_shared {
+            notes.push(ConversionNote::PositionDependentCode);
+        }
+
+        match programs.iter().find(|p| p.ty == ProgramType::Dynamic) {
+            None => notes.push(ConversionNote::NoDynamicSection),
+            Some(dynamic) => {
+                let start = dynamic.file_offset.0 as usize;
+                let mut flags = None;
+                let mut nulls = Vec::new();
+                for j in 0..(dynamic.file_size as usize / 0x10) {
+                    let offset = start + j * 0x10;
+                    match self.read_u64(offset)? {
+                        0 => nulls.push(offset),
+                        DT_FLAGS_1 => flags = Some(offset),
+                        _ => (),
+                    }
+                }
+                // the last null terminates the table, others are spare
+                let offset = match (flags, nulls.len()) {
+                    (Some(offset), _) => Some(offset),
+                    (None, n) if n >= 2 && to_shared => {
+                        self.write_u64(nulls[0], DT_FLAGS_1);
+                        self.write_u64(nulls[0] + 0x08, 0);
+                        Some(nulls[0])
+                    }
+                    _ => None,
+                };
+                match offset {
+                    Some(offset) => {
+                        let value = self.read_u64(offset + 0x08)?;
+                        if to_shared {
+                            self.write_u64(offset + 0x08, value | DF_1_PIE);
+                            notes.push(ConversionNote::PieFlagSet);
+                        } else if value & DF_1_PIE != 0 {
+                            self.write_u64(offset + 0x08, value & !DF_1_PIE);
+                            notes.push(ConversionNote::PieFlagCleared);
+                        }
+                    }
+                    None if to_shared => notes.push(ConversionNote::NoRoomForFlags),
+                    None => (),
+                }
+            }
+        }
+
+        self.write_u16(0x10, ty.into());
+        Ok(notes)
+    }
+
+    // Computes the build id over the content of loadable segments, with the build id itself
+    // zeroed, and writes it into the `NT_GNU_BUILD_ID` note. The digest is truncated or padded
+    // with zeros to the size of the note. A note added now would not be loaded, so
+    // the linker must have reserved it, `--build-id`, a file without it is `Error::NotPresent`.
+    pub fn update_build_id<H>(&mut self, mut hasher: H) -> Result<Vec<u8>, Error>
+    where
+        H: Hasher,
+    {
+        let (start, end, loads) = {
+            let elf = self.elf()?;
+            let mut location = None;
+            for i in 0..elf.section_number() {
+                let table = match elf.section(SectionIndex(i))? {
+                    Some(section) => match section.data {
+                        SectionData::Note(table) => table,
+                        _ => continue,
+                    },
+                    None => continue,
+                };
+                // the table is the content at `sh_offset`
+                let offset = elf.section_table.pick(i)?.offset.0 as usize;
+                let mut position = 0;
+                while let Ok((note, description)) = table.locate(&mut position) {
+                    if note.name == b"GNU" && note.ty == 3 {
+                        location = Some((offset + description.start, offset + description.end));
+                        break;
+                    }
+                }
+                if location.is_some() {
+                    break;
+                }
+            }
+            let (start, end) = location.ok_or(Error::NotPresent)?;
+            let mut loads = Vec::new();
+            for i in 0..elf.program_number() {
+                let header = elf.program_table.pick(i)?;
+                if header.ty == ProgramType::Load {
+                    let load_start = to_usize(header.file_offset.0)?;
+                    let load_end = load_start
+                        .checked_add(to_usize(header.file_size)?)
+                        .ok_or(Error::ArithmeticOverflow)?;
+                    loads.push((load_start, load_end));
+                }
+            }
+            (start, end, loads)
+        };
+
+        for (load_start, load_end) in loads {
+            let slice = self
+                .raw
+                .get(load_start..load_end)
+                .ok_or(Error::SliceTooShort)?;
+            if load_start <= start && end <= load_end && start != end {
+                hasher.update(&slice[..(start - load_start)]);
+                hasher.update(&vec![0; end - start]);
+                hasher.update(&slice[(end - load_start)..]);
+            } else {
+                hasher.update(slice);
+            }
+        }
+        let digest = hasher.finish();
+
+        let mut id = digest.as_ref().to_vec();
+        id.resize(end - start, 0);
+        self.raw[start..end].clone_from_slice(&id);
+        Ok(id)
+    }
+
+    // Moves the image to another base address, the file layout stays the same.
+    // Only position independent code survives it, the code itself is not patched.
+    // Rewrites the addresses in program headers, section headers, the entry point,
+    // values of symbols defined in sections (but TLS), relocation targets,
+    // the addends of relative relocations, the addresses of `SHT_RELR` and the words
+    // they relocate, and the pointers in the dynamic section. Every word is found
+    // and checked first, so it fails without side effects.
+    pub fn rebase(&mut self, delta: i64) -> Result<(), Error> {
+        // `DT_SYMTAB_SHNDX`, `DT_RELR` and the others besides `DT_ADDRRNGLO..=DT_ADDRRNGHI`
+        const POINTERS: [u64; 19] = [
+            3, 4, 5, 6, 7, 12, 13, 17, 23, 25, 26, 32, 34, 36, 0x6ffffef9, 0x6ffffff0, 0x6ffffffc,
+            0x6ffffffe, 0x6ffffef5,
+        ];
+        const ADDRESS_RANGE: RangeInclusive<u64> = 0x6ffffe00..=0x6ffffeff;
+
+        let (program_number, program_table, section_number, section_table, ty, machine) = {
+            let elf = self.elf()?;
+            (
+                elf.program_number(),
+                elf.header.program_headers_offset.0 as usize,
+                elf.section_number(),
+                elf.header.section_headers_offset.0 as usize,
+                elf.ty(),
+                elf.machine(),
+            )
+        };
+        let relative_types: &[u32] = match machine {
+            Machine::X86_64 => &[8, 37],
+            Machine::AArch64 => &[1027, 1032],
+            _ => &[],
+        };
+
+        // the offsets of the words to shift
+        let mut words = Vec::new();
+        if self.read_u64(0x18)? != 0 {
+            words.push(0x18);
+        }
+
+        let mut loads = Vec::new();
+        for i in 0..program_number {
+            let offset = program_table + i * ProgramHeader::SIZE;
+            let address = self.read_u64(offset + 0x10)?;
+            let memory_size = self.read_u64(offset + 0x28)?;
+            if self.read_u32(offset)? == ProgramType::Load.into() {
+                let file_offset = self.read_u64(offset + 0x08)?;
+                let file_size = self.read_u64(offset + 0x20)?;
+                loads.push((address, file_offset, file_size));
+            }
+            if address == 0 && memory_size == 0 {
+                continue;
+            }
+            words.push(offset + 0x10);
+            words.push(offset + 0x18);
+        }
+        // the offset of the word of the address, `SliceTooShort` if it is not in the file
+        let offset_of = |address: u64| {
+            loads
+                .iter()
+                .find_map(|&(start, offset, size)| {
+                    let distance = address.checked_sub(start)?;
+                    let end = distance.checked_add(8)?;
+                    if end <= size {
+                        to_usize(offset.checked_add(distance)?).ok()
+                    } else {
+                        None
+                    }
+                })
+                .ok_or(Error::SliceTooShort)
+        };
+
+        for i in 0..section_number {
+            let header = self.section_header(i)?;
+            if header.flags.contains(SectionFlags::ALLOC) {
+                words.push(section_table + i * SectionHeader::SIZE + 0x10);
+            }
+
+            let content = match header.ty {
+                SectionType::SymbolTable
+                | SectionType::DynamicSymbolTable
+                | SectionType::Rel
+                | SectionType::Rela
+                | SectionType::Dynamic
+                | SectionType::Unknown(SHT_RELR) => self.section_content(&header)?,
+                _ => continue,
+            };
+            let start = header.offset.0 as usize;
+            match header.ty {
+                SectionType::SymbolTable | SectionType::DynamicSymbolTable => {
+                    for j in 0..(content.len() / SymbolEntry::SIZE) {
+                        let offset = start + j * SymbolEntry::SIZE;
+                        let info = self.read_u8(offset + 0x04)?;
+                        let section_index = self.read_u16(offset + 0x06)?;
+                        let defined =
+                            (0x0001..0xff00).contains(&section_index) || section_index == 0xffff;
+                        if defined && info & 0x0f != 0x06 {
+                            words.push(offset + 0x08);
+                        }
+                    }
+                }
+                SectionType::Rel | SectionType::Rela if ty != Type::Relocatable => {
+                    let rela = header.ty == SectionType::Rela;
+                    let entry_size = if rela {
+                        RelaEntry::SIZE
+                    } else {
+                        RelEntry::SIZE
+                    };
+                    for j in 0..(content.len() / entry_size) {
+                        let offset = start + j * entry_size;
+                        words.push(offset);
+                        let relocation_type = self.read_u64(offset + 0x08)? as u32;
+                        if rela && relative_types.contains(&relocation_type) {
+                            words.push(offset + 0x10);
+                        }
+                    }
+                }
+                SectionType::Dynamic => {
+                    for j in 0..(content.len() / 0x10) {
+                        let offset = start + j * 0x10;
+                        let tag = self.read_u64(offset)?;
+                        if tag == 0 {
+                            break;
+                        }
+                        if POINTERS.contains(&tag) || ADDRESS_RANGE.contains(&tag) {
+                            words.push(offset + 0x08);
+                        }
+                    }
+                }
+                // the addresses are the even words, the bitmaps stay, the words they
+                // relocate are the implicit addends
+                SectionType::Unknown(SHT_RELR) if ty != Type::Relocatable => {
+                    let table = WordTable::<u64>::new(content, self.encoding.clone());
+                    for j in 0..table.len() {
+                        if table.get(j).is_some_and(|word| word & 1 == 0) {
+                            words.push(start + j * 8);
+                        }
+                    }
+                    let (mut entry, mut bit, mut base) = (0, 0, Address(0));
+                    while let Some(address) = next_relative(&table, &mut entry, &mut bit, &mut base)
+                    {
+                        words.push(offset_of(address.0)?);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        // each word once, and every one is in the file before the first is written
+        words.sort_unstable();
+        words.dedup();
+        let values = words
+            .iter()
+            .map(|&offset| self.read_u64(offset))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (offset, value) in words.into_iter().zip(values) {
+            self.write_u64(offset, value.wrapping_add(delta as u64));
+        }
+        Ok(())
+    }
+
+    fn symbol_table(&self, index: usize) -> Result<(SectionHeader, usize), Error> {
+        let header = self.section_header(index)?;
+        if header.ty != SectionType::SymbolTable {
+            return Err(Error::UnexpectedSectionType);
+        }
+        match header.link {
+            Index::Regular(strings) => Ok((header, strings.0)),
+            _ => Err(Error::UnexpectedSectionType),
+        }
+    }
+
+    // returns the offset of the string, reuses an existing one, even if it is a suffix
+    fn intern(&mut self, strings: usize, name: &[u8]) -> Result<u32, Error> {
+        let header = self.section_header(strings)?;
+        if header.ty != SectionType::StringTable {
+            return Err(Error::UnexpectedSectionType);
+        }
+
+        let content = self.section_content(&header)?;
+        let existing = content
+            .windows(name.len() + 1)
+            .position(|w| w[..name.len()].eq(name) && w[name.len()] == 0);
+        if let Some(offset) = existing {
+            return Ok(offset as u32);
+        }
+
+        let mut content = content.to_vec();
+        if content.is_empty() {
+            content.push(0);
+        }
+        let offset = content.len();
+        content.extend_from_slice(name);
+        content.push(0);
+        self.set_section_content(strings, &content)?;
+
+        Ok(offset as u32)
+    }
+
+    // Renumbers what refers the symbols of the table, `r_info` of the relocations,
+    // `sh_info` of the groups, and moves the entries of `.symtab_shndx` and of the
+    // versions along with the symbols. Checks everything first, and only then writes,
+    // so it fails without side effects.
+    fn remap_symbols(&mut self, symtab: usize, edit: SymbolEdit) -> Result<(), Error> {
+        let elf = self.elf()?;
+        let mut updates = Vec::new();
+        let mut groups = Vec::new();
+        let mut arrays = Vec::new();
+        for i in 0..elf.section_number() {
+            let header = elf.section_table.pick(i)?;
+            if header.link != Index::Regular(SectionIndex(symtab)) {
+                continue;
+            }
+            let entry_size = match u32::from(header.ty.clone()) {
+                SHT_REL => RelEntry::SIZE,
+                SHT_RELA => RelaEntry::SIZE,
+                SHT_GROUP => {
+                    let new = edit.map(header.info).ok_or(Error::SymbolInUse)?;
+                    if new != header.info {
+                        groups.push((self.section_header_offset(i)? + 0x2c, new));
+                    }
+                    continue;
+                }
+                ty @ SHT_SYMTAB_SHNDX | ty @ SHT_GNU_VERSYM => {
+                    let width = if ty == SHT_SYMTAB_SHNDX { 4 } else { 2 };
+                    let mut content = self.section_content(&header)?.to_vec();
+                    match edit {
+                        SymbolEdit::Insert(at, local) => {
+                            let at = at as usize * width;
+                            if at > content.len() {
+                                return Err(Error::SliceTooShort);
+                            }
+                            // no extended index, `VER_NDX_LOCAL` or `VER_NDX_GLOBAL`
+                            let mut inserted = [0; 4];
+                            if ty == SHT_GNU_VERSYM {
+                                let version = u16::from(!local);
+                                write_int!(&mut inserted[..], &self.encoding, u16, version);
+                            }
+                            content.splice(at..at, inserted[..width].iter().cloned());
+                        }
+                        SymbolEdit::Remove(at) => {
+                            let at = at as usize * width;
+                            if at + width > content.len() {
+                                return Err(Error::SliceTooShort);
+                            }
+                            content.drain(at..(at + width));
+                        }
+                    }
+                    if content.len() as u64 > header.size
+                        && header.flags.contains(SectionFlags::ALLOC)
+                    {
+                        return Err(Error::CannotGrowSection);
+                    }
+                    arrays.push((i, content));
+                    continue;
+                }
+                _ => continue,
+            };
+
+            let start = header.offset.0 as usize;
+            let number = header.size as usize / entry_size;
+            if self.raw.len() < start + number * entry_size {
+                return Err(Error::SliceTooShort);
+            }
+            for j in 0..number {
+                let offset = start + j * entry_size + 0x08;
+                let info = read_int!(&self.raw[offset..], &self.encoding, u64);
+                let symbol = (info >> 32) as u32;
+                let new = edit.map(symbol).ok_or(Error::SymbolInUse)?;
+                if new != symbol {
+                    updates.push((offset, ((new as u64) << 32) | (info & 0xffffffff)));
+                }
+            }
+        }
+
+        for (offset, info) in updates {
+            self.write_u64(offset, info);
+        }
+        for (offset, info) in groups {
+            self.write_u32(offset, info);
+        }
+        for (index, content) in arrays {
+            self.set_section_content(index, &content)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn section_header(&self, index: usize) -> Result<SectionHeader, Error> {
+        let elf = self.elf()?;
+        if index >= elf.section_number() {
+            return Err(Error::IndexOutOfBounds);
+        }
+        elf.section_table.pick(index)
+    }
+
+    pub(crate) fn section_header_offset(&self, index: usize) -> Result<usize, Error> {
+        let elf = self.elf()?;
+        Ok(elf.header.section_headers_offset.0 as usize + index * SectionHeader::SIZE)
+    }
+
+    pub(crate) fn section_content(&self, header: &SectionHeader) -> Result<&[u8], Error> {
+        if header.ty == SectionType::NoBits {
+            return Ok(&[]);
+        }
+        let start = to_usize(header.offset.0)?;
+        let end = start
+            .checked_add(to_usize(header.size)?)
+            .ok_or(Error::ArithmeticOverflow)?;
+        self.raw.get(start..end).ok_or(Error::SliceTooShort)
+    }
+
+    // writes in place if the content fits, otherwise moves the section to the end of the file,
+    // which is only possible for sections that are not loaded in memory
+    pub(crate) fn set_section_content(
+        &mut self,
+        index: usize,
+        content: &[u8],
+    ) -> Result<(), Error> {
+        let header = self.section_header(index)?;
+        let header_offset = self.section_header_offset(index)?;
+        if header.ty == SectionType::NoBits {
+            return Err(Error::UnexpectedSectionType);
+        }
+
+        let in_place = content.len() as u64 <= header.size;
+        let offset = if in_place {
+            // the range of the header is checked, it comes from the file
+            let old = self.section_content(&header)?.len();
+            let offset = header.offset.0 as usize;
+            let end = offset + content.len();
+            self.raw[offset..end].clone_from_slice(content);
+            self.raw[end..(offset + old)]
+                .iter_mut()
+                .for_each(|b| *b = 0);
+            offset
+        } else if header.flags.contains(SectionFlags::ALLOC) {
+            return Err(Error::CannotGrowSection);
+        } else {
+            self.append(content, header.address_alignment)?
+        };
+
+        self.write_u64(header_offset + 0x18, offset as u64);
+        self.write_u64(header_offset + 0x20, content.len() as u64);
+        Ok(())
+    }
+
+    // appends to the end of the file, returns the offset, the alignment is checked
+    // first, it may come from the file
+    pub(crate) fn append(&mut self, content: &[u8], alignment: u64) -> Result<usize, Error> {
+        let alignment = checked_alignment(alignment)? as usize;
+        let offset = self.raw.len().div_ceil(alignment) * alignment;
+        self.raw.resize(offset, 0);
+        self.raw.extend_from_slice(content);
+        Ok(offset)
+    }
+
+    pub(crate) fn read_u8(&self, offset: usize) -> Result<u8, Error> {
+        self.raw.get(offset).cloned().ok_or(Error::SliceTooShort)
+    }
+
+    pub(crate) fn read_u16(&self, offset: usize) -> Result<u16, Error> {
+        let slice = self
+            .raw
+            .get(offset..(offset + 2))
+            .ok_or(Error::SliceTooShort)?;
+        Ok(read_int!(slice, &self.encoding, u16))
+    }
+
+    pub(crate) fn read_u32(&self, offset: usize) -> Result<u32, Error> {
+        let slice = self
+            .raw
+            .get(offset..(offset + 4))
+            .ok_or(Error::SliceTooShort)?;
+        Ok(read_int!(slice, &self.encoding, u32))
+    }
+
+    pub(crate) fn read_u64(&self, offset: usize) -> Result<u64, Error> {
+        let slice = self
+            .raw
+            .get(offset..(offset + 8))
+            .ok_or(Error::SliceTooShort)?;
+        Ok(read_int!(slice, &self.encoding, u64))
+    }
+
+    pub(crate) fn write_u16(&mut self, offset: usize, value: u16) {
+        write_int!(&mut self.raw[offset..], &self.encoding, u16, value);
+    }
+
+    pub(crate) fn write_u32(&mut self, offset: usize, value: u32) {
+        write_int!(&mut self.raw[offset..], &self.encoding, u32, value);
+    }
+
+    pub(crate) fn write_u64(&mut self, offset: usize, value: u64) {
+        write_int!(&mut self.raw[offset..], &self.encoding, u64, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{
+        Patcher, MAX_SEGMENT_ALIGNMENT, PN_XNUM, SHN_LORESERVE, SHT_GROUP, SHT_RELR,
+        SHT_SYMTAB_SHNDX,
+    };
+    use crate::{
+        Error, Address, SectionIndex, SymbolIndex, Index, Encoding, Type, SymbolEntry, SymbolInfo,
+        SymbolBinding, SymbolType, Machine, ElfBuilder, SectionType, SectionFlags, ProgramType,
+        ProgramFlags, ProgramHeader, Entry, Fnv1a,
+    };
+
+    fn executable() -> Vec<u8> {
+        let mut builder = ElfBuilder::new(Encoding::Little, Type::Executable, Machine::X86_64);
+        let text = builder.add_section(
+            b".text",
+            SectionType::ProgramBits,
+            SectionFlags::ALLOC | SectionFlags::EXECINSTR,
+            16,
+            vec![0xc3; 16],
+        );
+        builder.set_entry(text, 0);
+        builder.build().unwrap()
+    }
+
+    // the offset of the first `PT_LOAD` in the file
+    fn first_load(patcher: &Patcher) -> usize {
+        let elf = patcher.elf().unwrap();
+        let index = (0..elf.program_number())
+            .find(|&i| elf.program_table.pick(i).unwrap().ty == ProgramType::Load)
+            .unwrap();
+        elf.header.program_headers_offset.0 as usize + index * ProgramHeader::SIZE
+    }
+
+    #[test]
+    fn add_segment() {
+        let mut patcher = Patcher::new(executable()).unwrap();
+        let number = patcher.elf().unwrap().program_number();
+        let address = patcher.add_segment(b"payload", ProgramFlags::READ).unwrap();
+        let elf = patcher.elf().unwrap();
+        assert_eq!(elf.program_number(), number + 1);
+        assert_eq!(elf.read_virtual(address, 7).unwrap(), b"payload");
+    }
+
+    #[test]
+    fn segment_alignment() {
+        for alignment in [MAX_SEGMENT_ALIGNMENT * 2, 1 << 54, 0x1001] {
+            let mut patcher = Patcher::new(executable()).unwrap();
+            let load = first_load(&patcher);
+            patcher.write_u64(load + 0x30, alignment);
+            let size = patcher.as_slice().len();
+            assert_eq!(
+                patcher.add_segment(b"payload", ProgramFlags::READ),
+                Err(Error::BadAlignment(alignment)),
+            );
+            assert_eq!(patcher.as_slice().len(), size);
+        }
+
+        let mut patcher = Patcher::new(executable()).unwrap();
+        let load = first_load(&patcher);
+        patcher.write_u64(load + 0x30, MAX_SEGMENT_ALIGNMENT);
+        let Address(address) = patcher.add_segment(b"payload", ProgramFlags::READ).unwrap();
+        assert_ne!(address, 0);
+    }
+
+    #[test]
+    fn segment_end_overflow() {
+        let mut patcher = Patcher::new(executable()).unwrap();
+        let load = first_load(&patcher);
+        patcher.write_u64(load + 0x28, u64::MAX);
+        assert_eq!(
+            patcher.add_segment(b"payload", ProgramFlags::READ),
+            Err(Error::ArithmeticOverflow),
+        );
+    }
+
+    // a table of `number` program headers at the end of the file, the ones of the file
+    // and `PT_NULL` after them
+    fn with_programs(raw: Vec<u8>, number: usize) -> Patcher {
+        let mut patcher = Patcher::new(raw).unwrap();
+        let (offset, old) = {
+            let elf = patcher.elf().unwrap();
+            let offset = elf.header.program_headers_offset.0 as usize;
+            (offset, elf.program_number())
+        };
+        let mut table = patcher.as_slice()[offset..(offset + old * ProgramHeader::SIZE)].to_vec();
+        table.resize(number * ProgramHeader::SIZE, 0);
+        let offset = patcher.append(&table, 8).unwrap();
+        patcher.write_u64(0x20, offset as u64);
+        patcher.write_u16(0x38, number as u16);
+        patcher
+    }
+
+    #[test]
+    fn extended_program_number() {
+        let mut patcher = with_programs(executable(), PN_XNUM as usize - 1);
+        patcher.add_segment(b"payload", ProgramFlags::READ).unwrap();
+        let elf = patcher.elf().unwrap();
+        assert_eq!(elf.header.program_header_number, PN_XNUM);
+        assert_eq!(elf.section_table.pick(0).unwrap().info, PN_XNUM as u32);
+        assert_eq!(elf.program_number(), PN_XNUM as usize);
+        let last = elf.program_table.pick(PN_XNUM as usize - 1).unwrap();
+        assert_eq!(last.ty, ProgramType::Load);
+
+        patcher.add_segment(b"payload", ProgramFlags::READ).unwrap();
+        assert_eq!(
+            patcher.elf().unwrap().program_number(),
+            PN_XNUM as usize + 1
+        );
+    }
+
+    #[test]
+    fn extended_section_number() {
+        let mut builder = ElfBuilder::new(Encoding::Little, Type::Relocatable, Machine::X86_64);
+        // with the null section and `.shstrtab` two less than `SHN_LORESERVE`
+        for _ in 0..(SHN_LORESERVE - 4) {
+            builder.add_section(
+                b".a",
+                SectionType::ProgramBits,
+                SectionFlags::empty(),
+                1,
+                vec![],
+            );
+        }
+        let mut patcher = Patcher::new(builder.build().unwrap()).unwrap();
+        let header = patcher.section_header(1).unwrap();
+        let added = patcher.add_section(b".b", &header, b"b");
+        assert_eq!(added, Ok(SectionIndex(SHN_LORESERVE - 2)));
+        let number = patcher.elf().unwrap().header.section_header_number;
+        assert_eq!(number as usize, SHN_LORESERVE - 1);
+        let added = patcher.add_section(b".c", &header, b"c");
+        assert_eq!(added, Ok(SectionIndex(SHN_LORESERVE - 1)));
+
+        let elf = patcher.elf().unwrap();
+        assert_eq!(elf.header.section_header_number, 0);
+        assert_eq!(elf.section_number(), SHN_LORESERVE);
+        let last = elf
+            .section(SectionIndex(SHN_LORESERVE - 1))
+            .unwrap()
+            .unwrap();
+        assert_eq!(last.name, b".c");
+        let names = elf
+            .section_info(SectionIndex(SHN_LORESERVE - 3))
+            .unwrap()
+            .unwrap();
+        assert_eq!(names.name, b".shstrtab");
+    }
+
+    fn object_with_symbol() -> (Vec<u8>, SectionIndex) {
+        let mut builder = ElfBuilder::new(Encoding::Little, Type::Relocatable, Machine::X86_64);
+        let info = SymbolInfo {
+            binding: SymbolBinding::Global,
+            ty: SymbolType::Function,
+        };
+        builder.add_symbol(b"f", info, Index::AbsoluteValue, 0, 0);
+        let symtab = builder.symbol_table();
+        (builder.build().unwrap(), symtab)
+    }
+
+    #[test]
+    fn add_symbol() {
+        let (raw, symtab) = object_with_symbol();
+        let mut patcher = Patcher::new(raw).unwrap();
+        let entry = SymbolEntry {
+            name: 0,
+            info: SymbolInfo {
+                binding: SymbolBinding::Local,
+                ty: SymbolType::Object,
+            },
+            reserved: 0,
+            section_index: Index::AbsoluteValue,
+            value: Address(0x10),
+            size: 0,
+        };
+        assert_eq!(
+            patcher.add_symbol(symtab, b"local", &entry),
+            Ok(SymbolIndex(1))
+        );
+        let elf = patcher.elf().unwrap();
+        let source = elf.symbol_source_at(symtab.0).unwrap();
+        assert_eq!(source.get(1).unwrap().name, b"local");
+        assert_eq!(source.get(2).unwrap().name, b"f");
+        assert_eq!(elf.section_table.pick(symtab.0).unwrap().info, 2);
+    }
+
+    #[test]
+    fn add_symbol_bad_local_count() {
+        let (raw, symtab) = object_with_symbol();
+        let mut patcher = Patcher::new(raw).unwrap();
+        let offset = patcher.section_header_offset(symtab.0).unwrap();
+        for info in [3, u32::MAX] {
+            patcher.write_u32(offset + 0x2c, info);
+            let before = patcher.as_slice().to_vec();
+            let entry = SymbolEntry {
+                name: 0,
+                info: SymbolInfo {
+                    binding: SymbolBinding::Local,
+                    ty: SymbolType::Object,
+                },
+                reserved: 0,
+                section_index: Index::AbsoluteValue,
+                value: Address(0),
+                size: 0,
+            };
+            let added = patcher.add_symbol(symtab, b"local", &entry);
+            assert_eq!(added, Err(Error::IndexOutOfBounds));
+            assert_eq!(patcher.as_slice(), &before[..]);
+        }
+    }
+
+    #[test]
+    fn symbols_of_a_group() {
+        let mut builder = ElfBuilder::new(Encoding::Little, Type::Relocatable, Machine::X86_64);
+        let text = builder.add_section(
+            b".text._Z1fi",
+            SectionType::ProgramBits,
+            SectionFlags::ALLOC | SectionFlags::EXECINSTR | SectionFlags::GROUP,
+            16,
+            vec![0xc3],
+        );
+        let mut members = vec![1, 0, 0, 0];
+        members.extend_from_slice(&(text.0 as u32).to_le_bytes());
+        let group = builder.add_section(
+            b".group",
+            SectionType::Unknown(SHT_GROUP),
+            SectionFlags::empty(),
+            4,
+            members,
+        );
+        let info = SymbolInfo {
+            binding: SymbolBinding::Global,
+            ty: SymbolType::Function,
+        };
+        builder.add_symbol(b"_Z1fi", info, Index::Regular(text), 0, 1);
+        // the entries of the null symbol and `_Z1fi`
+        let indices = builder.add_section(
+            b".symtab_shndx",
+            SectionType::Unknown(SHT_SYMTAB_SHNDX),
+            SectionFlags::empty(),
+            4,
+            vec![0, 0, 0, 0, 7, 0, 0, 0],
+        );
+        let symtab = builder.symbol_table();
+        builder.set_link(group, symtab).unwrap();
+        builder.set_info(group, 1).unwrap();
+        builder.set_link(indices, symtab).unwrap();
+        let mut patcher = Patcher::new(builder.build().unwrap()).unwrap();
+
+        let entry = SymbolEntry {
+            name: 0,
+            info: SymbolInfo {
+                binding: SymbolBinding::Local,
+                ty: SymbolType::Object,
+            },
+            reserved: 0,
+            section_index: Index::AbsoluteValue,
+            value: Address(0),
+            size: 0,
+        };
+        assert_eq!(
+            patcher.add_symbol(symtab, b"local", &entry),
+            Ok(SymbolIndex(1))
+        );
+        let elf = patcher.elf().unwrap();
+        let groups = elf.groups().unwrap();
+        assert_eq!(groups[0].signature, b"_Z1fi");
+        let header = elf.section_table.pick(indices.0).unwrap();
+        let content = patcher.section_content(&header).unwrap();
+        assert_eq!(content, &[0, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0]);
+
+        assert_eq!(
+            patcher.remove_symbol(symtab, SymbolIndex(2)),
+            Err(Error::SymbolInUse)
+        );
+        patcher.remove_symbol(symtab, SymbolIndex(1)).unwrap();
+        let elf = patcher.elf().unwrap();
+        assert_eq!(elf.groups().unwrap()[0].signature, b"_Z1fi");
+        assert_eq!(elf.section_table.pick(group.0).unwrap().info, 1);
+        let header = elf.section_table.pick(indices.0).unwrap();
+        let content = patcher.section_content(&header).unwrap();
+        assert_eq!(content, &[0, 0, 0, 0, 7, 0, 0, 0]);
+    }
+
+    // the index of the section of the name
+    fn section_of(patcher: &Patcher, name: &[u8]) -> SectionIndex {
+        let elf = patcher.elf().unwrap();
+        (0..elf.section_number())
+            .map(SectionIndex)
+            .find(|&i| elf.section_info(i).unwrap().unwrap().name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn replace_section() {
+        let mut patcher = Patcher::new(executable()).unwrap();
+        let names = section_of(&patcher, b".shstrtab");
+        let header = patcher.section_header(names.0).unwrap();
+        let mut table = patcher.section_content(&header).unwrap().to_vec();
+        table.extend_from_slice(&[0; 0x100]);
+        patcher.replace_section(names, &table).unwrap();
+        let elf = patcher.elf().unwrap();
+        assert_eq!(
+            elf.section_table.pick(names.0).unwrap().size,
+            table.len() as u64
+        );
+        let text = elf.section_info(SectionIndex(1)).unwrap().unwrap();
+        assert_eq!(text.name, b".text");
+    }
+
+    #[test]
+    fn replace_null_section() {
+        let mut patcher = Patcher::new(executable()).unwrap();
+        let before = patcher.as_slice().to_vec();
+        assert_eq!(
+            patcher.replace_section(SectionIndex(0), b"content"),
+            Err(Error::UnexpectedSectionType),
+        );
+        assert_eq!(patcher.as_slice(), &before[..]);
+    }
+
+    #[test]
+    fn replace_section_alignment() {
+        for alignment in [MAX_SEGMENT_ALIGNMENT * 2, 0x63_0000_0000, 0x1001] {
+            let mut patcher = Patcher::new(executable()).unwrap();
+            let load = first_load(&patcher);
+            // the segment follows `.text` if the segment starts after it
+            patcher.write_u64(load + 0x08, patcher.as_slice().len() as u64);
+            patcher.write_u64(load + 0x30, alignment);
+            let before = patcher.as_slice().to_vec();
+            assert_eq!(
+                patcher.replace_section(SectionIndex(1), &[0xc3; 0x20]),
+                Err(Error::BadAlignment(alignment)),
+            );
+            assert_eq!(patcher.as_slice(), &before[..]);
+        }
+    }
+
+    #[test]
+    fn replace_section_overflow() {
+        let mut patcher = Patcher::new(executable()).unwrap();
+        let offset = patcher.section_header_offset(1).unwrap();
+        patcher.write_u64(offset + 0x20, u64::MAX);
+        assert_eq!(
+            patcher.replace_section(SectionIndex(1), b"content"),
+            Err(Error::ArithmeticOverflow),
+        );
+    }
+
+    #[test]
+    fn compact() {
+        let patcher = Patcher::new(executable()).unwrap();
+        // a gap before the section header table
+        let table = patcher.elf().unwrap().header.section_headers_offset.0 as usize;
+        let mut raw = patcher.as_slice().to_vec();
+        raw.splice(table..table, vec![0; 0x100]);
+        raw[0x28..0x30].copy_from_slice(&((table + 0x100) as u64).to_le_bytes());
+        let mut patcher = Patcher::new(raw).unwrap();
+        assert_eq!(patcher.compact(), Ok(0x100));
+        let elf = patcher.elf().unwrap();
+        let text = elf.section_info(SectionIndex(1)).unwrap().unwrap();
+        assert_eq!(text.name, b".text");
+    }
+
+    #[test]
+    fn compact_of_crafted_headers() {
+        let offset = |patcher: &Patcher| patcher.section_header_offset(1).unwrap();
+        for (field, value, error) in [
+            (0x20, u64::MAX, Error::ArithmeticOverflow),
+            (0x18, u64::MAX - 1, Error::ArithmeticOverflow),
+            (0x30, 0x1001, Error::BadAlignment(0x1001)),
+            (0x30, 1 << 40, Error::BadAlignment(1 << 40)),
+        ] {
+            let mut patcher = Patcher::new(executable()).unwrap();
+            let header = offset(&patcher);
+            patcher.write_u64(header + field, value);
+            let before = patcher.as_slice().to_vec();
+            assert_eq!(patcher.compact(), Err(error));
+            assert_eq!(patcher.as_slice(), &before[..]);
+        }
+
+        let mut patcher = Patcher::new(executable()).unwrap();
+        let load = first_load(&patcher);
+        patcher.write_u64(load + 0x08, 0x10);
+        patcher.write_u64(load + 0x20, u64::MAX);
+        assert_eq!(patcher.compact(), Err(Error::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn section_content_past_the_end() {
+        let (raw, symtab) = object_with_symbol();
+        let patcher = Patcher::new(raw).unwrap();
+        let strings = match patcher.section_header(symtab.0).unwrap().link {
+            Index::Regular(strings) => strings.0,
+            _ => panic!(),
+        };
+        let offset = patcher.section_header_offset(strings).unwrap();
+        let size = patcher.as_slice().len() as u64;
+        // the content fits in the size of the header, but not in the file
+        for (field, value) in [(0x18, size - 1), (0x18, u64::MAX), (0x20, size)] {
+            let mut patcher = patcher.clone();
+            patcher.write_u64(offset + field, value);
+            let before = patcher.as_slice().to_vec();
+            assert!(matches!(
+                patcher.set_section_content(strings, b"\0g\0"),
+                Err(Error::SliceTooShort) | Err(Error::ArithmeticOverflow)
+            ));
+            assert_eq!(patcher.as_slice(), &before[..]);
+        }
+    }
+
+    #[test]
+    fn moved_section_alignment() {
+        let (raw, symtab) = object_with_symbol();
+        let patcher = Patcher::new(raw).unwrap();
+        let strings = match patcher.section_header(symtab.0).unwrap().link {
+            Index::Regular(strings) => strings.0,
+            _ => panic!(),
+        };
+        let offset = patcher.section_header_offset(strings).unwrap();
+        for alignment in [1 << 40, 0x1001] {
+            let mut patcher = patcher.clone();
+            patcher.write_u64(offset + 0x30, alignment);
+            let before = patcher.as_slice().to_vec();
+            // the new name does not fit, the table moves to the end of the file
+            assert_eq!(
+                patcher.rename_symbol(symtab, SymbolIndex(1), b"a_longer_name"),
+                Err(Error::BadAlignment(alignment)),
+            );
+            assert_eq!(patcher.as_slice(), &before[..]);
+
+            let mut header = patcher.section_header(1).unwrap();
+            header.address_alignment = alignment;
+            assert_eq!(
+                patcher.add_section(b".new", &header, b"new"),
+                Err(Error::BadAlignment(alignment)),
+            );
+            assert_eq!(patcher.as_slice(), &before[..]);
+        }
+    }
+
+    // the segment takes the file as much as its content, not up to the addresses
+    #[test]
+    fn segment_after_zero_filled_memory() {
+        let mut builder = ElfBuilder::new(Encoding::Little, Type::Executable, Machine::X86_64);
+        let text = builder.add_section(
+            b".text",
+            SectionType::ProgramBits,
+            SectionFlags::ALLOC | SectionFlags::EXECINSTR,
+            16,
+            vec![0xc3; 16],
+        );
+        let bss = builder.add_no_bits(
+            b".bss",
+            SectionFlags::ALLOC | SectionFlags::WRITE,
+            8,
+            1 << 40,
+        );
+        builder.set_entry(text, 0);
+        let mut patcher = Patcher::new(builder.build().unwrap()).unwrap();
+        let bss_end = {
+            let elf = patcher.elf().unwrap();
+            let bss = elf.section_info(bss).unwrap().unwrap().header;
+            bss.address + bss.size
+        };
+        let size = patcher.as_slice().len();
+        let address = patcher.add_segment(b"payload", ProgramFlags::READ).unwrap();
+        assert!(address >= bss_end);
+        assert!(patcher.as_slice().len() < size + 0x1000);
+        let elf = patcher.elf().unwrap();
+        assert_eq!(elf.read_virtual(address, 7).unwrap(), b"payload");
+
+        let mut patcher = Patcher::new(executable()).unwrap();
+        let load = first_load(&patcher);
+        patcher.write_u64(load + 0x10, u64::MAX - 0x1000);
+        patcher.write_u64(load + 0x28, 0x10);
+        assert_eq!(
+            patcher.add_segment(b"payload", ProgramFlags::READ),
+            Err(Error::ArithmeticOverflow),
+        );
+    }
+
+    // an executable with a word in `.data` relocated by `.relr.dyn`, and a tag of the
+    // address range in `.dynamic`
+    fn relative_executable() -> (Patcher, usize, usize, usize) {
+        let mut builder = ElfBuilder::new(Encoding::Little, Type::Executable, Machine::X86_64);
+        let text = builder.add_section(
+            b".text",
+            SectionType::ProgramBits,
+            SectionFlags::ALLOC | SectionFlags::EXECINSTR,
+            16,
+            vec![0xc3; 16],
+        );
+        let data = builder.add_section(
+            b".data",
+            SectionType::ProgramBits,
+            SectionFlags::ALLOC | SectionFlags::WRITE,
+            8,
+            vec![0; 16],
+        );
+        let relr = builder.add_section(
+            b".relr.dyn",
+            SectionType::Unknown(SHT_RELR),
+            SectionFlags::ALLOC,
+            8,
+            vec![0; 8],
+        );
+        let mut dynamic = vec![0; 0x20];
+        dynamic[..8].copy_from_slice(&0x6ffffef7_u64.to_le_bytes());
+        dynamic[8..0x10].copy_from_slice(&0x1234_u64.to_le_bytes());
+        let dynamic = builder.add_section(
+            b".dynamic",
+            SectionType::Dynamic,
+            SectionFlags::ALLOC | SectionFlags::WRITE,
+            8,
+            dynamic,
+        );
+        builder.set_entry(text, 0);
+        let mut patcher = Patcher::new(builder.build().unwrap()).unwrap();
+
+        let data = patcher.section_header(data.0 as usize).unwrap();
+        let relr = patcher.section_header(relr.0 as usize).unwrap().offset.0 as usize;
+        let dynamic = patcher.section_header(dynamic.0 as usize).unwrap().offset.0 as usize;
+        patcher.write_u64(relr, data.address.0 + 8);
+        patcher.write_u64(data.offset.0 as usize + 8, data.address.0 + 0x10);
+        (patcher, data.offset.0 as usize + 8, relr, dynamic)
+    }
+
+    #[test]
+    fn rebase_relative() {
+        let (mut patcher, word, relr, dynamic) = relative_executable();
+        let before = [word, relr, dynamic + 8].map(|offset| patcher.read_u64(offset).unwrap());
+        patcher.rebase(0x1000).unwrap();
+        let after = [word, relr, dynamic + 8].map(|offset| patcher.read_u64(offset).unwrap());
+        assert_eq!(after, before.map(|value| value + 0x1000));
+        // the terminator is not a pointer
+        assert_eq!(patcher.read_u64(dynamic + 0x18).unwrap(), 0);
+    }
+
+    #[test]
+    fn rebase_relative_past_the_end() {
+        let (mut patcher, _, relr, _) = relative_executable();
+        patcher.write_u64(relr, 0x7fff_0000);
+        let before = patcher.as_slice().to_vec();
+        assert_eq!(patcher.rebase(0x1000), Err(Error::SliceTooShort));
+        assert_eq!(patcher.as_slice(), &before[..]);
+    }
+
+    #[test]
+    fn update_build_id() {
+        let mut patcher = Patcher::new(crate::tests::executable(Encoding::Little)).unwrap();
+        let id = patcher.update_build_id(Fnv1a::default()).unwrap();
+        assert_eq!(id.len(), 4);
+        let elf = patcher.elf().unwrap();
+        let note = elf
+            .notes()
+            .map(Result::unwrap)
+            .find(|note| note.name == b"GNU" && note.ty == 3)
+            .unwrap();
+        assert_eq!(note.description, &id[..]);
+        // the note is zeroed while hashing, so the id is the same
+        assert_eq!(patcher.update_build_id(Fnv1a::default()), Ok(id));
+
+        let mut patcher = Patcher::new(executable()).unwrap();
+        let before = patcher.as_slice().to_vec();
+        assert_eq!(
+            patcher.update_build_id(Fnv1a::default()),
+            Err(Error::NotPresent),
+        );
+        assert_eq!(patcher.as_slice(), &before[..]);
+    }
+
+    #[test]
+    fn rename_symbol_past_the_end() {
+        let (raw, symtab) = object_with_symbol();
+        let mut patcher = Patcher::new(raw).unwrap();
+        let offset = patcher.section_header_offset(symtab.0).unwrap();
+        patcher.write_u64(offset + 0x20, 0x1000 * SymbolEntry::SIZE as u64);
+        let before = patcher.as_slice().to_vec();
+        assert_eq!(
+            patcher.rename_symbol(symtab, SymbolIndex(0xfff), b"name"),
+            Err(Error::SliceTooShort),
+        );
+        assert_eq!(patcher.as_slice(), &before[..]);
+    }
+}
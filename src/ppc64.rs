@@ -0,0 +1,135 @@
+use super::{
+    Error, SectionIndex, Address, Encoding, Elf64, Entry, Table, Machine, SectionData, SectionType,
+    SymbolType,
+};
+
+// `EF_PPC64_ABI`, 1 is ELFv1, 2 is ELFv2, 0 is unspecified, usually ELFv1
+const EF_PPC64_ABI: u32 = 0x3;
+
+// An element of `.opd` of ELFv1, a function symbol points at it, not at the code
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct FunctionDescriptor {
+    // of the code
+    pub address: Address,
+    pub toc: Address,
+    // the environment pointer, unused by C
+    pub environment: u64,
+}
+
+impl Entry for FunctionDescriptor {
+    type Error = Error;
+
+    const SIZE: usize = 0x18;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        Ok(FunctionDescriptor {
+            address: Address(read_int!(&slice[0x00..], &encoding, u64)),
+            toc: Address(read_int!(&slice[0x08..], &encoding, u64)),
+            environment: read_int!(&slice[0x10..], &encoding, u64),
+        })
+    }
+}
+
+// `.opd`, the descriptors are not relocated, so a relocatable file has zeros there
+#[derive(Clone, Debug)]
+pub struct Opd<'a> {
+    pub address: Address,
+    pub table: Table<'a, FunctionDescriptor>,
+    pub number: usize,
+}
+
+impl<'a> Opd<'a> {
+    // the descriptor at the address, `None` if the address is not of a descriptor
+    pub fn descriptor_at(&self, address: Address) -> Result<Option<FunctionDescriptor>, Error> {
+        let offset = match address.checked_sub(self.address) {
+            Some(offset) if offset % FunctionDescriptor::SIZE as u64 == 0 => offset as usize,
+            _ => return Ok(None),
+        };
+        let index = offset / FunctionDescriptor::SIZE;
+        if index >= self.number {
+            return Ok(None);
+        }
+        self.table.pick(index).map(Some)
+    }
+
+    // the address of the code if the address is of a descriptor, the same address otherwise
+    pub fn code_address(&self, address: Address) -> Result<Address, Error> {
+        Ok(self
+            .descriptor_at(address)?
+            .map(|descriptor| descriptor.address)
+            .unwrap_or(address))
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // `.opd` of an ELFv1 PowerPC64 file, `None` for any other file
+    pub fn opd(&self) -> Result<Option<Opd<'a>>, Error> {
+        if self.machine() != Machine::PowerPC64 || self.flags() & EF_PPC64_ABI == 2 {
+            return Ok(None);
+        }
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            if header.ty != SectionType::ProgramBits {
+                continue;
+            }
+            let section = match self.section(SectionIndex(index))? {
+                Some(section) if section.name == b".opd" => section,
+                _ => continue,
+            };
+            if let SectionData::ProgramBits(slice) = section.data {
+                return Ok(Some(Opd {
+                    address: header.address,
+                    table: Table::new(slice, self.encoding()),
+                    number: slice.len() / FunctionDescriptor::SIZE,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    // The value of `r2` the code of the file expects, the one of the descriptor
+    // for ELFv1, `.TOC.` or 0x8000 past `.got` for ELFv2
+    pub fn toc_base(&self) -> Result<Option<Address>, Error> {
+        if self.machine() != Machine::PowerPC64 {
+            return Ok(None);
+        }
+        if let Some(opd) = self.opd()? {
+            if opd.number != 0 {
+                return opd.table.pick(0).map(|descriptor| Some(descriptor.toc));
+            }
+        }
+        for symbol in self.merged_symbols()? {
+            let symbol = symbol?;
+            if symbol.name == b".TOC." && symbol.entry.is_defined() {
+                return Ok(Some(symbol.entry.value));
+            }
+        }
+        for index in 0..self.section_number() {
+            match self.section_info(SectionIndex(index))? {
+                Some(info) if info.name == b".got" => {
+                    return Ok(Some(info.header.address.saturating_add(0x8000)))
+                }
+                _ => (),
+            }
+        }
+        Ok(None)
+    }
+
+    // the code address of a function symbol, it is the value, or the address
+    // in the descriptor the value points at
+    pub(crate) fn function_address(
+        &self,
+        opd: Option<&Opd<'a>>,
+        ty: &SymbolType,
+        value: Address,
+    ) -> Result<Address, Error> {
+        match opd {
+            Some(opd) if *ty == SymbolType::Function => opd.code_address(value),
+            _ => Ok(value),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use core::ops::Range;
+use alloc::vec::Vec;
+
+use super::{
+    Error, Offset, Address, SectionIndex, Encoding, Elf64, Index, SectionType, ProgramType,
+    ProgramHeader, SectionHeader, Entry,
+};
+use super::header::{Header, PN_XNUM};
+use super::common::{file_slice, saturating_usize};
+
+// `DT_STRTAB`
+const DT_STRTAB: u64 = 5;
+// `DT_STRSZ`
+const DT_STRSZ: u64 = 10;
+// `SHT_RELR`
+const SHT_RELR: u32 = 0x13;
+
+// What an upcoming call reads from the file
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Operation {
+    SectionNames,
+    // `.symtab` and its strings
+    Symbols,
+    // `.dynsym` and its strings
+    DynamicSymbols,
+    // `SHT_DYNAMIC`, or `PT_DYNAMIC`, and its strings
+    Dynamic,
+    Notes,
+    // every relocation section and the symbol tables they refer to
+    Relocations,
+    Section(SectionIndex),
+    Program(usize),
+}
+
+impl<'a> Elf64<'a> {
+    // The header and the header tables, all `Elf64::from_fragments` needs,
+    // from the first `Header::SIZE` bytes of the file. With the extended numbering
+    // a table of an unknown size is not there, the section zero with its size is.
+    pub fn header_ranges(header: &[u8]) -> Result<Vec<Range<Offset>>, Error> {
+        let header = Header::new(header)?;
+        let mut ranges = Vec::new();
+        push(&mut ranges, Offset(0), Header::SIZE as u64);
+        let extended = header.program_header_number == PN_XNUM
+            || header.section_header_number == 0
+            || header.section_names == Index::Extended;
+        if extended && header.section_headers_offset != Offset(0) {
+            push(
+                &mut ranges,
+                header.section_headers_offset,
+                SectionHeader::SIZE as u64,
+            );
+        }
+        if header.program_header_number != 0 && header.program_header_number != PN_XNUM {
+            let size = header.program_header_number as u64 * ProgramHeader::SIZE as u64;
+            push(&mut ranges, header.program_headers_offset, size);
+        }
+        if header.section_header_number != 0 {
+            let size = header.section_header_number as u64 * SectionHeader::SIZE as u64;
+            push(&mut ranges, header.section_headers_offset, size);
+        }
+        Ok(normalize(ranges))
+    }
+
+    // The ranges of the file the operation needs that are not captured yet,
+    // sorted and merged, nothing is missing if it is empty.
+    // Some ranges are found in the data of others, e.g. the strings of `PT_DYNAMIC`,
+    // they are reported after the others are captured and the file is parsed again.
+    pub fn prefetch(&self, operation: Operation) -> Result<Vec<Range<Offset>>, Error> {
+        let mut ranges = Vec::new();
+        match operation {
+            Operation::SectionNames => {
+                if let Index::Regular(index) = self.section_names() {
+                    self.push_section(&mut ranges, index)?;
+                }
+            }
+            Operation::Symbols => self.push_symbols(&mut ranges, SectionType::SymbolTable)?,
+            Operation::DynamicSymbols => {
+                self.push_symbols(&mut ranges, SectionType::DynamicSymbolTable)?
+            }
+            Operation::Dynamic => self.push_dynamic(&mut ranges)?,
+            Operation::Notes => {
+                if self.program_number() != 0 {
+                    self.push_programs(&mut ranges, ProgramType::Note)?;
+                } else {
+                    self.push_sections(&mut ranges, |ty| ty == &SectionType::Note)?;
+                }
+            }
+            Operation::Relocations => {
+                for index in 0..self.section_number() {
+                    let header = self.section_table.pick(index)?;
+                    match header.ty {
+                        SectionType::Rel | SectionType::Rela => {
+                            push(&mut ranges, header.offset, header.size);
+                            if let Index::Regular(link) = header.link {
+                                self.push_symbol_table(&mut ranges, link)?;
+                            }
+                        }
+                        SectionType::Unknown(SHT_RELR) => {
+                            push(&mut ranges, header.offset, header.size)
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            Operation::Section(index) => self.push_section(&mut ranges, index)?,
+            Operation::Program(index) => {
+                if index < self.program_number() {
+                    let header = self.program_table.pick(index)?;
+                    push(&mut ranges, header.file_offset, header.file_size);
+                }
+            }
+        }
+        ranges.retain(|range| {
+            let start = saturating_usize(range.start.0);
+            self.raw
+                .get(start, Some(saturating_usize(range.end.0)))
+                .is_err()
+        });
+        Ok(normalize(ranges))
+    }
+
+    fn push_section(
+        &self,
+        ranges: &mut Vec<Range<Offset>>,
+        index: SectionIndex,
+    ) -> Result<(), Error> {
+        if index.0 < self.section_number() {
+            let header = self.section_table.pick(index.0)?;
+            if header.ty != SectionType::NoBits {
+                push(ranges, header.offset, header.size);
+            }
+        }
+        Ok(())
+    }
+
+    fn push_sections<F>(&self, ranges: &mut Vec<Range<Offset>>, filter: F) -> Result<(), Error>
+    where
+        F: Fn(&SectionType) -> bool,
+    {
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            if filter(&header.ty) {
+                push(ranges, header.offset, header.size);
+            }
+        }
+        Ok(())
+    }
+
+    fn push_programs(&self, ranges: &mut Vec<Range<Offset>>, ty: ProgramType) -> Result<(), Error> {
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            if header.ty == ty {
+                push(ranges, header.file_offset, header.file_size);
+            }
+        }
+        Ok(())
+    }
+
+    fn push_symbol_table(
+        &self,
+        ranges: &mut Vec<Range<Offset>>,
+        index: SectionIndex,
+    ) -> Result<(), Error> {
+        self.push_section(ranges, index)?;
+        if index.0 < self.section_number() {
+            if let Index::Regular(link) = self.section_table.pick(index.0)?.link {
+                self.push_section(ranges, link)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn push_symbols(&self, ranges: &mut Vec<Range<Offset>>, ty: SectionType) -> Result<(), Error> {
+        for index in 0..self.section_number() {
+            if self.section_table.pick(index)?.ty == ty {
+                return self.push_symbol_table(ranges, SectionIndex(index));
+            }
+        }
+        Ok(())
+    }
+
+    fn push_dynamic(&self, ranges: &mut Vec<Range<Offset>>) -> Result<(), Error> {
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            if header.ty == SectionType::Dynamic {
+                push(ranges, header.offset, header.size);
+                if let Index::Regular(link) = header.link {
+                    self.push_section(ranges, link)?;
+                }
+                return Ok(());
+            }
+        }
+
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            if header.ty != ProgramType::Dynamic {
+                continue;
+            }
+            push(ranges, header.file_offset, header.file_size);
+            // the strings are known only when the entries are captured
+            let slice = match file_slice(self.raw, header.file_offset, header.file_size) {
+                Ok(slice) => slice,
+                Err(_) => return Ok(()),
+            };
+            let encoding = self.encoding();
+            let (mut address, mut size) = (None, None);
+            for entry in slice.chunks_exact(0x10) {
+                match read_int!(&entry[0x00..], &encoding, u64) {
+                    0 => break,
+                    DT_STRTAB => address = Some(read_int!(&entry[0x08..], &encoding, u64)),
+                    DT_STRSZ => size = Some(read_int!(&entry[0x08..], &encoding, u64)),
+                    _ => (),
+                }
+            }
+            if let (Some(address), Some(size)) = (address, size) {
+                if let Some(offset) = self.file_offset_of(Address(address))? {
+                    push(ranges, offset, size);
+                }
+            }
+            return Ok(());
+        }
+        Ok(())
+    }
+
+    // where the `PT_LOAD` that maps the address has it in the file
+    fn file_offset_of(&self, address: Address) -> Result<Option<Offset>, Error> {
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            let start = header.virtual_address;
+            if header.ty == ProgramType::Load
+                && start <= address
+                && address < start.saturating_add(header.file_size)
+            {
+                return Ok(Some(header.file_offset.saturating_add(address - start)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn push(ranges: &mut Vec<Range<Offset>>, offset: Offset, size: u64) {
+    if size != 0 {
+        ranges.push(offset..offset.saturating_add(size));
+    }
+}
+
+fn normalize(mut ranges: Vec<Range<Offset>>) -> Vec<Range<Offset>> {
+    ranges.sort_by_key(|range| range.start);
+    let mut merged = Vec::<Range<Offset>>::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
@@ -0,0 +1,82 @@
+use super::{Error, Elf64};
+use super::common::file_slice;
+
+// How much of the file part of a segment, `p_filesz`, the prefix of the file has
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Availability {
+    // a segment without bytes in the file is full as well
+    Full,
+    // the first bytes of the file part
+    Partial(u64),
+    Missing,
+}
+
+impl<'a> Elf64<'a> {
+    // Parses the first bytes of a file that is still arriving, e.g. an image a network
+    // bootloader streams. The header and the program headers must be in the prefix,
+    // `Error::SliceTooShort` until they are, the section headers are attached only once
+    // all of them and the section names arrived, the file has no sections until then.
+    pub fn from_prefix(prefix: &'a [u8]) -> Result<Self, Error> {
+        let elf = Elf64::from_header(prefix)?.with_program_table()?;
+        for index in 0..elf.program_number() {
+            elf.program_table.pick(index)?;
+        }
+        let with_sections = elf.clone().with_section_table().and_then(|elf| {
+            for index in 0..elf.section_number() {
+                elf.section_table.pick(index)?;
+            }
+            elf.with_section_names()
+        });
+        Ok(with_sections.unwrap_or(elf))
+    }
+
+    // `None` only if the index is out of range
+    pub fn availability(&self, index: usize) -> Result<Option<Availability>, Error> {
+        let header = match self.program_info(index)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let available = (self.raw.len() as u64)
+            .saturating_sub(header.file_offset.0)
+            .min(header.file_size);
+        Ok(Some(if available == header.file_size {
+            Availability::Full
+        } else if available == 0 {
+            Availability::Missing
+        } else {
+            Availability::Partial(available)
+        }))
+    }
+
+    // The bytes of the file part of the segment the prefix has, from the start
+    pub fn available_data(&self, index: usize) -> Result<Option<&'a [u8]>, Error> {
+        let header = match self.program_info(index)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let size = match self.availability(index)? {
+            Some(Availability::Full) => header.file_size,
+            Some(Availability::Partial(size)) => size,
+            _ => return Ok(Some(&[])),
+        };
+        file_slice(self.raw, header.file_offset, size).map(Some)
+    }
+
+    // Copies the available bytes of the segment to the start of its memory, `p_memsz`
+    // bytes or fewer, and the zeros past `p_filesz` once the file part is full.
+    // Returns the number of the bytes of the file copied, the copy resumes from there
+    // when more of the file arrives.
+    pub fn load_available(&self, index: usize, memory: &mut [u8]) -> Result<usize, Error> {
+        let header = self.program_info(index)?.ok_or(Error::IndexOutOfBounds)?;
+        let data = self.available_data(index)?.unwrap_or(&[]);
+        let target = memory.get_mut(..data.len()).ok_or(Error::SliceTooShort)?;
+        target.copy_from_slice(data);
+        if data.len() as u64 == header.file_size {
+            let end = (header.memory_size.min(memory.len() as u64)) as usize;
+            if let Some(zeros) = memory.get_mut(data.len()..end) {
+                zeros.iter_mut().for_each(|b| *b = 0);
+            }
+        }
+        Ok(data.len())
+    }
+}
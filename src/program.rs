@@ -1,7 +1,8 @@
 use core::fmt;
-use super::{Address, Offset, Error, Encoding, Entry};
+use super::{Address, Offset, Error, Encoding, Entry, Encode};
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ProgramType {
     Null,
     Load,
@@ -10,6 +11,13 @@ pub enum ProgramType {
     Note,
     Shlib,
     ProgramHeaderTable,
+    // `PT_TLS`
+    Tls,
+    // `PT_GNU_EH_FRAME`, `.eh_frame_hdr`
+    GnuEhFrame,
+    GnuStack,
+    GnuRelro,
+    GnuProperty,
     OsSpecific(u32),
     ProcessorSprcific(u32),
     Unknown(u32),
@@ -25,6 +33,11 @@ impl From<u32> for ProgramType {
             0x00000004 => ProgramType::Note,
             0x00000005 => ProgramType::Shlib,
             0x00000006 => ProgramType::ProgramHeaderTable,
+            0x00000007 => ProgramType::Tls,
+            0x6474e550 => ProgramType::GnuEhFrame,
+            0x6474e551 => ProgramType::GnuStack,
+            0x6474e552 => ProgramType::GnuRelro,
+            0x6474e553 => ProgramType::GnuProperty,
             t @ 0x60000000..=0x6fffffff => ProgramType::OsSpecific(t),
             t @ 0x70000000..=0x7fffffff => ProgramType::ProcessorSprcific(t),
             t => ProgramType::Unknown(t),
@@ -32,15 +45,96 @@ impl From<u32> for ProgramType {
     }
 }
 
+impl From<ProgramType> for u32 {
+    fn from(v: ProgramType) -> Self {
+        match v {
+            ProgramType::Null => 0x00000000,
+            ProgramType::Load => 0x00000001,
+            ProgramType::Dynamic => 0x00000002,
+            ProgramType::Interpreter => 0x00000003,
+            ProgramType::Note => 0x00000004,
+            ProgramType::Shlib => 0x00000005,
+            ProgramType::ProgramHeaderTable => 0x00000006,
+            ProgramType::Tls => 0x00000007,
+            ProgramType::GnuEhFrame => 0x6474e550,
+            ProgramType::GnuStack => 0x6474e551,
+            ProgramType::GnuRelro => 0x6474e552,
+            ProgramType::GnuProperty => 0x6474e553,
+            ProgramType::OsSpecific(t) => t,
+            ProgramType::ProcessorSprcific(t) => t,
+            ProgramType::Unknown(t) => t,
+        }
+    }
+}
+
+// The bits of `PF_MASKOS` and `PF_MASKPROC` are kept as they are, the names are of
+// the operating system or of the machine the file is for, some of them share a bit
 bitflags! {
     pub struct ProgramFlags: u32 {
         const EXECUTE = 0b00000001;
         const WRITE = 0b00000010;
         const READ = 0b00000100;
+        // `PF_PAGEEXEC` and the rest of the PaX markings, in the reserved bits
+        const PAX_PAGEEXEC = 0x00000010;
+        const PAX_NOPAGEEXEC = 0x00000020;
+        const PAX_SEGMEXEC = 0x00000040;
+        const PAX_NOSEGMEXEC = 0x00000080;
+        const PAX_MPROTECT = 0x00000100;
+        const PAX_NOMPROTECT = 0x00000200;
+        const PAX_RANDEXEC = 0x00000400;
+        const PAX_NORANDEXEC = 0x00000800;
+        const PAX_EMUTRAMP = 0x00001000;
+        const PAX_NOEMUTRAMP = 0x00002000;
+        const PAX_RANDMMAP = 0x00004000;
+        const PAX_NORANDMMAP = 0x00008000;
+        // `PF_MASKOS`
+        const MASK_OS = 0x0ff00000;
+        // `PF_HP_*` of HP-UX
+        const HP_PAGE_SIZE = 0x00100000;
+        const HP_FAR_SHARED = 0x00200000;
+        const HP_NEAR_SHARED = 0x00400000;
+        const HP_CODE = 0x01000000;
+        const HP_MODIFY = 0x02000000;
+        const HP_LAZYSWAP = 0x04000000;
+        const HP_SBP = 0x08000000;
+        // `PF_MASKPROC`
+        const MASK_PROCESSOR = 0xf0000000;
+        // `PF_ARM_SB`, `PF_ARM_PI`, `PF_ARM_ABS`
+        const ARM_SB = 0x10000000;
+        const ARM_PI = 0x20000000;
+        const ARM_ABS = 0x40000000;
+        // `PF_MIPS_LOCAL`
+        const MIPS_LOCAL = 0x10000000;
+        // `PF_X86_64_LARGE`
+        const X86_64_LARGE = 0x10000000;
+    }
+}
+
+impl ProgramFlags {
+    // the bits of `PF_MASKOS`
+    pub fn os_bits(&self) -> u32 {
+        self.bits() & Self::MASK_OS.bits()
+    }
+
+    // the bits of `PF_MASKPROC`
+    pub fn processor_bits(&self) -> u32 {
+        self.bits() & Self::MASK_PROCESSOR.bits()
     }
 }
 
-#[derive(Clone, Eq, PartialEq)]
+// the raw bits, bitflags 1 has no support of serde
+#[cfg(feature = "serde")]
+impl serde::Serialize for ProgramFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u32(self.bits())
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ProgramHeader {
     pub ty: ProgramType,
     pub flags: ProgramFlags,
@@ -89,12 +183,30 @@ impl Entry for ProgramHeader {
         Ok(ProgramHeader {
             ty: read_int!(&slice[0x00..], &encoding, u32).into(),
             flags: ProgramFlags::from_bits_truncate(read_int!(&slice[0x04..], &encoding, u32)),
-            file_offset: read_int!(&slice[0x08..], &encoding, u64),
-            virtual_address: read_int!(&slice[0x10..], &encoding, u64),
-            physical_address: read_int!(&slice[0x18..], &encoding, u64),
+            file_offset: Offset(read_int!(&slice[0x08..], &encoding, u64)),
+            virtual_address: Address(read_int!(&slice[0x10..], &encoding, u64)),
+            physical_address: Address(read_int!(&slice[0x18..], &encoding, u64)),
             file_size: read_int!(&slice[0x20..], &encoding, u64),
             memory_size: read_int!(&slice[0x28..], &encoding, u64),
             address_alignment: read_int!(&slice[0x30..], &encoding, u64),
         })
     }
 }
+
+impl Encode for ProgramHeader {
+    fn encode(&self, slice: &mut [u8], encoding: Encoding) -> Result<(), Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        write_int!(&mut slice[0x00..], &encoding, u32, self.ty.clone().into());
+        write_int!(&mut slice[0x04..], &encoding, u32, self.flags.bits());
+        write_int!(&mut slice[0x08..], &encoding, u64, self.file_offset.0);
+        write_int!(&mut slice[0x10..], &encoding, u64, self.virtual_address.0);
+        write_int!(&mut slice[0x18..], &encoding, u64, self.physical_address.0);
+        write_int!(&mut slice[0x20..], &encoding, u64, self.file_size);
+        write_int!(&mut slice[0x28..], &encoding, u64, self.memory_size);
+        write_int!(&mut slice[0x30..], &encoding, u64, self.address_alignment);
+        Ok(())
+    }
+}
@@ -0,0 +1,278 @@
+use super::{
+    Error, Address, Class, Encoding, Type, Machine, Header, ProgramHeader, SectionHeader,
+    SectionType, SectionIndex, Index, Entry, Offset,
+};
+use super::header::{PN_XNUM, extended_names};
+use super::common::to_usize;
+
+// The bytes of a file that is not in memory, a block device, a flash, read by parts.
+// The error of the reader holds the errors of the content as well.
+pub trait ReadAt {
+    type Error: From<Error>;
+
+    // fills the whole buffer with the bytes from the offset, or fails
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+impl ReadAt for [u8] {
+    type Error = Error;
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let start = to_usize(offset)?;
+        let slice = self
+            .get(start..)
+            .and_then(|slice| slice.get(..buf.len()))
+            .ok_or(Error::SliceTooShort)?;
+        buf.clone_from_slice(slice);
+        Ok(())
+    }
+}
+
+impl<R> ReadAt for &R
+where
+    R: ReadAt + ?Sized,
+{
+    type Error = R::Error;
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).read_at(offset, buf)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(v: Error) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, v)
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+impl ReadAt for std::fs::File {
+    type Error = std::io::Error;
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), Self::Error> {
+        std::os::unix::fs::FileExt::read_exact_at(self, buf, offset)
+    }
+}
+
+// The headers of a file parsed by `ReadAt` one entry at a time, into buffers
+// on the stack, what `Elf64::new` does with the whole file in memory
+#[derive(Clone)]
+pub struct Elf64Reader<R> {
+    reader: R,
+    header: Header,
+    program_number: usize,
+    section_number: usize,
+    section_names: Index,
+}
+
+impl<R> Elf64Reader<R>
+where
+    R: ReadAt,
+{
+    // Reads the file header, and the section zero if the numbers are extended
+    pub fn new(reader: R) -> Result<Self, R::Error> {
+        let mut buf = [0; Header::SIZE];
+        reader.read_at(0, &mut buf)?;
+        let header = Header::new(&buf)?;
+
+        let mut elf = Elf64Reader {
+            reader,
+            program_number: header.program_header_number as usize,
+            section_number: header.section_header_number as usize,
+            section_names: header.section_names.clone(),
+            header,
+        };
+        let extended = elf.header.program_header_number == PN_XNUM
+            || elf.header.section_header_number == 0
+            || elf.header.section_names == Index::Extended;
+        if extended && elf.header.section_headers_offset.0 != 0 {
+            let first = elf.read_section_header(0)?;
+            if elf.header.program_header_number == PN_XNUM {
+                elf.program_number = first.info as usize;
+            }
+            if elf.header.section_header_number == 0 {
+                elf.section_number = to_usize(first.size)?;
+            }
+            if elf.header.section_names == Index::Extended {
+                elf.section_names = extended_names(&first);
+            }
+        } else if elf.header.section_names == Index::Extended {
+            elf.section_names = Index::Undefined;
+        }
+        Ok(elf)
+    }
+
+    pub fn class(&self) -> Class {
+        self.header.identifier.class.clone()
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.header.identifier.encoding.clone()
+    }
+
+    pub fn ty(&self) -> Type {
+        self.header.ty.clone()
+    }
+
+    pub fn machine(&self) -> Machine {
+        self.header.machine.clone()
+    }
+
+    pub fn entry(&self) -> Address {
+        self.header.entry
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.header.flags
+    }
+
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    // of the extended numbering if the file has it
+    pub fn program_number(&self) -> usize {
+        self.program_number
+    }
+
+    pub fn section_number(&self) -> usize {
+        self.section_number
+    }
+
+    // `None` only if the index is out of range
+    pub fn program_header(&self, index: usize) -> Result<Option<ProgramHeader>, R::Error> {
+        if index >= self.program_number {
+            return Ok(None);
+        }
+        let offset = entry_offset(
+            self.header.program_headers_offset,
+            index,
+            ProgramHeader::SIZE,
+        )?;
+        let mut buf = [0; ProgramHeader::SIZE];
+        self.reader.read_at(offset, &mut buf)?;
+        Ok(Some(ProgramHeader::new(&buf, self.encoding())?))
+    }
+
+    // `None` only if the index is out of range
+    pub fn section_header(&self, index: SectionIndex) -> Result<Option<SectionHeader>, R::Error> {
+        if index.0 >= self.section_number {
+            return Ok(None);
+        }
+        self.read_section_header(index.0).map(Some)
+    }
+
+    fn read_section_header(&self, index: usize) -> Result<SectionHeader, R::Error> {
+        let offset = entry_offset(
+            self.header.section_headers_offset,
+            index,
+            SectionHeader::SIZE,
+        )?;
+        let mut buf = [0; SectionHeader::SIZE];
+        self.reader.read_at(offset, &mut buf)?;
+        Ok(SectionHeader::new(&buf, self.encoding())?)
+    }
+
+    pub fn program_headers(&self) -> impl Iterator<Item = Result<ProgramHeader, R::Error>> + '_ {
+        (0..self.program_number).filter_map(move |index| self.program_header(index).transpose())
+    }
+
+    pub fn section_headers(&self) -> impl Iterator<Item = Result<SectionHeader, R::Error>> + '_ {
+        (0..self.section_number)
+            .filter_map(move |index| self.section_header(SectionIndex(index)).transpose())
+    }
+
+    // The name of the section in `e_shstrndx`, read into the buffer, a name longer
+    // than the buffer is `Error::SliceTooShort`
+    pub fn section_name<'b>(
+        &self,
+        header: &SectionHeader,
+        buf: &'b mut [u8],
+    ) -> Result<&'b [u8], R::Error> {
+        let names = match &self.section_names {
+            Index::Regular(index) => self
+                .section_header(*index)?
+                .ok_or(Error::IndexOutOfBounds)?,
+            _ => return Err(Error::NotPresent.into()),
+        };
+        if names.ty != SectionType::StringTable {
+            return Err(Error::UnexpectedSectionType.into());
+        }
+        let start = u64::from(header.name);
+        let available = names.size.checked_sub(start).ok_or(Error::SliceTooShort)?;
+        let length = buf.len().min(to_usize(available)?);
+        let offset = names
+            .offset
+            .0
+            .checked_add(start)
+            .ok_or(Error::ArithmeticOverflow)?;
+        let buf = &mut buf[..length];
+        self.reader.read_at(offset, buf)?;
+        match buf.iter().position(|byte| *byte == 0) {
+            Some(end) => Ok(&buf[..end]),
+            None => Err(Error::SliceTooShort.into()),
+        }
+    }
+
+    // The content of the section, or of its part from `offset`, read into the buffer,
+    // as much as the buffer holds, returns the number of the bytes read
+    pub fn read_section(
+        &self,
+        header: &SectionHeader,
+        offset: u64,
+        buf: &mut [u8],
+    ) -> Result<usize, R::Error> {
+        if header.ty == SectionType::NoBits {
+            return Err(Error::UnexpectedSectionType.into());
+        }
+        read_part(&self.reader, header.offset, header.size, offset, buf)
+    }
+
+    // The part of the segment in the file, as `read_section`
+    pub fn read_program(
+        &self,
+        header: &ProgramHeader,
+        offset: u64,
+        buf: &mut [u8],
+    ) -> Result<usize, R::Error> {
+        read_part(
+            &self.reader,
+            header.file_offset,
+            header.file_size,
+            offset,
+            buf,
+        )
+    }
+}
+
+fn entry_offset(table: Offset, index: usize, size: usize) -> Result<u64, Error> {
+    (index as u64)
+        .checked_mul(size as u64)
+        .and_then(|offset| table.0.checked_add(offset))
+        .ok_or(Error::ArithmeticOverflow)
+}
+
+fn read_part<R>(
+    reader: &R,
+    start: Offset,
+    size: u64,
+    offset: u64,
+    buf: &mut [u8],
+) -> Result<usize, R::Error>
+where
+    R: ReadAt + ?Sized,
+{
+    let available = size.checked_sub(offset).ok_or(Error::SliceTooShort)?;
+    let length = buf.len().min(to_usize(available)?);
+    let position = start
+        .0
+        .checked_add(offset)
+        .ok_or(Error::ArithmeticOverflow)?;
+    reader.read_at(position, &mut buf[..length])?;
+    Ok(length)
+}
@@ -1,10 +1,18 @@
-use super::{Address, Error, Encoding, Entry};
+use core::ops::Range;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+use super::{
+    Address, SectionIndex, SymbolIndex, Error, Encoding, Entry, Encode, Elf64, SectionType,
+    SectionFlags, SectionData, WordTable,
+};
+#[cfg(feature = "alloc")]
+use super::SectionHeader;
+use super::common::saturating_usize;
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct RelEntry {
-    address: Address,
-    symbol_index: u32,
-    relocation_type: u32,
+    pub address: Address,
+    pub symbol_index: SymbolIndex,
+    pub relocation_type: u32,
 }
 
 impl Entry for RelEntry {
@@ -19,17 +27,30 @@ impl Entry for RelEntry {
 
         let temp = read_int!(&slice[0x08..], &encoding, u64);
         Ok(RelEntry {
-            address: read_int!(&slice[0x00..], &encoding, u64),
-            symbol_index: (temp / 0x100000000) as u32,
+            address: Address(read_int!(&slice[0x00..], &encoding, u64)),
+            symbol_index: ((temp >> 32) as u32).into(),
             relocation_type: (temp & 0xffffffff) as u32,
         })
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl Encode for RelEntry {
+    fn encode(&self, slice: &mut [u8], encoding: Encoding) -> Result<(), Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let temp = ((self.symbol_index.0 as u64) << 32) | (self.relocation_type as u64);
+        write_int!(&mut slice[0x00..], &encoding, u64, self.address.0);
+        write_int!(&mut slice[0x08..], &encoding, u64, temp);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct RelaEntry {
     pub address: Address,
-    pub symbol_index: u32,
+    pub symbol_index: SymbolIndex,
     pub relocation_type: u32,
     pub addend: i64,
 }
@@ -46,10 +67,307 @@ impl Entry for RelaEntry {
 
         let temp = read_int!(&slice[0x08..], &encoding, u64);
         Ok(RelaEntry {
-            address: read_int!(&slice[0x00..], &encoding, u64),
-            symbol_index: (temp / 0x100000000) as u32,
+            address: Address(read_int!(&slice[0x00..], &encoding, u64)),
+            symbol_index: ((temp >> 32) as u32).into(),
             relocation_type: (temp & 0xffffffff) as u32,
             addend: read_int!(&slice[0x10..], &encoding, i64),
         })
     }
 }
+
+impl Encode for RelaEntry {
+    fn encode(&self, slice: &mut [u8], encoding: Encoding) -> Result<(), Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let temp = ((self.symbol_index.0 as u64) << 32) | (self.relocation_type as u64);
+        write_int!(&mut slice[0x00..], &encoding, u64, self.address.0);
+        write_int!(&mut slice[0x08..], &encoding, u64, temp);
+        write_int!(&mut slice[0x10..], &encoding, i64, self.addend);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Elf64<'a> {
+    // every entry of every `SHT_RELA` section, with the section and the entry index
+    pub(crate) fn visit_rela<F>(&self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(usize, &SectionHeader, usize, RelaEntry) -> Result<(), Error>,
+    {
+        for section in 0..self.section_number() {
+            let header = self.section_table.pick(section)?;
+            if header.ty != SectionType::Rela {
+                continue;
+            }
+            let table = match self.section(SectionIndex(section))?.map(|s| s.data) {
+                Some(SectionData::Rela { table, .. }) => table,
+                _ => continue,
+            };
+            for index in 0..(saturating_usize(header.size) / RelaEntry::SIZE) {
+                f(section, &header, index, table.pick(index)?)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// `SHT_RELR`, relative relocations packed as addresses and bitmaps
+const SHT_RELR: u32 = 0x13;
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum Relocation {
+    Rel(RelEntry),
+    Rela(RelaEntry),
+    // from `SHT_RELR`, the base address is added to the word at the address
+    Relative(Address),
+}
+
+impl Relocation {
+    // where the relocation writes
+    pub fn address(&self) -> Address {
+        match self {
+            Relocation::Rel(entry) => entry.address,
+            Relocation::Rela(entry) => entry.address,
+            Relocation::Relative(address) => *address,
+        }
+    }
+
+    // `None` for `SHT_RELR`, it is the relative type of the machine
+    pub fn relocation_type(&self) -> Option<u32> {
+        self.any().map(|relocation| relocation.relocation_type)
+    }
+
+    // `None` for `SHT_RELR`
+    pub fn any(&self) -> Option<AnyRelocation> {
+        match self {
+            Relocation::Rel(entry) => Some(entry.clone().into()),
+            Relocation::Rela(entry) => Some(entry.clone().into()),
+            Relocation::Relative(_) => None,
+        }
+    }
+}
+
+// A `RelEntry` or a `RelaEntry` for the code that handles both, e.g. a file with
+// `.rel.dyn` and `.rela.plt`, or a machine that uses either. `addend` is `None`
+// for `SHT_REL`, the addend is then in the place the relocation writes to.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct AnyRelocation {
+    pub address: Address,
+    pub symbol_index: SymbolIndex,
+    pub relocation_type: u32,
+    pub addend: Option<i64>,
+}
+
+impl AnyRelocation {
+    // the explicit addend, or the one read from the place of `SHT_REL`
+    pub fn addend_or(&self, implicit: i64) -> i64 {
+        self.addend.unwrap_or(implicit)
+    }
+
+    pub fn has_implicit_addend(&self) -> bool {
+        self.addend.is_none()
+    }
+}
+
+impl From<RelEntry> for AnyRelocation {
+    fn from(v: RelEntry) -> Self {
+        AnyRelocation {
+            address: v.address,
+            symbol_index: v.symbol_index,
+            relocation_type: v.relocation_type,
+            addend: None,
+        }
+    }
+}
+
+impl From<RelaEntry> for AnyRelocation {
+    fn from(v: RelaEntry) -> Self {
+        AnyRelocation {
+            address: v.address,
+            symbol_index: v.symbol_index,
+            relocation_type: v.relocation_type,
+            addend: Some(v.addend),
+        }
+    }
+}
+
+// Relocations of every relocation section that applies to a section, or of all of them,
+// with the index of the relocation section, its link is the symbol table. A corrupted
+// entry is an error and the next one follows, a corrupted section is skipped.
+#[derive(Clone)]
+pub struct SectionRelocations<'a> {
+    elf: Elf64<'a>,
+    // `None` is every section
+    target: Option<usize>,
+    // the address range of the target, `SHT_RELR` does not name a section
+    range: Range<Address>,
+    section: usize,
+    entry: usize,
+    // the position in the current `SHT_RELR` bitmap word, and the address of its first bit
+    bit: u32,
+    base: Address,
+}
+
+impl<'a> SectionRelocations<'a> {
+    fn advance(&mut self) {
+        self.section += 1;
+        self.entry = 0;
+        self.bit = 0;
+    }
+
+    fn next_relative(&mut self, slice: &[u8]) -> Option<Address> {
+        let words = WordTable::<u64>::new(slice, self.elf.encoding());
+        next_relative(&words, &mut self.entry, &mut self.bit, &mut self.base)
+    }
+}
+
+// the next address of `SHT_RELR` from the word at `entry`, the bit of the bitmap
+// the last address was, and the address of the first bit
+pub(crate) fn next_relative(
+    words: &WordTable<'_, u64>,
+    entry: &mut usize,
+    bit: &mut u32,
+    base: &mut Address,
+) -> Option<Address> {
+    loop {
+        let word = words.get(*entry)?;
+        if word & 1 == 0 {
+            *entry += 1;
+            *base = Address(word.wrapping_add(8));
+            return Some(Address(word));
+        }
+        while *bit < 63 {
+            *bit += 1;
+            if (word >> *bit) & 1 != 0 {
+                return Some(base.wrapping_add((*bit as u64 - 1) * 8));
+            }
+        }
+        *entry += 1;
+        *bit = 0;
+        *base = base.wrapping_add(63 * 8);
+    }
+}
+
+impl<'a> Iterator for SectionRelocations<'a> {
+    type Item = Result<(usize, Relocation), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.section < self.elf.section_number() {
+            let section = self.section;
+            let header = match self.elf.section_table.pick(section) {
+                Ok(header) => header,
+                Err(error) => {
+                    self.advance();
+                    return Some(Err(error));
+                }
+            };
+            // zero is no section, e.g. `.rela.dyn`
+            let targets = match self.target {
+                Some(target) => header.info != 0 && header.info as usize == target,
+                None => true,
+            };
+            let data = match header.ty {
+                SectionType::Rel | SectionType::Rela if targets => {
+                    self.elf.section(SectionIndex(section))
+                }
+                SectionType::Unknown(SHT_RELR) => self.elf.section(SectionIndex(section)),
+                _ => {
+                    self.advance();
+                    continue;
+                }
+            };
+            let data = match data {
+                Ok(section) => section.map(|s| s.data),
+                Err(error) => {
+                    self.advance();
+                    return Some(Err(error));
+                }
+            };
+            let index = self.entry;
+            let relocation = match data {
+                Some(SectionData::Rel { table, .. }) => {
+                    let number = saturating_usize(header.size) / RelEntry::SIZE;
+                    if index < number {
+                        self.entry += 1;
+                        Some(table.pick(index).map(Relocation::Rel))
+                    } else {
+                        None
+                    }
+                }
+                Some(SectionData::Rela { table, .. }) => {
+                    let number = saturating_usize(header.size) / RelaEntry::SIZE;
+                    if index < number {
+                        self.entry += 1;
+                        Some(table.pick(index).map(Relocation::Rela))
+                    } else {
+                        None
+                    }
+                }
+                Some(SectionData::Unknown { slice, .. }) => loop {
+                    match self.next_relative(slice) {
+                        Some(address) if self.range.contains(&address) => {
+                            break Some(Ok(Relocation::Relative(address)))
+                        }
+                        Some(_) => (),
+                        None => break None,
+                    }
+                },
+                _ => None,
+            };
+            match relocation {
+                Some(relocation) => return Some(relocation.map(|r| (section, r))),
+                None => self.advance(),
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Every `SHT_REL` and `SHT_RELA` section with `sh_info` of the section,
+    // and the `SHT_RELR` entries in its address range, one after another
+    pub fn relocations_for_section(
+        &self,
+        index: SectionIndex,
+    ) -> Result<SectionRelocations<'a>, Error> {
+        if index.0 >= self.section_number() {
+            return Err(Error::IndexOutOfBounds);
+        }
+        let header = self.section_table.pick(index.0)?;
+        // a section that is not loaded has no address, nothing `SHT_RELR` could name
+        let range = if header.flags.contains(SectionFlags::ALLOC) {
+            header.address..header.address.saturating_add(header.size)
+        } else {
+            Address(0)..Address(0)
+        };
+        Ok(SectionRelocations {
+            elf: self.clone(),
+            target: Some(index.0),
+            range,
+            section: 0,
+            entry: 0,
+            bit: 0,
+            base: Address(0),
+        })
+    }
+
+    // Every `SHT_REL`, `SHT_RELA` and `SHT_RELR` entry of the file
+    pub fn relocations(&self) -> SectionRelocations<'a> {
+        self.relocations_from(0)
+    }
+
+    // the entries of the relocation sections from the given one
+    pub(crate) fn relocations_from(&self, section: usize) -> SectionRelocations<'a> {
+        SectionRelocations {
+            elf: self.clone(),
+            target: None,
+            range: Address(0)..Address::MAX,
+            section,
+            entry: 0,
+            bit: 0,
+            base: Address(0),
+        }
+    }
+}
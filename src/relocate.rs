@@ -0,0 +1,152 @@
+use core::convert::TryFrom;
+
+use super::{
+    Error, Address, Encoding, Machine, Elf64, Entry, Symbol, SymbolEntry, SymbolBinding,
+    DynamicTag, Relocation, RelocationKind, X86_64Relocation, AArch64Relocation, Formula,
+    FormulaInputs, Operand,
+};
+
+// The memory a file is loaded in, writable, from `address` on, the file is loaded
+// at `bias`, zero for `ET_EXEC`
+pub struct MappedImage<'m> {
+    pub data: &'m mut [u8],
+    pub address: Address,
+    pub bias: Address,
+}
+
+impl<'m> MappedImage<'m> {
+    // the word at the run time address
+    fn place(&mut self, address: Address) -> Result<&mut [u8], Error> {
+        let offset = address
+            .0
+            .checked_sub(self.address.0)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .ok_or(Error::SliceTooShort)?;
+        self.data
+            .get_mut(offset..)
+            .and_then(|data| data.get_mut(..8))
+            .ok_or(Error::SliceTooShort)
+    }
+}
+
+// the relocation types of a dynamic loader, `None` for those that do nothing
+fn formula(machine: &Machine, ty: u32) -> Result<Option<Formula>, Error> {
+    let kind = RelocationKind::new(machine, ty);
+    match kind {
+        RelocationKind::X86_64(X86_64Relocation::None)
+        | RelocationKind::AArch64(AArch64Relocation::None) => Ok(None),
+        RelocationKind::X86_64(X86_64Relocation::Relative)
+        | RelocationKind::X86_64(X86_64Relocation::GlobalData)
+        | RelocationKind::X86_64(X86_64Relocation::JumpSlot)
+        | RelocationKind::X86_64(X86_64Relocation::Absolute64)
+        | RelocationKind::AArch64(AArch64Relocation::Relative)
+        | RelocationKind::AArch64(AArch64Relocation::GlobalData)
+        | RelocationKind::AArch64(AArch64Relocation::JumpSlot)
+        | RelocationKind::AArch64(AArch64Relocation::Absolute64) => Ok(kind.formula()),
+        RelocationKind::X86_64(_) | RelocationKind::AArch64(_) => {
+            Err(Error::UnsupportedRelocation(ty))
+        }
+        _ => Err(Error::UnsupportedMachine),
+    }
+}
+
+// Applies a relocation of the dynamic loader to the image, `R_*_RELATIVE`,
+// `R_*_GLOB_DAT`, `R_*_JUMP_SLOT` and `R_*_64`, of x86-64 and AArch64.
+// `symbol` is the run time address of the symbol of the relocation, the addend
+// of `Relocation::Rel` is the word in the image.
+pub fn apply_dynamic_relocation(
+    image: &mut MappedImage<'_>,
+    relocation: &Relocation,
+    symbol: Address,
+    machine: &Machine,
+    encoding: &Encoding,
+) -> Result<(), Error> {
+    let bias = image.bias;
+    let place = image.place(bias.wrapping_add(relocation.address().0))?;
+    let implicit = read_int!(place, encoding, u64) as i64;
+    let (formula, addend) = match relocation {
+        Relocation::Rela(entry) => (formula(machine, entry.relocation_type)?, entry.addend),
+        Relocation::Rel(entry) => (formula(machine, entry.relocation_type)?, implicit),
+        Relocation::Relative(_) => (Some(Formula::RELATIVE), implicit),
+    };
+    let formula = match formula {
+        Some(formula) => formula,
+        None => return Ok(()),
+    };
+    let value = formula.evaluate(&FormulaInputs {
+        s: Some(symbol.0),
+        a: Some(addend),
+        b: Some(bias.0),
+        ..FormulaInputs::default()
+    })?;
+    write_int!(place, encoding, u64, value);
+    Ok(())
+}
+
+impl<'a> Elf64<'a> {
+    // The entry of `DT_SYMTAB` with its name in `DT_STRTAB`, found by the dynamic
+    // tags only, the table has no size, the index is not checked against it
+    pub(crate) fn dynamic_symbol(&self, index: usize) -> Result<Symbol<'a>, Error> {
+        let source = self.dynamic_source()?.ok_or(Error::NotPresent)?;
+        let table = source
+            .value(DynamicTag::SymbolTable)?
+            .ok_or(Error::NotPresent)?;
+        let size = source
+            .value(DynamicTag::SymbolEntrySize)?
+            .unwrap_or(SymbolEntry::SIZE as u64);
+        if size < SymbolEntry::SIZE as u64 {
+            return Err(Error::SliceTooShort);
+        }
+        let address = (index as u64)
+            .checked_mul(size)
+            .and_then(|offset| table.checked_add(offset))
+            .ok_or(Error::ArithmeticOverflow)?;
+        let slice = self.virtual_slice(Address(address), SymbolEntry::SIZE as u64)?;
+        let entry = SymbolEntry::new(slice, self.encoding())?;
+        let name = source.string(u64::from(entry.name))?;
+        Ok(Symbol { name, entry })
+    }
+
+    // Applies the relocations of `DT_RELR`, `DT_REL`, `DT_RELA` and `DT_JMPREL` to
+    // the image, what a loader does before it runs the code, the lazy binding is not
+    // supported. The resolver gives the run time address of the symbol, of a symbol
+    // the file defines as well, the order of the lookup is of the caller. An undefined
+    // weak symbol the resolver does not find is zero. Returns the number of the
+    // relocations applied.
+    pub fn relocate_image<F>(
+        &self,
+        image: &mut MappedImage<'_>,
+        mut resolve: F,
+    ) -> Result<usize, Error>
+    where
+        F: FnMut(&Symbol<'a>) -> Option<Address>,
+    {
+        let machine = self.machine();
+        let encoding = self.encoding();
+        let mut number = 0;
+        for relocation in self.dynamic_relocations()? {
+            let (_, relocation) = relocation?;
+            let symbol = match relocation.any() {
+                Some(any) if any.symbol_index.0 != 0 => {
+                    match formula(&machine, any.relocation_type)? {
+                        Some(f) if f.operands().any(|o| o == Operand::Symbol) => {
+                            let symbol = self.dynamic_symbol(any.symbol_index.0)?;
+                            match resolve(&symbol) {
+                                Some(address) => address,
+                                None if symbol.entry.info.binding == SymbolBinding::Weak => {
+                                    Address(0)
+                                }
+                                None => return Err(Error::UnresolvedSymbol),
+                            }
+                        }
+                        _ => Address(0),
+                    }
+                }
+                _ => Address(0),
+            };
+            apply_dynamic_relocation(image, &relocation, symbol, &machine, &encoding)?;
+            number += 1;
+        }
+        Ok(number)
+    }
+}
@@ -0,0 +1,269 @@
+use core::fmt;
+
+use super::{Error, RelocationKind, X86_64Relocation, AArch64Relocation, RiscVRelocation};
+
+use self::Operand::{Symbol, Addend, Place, Base, GotEntry, Got, Plt, Size};
+use self::Term::{Add, Subtract};
+
+// An operand of a relocation calculation, the letter of the ABI documents
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Operand {
+    // `S`, the value of the symbol
+    Symbol,
+    // `A`, the addend
+    Addend,
+    // `P`, the address of the place being relocated
+    Place,
+    // `B`, the load address of the object
+    Base,
+    // `G`, the offset of the GOT entry of the symbol in the GOT
+    GotEntry,
+    // `GOT`, the address of the GOT
+    Got,
+    // `L`, the address of the PLT entry of the symbol
+    Plt,
+    // `Z`, the size of the symbol
+    Size,
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operand::Symbol => "S",
+            Operand::Addend => "A",
+            Operand::Place => "P",
+            Operand::Base => "B",
+            Operand::GotEntry => "G",
+            Operand::Got => "GOT",
+            Operand::Plt => "L",
+            Operand::Size => "Z",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Term {
+    Add(Operand),
+    Subtract(Operand),
+}
+
+// The value a relocation computes, before it is fitted into the field of the place.
+// The field, its width, the overflow check and the bits of an instruction it takes,
+// is up to the relocation engine.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Formula {
+    pub terms: &'static [Term],
+    // `Page(..) - Page(P)` of AArch64, the sum of the other terms and `P` are
+    // rounded down to 4 KiB before the subtraction
+    pub page: bool,
+}
+
+// The values of the operands, `None` for those not known
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct FormulaInputs {
+    pub s: Option<u64>,
+    pub a: Option<i64>,
+    pub p: Option<u64>,
+    pub b: Option<u64>,
+    pub g: Option<u64>,
+    pub got: Option<u64>,
+    pub l: Option<u64>,
+    pub z: Option<u64>,
+}
+
+impl FormulaInputs {
+    fn value(&self, operand: Operand) -> Result<u64, Error> {
+        match operand {
+            Operand::Symbol => self.s,
+            Operand::Addend => self.a.map(|a| a as u64),
+            Operand::Place => self.p,
+            Operand::Base => self.b,
+            Operand::GotEntry => self.g,
+            Operand::Got => self.got,
+            Operand::Plt => self.l,
+            Operand::Size => self.z,
+        }
+        .ok_or(Error::MissingOperand(operand))
+    }
+}
+
+const fn formula(terms: &'static [Term]) -> Formula {
+    Formula { terms, page: false }
+}
+
+const PAGE_MASK: u64 = !0xfff;
+
+impl Formula {
+    // `S`
+    pub const SYMBOL: Formula = formula(&[Add(Symbol)]);
+    // `S + A`
+    pub const ABSOLUTE: Formula = formula(&[Add(Symbol), Add(Addend)]);
+    // `S + A - P`
+    pub const PC_RELATIVE: Formula = formula(&[Add(Symbol), Add(Addend), Subtract(Place)]);
+    // `B + A`, of `R_*_RELATIVE` and of `SHT_RELR`
+    pub const RELATIVE: Formula = formula(&[Add(Base), Add(Addend)]);
+
+    // the operands of the terms, in order
+    pub fn operands(&self) -> impl Iterator<Item = Operand> + '_ {
+        self.terms.iter().map(|term| match term {
+            Add(operand) | Subtract(operand) => *operand,
+        })
+    }
+
+    // Wrapping, the result of a subtraction of a larger value is the two's
+    // complement of the distance, `Error::MissingOperand` if an input is `None`
+    pub fn evaluate(&self, inputs: &FormulaInputs) -> Result<u64, Error> {
+        let mut sum = 0u64;
+        let mut place = 0u64;
+        for term in self.terms {
+            match term {
+                Subtract(Place) if self.page => place = inputs.value(Place)?,
+                Add(operand) => sum = sum.wrapping_add(inputs.value(*operand)?),
+                Subtract(operand) => sum = sum.wrapping_sub(inputs.value(*operand)?),
+            }
+        }
+        if self.page {
+            Ok((sum & PAGE_MASK).wrapping_sub(place & PAGE_MASK))
+        } else {
+            Ok(sum)
+        }
+    }
+}
+
+// `S + A - P`, or `Page(S + A) - Page(P)`
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.page {
+            f.write_str("Page(")?;
+        }
+        let mut first = true;
+        for term in self.terms {
+            match (term, first) {
+                (Subtract(Place), _) if self.page => continue,
+                (Add(operand), true) => write!(f, "{}", operand)?,
+                (Subtract(operand), true) => write!(f, "-{}", operand)?,
+                (Add(operand), false) => write!(f, " + {}", operand)?,
+                (Subtract(operand), false) => write!(f, " - {}", operand)?,
+            }
+            first = false;
+        }
+        if self.page {
+            f.write_str(") - Page(P)")?;
+        }
+        Ok(())
+    }
+}
+
+impl RelocationKind {
+    // The calculation of the psABI of the machine, `None` for the relocations that
+    // compute nothing, `R_*_NONE` and `R_*_COPY`, for the TLS descriptors and models
+    // the linker rewrites, and for those not listed. The value of a TLS symbol in
+    // `R_X86_64_DTPOFF64`, `R_AARCH64_TLS_DTPREL` and the others like them is its
+    // offset in the block of the module. A new machine is one more match of
+    // its relocation types here.
+    pub fn formula(&self) -> Option<Formula> {
+        match self {
+            RelocationKind::X86_64(ty) => x86_64(*ty),
+            RelocationKind::AArch64(ty) => aarch64(*ty),
+            RelocationKind::RiscV(ty) => riscv(*ty),
+            RelocationKind::Unknown(_) => None,
+        }
+    }
+}
+
+fn x86_64(ty: X86_64Relocation) -> Option<Formula> {
+    use super::X86_64Relocation as R;
+
+    match ty {
+        R::Absolute64
+        | R::Absolute32
+        | R::Absolute32Signed
+        | R::Absolute16
+        | R::Absolute8
+        | R::DtpOff64
+        | R::DtpOff32 => Some(Formula::ABSOLUTE),
+        R::Pc32 | R::Pc16 | R::Pc8 | R::Pc64 => Some(Formula::PC_RELATIVE),
+        R::Got32 => Some(formula(&[Add(GotEntry), Add(Addend)])),
+        R::Plt32 => Some(formula(&[Add(Plt), Add(Addend), Subtract(Place)])),
+        R::GlobalData | R::JumpSlot => Some(Formula::SYMBOL),
+        // the resolver at `B + A` computes the value of `R_X86_64_IRELATIVE`
+        R::Relative | R::IRelative => Some(Formula::RELATIVE),
+        R::GotPcRel | R::GotPcRelX | R::RexGotPcRelX => Some(formula(&[
+            Add(GotEntry),
+            Add(Got),
+            Add(Addend),
+            Subtract(Place),
+        ])),
+        R::GotOff64 => Some(formula(&[Add(Symbol), Add(Addend), Subtract(Got)])),
+        R::GotPc32 => Some(formula(&[Add(Got), Add(Addend), Subtract(Place)])),
+        R::Size32 | R::Size64 => Some(formula(&[Add(Size), Add(Addend)])),
+        _ => None,
+    }
+}
+
+fn aarch64(ty: AArch64Relocation) -> Option<Formula> {
+    use super::AArch64Relocation as R;
+
+    match ty {
+        R::Absolute64
+        | R::Absolute32
+        | R::Absolute16
+        | R::AddAbsoluteLow12
+        | R::LoadStore8AbsoluteLow12
+        | R::LoadStore16AbsoluteLow12
+        | R::LoadStore32AbsoluteLow12
+        | R::LoadStore64AbsoluteLow12
+        | R::LoadStore128AbsoluteLow12
+        | R::GlobalData
+        | R::JumpSlot
+        | R::TlsDtpRel => Some(Formula::ABSOLUTE),
+        R::Prel64 | R::Prel32 | R::Prel16 | R::Jump26 | R::Call26 => Some(Formula::PC_RELATIVE),
+        R::AdrPrelPageHigh21 => Some(Formula {
+            page: true,
+            ..Formula::PC_RELATIVE
+        }),
+        // the address of the GOT entry, `G(GDAT(S + A))`
+        R::AdrGotPage => Some(Formula {
+            terms: &[Add(Got), Add(GotEntry), Subtract(Place)],
+            page: true,
+        }),
+        R::Load64GotLow12 => Some(formula(&[Add(Got), Add(GotEntry)])),
+        R::Relative | R::IRelative => Some(Formula::RELATIVE),
+        _ => None,
+    }
+}
+
+fn riscv(ty: RiscVRelocation) -> Option<Formula> {
+    use super::RiscVRelocation as R;
+
+    match ty {
+        R::Absolute32
+        | R::Absolute64
+        | R::High20
+        | R::Low12I
+        | R::Low12S
+        | R::Set6
+        | R::Set8
+        | R::Set16
+        | R::Set32
+        | R::TlsDtpRel32
+        | R::TlsDtpRel64 => Some(Formula::ABSOLUTE),
+        R::Branch
+        | R::Jal
+        | R::RvcBranch
+        | R::RvcJump
+        | R::PcRelHigh20
+        | R::Set32PcRel
+        | R::Call => Some(Formula::PC_RELATIVE),
+        R::CallPlt => Some(formula(&[Add(Plt), Add(Addend), Subtract(Place)])),
+        R::GotHigh20 => Some(formula(&[
+            Add(Got),
+            Add(GotEntry),
+            Add(Addend),
+            Subtract(Place),
+        ])),
+        R::JumpSlot => Some(Formula::SYMBOL),
+        R::Relative | R::IRelative => Some(Formula::RELATIVE),
+        _ => None,
+    }
+}
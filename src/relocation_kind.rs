@@ -0,0 +1,258 @@
+use super::{Machine, RelEntry, RelaEntry, AnyRelocation};
+
+macro_rules! relocation_types {
+    ($name:ident { $($(#[$meta:meta])* $variant:ident = $value:expr,)* }) => {
+        #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+        pub enum $name {
+            $($(#[$meta])* $variant,)*
+            Unknown(u32),
+        }
+
+        impl From<u32> for $name {
+            fn from(v: u32) -> Self {
+                match v {
+                    $($value => $name::$variant,)*
+                    t => $name::Unknown(t),
+                }
+            }
+        }
+
+        impl From<$name> for u32 {
+            fn from(v: $name) -> Self {
+                match v {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(t) => t,
+                }
+            }
+        }
+    };
+}
+
+relocation_types!(X86_64Relocation {
+    None = 0,
+    // `R_X86_64_64`
+    Absolute64 = 1,
+    Pc32 = 2,
+    Got32 = 3,
+    Plt32 = 4,
+    Copy = 5,
+    // `R_X86_64_GLOB_DAT`
+    GlobalData = 6,
+    JumpSlot = 7,
+    Relative = 8,
+    GotPcRel = 9,
+    // `R_X86_64_32`
+    Absolute32 = 10,
+    // `R_X86_64_32S`
+    Absolute32Signed = 11,
+    Absolute16 = 12,
+    Pc16 = 13,
+    Absolute8 = 14,
+    Pc8 = 15,
+    DtpMod64 = 16,
+    DtpOff64 = 17,
+    TpOff64 = 18,
+    TlsGd = 19,
+    TlsLd = 20,
+    DtpOff32 = 21,
+    GotTpOff = 22,
+    TpOff32 = 23,
+    Pc64 = 24,
+    GotOff64 = 25,
+    GotPc32 = 26,
+    Size32 = 32,
+    Size64 = 33,
+    GotPc32TlsDesc = 34,
+    TlsDescCall = 35,
+    TlsDesc = 36,
+    IRelative = 37,
+    GotPcRelX = 41,
+    RexGotPcRelX = 42,
+});
+
+relocation_types!(AArch64Relocation {
+    None = 0,
+    Absolute64 = 257,
+    Absolute32 = 258,
+    Absolute16 = 259,
+    Prel64 = 260,
+    Prel32 = 261,
+    Prel16 = 262,
+    // `R_AARCH64_ADR_PREL_PG_HI21`
+    AdrPrelPageHigh21 = 275,
+    // `R_AARCH64_ADD_ABS_LO12_NC`
+    AddAbsoluteLow12 = 277,
+    // `R_AARCH64_LDST8_ABS_LO12_NC` and the others by the size of the access
+    LoadStore8AbsoluteLow12 = 278,
+    Jump26 = 282,
+    Call26 = 283,
+    LoadStore16AbsoluteLow12 = 284,
+    LoadStore32AbsoluteLow12 = 285,
+    LoadStore64AbsoluteLow12 = 286,
+    LoadStore128AbsoluteLow12 = 299,
+    // `R_AARCH64_ADR_GOT_PAGE`
+    AdrGotPage = 311,
+    // `R_AARCH64_LD64_GOT_LO12_NC`
+    Load64GotLow12 = 312,
+    Copy = 1024,
+    // `R_AARCH64_GLOB_DAT`
+    GlobalData = 1025,
+    JumpSlot = 1026,
+    Relative = 1027,
+    TlsDtpMod = 1028,
+    TlsDtpRel = 1029,
+    TlsTpRel = 1030,
+    TlsDesc = 1031,
+    IRelative = 1032,
+});
+
+relocation_types!(RiscVRelocation {
+    None = 0,
+    Absolute32 = 1,
+    Absolute64 = 2,
+    Relative = 3,
+    Copy = 4,
+    JumpSlot = 5,
+    TlsDtpMod32 = 6,
+    TlsDtpMod64 = 7,
+    TlsDtpRel32 = 8,
+    TlsDtpRel64 = 9,
+    TlsTpRel32 = 10,
+    TlsTpRel64 = 11,
+    TlsDesc = 12,
+    Branch = 16,
+    Jal = 17,
+    Call = 18,
+    CallPlt = 19,
+    GotHigh20 = 20,
+    TlsGotHigh20 = 21,
+    TlsGdHigh20 = 22,
+    PcRelHigh20 = 23,
+    PcRelLow12I = 24,
+    PcRelLow12S = 25,
+    High20 = 26,
+    Low12I = 27,
+    Low12S = 28,
+    TpRelHigh20 = 29,
+    TpRelLow12I = 30,
+    TpRelLow12S = 31,
+    TpRelAdd = 32,
+    Add8 = 33,
+    Add16 = 34,
+    Add32 = 35,
+    Add64 = 36,
+    Sub8 = 37,
+    Sub16 = 38,
+    Sub32 = 39,
+    Sub64 = 40,
+    Align = 43,
+    RvcBranch = 44,
+    RvcJump = 45,
+    Relax = 51,
+    Sub6 = 52,
+    Set6 = 53,
+    Set8 = 54,
+    Set16 = 55,
+    Set32 = 56,
+    Set32PcRel = 57,
+    IRelative = 58,
+});
+
+// `r_type` by the machine of the file
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum RelocationKind {
+    X86_64(X86_64Relocation),
+    AArch64(AArch64Relocation),
+    RiscV(RiscVRelocation),
+    // of a machine without the names
+    Unknown(u32),
+}
+
+impl RelocationKind {
+    pub fn new(machine: &Machine, ty: u32) -> Self {
+        match machine {
+            Machine::X86_64 => RelocationKind::X86_64(ty.into()),
+            Machine::AArch64 => RelocationKind::AArch64(ty.into()),
+            Machine::RiscV => RelocationKind::RiscV(ty.into()),
+            _ => RelocationKind::Unknown(ty),
+        }
+    }
+
+    // the load address is added to the addend, `SHT_RELR` packs these
+    pub fn is_relative(&self) -> bool {
+        matches!(
+            self,
+            RelocationKind::X86_64(X86_64Relocation::Relative)
+                | RelocationKind::AArch64(AArch64Relocation::Relative)
+                | RelocationKind::RiscV(RiscVRelocation::Relative)
+        )
+    }
+
+    // the result of the resolver at the addend, a `STT_GNU_IFUNC`
+    pub fn is_indirect_relative(&self) -> bool {
+        matches!(
+            self,
+            RelocationKind::X86_64(X86_64Relocation::IRelative)
+                | RelocationKind::AArch64(AArch64Relocation::IRelative)
+                | RelocationKind::RiscV(RiscVRelocation::IRelative)
+        )
+    }
+
+    // the loader copies the object of a library into the executable
+    pub fn is_copy(&self) -> bool {
+        matches!(
+            self,
+            RelocationKind::X86_64(X86_64Relocation::Copy)
+                | RelocationKind::AArch64(AArch64Relocation::Copy)
+                | RelocationKind::RiscV(RiscVRelocation::Copy)
+        )
+    }
+
+    // a PLT slot the lazy binding may defer
+    pub fn is_jump_slot(&self) -> bool {
+        matches!(
+            self,
+            RelocationKind::X86_64(X86_64Relocation::JumpSlot)
+                | RelocationKind::AArch64(AArch64Relocation::JumpSlot)
+                | RelocationKind::RiscV(RiscVRelocation::JumpSlot)
+        )
+    }
+
+    // the address of the symbol in a GOT entry, riscv uses `R_RISCV_64` for it
+    pub fn is_global_data(&self) -> bool {
+        matches!(
+            self,
+            RelocationKind::X86_64(X86_64Relocation::GlobalData)
+                | RelocationKind::AArch64(AArch64Relocation::GlobalData)
+        )
+    }
+}
+
+impl From<RelocationKind> for u32 {
+    fn from(v: RelocationKind) -> Self {
+        match v {
+            RelocationKind::X86_64(t) => t.into(),
+            RelocationKind::AArch64(t) => t.into(),
+            RelocationKind::RiscV(t) => t.into(),
+            RelocationKind::Unknown(t) => t,
+        }
+    }
+}
+
+impl RelEntry {
+    pub fn kind(&self, machine: &Machine) -> RelocationKind {
+        RelocationKind::new(machine, self.relocation_type)
+    }
+}
+
+impl RelaEntry {
+    pub fn kind(&self, machine: &Machine) -> RelocationKind {
+        RelocationKind::new(machine, self.relocation_type)
+    }
+}
+
+impl AnyRelocation {
+    pub fn kind(&self, machine: &Machine) -> RelocationKind {
+        RelocationKind::new(machine, self.relocation_type)
+    }
+}
@@ -0,0 +1,67 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use super::{Error, SectionIndex, Elf64};
+
+// How many relocations of a type one relocation section has
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct RelocationCount {
+    // the index of the `SHT_REL`, `SHT_RELA` or `SHT_RELR` section
+    pub section: SectionIndex,
+    // `None` is of `SHT_RELR`, the relative relocations it packs have no type
+    pub relocation_type: Option<u32>,
+    pub count: usize,
+}
+
+// The relocations of the file by section and type, in that order
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RelocationStatistics {
+    pub counts: Vec<RelocationCount>,
+}
+
+impl RelocationStatistics {
+    // every relocation of the file
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|count| count.count).sum()
+    }
+
+    // of the type in every section, `None` is the entries of `SHT_RELR`
+    pub fn of_type(&self, relocation_type: Option<u32>) -> usize {
+        self.counts
+            .iter()
+            .filter(|count| count.relocation_type == relocation_type)
+            .map(|count| count.count)
+            .sum()
+    }
+
+    // of the section by type
+    pub fn of_section(&self, section: SectionIndex) -> impl Iterator<Item = &RelocationCount> {
+        self.counts
+            .iter()
+            .filter(move |count| count.section == section)
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Counts the entries of the relocation sections by their type,
+    // the first corrupted entry is the error
+    pub fn relocation_statistics(&self) -> Result<RelocationStatistics, Error> {
+        let mut counts = BTreeMap::<_, usize>::new();
+        for relocation in self.relocations() {
+            let (section, relocation) = relocation?;
+            let relocation_type = relocation.relocation_type();
+            *counts
+                .entry((SectionIndex(section), relocation_type))
+                .or_default() += 1;
+        }
+        Ok(RelocationStatistics {
+            counts: counts
+                .into_iter()
+                .map(|((section, relocation_type), count)| RelocationCount {
+                    section,
+                    relocation_type,
+                    count,
+                })
+                .collect(),
+        })
+    }
+}
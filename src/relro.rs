@@ -0,0 +1,141 @@
+use alloc::vec::Vec;
+
+use super::{
+    Error, SectionIndex, Address, Elf64, ProgramType, SectionFlags, DynamicTag,
+    DynamicRelocationTable, Relocation, GnuRelro, RelroCoverage,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RelroObjectKind<'a> {
+    // an allocated writable section, e.g. `.got` or `.data.rel.ro`
+    Section {
+        index: SectionIndex,
+        name: &'a [u8],
+    },
+    // `PT_DYNAMIC`
+    Dynamic,
+    // `DT_PREINIT_ARRAY`
+    PreinitArray,
+    // `DT_INIT_ARRAY`
+    InitArray,
+    // `DT_FINI_ARRAY`
+    FiniArray,
+    // the word a dynamic relocation writes, a GOT slot or a pointer of the data
+    Slot {
+        table: DynamicRelocationTable,
+        relocation: Relocation,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RelroObject<'a> {
+    pub kind: RelroObjectKind<'a>,
+    pub address: Address,
+    pub size: u64,
+    pub coverage: RelroCoverage,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RelroObjects<'a> {
+    // `None` if there is no `PT_GNU_RELRO`, then nothing is protected
+    pub relro: Option<GnuRelro>,
+    // the sections, the arrays and `PT_DYNAMIC` first, then the slots,
+    // each in the order of the file
+    pub objects: Vec<RelroObject<'a>>,
+}
+
+impl<'a> RelroObjects<'a> {
+    // what stays writable for the whole life of the process
+    pub fn unprotected(&self) -> impl Iterator<Item = &RelroObject<'a>> {
+        self.objects
+            .iter()
+            .filter(|object| object.coverage != RelroCoverage::Full)
+    }
+
+    // the lazy binding writes the slots of `DT_JMPREL` after the relocation,
+    // the linker puts them in `PT_GNU_RELRO` only with `-z now`
+    pub fn is_fully_protected(&self) -> bool {
+        self.unprotected().next().is_none()
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The data the loader writes and then may protect: the allocated writable
+    // sections, `PT_DYNAMIC`, the init and fini arrays and the word of every dynamic
+    // relocation, each with how much of it `PT_GNU_RELRO` covers with pages
+    // of the size. Without section headers there are no sections.
+    pub fn relro_objects(&self, page_size: u64) -> Result<RelroObjects<'a>, Error> {
+        if !page_size.is_power_of_two() {
+            return Err(Error::BadAlignment(page_size));
+        }
+        let relro = self.gnu_relro()?;
+        let coverage = |address, size| match &relro {
+            Some(relro) => relro.coverage(address, size, page_size),
+            None => RelroCoverage::None,
+        };
+        let mut objects = Vec::new();
+        let mut push = |kind, address, size| {
+            objects.push(RelroObject {
+                kind,
+                address,
+                size,
+                coverage: coverage(address, size),
+            })
+        };
+
+        for index in 0..self.section_number() {
+            let info = match self.section_info(SectionIndex(index))? {
+                Some(info) => info,
+                None => continue,
+            };
+            let flags = info.header.flags;
+            if flags.contains(SectionFlags::ALLOC | SectionFlags::WRITE) && info.header.size != 0 {
+                let kind = RelroObjectKind::Section {
+                    index: SectionIndex(index),
+                    name: info.name,
+                };
+                push(kind, info.header.address, info.header.size);
+            }
+        }
+        for program in 0..self.program_number() {
+            let header = self.program_table.pick(program)?;
+            if header.ty == ProgramType::Dynamic {
+                push(
+                    RelroObjectKind::Dynamic,
+                    header.virtual_address,
+                    header.memory_size,
+                );
+            }
+        }
+        if let Some(dynamic) = self.dynamic_source()? {
+            let mut arrays = [
+                (RelroObjectKind::PreinitArray, None, None),
+                (RelroObjectKind::InitArray, None, None),
+                (RelroObjectKind::FiniArray, None, None),
+            ];
+            for entry in dynamic.entries() {
+                let entry = entry?;
+                match entry.tag {
+                    DynamicTag::PreinitArray => arrays[0].1 = Some(entry.value),
+                    DynamicTag::PreinitArraySize => arrays[0].2 = Some(entry.value),
+                    DynamicTag::InitArray => arrays[1].1 = Some(entry.value),
+                    DynamicTag::InitArraySize => arrays[1].2 = Some(entry.value),
+                    DynamicTag::FiniArray => arrays[2].1 = Some(entry.value),
+                    DynamicTag::FiniArraySize => arrays[2].2 = Some(entry.value),
+                    _ => (),
+                }
+            }
+            for (kind, address, size) in arrays.iter().cloned() {
+                if let (Some(address), Some(size)) = (address, size) {
+                    push(kind, Address(address), size);
+                }
+            }
+        }
+        for relocation in self.dynamic_relocations()? {
+            let (table, relocation) = relocation?;
+            let address = relocation.address();
+            push(RelroObjectKind::Slot { table, relocation }, address, 8);
+        }
+        Ok(RelroObjects { relro, objects })
+    }
+}
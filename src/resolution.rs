@@ -0,0 +1,224 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use super::{Error, Elf64, Index, SectionType, SymbolBinding};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Candidate {
+    // the index of the object in the slice
+    pub object: usize,
+    // the index of the symbol in its `.symtab`
+    pub index: usize,
+}
+
+// two strong definitions of the same name, the first one is used
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Conflict<'a> {
+    pub name: &'a [u8],
+    pub first: Candidate,
+    pub second: Candidate,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+enum Strength {
+    Weak,
+    Common,
+    Strong,
+}
+
+// The definitions of the global symbols of relocatable objects linked together,
+// a strong definition wins over a common, a common wins over a weak one,
+// otherwise the first definition wins
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SymbolResolution<'a> {
+    definitions: BTreeMap<&'a [u8], (Candidate, Strength)>,
+    pub conflicts: Vec<Conflict<'a>>,
+}
+
+impl<'a> SymbolResolution<'a> {
+    pub fn new(objects: &[Elf64<'a>]) -> Result<Self, Error> {
+        let span = enter_span!("symbol_resolution", objects = objects.len(); symbols, conflicts);
+        let mut resolution = SymbolResolution::default();
+        let mut number = 0;
+        for (object, elf) in objects.iter().enumerate() {
+            let symbols = match elf.symbol_source(SectionType::SymbolTable)? {
+                Some(symbols) => symbols,
+                None => continue,
+            };
+            number += symbols.number;
+            for index in symbols.locals.max(1)..symbols.number {
+                let symbol = symbols.get(index)?;
+                let entry = &symbol.entry;
+                let strength = match (&entry.info.binding, &entry.section_index) {
+                    (_, Index::Undefined) => continue,
+                    (SymbolBinding::Weak, _) => Strength::Weak,
+                    (SymbolBinding::Global, Index::Common) => Strength::Common,
+                    (SymbolBinding::Global, _) => Strength::Strong,
+                    _ => continue,
+                };
+                let candidate = Candidate { object, index };
+                match resolution.definitions.get_mut(symbol.name) {
+                    None => {
+                        resolution
+                            .definitions
+                            .insert(symbol.name, (candidate, strength));
+                    }
+                    Some((first, Strength::Strong)) if strength == Strength::Strong => {
+                        resolution.conflicts.push(Conflict {
+                            name: symbol.name,
+                            first: first.clone(),
+                            second: candidate,
+                        });
+                    }
+                    Some(existing) if strength > existing.1 => *existing = (candidate, strength),
+                    Some(_) => (),
+                }
+            }
+        }
+        record_span!(span, symbols, number);
+        record_span!(span, conflicts, resolution.conflicts.len());
+        Ok(resolution)
+    }
+
+    pub fn get(&self, name: &[u8]) -> Option<&Candidate> {
+        self.definitions.get(name).map(|(candidate, _)| candidate)
+    }
+
+    // the chosen definition of every name
+    pub fn definitions(&self) -> impl Iterator<Item = (&'a [u8], &Candidate)> + '_ {
+        self.definitions
+            .iter()
+            .map(|(&name, (candidate, _))| (name, candidate))
+    }
+
+    pub fn is_weak(&self, name: &[u8]) -> bool {
+        matches!(self.definitions.get(name), Some((_, Strength::Weak)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{SymbolResolution, Candidate, Conflict};
+    use crate::{
+        Elf64, ElfBuilder, Encoding, Type, Machine, Index, SectionIndex, SectionType, SectionFlags,
+        SymbolInfo, SymbolBinding, SymbolType,
+    };
+
+    // `.text` is the first added section
+    const TEXT: Index = Index::Regular(SectionIndex(1));
+
+    // the symbols are at the indices one after the other from one
+    fn object(symbols: &[(&[u8], SymbolBinding, Index)]) -> Vec<u8> {
+        let mut builder = ElfBuilder::new(Encoding::Little, Type::Relocatable, Machine::X86_64);
+        builder.add_section(
+            b".text",
+            SectionType::ProgramBits,
+            SectionFlags::ALLOC | SectionFlags::EXECINSTR,
+            16,
+            vec![0xc3; 16],
+        );
+        for (name, binding, section) in symbols {
+            let info = SymbolInfo {
+                binding: binding.clone(),
+                ty: SymbolType::Object,
+            };
+            builder.add_symbol(name, info, section.clone(), 0, 8);
+        }
+        builder.build().unwrap()
+    }
+
+    fn resolve<'a>(raws: &'a [Vec<u8>]) -> SymbolResolution<'a> {
+        let objects = raws
+            .iter()
+            .map(|raw| Elf64::new(raw).unwrap())
+            .collect::<Vec<_>>();
+        SymbolResolution::new(&objects).unwrap()
+    }
+
+    fn candidate(object: usize, index: usize) -> Option<Candidate> {
+        Some(Candidate { object, index })
+    }
+
+    #[test]
+    fn strong_over_weak() {
+        let raws = [
+            object(&[(b"f", SymbolBinding::Weak, TEXT)]),
+            object(&[(b"f", SymbolBinding::Global, TEXT)]),
+            object(&[(b"f", SymbolBinding::Weak, TEXT)]),
+        ];
+        let resolution = resolve(&raws);
+        assert_eq!(resolution.get(b"f").cloned(), candidate(1, 1));
+        assert!(!resolution.is_weak(b"f"));
+        assert!(resolution.conflicts.is_empty());
+    }
+
+    #[test]
+    fn first_weak_definition() {
+        let raws = [
+            object(&[(b"f", SymbolBinding::Weak, TEXT)]),
+            object(&[(b"f", SymbolBinding::Weak, TEXT)]),
+        ];
+        let resolution = resolve(&raws);
+        assert_eq!(resolution.get(b"f").cloned(), candidate(0, 1));
+        assert!(resolution.is_weak(b"f"));
+    }
+
+    #[test]
+    fn common_between_weak_and_strong() {
+        let raws = [
+            object(&[(b"c", SymbolBinding::Weak, TEXT)]),
+            object(&[(b"c", SymbolBinding::Global, Index::Common)]),
+            object(&[(b"c", SymbolBinding::Global, Index::Common)]),
+        ];
+        assert_eq!(resolve(&raws).get(b"c").cloned(), candidate(1, 1));
+        let raws = [
+            object(&[(b"c", SymbolBinding::Global, Index::Common)]),
+            object(&[(b"c", SymbolBinding::Global, TEXT)]),
+        ];
+        let resolution = resolve(&raws);
+        assert_eq!(resolution.get(b"c").cloned(), candidate(1, 1));
+        assert!(resolution.conflicts.is_empty());
+    }
+
+    #[test]
+    fn strong_conflict() {
+        let raws = [
+            object(&[(b"g", SymbolBinding::Global, TEXT)]),
+            object(&[
+                (b"h", SymbolBinding::Global, TEXT),
+                (b"g", SymbolBinding::Global, TEXT),
+            ]),
+        ];
+        let resolution = resolve(&raws);
+        assert_eq!(resolution.get(b"g").cloned(), candidate(0, 1));
+        assert_eq!(
+            resolution.conflicts,
+            [Conflict {
+                name: b"g",
+                first: Candidate {
+                    object: 0,
+                    index: 1,
+                },
+                second: Candidate {
+                    object: 1,
+                    index: 2,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn undefined_and_local_symbols() {
+        let raws = [object(&[
+            (b"local", SymbolBinding::Local, TEXT),
+            (b"undefined", SymbolBinding::Global, Index::Undefined),
+            (b"defined", SymbolBinding::Global, TEXT),
+        ])];
+        let resolution = resolve(&raws);
+        assert_eq!(resolution.get(b"local"), None);
+        assert_eq!(resolution.get(b"undefined"), None);
+        let names = resolution.definitions().map(|(name, _)| name);
+        assert_eq!(names.collect::<Vec<_>>(), [b"defined"]);
+    }
+}
@@ -0,0 +1,191 @@
+use alloc::vec::Vec;
+
+use super::{Error, Offset, Elf64, Entry, ProgramHeader, SectionHeader, Structure};
+
+const HEADER_FIELDS: [(usize, &str); 20] = [
+    (0x00, "magic"),
+    (0x04, "class"),
+    (0x05, "encoding"),
+    (0x06, "version"),
+    (0x07, "abi"),
+    (0x08, "abi_version"),
+    (0x09, "padding"),
+    (0x10, "type"),
+    (0x12, "machine"),
+    (0x14, "format_version"),
+    (0x18, "entry"),
+    (0x20, "program_headers_offset"),
+    (0x28, "section_headers_offset"),
+    (0x30, "flags"),
+    (0x34, "header_size"),
+    (0x36, "program_header_size"),
+    (0x38, "program_header_number"),
+    (0x3a, "section_header_size"),
+    (0x3c, "section_header_number"),
+    (0x3e, "section_names"),
+];
+
+const PROGRAM_HEADER_FIELDS: [(usize, &str); 8] = [
+    (0x00, "type"),
+    (0x04, "flags"),
+    (0x08, "file_offset"),
+    (0x10, "virtual_address"),
+    (0x18, "physical_address"),
+    (0x20, "file_size"),
+    (0x28, "memory_size"),
+    (0x30, "address_alignment"),
+];
+
+const SECTION_HEADER_FIELDS: [(usize, &str); 10] = [
+    (0x00, "name"),
+    (0x04, "type"),
+    (0x08, "flags"),
+    (0x10, "address"),
+    (0x18, "offset"),
+    (0x20, "size"),
+    (0x28, "link"),
+    (0x2c, "info"),
+    (0x30, "address_alignment"),
+    (0x38, "number_of_entries"),
+];
+
+fn field(fields: &[(usize, &'static str)], offset: usize) -> Option<&'static str> {
+    fields
+        .iter()
+        .rev()
+        .find(|&&(start, _)| start <= offset)
+        .map(|&(_, name)| name)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Difference {
+    pub offset: Offset,
+    pub size: u64,
+    pub structure: Structure,
+    // the name of the header field, if the structure is a header
+    pub field: Option<&'static str>,
+}
+
+impl<'a> Elf64<'a> {
+    fn owner(&self, offset: usize) -> Result<(Structure, Option<&'static str>), Error> {
+        let structure = self.claimant(offset)?;
+        let field = match structure {
+            Structure::Header => field(&HEADER_FIELDS, offset),
+            Structure::ProgramHeader(_) => {
+                let table = self.header.program_headers_offset.0 as usize;
+                field(
+                    &PROGRAM_HEADER_FIELDS,
+                    (offset - table) % ProgramHeader::SIZE,
+                )
+            }
+            Structure::SectionHeader(_) => {
+                let table = self.header.section_headers_offset.0 as usize;
+                field(
+                    &SECTION_HEADER_FIELDS,
+                    (offset - table) % SectionHeader::SIZE,
+                )
+            }
+            _ => None,
+        };
+        Ok((structure, field))
+    }
+}
+
+// Parses the file, serializes it back and reports the ranges that differ,
+// an empty list means the parser and the writer are lossless for this file.
+pub fn round_trip(raw: &[u8]) -> Result<Vec<Difference>, Error> {
+    let elf = Elf64::new(raw)?;
+    let serialized = elf.serialize()?;
+
+    let mut differences = Vec::<Difference>::new();
+    for (offset, (a, b)) in raw.iter().zip(serialized.iter()).enumerate() {
+        if a == b {
+            continue;
+        }
+        let (structure, field) = elf.owner(offset)?;
+        match differences.last_mut() {
+            Some(last)
+                if last.offset + last.size == Offset(offset as u64)
+                    && last.structure == structure
+                    && last.field == field =>
+            {
+                last.size += 1;
+            }
+            _ => differences.push(Difference {
+                offset: Offset(offset as u64),
+                size: 1,
+                structure,
+                field,
+            }),
+        }
+    }
+
+    Ok(differences)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::round_trip;
+    use crate::{
+        Error, Elf64, Encoding, Type, Machine, ElfBuilder, SectionType, SectionFlags, SectionIndex,
+        Index,
+    };
+
+    fn object(encoding: Encoding) -> Vec<u8> {
+        let mut builder = ElfBuilder::new(encoding, Type::Relocatable, Machine::X86_64);
+        builder.add_section(
+            b".text",
+            SectionType::ProgramBits,
+            SectionFlags::ALLOC | SectionFlags::EXECINSTR,
+            16,
+            vec![0x90; 16],
+        );
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn lossless() {
+        for encoding in [Encoding::Little, Encoding::Big] {
+            assert_eq!(round_trip(&object(encoding)), Ok(vec![]));
+        }
+    }
+
+    // GNU as leaves `e_phentsize` zero in an object file
+    #[test]
+    fn zero_program_header_size() {
+        let mut raw = object(Encoding::Little);
+        raw[0x36..0x38].copy_from_slice(&[0, 0]);
+        assert_eq!(Elf64::new(&raw).unwrap().header.program_header_size, 0);
+        assert_eq!(round_trip(&raw), Ok(vec![]));
+    }
+
+    #[test]
+    fn crafted_section_size() {
+        let raw = object(Encoding::Little);
+        let elf = Elf64::new(&raw).unwrap();
+        let table = elf.header.section_headers_offset.0 as usize;
+        for size in [u64::MAX, u64::MAX - 0x10, raw.len() as u64] {
+            let mut raw = raw.clone();
+            // `sh_size` of `.text`
+            let field = table + 0x40 + 0x20;
+            raw[field..(field + 8)].copy_from_slice(&size.to_le_bytes());
+            assert!(matches!(
+                round_trip(&raw),
+                Err(Error::ArithmeticOverflow) | Err(Error::SliceTooShort)
+            ));
+        }
+    }
+
+    // `e_shnum` zero and `e_shstrndx` `SHN_XINDEX` stay in the header
+    #[test]
+    fn extended_numbering() {
+        let raw = crate::tests::with_extended_numbering(0xff00, [0, 0]);
+        let elf = Elf64::new(&raw).unwrap();
+        assert_eq!(elf.header.section_names, Index::Extended);
+        let names = elf.section_info(SectionIndex(0xff03)).unwrap().unwrap();
+        assert_eq!(names.name, b".shstrtab");
+        assert_eq!(round_trip(&raw), Ok(vec![]));
+    }
+}
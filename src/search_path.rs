@@ -0,0 +1,89 @@
+use alloc::vec::Vec;
+
+use super::{Error, Elf64};
+
+// The values of the dynamic string tokens, the runtime linker knows them,
+// a tool has to tell them
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchPathTokens<'t> {
+    // `$ORIGIN`, the directory of the object, not of the executable
+    pub origin: &'t [u8],
+    // `$LIB`, e.g. `lib64` or `lib/x86_64-linux-gnu`
+    pub lib: &'t [u8],
+    // `$PLATFORM`, e.g. `x86_64` or `haswell`
+    pub platform: &'t [u8],
+}
+
+impl<'t> SearchPathTokens<'t> {
+    // `$ORIGIN` is the directory of the path, `.` if the path has no directory
+    pub fn new(path: &'t [u8], lib: &'t [u8], platform: &'t [u8]) -> Self {
+        let origin = match path.iter().rposition(|c| *c == b'/') {
+            Some(0) => &path[..1],
+            Some(position) => &path[..position],
+            None => b".",
+        };
+        SearchPathTokens {
+            origin,
+            lib,
+            platform,
+        }
+    }
+
+    // `$NAME` or `${NAME}`, an unknown token stays as it is, the way glibc
+    // sees it the unbraced name ends at a character that is not of a name
+    pub fn expand(&self, path: &[u8]) -> Vec<u8> {
+        let mut expanded = Vec::with_capacity(path.len());
+        let mut rest = path;
+        while let Some(position) = rest.iter().position(|c| *c == b'$') {
+            expanded.extend_from_slice(&rest[..position]);
+            rest = &rest[position..];
+            let (name, length) = if rest.get(1) == Some(&b'{') {
+                match rest.iter().position(|c| *c == b'}') {
+                    Some(end) => (&rest[2..end], end + 1),
+                    None => (&rest[..0], 0),
+                }
+            } else {
+                let end = rest[1..]
+                    .iter()
+                    .position(|c| !(c.is_ascii_alphanumeric() || *c == b'_'))
+                    .map_or(rest.len(), |end| end + 1);
+                (&rest[1..end], end)
+            };
+            let value = match name {
+                b"ORIGIN" => Some(self.origin),
+                b"LIB" => Some(self.lib),
+                b"PLATFORM" => Some(self.platform),
+                _ => None,
+            };
+            match value {
+                Some(value) if length != 0 => {
+                    expanded.extend_from_slice(value);
+                    rest = &rest[length..];
+                }
+                _ => {
+                    expanded.push(b'$');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        expanded.extend_from_slice(rest);
+        expanded
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The directories of `DT_RUNPATH`, or of `DT_RPATH` if there is no `DT_RUNPATH`,
+    // in the order the runtime linker searches them, with the tokens expanded.
+    // An empty directory is the current one, it stays empty.
+    pub fn search_paths(&self, tokens: &SearchPathTokens<'_>) -> Result<Vec<Vec<u8>>, Error> {
+        let list = match self.runpath()? {
+            Some(list) => Some(list),
+            None => self.rpath()?,
+        };
+        Ok(list
+            .into_iter()
+            .flat_map(|list| list.split(|c| *c == b':'))
+            .map(|path| tokens.expand(path))
+            .collect())
+    }
+}
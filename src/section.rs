@@ -1,14 +1,18 @@
-use core::fmt;
-use super::{Address, Offset, Error, Encoding, Entry};
+use core::{convert::TryFrom, fmt};
+use super::{Address, Offset, SectionIndex, Error, Encoding, Entry, Encode};
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Index {
     Undefined,
     ProcessorSecific(u8),
     EnvironmentSpecific(u8),
     AbsoluteValue,
     Common,
-    Regular(u16),
+    // `SHN_XINDEX`, the index does not fit, it is in `SHT_SYMTAB_SHNDX` for a symbol,
+    // in `sh_link` of the section zero for `e_shstrndx`
+    Extended,
+    Regular(SectionIndex),
 }
 
 impl From<u16> for Index {
@@ -19,12 +23,60 @@ impl From<u16> for Index {
             t @ 0xff20..=0xff3f => Index::EnvironmentSpecific((t & 0x001f) as u8),
             0xfff1 => Index::AbsoluteValue,
             0xfff2 => Index::Common,
-            t => Index::Regular(t),
+            0xffff => Index::Extended,
+            t => Index::Regular(SectionIndex(t as usize)),
         }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+// `sh_link` is 32 bits wide, it needs no `SHN_XINDEX`, an index from 0xffff on
+// is a regular one
+impl From<u32> for Index {
+    fn from(v: u32) -> Self {
+        match u16::try_from(v) {
+            Ok(0xffff) | Err(_) => Index::Regular(SectionIndex(v as usize)),
+            Ok(v) => v.into(),
+        }
+    }
+}
+
+impl From<Index> for u16 {
+    fn from(v: Index) -> Self {
+        match v {
+            Index::Undefined => 0x0000,
+            Index::ProcessorSecific(t) => 0xff00 | (t as u16),
+            Index::EnvironmentSpecific(t) => 0xff20 | (t as u16),
+            Index::AbsoluteValue => 0xfff1,
+            Index::Common => 0xfff2,
+            Index::Extended => 0xffff,
+            Index::Regular(t) => t.0 as u16,
+        }
+    }
+}
+
+impl From<Index> for u32 {
+    fn from(v: Index) -> Self {
+        match v {
+            Index::Regular(t) => t.0 as u32,
+            v => u16::from(v) as u32,
+        }
+    }
+}
+
+// only a regular index refers to a section
+impl TryFrom<Index> for SectionIndex {
+    type Error = Error;
+
+    fn try_from(v: Index) -> Result<Self, Self::Error> {
+        match v {
+            Index::Regular(index) => Ok(index),
+            _ => Err(Error::IndexOutOfBounds),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum SectionType {
     Null,
     ProgramBits,
@@ -65,15 +117,87 @@ impl From<u32> for SectionType {
     }
 }
 
+impl From<SectionType> for u32 {
+    fn from(v: SectionType) -> Self {
+        match v {
+            SectionType::Null => 0x00000000,
+            SectionType::ProgramBits => 0x00000001,
+            SectionType::SymbolTable => 0x00000002,
+            SectionType::StringTable => 0x00000003,
+            SectionType::Rela => 0x00000004,
+            SectionType::Hash => 0x00000005,
+            SectionType::Dynamic => 0x00000006,
+            SectionType::Note => 0x00000007,
+            SectionType::NoBits => 0x00000008,
+            SectionType::Rel => 0x00000009,
+            SectionType::Shlib => 0x0000000a,
+            SectionType::DynamicSymbolTable => 0x0000000b,
+            SectionType::OsSpecific(t) => t,
+            SectionType::ProcessorSprcific(t) => t,
+            SectionType::Unknown(t) => t,
+        }
+    }
+}
+
+// `sh_flags` is 64 bits wide, the bits of `SHF_MASKOS`, `SHF_MASKPROC` and the ones
+// the gABI reserves are kept as they are, a file comes back with the flags it has
 bitflags! {
-    pub struct SectionFlags: u32 {
+    pub struct SectionFlags: u64 {
         const WRITE = 0b00000001;
         const ALLOC = 0b00000010;
         const EXECINSTR = 0b00000100;
+        const MERGE = 0b00010000;
+        const STRINGS = 0b00100000;
+        const INFO_LINK = 0b01000000;
+        const LINK_ORDER = 0b10000000;
+        const OS_NONCONFORMING = 0b00000001_00000000;
+        const GROUP = 0b00000010_00000000;
+        const TLS = 0b00000100_00000000;
+        const COMPRESSED = 0b00001000_00000000;
+        // `SHF_MASKOS`
+        const MASK_OS = 0x0ff00000;
+        // `SHF_GNU_RETAIN`, the linker keeps the section with `--gc-sections`
+        const GNU_RETAIN = 0x00200000;
+        // `SHF_GNU_MBIND`
+        const GNU_MBIND = 0x01000000;
+        // `SHF_MASKPROC`
+        const MASK_PROCESSOR = 0xf0000000;
+        // `SHF_EXCLUDE`, the linker drops the section
+        const EXCLUDE = 0x80000000;
+        // `SHF_X86_64_LARGE`
+        const X86_64_LARGE = 0x10000000;
+        // `SHF_ARM_PURECODE`
+        const ARM_PURECODE = 0x20000000;
+        // the bits the gABI does not define
+        const RESERVED = 0xffffffff_000ff008;
     }
 }
 
-#[derive(Clone, Eq, PartialEq)]
+impl SectionFlags {
+    // the bits of `SHF_MASKOS`
+    pub fn os_bits(&self) -> u64 {
+        self.bits() & Self::MASK_OS.bits()
+    }
+
+    // the bits of `SHF_MASKPROC`
+    pub fn processor_bits(&self) -> u64 {
+        self.bits() & Self::MASK_PROCESSOR.bits()
+    }
+}
+
+// the raw bits, bitflags 1 has no support of serde
+#[cfg(feature = "serde")]
+impl serde::Serialize for SectionFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u64(self.bits())
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SectionHeader {
     pub name: u32,
     pub ty: SectionType,
@@ -87,6 +211,20 @@ pub struct SectionHeader {
     pub number_of_entries: u64,
 }
 
+impl SectionHeader {
+    // The section `sh_info` refers to: the one a relocation section applies to,
+    // or any with `SHF_INFO_LINK`. Zero means none.
+    pub fn info_section(&self) -> Option<SectionIndex> {
+        let refers = self.flags.contains(SectionFlags::INFO_LINK)
+            || matches!(self.ty, SectionType::Rel | SectionType::Rela);
+        match self.info {
+            0 => None,
+            info if refers => SectionIndex::try_from(info).ok(),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Debug for SectionHeader {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SectionHeader")
@@ -117,21 +255,86 @@ impl Entry for SectionHeader {
             return Err(Error::SliceTooShort);
         }
 
-        // WARNING:
-        //  slice[0x0c..0x10]
-        //  slice[0x2a..0x2c]
-        // ignored
         Ok(SectionHeader {
             name: read_int!(&slice[0x00..], &encoding, u32),
             ty: read_int!(&slice[0x04..], &encoding, u32).into(),
-            flags: SectionFlags::from_bits_truncate(read_int!(&slice[0x08..], &encoding, u32)),
-            address: read_int!(&slice[0x10..], &encoding, u64),
-            offset: read_int!(&slice[0x18..], &encoding, u64),
+            flags: SectionFlags::from_bits_truncate(read_int!(&slice[0x08..], &encoding, u64)),
+            address: Address(read_int!(&slice[0x10..], &encoding, u64)),
+            offset: Offset(read_int!(&slice[0x18..], &encoding, u64)),
             size: read_int!(&slice[0x20..], &encoding, u64),
-            link: read_int!(&slice[0x28..], &encoding, u16).into(),
+            link: read_int!(&slice[0x28..], &encoding, u32).into(),
             info: read_int!(&slice[0x2c..], &encoding, u32),
             address_alignment: read_int!(&slice[0x30..], &encoding, u64),
             number_of_entries: read_int!(&slice[0x38..], &encoding, u64),
         })
     }
 }
+
+impl Encode for SectionHeader {
+    fn encode(&self, slice: &mut [u8], encoding: Encoding) -> Result<(), Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let link: u32 = self.link.clone().into();
+        write_int!(&mut slice[0x00..], &encoding, u32, self.name);
+        write_int!(&mut slice[0x04..], &encoding, u32, self.ty.clone().into());
+        write_int!(&mut slice[0x08..], &encoding, u64, self.flags.bits());
+        write_int!(&mut slice[0x10..], &encoding, u64, self.address.0);
+        write_int!(&mut slice[0x18..], &encoding, u64, self.offset.0);
+        write_int!(&mut slice[0x20..], &encoding, u64, self.size);
+        write_int!(&mut slice[0x28..], &encoding, u32, link);
+        write_int!(&mut slice[0x2c..], &encoding, u32, self.info);
+        write_int!(&mut slice[0x30..], &encoding, u64, self.address_alignment);
+        write_int!(&mut slice[0x38..], &encoding, u64, self.number_of_entries);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SectionHeader, SectionFlags, SectionType, Index, Address, Offset, Encoding};
+    use crate::{Entry, Encode};
+
+    fn header(flags: SectionFlags) -> SectionHeader {
+        SectionHeader {
+            name: 1,
+            ty: SectionType::ProgramBits,
+            flags,
+            address: Address(0x1000),
+            offset: Offset(0x1000),
+            size: 0x10,
+            link: Index::Undefined,
+            info: 0,
+            address_alignment: 0x10,
+            number_of_entries: 0,
+        }
+    }
+
+    #[test]
+    fn flags_of_both_encodings() {
+        let flags = SectionFlags::ALLOC | SectionFlags::EXECINSTR;
+        for encoding in [Encoding::Little, Encoding::Big] {
+            let mut raw = [0; SectionHeader::SIZE];
+            header(flags).encode(&mut raw, encoding.clone()).unwrap();
+            let parsed = SectionHeader::new(&raw, encoding).unwrap();
+            assert_eq!(parsed.flags, flags);
+        }
+    }
+
+    #[test]
+    fn flags_keep_unknown_bits() {
+        let bits = 0x8000_0000_8020_1006;
+        let mut raw = [0; SectionHeader::SIZE];
+        raw[0x08..0x10].copy_from_slice(&u64::to_be_bytes(bits));
+        let parsed = SectionHeader::new(&raw, Encoding::Big).unwrap();
+        assert_eq!(parsed.flags.bits(), bits);
+        assert!(parsed
+            .flags
+            .contains(SectionFlags::GNU_RETAIN | SectionFlags::EXCLUDE));
+
+        let mut encoded = [0; SectionHeader::SIZE];
+        parsed.encode(&mut encoded, Encoding::Big).unwrap();
+        assert_eq!(encoded[0x08..0x10], raw[0x08..0x10]);
+    }
+}
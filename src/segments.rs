@@ -0,0 +1,368 @@
+use super::{
+    Error, SectionIndex, Address, Elf64, Elf32, ProgramType, ProgramHeader, ProgramData,
+    ProgramFlags, SectionType, SectionData, SectionFlags, NoteTable, NoteEntry, Capabilities,
+    Entry,
+};
+use super::common::file_slice;
+
+// `PT_GNU_STACK`, the permissions of the stack the file asks for
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct GnuStack {
+    pub flags: ProgramFlags,
+    // `p_memsz`, the size of the stack of the main thread or of the new threads,
+    // musl and some embedded runtimes honor it, `None` if it is zero
+    pub size: Option<u64>,
+}
+
+impl GnuStack {
+    pub fn is_executable(&self) -> bool {
+        self.flags.contains(ProgramFlags::EXECUTE)
+    }
+}
+
+// `PT_GNU_RELRO`, the part of a writable `PT_LOAD` the loader makes read-only
+// after the relocation
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct GnuRelro {
+    pub program: usize,
+    pub address: Address,
+    pub size: u64,
+}
+
+impl GnuRelro {
+    // The addresses `mprotect` gets, glibc rounds both ends down to the page,
+    // the bytes of the last partial page stay writable
+    pub fn protected(&self, page_size: u64) -> (Address, Address) {
+        let mask = !(page_size.max(1) - 1);
+        let end = self.address.0.saturating_add(self.size);
+        (Address(self.address.0 & mask), Address(end & mask))
+    }
+
+    pub fn coverage(&self, address: Address, size: u64, page_size: u64) -> RelroCoverage {
+        let (start, end) = self.protected(page_size);
+        let last = address.0.saturating_add(size.max(1));
+        if start.0 <= address.0 && last <= end.0 {
+            RelroCoverage::Full
+        } else if address.0 < end.0 && start.0 < last {
+            RelroCoverage::Partial
+        } else {
+            RelroCoverage::None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum RelroCoverage {
+    Full,
+    // some of the bytes stay writable, the object straddles an end of the segment
+    Partial,
+    None,
+}
+
+// The notes of `PT_NOTE` segments, or of `SHT_NOTE` sections of a file without
+// program headers, a corrupted note ends its table, the sizes of the rest are
+// not trusted, and the iteration goes on with the next table
+#[derive(Clone)]
+pub struct Notes<'a> {
+    file: NoteFile<'a>,
+    segments: bool,
+    index: usize,
+    table: Option<NoteTable<'a>>,
+    position: usize,
+}
+
+#[derive(Clone)]
+enum NoteFile<'a> {
+    Elf64(Elf64<'a>),
+    Elf32(Elf32<'a>),
+}
+
+impl<'a> NoteFile<'a> {
+    fn program(&self, index: usize) -> Result<Option<ProgramData<'a>>, Error> {
+        let program = match self {
+            NoteFile::Elf64(elf) => elf.program(index)?,
+            NoteFile::Elf32(elf) => elf.program(index)?,
+        };
+        Ok(program.map(|p| p.data))
+    }
+
+    // `Some(None)` for a section of another type
+    fn section(&self, index: SectionIndex) -> Result<Option<Option<NoteTable<'a>>>, Error> {
+        match self {
+            NoteFile::Elf64(elf) => Ok(elf.section(index)?.map(|s| match s.data {
+                SectionData::Note(table) => Some(table),
+                _ => None,
+            })),
+            NoteFile::Elf32(elf) => match elf.section_info(index)? {
+                Some(info) if info.header.ty == SectionType::Note => {
+                    let slice = elf.section_slice(index)?.unwrap_or(&[]);
+                    let alignment = info.header.address_alignment;
+                    Ok(Some(Some(NoteTable::with_alignment(
+                        slice,
+                        elf.encoding(),
+                        alignment,
+                    ))))
+                }
+                Some(_) => Ok(Some(None)),
+                None => Ok(None),
+            },
+        }
+    }
+}
+
+impl<'a> Notes<'a> {
+    // the segments of a file with program headers, else the sections
+    fn new(file: NoteFile<'a>, program_number: usize) -> Self {
+        if program_number == 0 {
+            log_debug!("notes: no program headers, the notes of SHT_NOTE sections");
+        }
+        Notes {
+            file,
+            segments: program_number != 0,
+            index: 0,
+            table: None,
+            position: 0,
+        }
+    }
+
+    // a loop rather than a recursion, a file may have tens of thousands of sections
+    fn next_table(&mut self) -> Result<Option<NoteTable<'a>>, Error> {
+        loop {
+            let index = self.index;
+            self.index += 1;
+            let data = if self.segments {
+                match self.file.program(index)? {
+                    Some(ProgramData::Note(table)) => Some(table),
+                    Some(_) => continue,
+                    None => None,
+                }
+            } else {
+                match self.file.section(SectionIndex(index))? {
+                    Some(Some(table)) => Some(table),
+                    Some(None) => continue,
+                    None => None,
+                }
+            };
+            return Ok(data);
+        }
+    }
+}
+
+impl<'a> Iterator for Notes<'a> {
+    type Item = Result<NoteEntry<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(table) = &self.table {
+                if self.position < table.as_raw().len() {
+                    let position = self.position;
+                    let note = table.next(&mut self.position);
+                    if note.is_err() {
+                        log_debug!(
+                            "notes: corrupted note at {}, the rest of the table is skipped",
+                            position
+                        );
+                        self.table = None;
+                    }
+                    return Some(note);
+                }
+            }
+            self.position = 0;
+            match self.next_table() {
+                Ok(Some(table)) => self.table = Some(table),
+                Ok(None) => return None,
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // The bytes of the file a `PT_LOAD` maps at the address, the whole range must be
+    // in the file part of a single segment
+    pub(crate) fn virtual_slice(&self, address: Address, size: u64) -> Result<&'a [u8], Error> {
+        match self.read_virtual(address, size) {
+            Err(Error::SpansSegments) | Err(Error::ZeroFilled) => Err(Error::NotPresent),
+            result => result,
+        }
+    }
+
+    // The first `PT_LOAD` that has the address in memory, with its index,
+    // the part past `p_filesz` counts
+    pub fn segment_containing(
+        &self,
+        address: Address,
+    ) -> Result<Option<(usize, ProgramHeader)>, Error> {
+        self.require(Capabilities::PROGRAM_HEADERS)?;
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            if header.ty != ProgramType::Load {
+                continue;
+            }
+            let start = header.virtual_address;
+            if start <= address && address < start.saturating_add(header.memory_size) {
+                return Ok(Some((index, header)));
+            }
+        }
+        Ok(None)
+    }
+
+    // the segment of the range and the number of bytes of it in the file
+    fn virtual_range(&self, address: Address, size: u64) -> Result<(ProgramHeader, u64), Error> {
+        let end = address.checked_add(size).ok_or(Error::ArithmeticOverflow)?;
+        let (_, header) = self.segment_containing(address)?.ok_or(Error::NotPresent)?;
+        let start = header.virtual_address;
+        if end > start.saturating_add(header.memory_size) {
+            return Err(Error::SpansSegments);
+        }
+        let file_end = start.saturating_add(header.file_size).max(address);
+        Ok((header, size.min(file_end - address)))
+    }
+
+    // The bytes of the file at the address of the process, `Error::NotPresent`
+    // if no `PT_LOAD` maps the address, `Error::SpansSegments` if the range
+    // does not end in the same segment and `Error::ZeroFilled` if some of it
+    // is past `p_filesz`, e.g. in `.bss`
+    pub fn read_virtual(&self, address: Address, size: u64) -> Result<&'a [u8], Error> {
+        let (header, in_file) = self.virtual_range(address, size)?;
+        if in_file < size {
+            return Err(Error::ZeroFilled);
+        }
+        let offset = header
+            .file_offset
+            .saturating_add(address - header.virtual_address);
+        file_slice(self.raw, offset, size)
+    }
+
+    // `read_virtual` that copies the range into the buffer, the bytes past `p_filesz`
+    // are zeros the way the loader makes them
+    pub fn read_virtual_into(&self, address: Address, buffer: &mut [u8]) -> Result<(), Error> {
+        let (header, in_file) = self.virtual_range(address, buffer.len() as u64)?;
+        let (data, zeros) = buffer.split_at_mut(in_file as usize);
+        if !data.is_empty() {
+            let offset = header
+                .file_offset
+                .saturating_add(address - header.virtual_address);
+            data.copy_from_slice(file_slice(self.raw, offset, in_file)?);
+        }
+        zeros.iter_mut().for_each(|b| *b = 0);
+        Ok(())
+    }
+
+    // `PT_INTERP` without the terminator, `Error::NotPresent` if there are no program
+    // headers, `None` if the file is statically linked
+    pub fn interpreter(&self) -> Result<Option<&'a [u8]>, Error> {
+        self.require(Capabilities::PROGRAM_HEADERS)?;
+        for index in 0..self.program_number() {
+            if let Some(ProgramData::Interpreter(mut path)) = self.program(index)?.map(|p| p.data) {
+                while let Some((0, init)) = path.split_last() {
+                    path = init;
+                }
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
+    }
+
+    // `PT_GNU_STACK`, `None` if there is no such segment, then the stack is executable
+    // on most of the machines, there is no note that asks for a size. A relocatable
+    // file has `.note.GNU-stack` instead, executable if the section is.
+    pub fn gnu_stack(&self) -> Result<Option<GnuStack>, Error> {
+        if self.program_number() == 0 {
+            for index in 0..self.section_number() {
+                match self.section_info(SectionIndex(index))? {
+                    Some(info) if info.name == b".note.GNU-stack" => {
+                        let mut flags = ProgramFlags::READ | ProgramFlags::WRITE;
+                        if info.header.flags.contains(SectionFlags::EXECINSTR) {
+                            flags |= ProgramFlags::EXECUTE;
+                        }
+                        return Ok(Some(GnuStack { flags, size: None }));
+                    }
+                    _ => (),
+                }
+            }
+            return Ok(None);
+        }
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            if header.ty == ProgramType::GnuStack {
+                return Ok(Some(GnuStack {
+                    flags: header.flags,
+                    size: Some(header.memory_size).filter(|size| *size != 0),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn gnu_relro(&self) -> Result<Option<GnuRelro>, Error> {
+        for program in 0..self.program_number() {
+            let header = self.program_table.pick(program)?;
+            if header.ty == ProgramType::GnuRelro {
+                return Ok(Some(GnuRelro {
+                    program,
+                    address: header.virtual_address,
+                    size: header.memory_size,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    // The address of the program header table in memory, `AT_PHDR` of the process,
+    // `p_vaddr` of `PT_PHDR`, or the place of `e_phoff` in a `PT_LOAD` the way Linux
+    // finds it without `PT_PHDR`, `None` if no segment loads the table
+    pub fn program_header_address(&self) -> Result<Option<Address>, Error> {
+        self.require(Capabilities::PROGRAM_HEADERS)?;
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            if header.ty == ProgramType::ProgramHeaderTable {
+                return Ok(Some(header.virtual_address));
+            }
+        }
+        Ok(self.program_header_load()?.map(|(_, address)| address))
+    }
+
+    // the `PT_LOAD` that has the whole program header table in the file,
+    // and the address of the table in memory
+    pub(crate) fn program_header_load(&self) -> Result<Option<(usize, Address)>, Error> {
+        let offset = self.header.program_headers_offset;
+        let size = (self.program_number() * ProgramHeader::SIZE) as u64;
+        let end = offset.checked_add(size).ok_or(Error::ArithmeticOverflow)?;
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            if header.ty != ProgramType::Load {
+                continue;
+            }
+            let start = header.file_offset;
+            if start <= offset && end <= start.saturating_add(header.file_size) {
+                let address = header.virtual_address.wrapping_add(offset - start);
+                return Ok(Some((index, address)));
+            }
+        }
+        Ok(None)
+    }
+
+    // The load map, `PT_LOAD` in the order of the program header table
+    pub fn load_segments(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<ProgramHeader, Error>> + 'a, Error> {
+        self.require(Capabilities::PROGRAM_HEADERS)?;
+        Ok(self.program_headers().filter(|header| match header {
+            Ok(header) => header.ty == ProgramType::Load,
+            Err(_) => true,
+        }))
+    }
+
+    // Every note of the file, it works without section headers
+    pub fn notes(&self) -> Notes<'a> {
+        Notes::new(NoteFile::Elf64(self.clone()), self.program_number())
+    }
+}
+
+impl<'a> Elf32<'a> {
+    // Every note of the file, the same way as of `Elf64::notes`
+    pub fn notes(&self) -> Notes<'a> {
+        Notes::new(NoteFile::Elf32(self.clone()), self.program_number())
+    }
+}
@@ -0,0 +1,68 @@
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+// Collects strings for a string table, each distinct string gets its own identifier,
+// strings that are suffixes of others share the bytes, like GNU ld merges them
+#[derive(Default, Clone)]
+pub struct StringTableBuilder {
+    ids: BTreeMap<Vec<u8>, usize>,
+    strings: Vec<Vec<u8>>,
+}
+
+impl StringTableBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, string: &[u8]) -> usize {
+        if let Some(&id) = self.ids.get(string) {
+            return id;
+        }
+        let id = self.strings.len();
+        self.strings.push(string.to_vec());
+        self.ids.insert(string.to_vec(), id);
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    // returns the table and the offset of each string in it, indexed by identifier
+    pub fn build(&self) -> (Vec<u8>, Vec<u32>) {
+        let mut order = (0..self.strings.len()).collect::<Vec<_>>();
+        // descending by reversed bytes, so a suffix goes right after a string that ends with it
+        order.sort_by(|&a, &b| {
+            let a = self.strings[a].iter().rev();
+            let b = self.strings[b].iter().rev();
+            b.cmp(a)
+        });
+
+        let mut table = vec![0];
+        let mut offsets = vec![0; self.strings.len()];
+
+        let mut previous: Option<(&[u8], usize)> = None;
+        for id in order {
+            let string = self.strings[id].as_slice();
+            if string.is_empty() {
+                continue;
+            }
+            let offset = match previous {
+                Some((p, offset)) if p.ends_with(string) => offset + p.len() - string.len(),
+                _ => {
+                    let offset = table.len();
+                    table.extend_from_slice(string);
+                    table.push(0);
+                    offset
+                }
+            };
+            offsets[id] = offset as u32;
+            previous = Some((string, offset));
+        }
+
+        (table, offsets)
+    }
+}
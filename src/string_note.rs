@@ -1,34 +1,95 @@
-use super::{Error, Encoding};
+use core::{fmt, ops::Range, str, str::Utf8Error};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use super::{Error, Encoding, Offset, Limit};
+use super::common::to_usize;
+use super::nul::find_nul;
+use super::debug::Name;
 
 #[derive(Clone)]
 pub struct StringTable<'a> {
     slice: &'a [u8],
+    // `Limits::string_length`
+    limit: Option<u32>,
 }
 
 impl<'a> StringTable<'a> {
     pub fn new(slice: &'a [u8]) -> Self {
-        StringTable { slice }
+        StringTable { slice, limit: None }
+    }
+
+    pub(crate) fn limited(self, limit: Option<u32>) -> Self {
+        StringTable { limit, ..self }
     }
 
+    // the whole string up to the terminator, however long it is
     pub fn pick(&self, index: usize) -> Result<&'a [u8], Error> {
-        const MAX_LENGTH: usize = 0xff;
-        let mut length = 0;
-        loop {
-            if *self.slice.get(index + length).ok_or(Error::SliceTooShort)? == 0
-                || length == MAX_LENGTH
-            {
-                break;
-            } else {
-                length += 1;
-            }
+        let tail = self.slice.get(index..).ok_or(Error::SliceTooShort)?;
+        if let Some(limit) = self.limit.map(|limit| limit as usize) {
+            let window = &tail[..tail.len().min(limit.saturating_add(1))];
+            return match find_nul(window) {
+                Some(length) => Ok(&tail[..length]),
+                None if window.len() > limit => Err(Error::LimitExceeded(Limit::StringLength)),
+                None => Err(Error::UnterminatedString),
+            };
+        }
+        match find_nul(tail) {
+            Some(length) => Ok(&tail[..length]),
+            None => Err(Error::UnterminatedString),
         }
+    }
+
+    pub fn pick_str(&self, index: usize) -> Result<&'a str, Error> {
+        str::from_utf8(self.pick(index)?).map_err(|_| Error::InvalidUtf8)
+    }
 
-        Ok(&self.slice[index..(index + length)])
+    // the offset from a 64-bit field, e.g. the value of `DT_NEEDED`
+    pub fn pick_at(&self, offset: Offset) -> Result<&'a [u8], Error> {
+        self.pick(to_usize(offset.0)?)
     }
 
     pub fn as_raw(&self) -> &'a [u8] {
         self.slice
     }
+
+    // every string of the table with its offset, from the empty one at zero
+    pub fn strings(&self) -> Strings<'a> {
+        Strings {
+            table: self.clone(),
+            position: 0,
+        }
+    }
+}
+
+// The strings of `StringTable::strings` one after another, the iteration ends
+// with an error at an unterminated tail
+#[derive(Clone)]
+pub struct Strings<'a> {
+    table: StringTable<'a>,
+    position: usize,
+}
+
+impl<'a> Iterator for Strings<'a> {
+    type Item = Result<(usize, &'a [u8]), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.position;
+        if offset >= self.table.slice.len() {
+            return None;
+        }
+        match self.table.pick(offset) {
+            Ok(string) => {
+                self.position += string.len() + 1;
+                Some(Ok((offset, string)))
+            }
+            Err(error) => {
+                self.position = self.table.slice.len();
+                Some(Err(error))
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -36,49 +97,300 @@ pub struct NoteEntry<'a> {
     pub ty: u64,
     pub name: &'a [u8],
     pub description: &'a [u8],
+    encoding: Encoding,
+}
+
+impl<'a> NoteEntry<'a> {
+    pub fn name_str(&self) -> Result<&'a str, Utf8Error> {
+        str::from_utf8(self.name)
+    }
+
+    pub fn name_lossy(&self) -> Lossy<'a> {
+        Lossy(self.name)
+    }
+
+    pub(crate) fn encoding(&self) -> &Encoding {
+        &self.encoding
+    }
+}
+
+// Displays the bytes as UTF-8, invalid sequences are replaced with `U+FFFD`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Lossy<'a>(pub &'a [u8]);
+
+impl<'a> fmt::Display for Lossy<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = self.0;
+        loop {
+            match str::from_utf8(bytes) {
+                Ok(valid) => return f.write_str(valid),
+                Err(error) => {
+                    let (valid, rest) = bytes.split_at(error.valid_up_to());
+                    f.write_str(str::from_utf8(valid).unwrap_or_default())?;
+                    f.write_str("\u{FFFD}")?;
+                    match error.error_len() {
+                        Some(length) => bytes = &rest[length..],
+                        None => return Ok(()),
+                    }
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct NoteTable<'a> {
     slice: &'a [u8],
     encoding: Encoding,
+    alignment: usize,
+    // `Limits::note_size`
+    limit: Option<u32>,
 }
 
 impl<'a> NoteTable<'a> {
     pub fn new(slice: &'a [u8], encoding: Encoding) -> Self {
-        NoteTable { slice, encoding }
+        NoteTable {
+            slice,
+            encoding,
+            alignment: 4,
+            limit: None,
+        }
     }
 
+    // notes are 4-byte aligned, except the ones from 8-byte aligned containers,
+    // e.g. `.note.gnu.property`
+    pub fn with_alignment(slice: &'a [u8], encoding: Encoding, alignment: u64) -> Self {
+        NoteTable {
+            slice,
+            encoding,
+            alignment: if alignment == 8 { 8 } else { 4 },
+            limit: None,
+        }
+    }
+
+    pub(crate) fn limited(self, limit: Option<u32>) -> Self {
+        NoteTable { limit, ..self }
+    }
+
+    pub fn as_raw(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    // The sizes are checked before they are added up, a corrupted note in a huge core
+    // dump is an error on a 32-bit host as well, the position never wraps around
     pub fn next(&self, position: &mut usize) -> Result<NoteEntry<'a>, Error> {
-        if self.slice.len() < *position + 0x18 {
-            return Err(Error::SliceTooShort);
+        self.locate(position).map(|(entry, _)| entry)
+    }
+
+    // the note and the range of its description in the table
+    pub(crate) fn locate(
+        &self,
+        position: &mut usize,
+    ) -> Result<(NoteEntry<'a>, Range<usize>), Error> {
+        let header = position
+            .checked_add(0x0c)
+            .and_then(|end| self.slice.get(*position..end))
+            .ok_or(Error::SliceTooShort)?;
+        let name_size = read_int!(&header[0x00..], &self.encoding, u32) as u64;
+        let description_size = read_int!(&header[0x04..], &self.encoding, u32) as u64;
+        let ty = read_int!(&header[0x08..], &self.encoding, u32) as u64;
+        if let Some(limit) = self.limit {
+            if description_size > u64::from(limit) {
+                return Err(Error::LimitExceeded(Limit::NoteSize));
+            }
         }
 
-        let header = &self.slice[*position..(*position + 0x18)];
-        let name_size = read_int!(&header[0x00..], &self.encoding, u64) as usize;
-        let description_size = read_int!(&header[0x08..], &self.encoding, u64) as usize;
-        let ty = read_int!(&header[0x10..], &self.encoding, u64);
+        // in 64-bit arithmetic two 32-bit sizes cannot overflow
+        let mask = self.alignment as u64 - 1;
+        let align = |x: u64| (x + mask) & !mask;
 
-        let align8 = |x: usize| if x % 8 == 0 { x } else { x + 8 - x % 8 };
-        let name_size_aligned = align8(name_size);
-        let description_size = align8(description_size);
+        let str_start = *position as u64 + 0x0c;
+        let str_end = str_start + name_size;
+        let description_start = align(str_end);
+        let description_end = description_start + description_size;
 
-        let new_position = *position + 0x18 + name_size_aligned + description_size;
-        if self.slice.len() < new_position {
+        if (self.slice.len() as u64) < description_end {
             return Err(Error::SliceTooShort);
         }
+        // everything is within the slice, so fits in `usize`
+        let (str_start, str_end) = (str_start as usize, str_end as usize);
+        let (description_start, description_end) =
+            (description_start as usize, description_end as usize);
+        let new_position = align(description_end as u64).min(self.slice.len() as u64) as usize;
 
-        let str_start = *position + 0x18;
-        let str_end = str_start + name_size;
+        // the terminator is not a part of the name
+        let mut name = &self.slice[str_start..str_end];
+        while let Some((0, init)) = name.split_last() {
+            name = init;
+        }
 
         let entry = NoteEntry {
             ty,
-            name: &self.slice[str_start..str_end],
-            description: &self.slice[str_end..(str_end + description_size)],
+            name,
+            description: &self.slice[description_start..description_end],
+            encoding: self.encoding.clone(),
         };
 
         *position = new_position;
 
-        Ok(entry)
+        Ok((entry, description_start..description_end))
+    }
+
+    // every note, a corrupted one is the last item, the notes after it cannot be found
+    pub fn iter(&self) -> NoteEntries<'a> {
+        NoteEntries {
+            table: self.clone(),
+            position: 0,
+        }
+    }
+
+    // Reads the table once, the notes are counted and can be picked at random,
+    // e.g. in a huge `PT_NOTE` of a core dump. A corrupted note is the error.
+    #[cfg(feature = "alloc")]
+    pub fn entries(&self) -> Result<IndexedNotes<'a>, Error> {
+        let mut notes = IndexedNotes {
+            notes: Vec::new(),
+            ranges: Vec::new(),
+        };
+        let mut position = 0;
+        while position < self.slice.len() {
+            let start = position;
+            notes.notes.push(self.next(&mut position)?);
+            notes.ranges.push(start..position);
+        }
+        Ok(notes)
+    }
+}
+
+#[derive(Clone)]
+pub struct NoteEntries<'a> {
+    table: NoteTable<'a>,
+    position: usize,
+}
+
+impl<'a> Iterator for NoteEntries<'a> {
+    type Item = Result<NoteEntry<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.table.slice.len() {
+            return None;
+        }
+        match self.table.next(&mut self.position) {
+            Ok(entry) => Some(Ok(entry)),
+            Err(error) => {
+                self.position = self.table.slice.len();
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+// The notes of a table with their byte ranges in it, the padding is included
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct IndexedNotes<'a> {
+    notes: Vec<NoteEntry<'a>>,
+    ranges: Vec<Range<usize>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> IndexedNotes<'a> {
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&NoteEntry<'a>> {
+        self.notes.get(index)
+    }
+
+    // of the note in the table, `NoteTable::as_raw`
+    pub fn range(&self, index: usize) -> Option<Range<usize>> {
+        self.ranges.get(index).cloned()
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &NoteEntry<'a>> {
+        self.notes.iter()
+    }
+}
+
+impl<'a> IntoIterator for &NoteTable<'a> {
+    type Item = Result<NoteEntry<'a>, Error>;
+    type IntoIter = NoteEntries<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> fmt::Debug for StringTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slice = self.slice;
+        f.debug_struct("StringTable")
+            .field("size", &slice.len())
+            .field("number", &slice.iter().filter(|&&b| b == 0).count())
+            .finish()
+    }
+}
+
+impl<'a> fmt::Debug for NoteTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Notes<'a, 'b>(&'b NoteTable<'a>);
+
+        impl<'a, 'b> fmt::Debug for Notes<'a, 'b> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let mut list = f.debug_list();
+                let mut position = 0;
+                while position < self.0.slice.len() {
+                    match self.0.next(&mut position) {
+                        Ok(note) => list.entry(&format_args!(
+                            "{:?} 0x{:x} <{} bytes>",
+                            Name(note.name),
+                            note.ty,
+                            note.description.len(),
+                        )),
+                        Err(_) => break,
+                    };
+                }
+                list.finish()
+            }
+        }
+
+        f.debug_struct("NoteTable")
+            .field("size", &self.slice.len())
+            .field("notes", &Notes(self))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StringTable, NoteTable};
+    use crate::{Error, Encoding, Offset};
+
+    #[test]
+    fn string_offsets_above_4_gib() {
+        let table = StringTable::new(b"\0name\0");
+        assert_eq!(table.pick_at(Offset(1)), Ok(&b"name"[..]));
+        let error = table.pick_at(Offset(0x1_0000_0001)).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::SliceTooShort | Error::ArithmeticOverflow
+        ));
+    }
+
+    #[test]
+    fn note_sizes_of_32_bits() {
+        // the sizes add up past `u32::MAX`, and past `usize::MAX` of a 32-bit host
+        let mut note = [0xff; 0x10];
+        note[0x08..0x0c].copy_from_slice(&1u32.to_le_bytes());
+        let table = NoteTable::new(&note, Encoding::Little);
+        assert_eq!(table.next(&mut 0), Err(Error::SliceTooShort));
+        for mut position in [usize::MAX, usize::MAX - 0x0b] {
+            assert_eq!(table.next(&mut position), Err(Error::SliceTooShort));
+        }
     }
 }
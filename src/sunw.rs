@@ -0,0 +1,490 @@
+use super::{
+    Error, SectionIndex, SymbolIndex, Encoding, Elf64, Entry, Table, StringTable, Abi, Index,
+    SectionType, SectionData,
+};
+use super::dynamic::DynamicEntry;
+
+// `SHT_SUNW_cap`, the same code as `SHT_GNU_ATTRIBUTES`
+const SHT_SUNW_CAP: u32 = 0x6ffffff5;
+// `SHT_SUNW_move`
+const SHT_SUNW_MOVE: u32 = 0x6ffffffa;
+// `SHT_SUNW_syminfo`
+const SHT_SUNW_SYMINFO: u32 = 0x6ffffffc;
+
+bitflags! {
+    // `CA_SUNW_SF_1`
+    pub struct SoftwareCapabilities: u64 {
+        const FPKNWN = 0x001;
+        const FPUSED = 0x002;
+        const ADDR32 = 0x004;
+    }
+}
+
+bitflags! {
+    // `CA_SUNW_HW_1` of `EM_386` and `EM_AMD64`, `AV_386_*`
+    pub struct X86Capabilities: u64 {
+        const FPU = 0x00000001;
+        const TSC = 0x00000002;
+        const CX8 = 0x00000004;
+        const SEP = 0x00000008;
+        const AMD_SYSC = 0x00000010;
+        const CMOV = 0x00000020;
+        const MMX = 0x00000040;
+        const AMD_MMX = 0x00000080;
+        const AMD_3DNOW = 0x00000100;
+        const AMD_3DNOWX = 0x00000200;
+        const FXSR = 0x00000400;
+        const SSE = 0x00000800;
+        const SSE2 = 0x00001000;
+        const PAUSE = 0x00002000;
+        const SSE3 = 0x00004000;
+        const MON = 0x00008000;
+        const CX16 = 0x00010000;
+        const AHF = 0x00020000;
+        const TSCP = 0x00040000;
+        const AMD_SSE4A = 0x00080000;
+        const POPCNT = 0x00100000;
+        const AMD_LZCNT = 0x00200000;
+        const SSSE3 = 0x00400000;
+        const SSE4_1 = 0x00800000;
+        const SSE4_2 = 0x01000000;
+        const MOVBE = 0x02000000;
+        const AES = 0x04000000;
+        const PCLMULQDQ = 0x08000000;
+        const XSAVE = 0x10000000;
+        const AVX = 0x20000000;
+        const VMX = 0x40000000;
+        const AMD_SVM = 0x80000000;
+    }
+}
+
+bitflags! {
+    // `SYMINFO_FLG_*`
+    pub struct SyminfoFlags: u16 {
+        const DIRECT = 0x0001;
+        const FILTER = 0x0002;
+        const COPY = 0x0004;
+        const LAZYLOAD = 0x0008;
+        const DIRECTBIND = 0x0010;
+        const NOEXTDIRECT = 0x0020;
+        const AUXILIARY = 0x0040;
+        const INTERPOSE = 0x0080;
+        const CAP = 0x0100;
+        const DEFERRED = 0x0200;
+    }
+}
+
+bitflags! {
+    // `DT_FEATURE_1`, `DTF_1_*`
+    pub struct FeatureFlags: u64 {
+        const PARINIT = 0x1;
+        const CONFEXP = 0x2;
+    }
+}
+
+bitflags! {
+    // `DT_POSFLAG_1`, `DF_P1_*`, of the next entry, usually `DT_NEEDED`
+    pub struct PositionFlags: u64 {
+        const LAZYLOAD = 0x1;
+        const GROUPPERM = 0x2;
+        const DEFERRED = 0x4;
+    }
+}
+
+// `DT_SUNW_*` and the tags only the Solaris linker emits
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SunwDynamicTag {
+    // `DT_SUNW_AUXILIARY`, a string
+    Auxiliary,
+    // `DT_SUNW_RTLDINF`
+    RtldInfo,
+    // `DT_SUNW_FILTER`, a string
+    Filter,
+    // `DT_SUNW_CAP`
+    Capabilities,
+    // `DT_SUNW_SYMTAB`, the local symbols before `.dynsym`
+    SymbolTable,
+    // `DT_SUNW_SYMSZ`
+    SymbolTableSize,
+    // `DT_SUNW_SORTENT`
+    SortEntrySize,
+    // `DT_SUNW_SYMSORT`
+    SymbolSort,
+    // `DT_SUNW_SYMSORTSZ`
+    SymbolSortSize,
+    // `DT_SUNW_TLSSORT`
+    TlsSort,
+    // `DT_SUNW_TLSSORTSZ`
+    TlsSortSize,
+    // `DT_SUNW_CAPINFO`
+    CapabilityInfo,
+    // `DT_SUNW_STRPAD`
+    StringPadding,
+    // `DT_SUNW_CAPCHAIN`
+    CapabilityChain,
+    // `DT_SUNW_LDMACH`, the machine of the linker
+    LinkerMachine,
+    // `DT_SUNW_CAPCHAINENT`
+    CapabilityChainEntrySize,
+    // `DT_SUNW_CAPCHAINSZ`
+    CapabilityChainSize,
+    // `DT_SUNW_ASLR`
+    Aslr,
+    // `DT_CHECKSUM`
+    Checksum,
+    // `DT_PLTPADSZ`
+    PltPaddingSize,
+    // `DT_MOVEENT`
+    MoveEntrySize,
+    // `DT_MOVESZ`
+    MoveSize,
+    // `DT_FEATURE_1`, `FeatureFlags`
+    Feature1,
+    // `DT_POSFLAG_1`, `PositionFlags`
+    PositionFlags1,
+    // `DT_SYMINSZ`
+    SyminfoSize,
+    // `DT_SYMINENT`
+    SyminfoEntrySize,
+    // `DT_CONFIG`, a string
+    Config,
+    // `DT_PLTPAD`
+    PltPadding,
+    // `DT_MOVETAB`
+    MoveTable,
+    // `DT_SYMINFO`
+    Syminfo,
+}
+
+impl SunwDynamicTag {
+    pub fn new(tag: u64) -> Option<Self> {
+        Some(match tag {
+            0x6000000d => SunwDynamicTag::Auxiliary,
+            0x6000000e => SunwDynamicTag::RtldInfo,
+            0x6000000f => SunwDynamicTag::Filter,
+            0x60000010 => SunwDynamicTag::Capabilities,
+            0x60000011 => SunwDynamicTag::SymbolTable,
+            0x60000012 => SunwDynamicTag::SymbolTableSize,
+            0x60000013 => SunwDynamicTag::SortEntrySize,
+            0x60000014 => SunwDynamicTag::SymbolSort,
+            0x60000015 => SunwDynamicTag::SymbolSortSize,
+            0x60000016 => SunwDynamicTag::TlsSort,
+            0x60000017 => SunwDynamicTag::TlsSortSize,
+            0x60000018 => SunwDynamicTag::CapabilityInfo,
+            0x60000019 => SunwDynamicTag::StringPadding,
+            0x6000001a => SunwDynamicTag::CapabilityChain,
+            0x6000001b => SunwDynamicTag::LinkerMachine,
+            0x6000001d => SunwDynamicTag::CapabilityChainEntrySize,
+            0x6000001f => SunwDynamicTag::CapabilityChainSize,
+            0x60000023 => SunwDynamicTag::Aslr,
+            0x6ffffdf8 => SunwDynamicTag::Checksum,
+            0x6ffffdf9 => SunwDynamicTag::PltPaddingSize,
+            0x6ffffdfa => SunwDynamicTag::MoveEntrySize,
+            0x6ffffdfb => SunwDynamicTag::MoveSize,
+            0x6ffffdfc => SunwDynamicTag::Feature1,
+            0x6ffffdfd => SunwDynamicTag::PositionFlags1,
+            0x6ffffdfe => SunwDynamicTag::SyminfoSize,
+            0x6ffffdff => SunwDynamicTag::SyminfoEntrySize,
+            0x6ffffefa => SunwDynamicTag::Config,
+            0x6ffffefd => SunwDynamicTag::PltPadding,
+            0x6ffffefe => SunwDynamicTag::MoveTable,
+            0x6ffffeff => SunwDynamicTag::Syminfo,
+            _ => return None,
+        })
+    }
+
+    // the value is an offset in the dynamic string table
+    pub fn is_string(&self) -> bool {
+        matches!(
+            self,
+            SunwDynamicTag::Auxiliary | SunwDynamicTag::Filter | SunwDynamicTag::Config
+        )
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SunwDynamicEntry<'a> {
+    pub tag: SunwDynamicTag,
+    pub value: u64,
+    // the value resolved if the tag is a string
+    pub string: Option<&'a [u8]>,
+}
+
+impl<'a> SunwDynamicEntry<'a> {
+    pub fn feature_flags(&self) -> Option<FeatureFlags> {
+        match self.tag {
+            SunwDynamicTag::Feature1 => Some(FeatureFlags::from_bits_truncate(self.value)),
+            _ => None,
+        }
+    }
+
+    pub fn position_flags(&self) -> Option<PositionFlags> {
+        match self.tag {
+            SunwDynamicTag::PositionFlags1 => Some(PositionFlags::from_bits_truncate(self.value)),
+            _ => None,
+        }
+    }
+}
+
+// `Elf64_Cap`, a group of them ends with `Capability::Null`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Capability {
+    Null,
+    // the bits depend on the machine, e.g. `X86Capabilities`
+    Hardware1(u64),
+    Software1(SoftwareCapabilities),
+    Hardware2(u64),
+    // offsets in the string table the section links to
+    Platform(u32),
+    Machine(u32),
+    Id(u32),
+    Hardware3(u64),
+    Unknown { tag: u64, value: u64 },
+}
+
+impl Capability {
+    pub fn x86(&self) -> Option<X86Capabilities> {
+        match self {
+            Capability::Hardware1(value) => Some(X86Capabilities::from_bits_truncate(*value)),
+            _ => None,
+        }
+    }
+}
+
+impl Entry for Capability {
+    type Error = Error;
+
+    const SIZE: usize = 0x10;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let tag = read_int!(&slice[0x00..], &encoding, u64);
+        let value = read_int!(&slice[0x08..], &encoding, u64);
+        Ok(match tag {
+            0 => Capability::Null,
+            1 => Capability::Hardware1(value),
+            2 => Capability::Software1(SoftwareCapabilities::from_bits_truncate(value)),
+            3 => Capability::Hardware2(value),
+            4 => Capability::Platform(value as u32),
+            5 => Capability::Machine(value as u32),
+            6 => Capability::Id(value as u32),
+            7 => Capability::Hardware3(value),
+            tag => Capability::Unknown { tag, value },
+        })
+    }
+}
+
+// `si_boundto`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum BoundTo {
+    // `SYMINFO_BT_SELF`
+    SelfObject,
+    // `SYMINFO_BT_PARENT`
+    Parent,
+    // `SYMINFO_BT_NONE`
+    None,
+    // `SYMINFO_BT_EXTERN`
+    Extern,
+    // the index of the `DT_NEEDED` entry in the dynamic section
+    Dynamic(u16),
+}
+
+// `Elf64_Syminfo`, of the symbol of the same index of `.dynsym`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SyminfoEntry {
+    pub bound_to: BoundTo,
+    pub flags: SyminfoFlags,
+}
+
+impl Entry for SyminfoEntry {
+    type Error = Error;
+
+    const SIZE: usize = 0x04;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let bound_to = match read_int!(&slice[0x00..], &encoding, u16) {
+            0xffff => BoundTo::SelfObject,
+            0xfffe => BoundTo::Parent,
+            0xfffd => BoundTo::None,
+            0xfffc => BoundTo::Extern,
+            index => BoundTo::Dynamic(index),
+        };
+        let flags = read_int!(&slice[0x02..], &encoding, u16);
+        Ok(SyminfoEntry {
+            bound_to,
+            flags: SyminfoFlags::from_bits_truncate(flags),
+        })
+    }
+}
+
+// `Elf64_Move`, the initial value of a part of a partially initialized symbol
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct MoveEntry {
+    pub value: u64,
+    pub symbol_index: SymbolIndex,
+    // of the value, in bytes
+    pub size: u8,
+    // in the symbol
+    pub offset: u64,
+    pub repeat: u16,
+    pub stride: u16,
+}
+
+impl Entry for MoveEntry {
+    type Error = Error;
+
+    // with the padding
+    const SIZE: usize = 0x20;
+
+    fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        let info = read_int!(&slice[0x08..], &encoding, u64);
+        Ok(MoveEntry {
+            value: read_int!(&slice[0x00..], &encoding, u64),
+            symbol_index: SymbolIndex((info >> 8) as usize),
+            size: info as u8,
+            offset: read_int!(&slice[0x10..], &encoding, u64),
+            repeat: read_int!(&slice[0x18..], &encoding, u16),
+            stride: read_int!(&slice[0x1a..], &encoding, u16),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum SunwSection<'a> {
+    Capabilities {
+        table: Table<'a, Capability>,
+        number: usize,
+        // for `Capability::Platform` and the like
+        strings: Option<StringTable<'a>>,
+    },
+    Syminfo {
+        table: Table<'a, SyminfoEntry>,
+        number: usize,
+        // the symbols the entries are for
+        symbols: Index,
+    },
+    Move {
+        table: Table<'a, MoveEntry>,
+        number: usize,
+        symbols: Index,
+    },
+}
+
+impl<'a> Elf64<'a> {
+    // `Abi::Solaris`, or a `.SUNW_` section, the Solaris linker usually leaves `ELFOSABI_NONE`
+    // and other systems use some of its codes, e.g. `SHT_GNU_ATTRIBUTES` or `DT_ANDROID_REL`
+    pub(crate) fn solaris(&self) -> Result<bool, Error> {
+        if self.abi() == Abi::Solaris {
+            return Ok(true);
+        }
+        for index in 0..self.section_number() {
+            let name = self.section_info(SectionIndex(index))?.map(|s| s.name);
+            if name
+                .map(|name| name.starts_with(b".SUNW_"))
+                .unwrap_or(false)
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // The section decoded if it is `SHT_SUNW_cap`, `SHT_SUNW_syminfo` or `SHT_SUNW_move`,
+    // they are `SectionData::OsSpecific` otherwise, `SHT_SUNW_cap` only of a Solaris file
+    pub fn sunw_section(&self, index: SectionIndex) -> Result<Option<SunwSection<'a>>, Error> {
+        let section = match self.section(index)? {
+            Some(section) => section,
+            None => return Ok(None),
+        };
+        let (code, slice) = match section.data {
+            SectionData::OsSpecific { code, slice } => (code, slice),
+            _ => return Ok(None),
+        };
+        let header = self.section_table.pick(index.0)?;
+        let encoding = self.encoding();
+        let data = match code {
+            SHT_SUNW_CAP if self.solaris()? => {
+                let strings = match header.link {
+                    Index::Regular(link) => match self.section(link)?.map(|s| s.data) {
+                        Some(SectionData::StringTable(strings)) => Some(strings),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                SunwSection::Capabilities {
+                    table: Table::new(slice, encoding),
+                    number: slice.len() / Capability::SIZE,
+                    strings,
+                }
+            }
+            SHT_SUNW_SYMINFO => SunwSection::Syminfo {
+                table: Table::new(slice, encoding),
+                number: slice.len() / SyminfoEntry::SIZE,
+                symbols: header.link,
+            },
+            SHT_SUNW_MOVE => SunwSection::Move {
+                table: Table::new(slice, encoding),
+                number: slice.len() / MoveEntry::SIZE,
+                symbols: header.link,
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(data))
+    }
+
+    // The first `SHT_SUNW_cap`, the capabilities the object needs to run
+    pub fn sunw_capabilities(&self) -> Result<Option<SunwSection<'a>>, Error> {
+        for index in 0..self.section_number() {
+            if self.section_table.pick(index)?.ty != SectionType::OsSpecific(SHT_SUNW_CAP) {
+                continue;
+            }
+            if let Some(section) = self.sunw_section(SectionIndex(index))? {
+                return Ok(Some(section));
+            }
+        }
+        Ok(None)
+    }
+
+    // The entries of the dynamic section with a SUNW tag, in its order,
+    // the others are skipped, and `DT_SUNW_*` of a file that is not of Solaris
+    pub fn sunw_dynamic(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<SunwDynamicEntry<'a>, Error>> + 'a, Error> {
+        let solaris = self.solaris()?;
+        let source = self.dynamic_source()?;
+        Ok(source.into_iter().flat_map(move |source| {
+            let strings = source.clone();
+            source.entries().filter_map(move |entry| {
+                let DynamicEntry { tag, value } = match entry {
+                    Ok(entry) => entry,
+                    Err(error) => return Some(Err(error)),
+                };
+                let tag = u64::from(tag);
+                if !solaris && tag < 0x6ffff000 {
+                    return None;
+                }
+                let tag = SunwDynamicTag::new(tag)?;
+                let string = if tag.is_string() {
+                    match strings.string(value) {
+                        Ok(string) => Some(string),
+                        Err(error) => return Some(Err(error)),
+                    }
+                } else {
+                    None
+                };
+                Some(Ok(SunwDynamicEntry { tag, value, string }))
+            })
+        }))
+    }
+}
@@ -1,6 +1,9 @@
-use super::{Address, Error, Encoding, Index, Entry};
+use core::str;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+use super::{Address, Error, Encoding, Index, Entry, Encode, Lossy};
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum SymbolBinding {
     Local,
     Global,
@@ -10,7 +13,8 @@ pub enum SymbolBinding {
     Unknown(u8),
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum SymbolType {
     Nothing,
     Object,
@@ -22,7 +26,8 @@ pub enum SymbolType {
     Unknown(u8),
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SymbolInfo {
     pub binding: SymbolBinding,
     pub ty: SymbolType,
@@ -53,7 +58,32 @@ impl From<u8> for SymbolInfo {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl From<SymbolInfo> for u8 {
+    fn from(v: SymbolInfo) -> Self {
+        let binding = match v.binding {
+            SymbolBinding::Local => 0x00,
+            SymbolBinding::Global => 0x01,
+            SymbolBinding::Weak => 0x02,
+            SymbolBinding::OsSpecific(t) => t + 0x0a,
+            SymbolBinding::ProcessorSpecific(t) => t + 0x0d,
+            SymbolBinding::Unknown(t) => t,
+        };
+        let ty = match v.ty {
+            SymbolType::Nothing => 0x00,
+            SymbolType::Object => 0x01,
+            SymbolType::Function => 0x02,
+            SymbolType::Section => 0x03,
+            SymbolType::File => 0x04,
+            SymbolType::OsSpecific(t) => t + 0x0a,
+            SymbolType::ProcessorSpecific(t) => t + 0x0d,
+            SymbolType::Unknown(t) => t,
+        };
+        (binding << 4) | (ty & 0x0f)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SymbolEntry {
     pub name: u32,
     pub info: SymbolInfo,
@@ -63,6 +93,47 @@ pub struct SymbolEntry {
     pub size: u64,
 }
 
+impl SymbolEntry {
+    pub fn is_defined(&self) -> bool {
+        self.section_index != Index::Undefined
+    }
+
+    // The final address of a symbol of a relocatable file, `base` gives the address
+    // the loader chose for the section with the index. Undefined symbols must be
+    // resolved elsewhere and common symbols allocated first.
+    pub fn resolve<F>(&self, mut base: F) -> Result<Address, Error>
+    where
+        F: FnMut(usize) -> Option<Address>,
+    {
+        match self.section_index {
+            Index::Undefined => Err(Error::UnresolvedSymbol),
+            Index::AbsoluteValue => Ok(self.value),
+            Index::Common => Err(Error::CommonSymbol),
+            Index::Regular(index) => base(index.0)
+                .map(|address| address.wrapping_add(self.value.0))
+                .ok_or(Error::SectionNotPlaced(index.0)),
+            _ => Err(Error::UnexpectedSectionType),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Symbol<'a> {
+    pub name: &'a [u8],
+    pub entry: SymbolEntry,
+}
+
+impl<'a> Symbol<'a> {
+    pub fn name_str(&self) -> Result<&'a str, str::Utf8Error> {
+        str::from_utf8(self.name)
+    }
+
+    pub fn name_lossy(&self) -> Lossy<'a> {
+        Lossy(self.name)
+    }
+}
+
 impl Entry for SymbolEntry {
     type Error = Error;
 
@@ -78,8 +149,29 @@ impl Entry for SymbolEntry {
             info: slice[0x04].into(),
             reserved: slice[0x05],
             section_index: read_int!(&slice[0x06..], &encoding, u16).into(),
-            value: read_int!(&slice[0x08..], &encoding, u64),
+            value: Address(read_int!(&slice[0x08..], &encoding, u64)),
             size: read_int!(&slice[0x10..], &encoding, u64),
         })
     }
 }
+
+impl Encode for SymbolEntry {
+    fn encode(&self, slice: &mut [u8], encoding: Encoding) -> Result<(), Self::Error> {
+        if slice.len() < Self::SIZE {
+            return Err(Error::SliceTooShort);
+        }
+
+        write_int!(&mut slice[0x00..], &encoding, u32, self.name);
+        slice[0x04] = self.info.clone().into();
+        slice[0x05] = self.reserved;
+        write_int!(
+            &mut slice[0x06..],
+            &encoding,
+            u16,
+            self.section_index.clone().into()
+        );
+        write_int!(&mut slice[0x08..], &encoding, u64, self.value.0);
+        write_int!(&mut slice[0x10..], &encoding, u64, self.size);
+        Ok(())
+    }
+}
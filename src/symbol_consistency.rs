@@ -0,0 +1,98 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use super::{Error, Address, Elf64, SectionType, SymbolEntry};
+
+// A defined symbol of `.dynsym` that `.symtab` disagrees with, `index` is in `.dynsym`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SymbolMismatch<'a> {
+    // no defined symbol of `.symtab` has the name
+    Missing {
+        index: usize,
+        name: &'a [u8],
+    },
+    Value {
+        index: usize,
+        name: &'a [u8],
+        dynamic: Address,
+        table: Address,
+    },
+    Size {
+        index: usize,
+        name: &'a [u8],
+        dynamic: u64,
+        table: u64,
+    },
+}
+
+// `.symtab` written by newer linkers has the version in the name, `foo@@VERSION`
+fn unversioned(name: &[u8]) -> &[u8] {
+    match name.iter().position(|&c| c == b'@') {
+        Some(position) => &name[..position],
+        None => name,
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Compares every defined symbol of `.dynsym` with the symbols of `.symtab`
+    // of the same name, the linker writes both from the same definition, a difference
+    // means the file was edited after the link. `None` if there is no `.symtab`.
+    pub fn cross_check_symbols(&self) -> Result<Option<Vec<SymbolMismatch<'a>>>, Error> {
+        let table = match self.symbol_source(SectionType::SymbolTable)? {
+            Some(table) => table,
+            None => return Ok(None),
+        };
+        let dynamic = match self.symbol_source(SectionType::DynamicSymbolTable)? {
+            Some(dynamic) => dynamic,
+            None => return Ok(Some(Vec::new())),
+        };
+
+        let mut definitions = BTreeMap::<_, Vec<SymbolEntry>>::new();
+        for index in 1..table.number {
+            let symbol = table.get(index)?;
+            if symbol.entry.is_defined() {
+                definitions
+                    .entry(unversioned(symbol.name))
+                    .or_default()
+                    .push(symbol.entry);
+            }
+        }
+
+        let mut mismatches = Vec::new();
+        for index in 1..dynamic.number {
+            let symbol = dynamic.get(index)?;
+            let entry = &symbol.entry;
+            if !entry.is_defined() || symbol.name.is_empty() {
+                continue;
+            }
+            let name = symbol.name;
+            let candidates = match definitions.get(name) {
+                Some(candidates) => candidates,
+                None => {
+                    mismatches.push(SymbolMismatch::Missing { index, name });
+                    continue;
+                }
+            };
+            if candidates
+                .iter()
+                .any(|c| c.value == entry.value && c.size == entry.size)
+            {
+                continue;
+            }
+            match candidates.iter().find(|c| c.value == entry.value) {
+                Some(candidate) => mismatches.push(SymbolMismatch::Size {
+                    index,
+                    name,
+                    dynamic: entry.size,
+                    table: candidate.size,
+                }),
+                None => mismatches.push(SymbolMismatch::Value {
+                    index,
+                    name,
+                    dynamic: entry.value,
+                    table: candidates[0].value,
+                }),
+            }
+        }
+        Ok(Some(mismatches))
+    }
+}
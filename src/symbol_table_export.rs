@@ -0,0 +1,181 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use std::io;
+
+use super::{Elf64, Symbol, SymbolType, SymbolBinding, SectionType, SectionIndex, Index, SymbolVersion};
+
+// The format of `Elf64::write_symbol_table`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TableFormat {
+    // RFC 4180, a field with a comma, a quote or a line break is quoted
+    Csv,
+    // a tab, a line break or a backslash of a field is escaped, `\t`, `\n`, `\r`, `\\`
+    Tsv,
+}
+
+const COLUMNS: [&[u8]; 7] = [
+    b"name", b"address", b"size", b"type", b"binding", b"section", b"version",
+];
+
+fn field<W>(writer: &mut W, format: TableFormat, value: &[u8]) -> io::Result<()>
+where
+    W: io::Write,
+{
+    match format {
+        TableFormat::Csv => {
+            if !value
+                .iter()
+                .any(|b| matches!(b, b',' | b'"' | b'\n' | b'\r'))
+            {
+                return writer.write_all(value);
+            }
+            writer.write_all(b"\"")?;
+            for (index, part) in value.split(|b| *b == b'"').enumerate() {
+                if index != 0 {
+                    writer.write_all(b"\"\"")?;
+                }
+                writer.write_all(part)?;
+            }
+            writer.write_all(b"\"")
+        }
+        TableFormat::Tsv => {
+            for byte in value {
+                match byte {
+                    b'\t' => writer.write_all(b"\\t")?,
+                    b'\n' => writer.write_all(b"\\n")?,
+                    b'\r' => writer.write_all(b"\\r")?,
+                    b'\\' => writer.write_all(b"\\\\")?,
+                    byte => writer.write_all(&[*byte])?,
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn row<W>(writer: &mut W, format: TableFormat, fields: &[&[u8]]) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let separator: &[u8] = match format {
+        TableFormat::Csv => b",",
+        TableFormat::Tsv => b"\t",
+    };
+    for (index, value) in fields.iter().enumerate() {
+        if index != 0 {
+            writer.write_all(separator)?;
+        }
+        field(writer, format, value)?;
+    }
+    writer.write_all(b"\n")
+}
+
+// the names of readelf
+fn type_name(ty: &SymbolType) -> String {
+    match ty {
+        SymbolType::Nothing => "NOTYPE".into(),
+        SymbolType::Object => "OBJECT".into(),
+        SymbolType::Function => "FUNC".into(),
+        SymbolType::Section => "SECTION".into(),
+        SymbolType::File => "FILE".into(),
+        SymbolType::OsSpecific(0) => "IFUNC".into(),
+        SymbolType::OsSpecific(t) => format!("OS+{}", t),
+        SymbolType::ProcessorSpecific(t) => format!("PROC+{}", t),
+        SymbolType::Unknown(5) => "COMMON".into(),
+        SymbolType::Unknown(6) => "TLS".into(),
+        SymbolType::Unknown(t) => format!("<{}>", t),
+    }
+}
+
+fn binding_name(binding: &SymbolBinding) -> String {
+    match binding {
+        SymbolBinding::Local => "LOCAL".into(),
+        SymbolBinding::Global => "GLOBAL".into(),
+        SymbolBinding::Weak => "WEAK".into(),
+        SymbolBinding::OsSpecific(0) => "UNIQUE".into(),
+        SymbolBinding::OsSpecific(t) => format!("OS+{}", t),
+        SymbolBinding::ProcessorSpecific(t) => format!("PROC+{}", t),
+        SymbolBinding::Unknown(t) => format!("<{}>", t),
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Writes `merged_symbols` as a table with a header row, `name`, `address`, `size`,
+    // `type`, `binding`, `section` and `version`. The address is hexadecimal with `0x`,
+    // the section is its name, or `UND`, `ABS` and `COM`, the version is the one of
+    // `.gnu.version` of the symbol in `.dynsym`, `@@` before a default version the file
+    // defines, `@` before a hidden or a required one, empty without a version.
+    // The bytes of a name are written as they are, the names are not always UTF-8.
+    // Returns the number of the symbols written.
+    pub fn write_symbol_table<W>(&self, writer: &mut W, format: TableFormat) -> io::Result<usize>
+    where
+        W: io::Write,
+    {
+        row(writer, format, &COLUMNS)?;
+        let dynamic = self.symbol_source(SectionType::DynamicSymbolTable)?;
+        let versions = self.versions()?;
+        let mut number = 0;
+        for symbol in self.merged_symbols()? {
+            let symbol = symbol?;
+            let version = match &dynamic {
+                Some(dynamic) => match dynamic.position(&symbol)? {
+                    Some(index) => versions.symbol_version(index)?,
+                    None => None,
+                },
+                None => None,
+            };
+            let version = match version {
+                Some(SymbolVersion::Defined { definition, hidden }) => {
+                    let mut name = if hidden {
+                        b"@".to_vec()
+                    } else {
+                        b"@@".to_vec()
+                    };
+                    name.extend_from_slice(definition.name);
+                    name
+                }
+                Some(SymbolVersion::Required(requirement)) => {
+                    let mut name = b"@".to_vec();
+                    name.extend_from_slice(requirement.name);
+                    name
+                }
+                _ => Vec::new(),
+            };
+            let entry = &symbol.entry;
+            row(
+                writer,
+                format,
+                &[
+                    symbol.name,
+                    format!("0x{:x}", entry.value).as_bytes(),
+                    entry.size.to_string().as_bytes(),
+                    type_name(&entry.info.ty).as_bytes(),
+                    binding_name(&entry.info.binding).as_bytes(),
+                    &self.section_column(&symbol)?,
+                    &version,
+                ],
+            )?;
+            number += 1;
+        }
+        Ok(number)
+    }
+
+    fn section_column(&self, symbol: &Symbol<'a>) -> io::Result<Vec<u8>> {
+        let name: &[u8] = match &symbol.entry.section_index {
+            Index::Undefined => b"UND",
+            Index::AbsoluteValue => b"ABS",
+            Index::Common => b"COM",
+            Index::Regular(SectionIndex(index)) => {
+                return Ok(match self.section_info(SectionIndex(*index))? {
+                    Some(info) => info.name.to_vec(),
+                    None => index.to_string().into_bytes(),
+                })
+            }
+            Index::Extended | Index::ProcessorSecific(_) | Index::EnvironmentSpecific(_) => b"RSV",
+        };
+        Ok(name.to_vec())
+    }
+}
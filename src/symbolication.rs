@@ -0,0 +1,259 @@
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+
+use super::{
+    Error, SectionIndex, Address, Elf64, Table, StringTable, SymbolEntry, Symbol, SymbolType,
+    SectionType, SectionData, Index,
+};
+#[cfg(feature = "alloc")]
+use super::IndexedStringTable;
+
+#[derive(Clone)]
+pub(crate) struct SymbolSource<'a> {
+    pub table: Table<'a, SymbolEntry>,
+    pub number: usize,
+    // `sh_info`, the locals are first
+    pub locals: usize,
+    pub names: StringTable<'a>,
+    // `Limits::symbols`
+    pub limit: Option<u32>,
+    // the terminators of the names, if `Symbols::with_name_index` found them
+    #[cfg(feature = "alloc")]
+    pub indexed: Option<Arc<IndexedStringTable<'a>>>,
+}
+
+impl<'a> SymbolSource<'a> {
+    pub fn get(&self, index: usize) -> Result<Symbol<'a>, Error> {
+        let entry = self.table.pick(index)?;
+        #[cfg(feature = "alloc")]
+        if let Some(indexed) = &self.indexed {
+            let name = indexed.pick(entry.name as usize)?;
+            return Ok(Symbol { name, entry });
+        }
+        let name = self.names.pick(entry.name as usize)?;
+        Ok(Symbol { name, entry })
+    }
+
+    // the index of the symbol of the same value and name
+    pub fn position(&self, symbol: &Symbol<'a>) -> Result<Option<usize>, Error> {
+        for index in 1..self.number {
+            let candidate = self.get(index)?;
+            if candidate.entry.value == symbol.entry.value && candidate.name == symbol.name {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+
+    fn find(&self, symbol: &Symbol<'a>) -> Result<Option<Symbol<'a>>, Error> {
+        match self.position(symbol)? {
+            Some(index) => self.get(index).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // the first section of the given type, `SectionType::SymbolTable` or
+    // `SectionType::DynamicSymbolTable`, with its linked string table
+    pub(crate) fn symbol_source(&self, ty: SectionType) -> Result<Option<SymbolSource<'a>>, Error> {
+        for index in 0..self.section_number() {
+            if self.section_table.pick(index)?.ty == ty {
+                return self.symbol_source_at(index).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn symbol_source_at(&self, index: usize) -> Result<SymbolSource<'a>, Error> {
+        let header = self.section_table.pick(index)?;
+        let table = match self.section(SectionIndex(index))?.map(|s| s.data) {
+            Some(SectionData::SymbolTable { table, .. }) => table,
+            Some(SectionData::DynamicSymbolTable { table, .. }) => table,
+            _ => return Err(Error::UnexpectedSectionType),
+        };
+        let names = match header.link {
+            Index::Regular(link) => match self.section(link)?.map(|s| s.data) {
+                Some(SectionData::StringTable(names)) => names,
+                _ => return Err(Error::UnexpectedSectionType),
+            },
+            _ => return Err(Error::UnexpectedSectionType),
+        };
+        Ok(SymbolSource {
+            number: table.len(),
+            table,
+            locals: header.info as usize,
+            names,
+            limit: self.limits.symbols,
+            #[cfg(feature = "alloc")]
+            indexed: None,
+        })
+    }
+
+    // Symbols of `.symtab` and then the ones of `.dynsym` that are not in `.symtab`,
+    // so it works the same for stripped and not stripped files.
+    pub fn merged_symbols(&self) -> Result<MergedSymbols<'a>, Error> {
+        Ok(MergedSymbols {
+            static_symbols: self.symbol_source(SectionType::SymbolTable)?,
+            dynamic_symbols: self.symbol_source(SectionType::DynamicSymbolTable)?,
+            static_index: 1,
+            dynamic_index: 1,
+        })
+    }
+
+    // The defined function or object symbol that covers the address, a function
+    // of ELFv1 PowerPC64 covers its code, not its descriptor in `.opd`, and
+    // the address of a descriptor is taken as the address of the code
+    pub fn symbolize(&self, address: Address) -> Result<Option<Symbol<'a>>, Error> {
+        let opd = self.opd()?;
+        let address = match &opd {
+            Some(opd) => opd.code_address(address)?,
+            None => address,
+        };
+        for symbol in self.merged_symbols()? {
+            let symbol = symbol?;
+            let entry = &symbol.entry;
+            match entry.info.ty {
+                SymbolType::Function | SymbolType::Object | SymbolType::Nothing => (),
+                _ => continue,
+            }
+            if !entry.is_defined() || entry.section_index == Index::AbsoluteValue {
+                continue;
+            }
+            let value = self.function_address(opd.as_ref(), &entry.info.ty, entry.value)?;
+            let end = value.saturating_add(entry.size.max(1));
+            if (value..end).contains(&address) {
+                return Ok(Some(symbol));
+            }
+        }
+        Ok(None)
+    }
+
+    // The symbols `symbolize` knows that overlap `start..end`, e.g. the functions
+    // of a corrupted page, a symbol without a size overlaps if its value is in the range
+    pub fn symbols_in_range(
+        &self,
+        start: Address,
+        end: Address,
+    ) -> Result<impl Iterator<Item = Result<Symbol<'a>, Error>> + 'a, Error> {
+        let elf = self.clone();
+        let opd = self.opd()?;
+        Ok(self.merged_symbols()?.filter_map(move |symbol| {
+            let symbol = match symbol {
+                Ok(symbol) => symbol,
+                Err(error) => return Some(Err(error)),
+            };
+            let entry = &symbol.entry;
+            match entry.info.ty {
+                SymbolType::Function | SymbolType::Object | SymbolType::Nothing => (),
+                _ => return None,
+            }
+            if !entry.is_defined() || entry.section_index == Index::AbsoluteValue {
+                return None;
+            }
+            let value = match elf.function_address(opd.as_ref(), &entry.info.ty, entry.value) {
+                Ok(value) => value,
+                Err(error) => return Some(Err(error)),
+            };
+            let symbol_end = value.saturating_add(entry.size.max(1));
+            if value < end && start < symbol_end {
+                Some(Ok(symbol))
+            } else {
+                None
+            }
+        }))
+    }
+
+    // The defined function symbol that covers the address, the same way `symbolize` does
+    pub(crate) fn function_at(&self, address: Address) -> Result<Option<Symbol<'a>>, Error> {
+        let opd = self.opd()?;
+        let address = match &opd {
+            Some(opd) => opd.code_address(address)?,
+            None => address,
+        };
+        for symbol in self.merged_symbols()? {
+            let symbol = symbol?;
+            let entry = &symbol.entry;
+            if entry.info.ty != SymbolType::Function || !entry.is_defined() {
+                continue;
+            }
+            let value = self.function_address(opd.as_ref(), &entry.info.ty, entry.value)?;
+            let end = value.saturating_add(entry.size.max(1));
+            if (value..end).contains(&address) {
+                return Ok(Some(symbol));
+            }
+        }
+        Ok(None)
+    }
+
+    // The function `e_entry` points into, usually `_start`
+    pub fn entry_symbol(&self) -> Result<Option<Symbol<'a>>, Error> {
+        match self.entry() {
+            Address(0) => Ok(None),
+            entry => self.function_at(entry),
+        }
+    }
+}
+
+// defined, sized and typed is better
+fn richness(symbol: &Symbol<'_>) -> (bool, bool, bool) {
+    (
+        symbol.entry.is_defined(),
+        symbol.entry.size != 0,
+        symbol.entry.info.ty != SymbolType::Nothing,
+    )
+}
+
+#[derive(Clone)]
+pub struct MergedSymbols<'a> {
+    static_symbols: Option<SymbolSource<'a>>,
+    dynamic_symbols: Option<SymbolSource<'a>>,
+    static_index: usize,
+    dynamic_index: usize,
+}
+
+impl<'a> Iterator for MergedSymbols<'a> {
+    type Item = Result<Symbol<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(static_symbols) = &self.static_symbols {
+            if self.static_index < static_symbols.number {
+                let index = self.static_index;
+                self.static_index += 1;
+                let symbol = match static_symbols.get(index) {
+                    Ok(symbol) => symbol,
+                    Err(error) => return Some(Err(error)),
+                };
+                if let Some(dynamic_symbols) = &self.dynamic_symbols {
+                    match dynamic_symbols.find(&symbol) {
+                        Ok(Some(duplicate)) if richness(&duplicate) > richness(&symbol) => {
+                            return Some(Ok(duplicate))
+                        }
+                        Ok(_) => (),
+                        Err(error) => return Some(Err(error)),
+                    }
+                }
+                return Some(Ok(symbol));
+            }
+        }
+
+        let dynamic_symbols = self.dynamic_symbols.as_ref()?;
+        while self.dynamic_index < dynamic_symbols.number {
+            let index = self.dynamic_index;
+            self.dynamic_index += 1;
+            let symbol = match dynamic_symbols.get(index) {
+                Ok(symbol) => symbol,
+                Err(error) => return Some(Err(error)),
+            };
+            if let Some(static_symbols) = &self.static_symbols {
+                match static_symbols.find(&symbol) {
+                    Ok(Some(_)) => continue,
+                    Ok(None) => (),
+                    Err(error) => return Some(Err(error)),
+                }
+            }
+            return Some(Ok(symbol));
+        }
+        None
+    }
+}
@@ -0,0 +1,290 @@
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+
+use super::{
+    Error, SectionIndex, Elf64, Index, Symbol, SymbolEntry, SymbolType, Section, SectionType,
+    SectionData, Table, WordTable, StringTable, Limit,
+};
+use super::symbolication::SymbolSource;
+use super::common::file_slice;
+#[cfg(feature = "alloc")]
+use super::IndexedStringTable;
+
+// Symbols of a symbol table, without the null symbol at zero,
+// the combinators narrow it down, a symbol with a bad name is an error
+// and the iteration goes on
+#[derive(Clone)]
+pub struct Symbols<'a> {
+    source: SymbolSource<'a>,
+    position: usize,
+    end: usize,
+    ty: Option<SymbolType>,
+    defined: Option<bool>,
+    section: Option<Index>,
+}
+
+impl<'a> Symbols<'a> {
+    fn new(source: SymbolSource<'a>) -> Self {
+        Symbols {
+            position: 1,
+            end: source.number,
+            source,
+            ty: None,
+            defined: None,
+            section: None,
+        }
+    }
+
+    pub fn functions(self) -> Self {
+        Symbols {
+            ty: Some(SymbolType::Function),
+            ..self
+        }
+    }
+
+    // global, weak and the rest of not local bindings, it skips the locals
+    // without reading them, they are first in the table
+    pub fn globals(self) -> Self {
+        Symbols {
+            position: self.position.max(self.source.locals.min(self.end)),
+            ..self
+        }
+    }
+
+    pub fn defined(self) -> Self {
+        Symbols {
+            defined: Some(true),
+            ..self
+        }
+    }
+
+    pub fn undefined(self) -> Self {
+        Symbols {
+            defined: Some(false),
+            ..self
+        }
+    }
+
+    // Finds the end of every name once, before the first symbol, a name is
+    // a binary search then, the clones share the index. It pays off for long
+    // names, hundreds of bytes of nested templates, even in one pass, names of
+    // tens of bytes are scanned faster without it, see `benches/symbol_names.rs`
+    #[cfg(feature = "alloc")]
+    pub fn with_name_index(mut self) -> Self {
+        if self.source.indexed.is_none() {
+            let indexed = IndexedStringTable::new(self.source.names.clone());
+            self.source.indexed = Some(Arc::new(indexed));
+        }
+        self
+    }
+
+    pub fn in_section(self, index: Index) -> Self {
+        Symbols {
+            section: Some(index),
+            ..self
+        }
+    }
+
+    fn accepts(&self, symbol: &Symbol<'a>) -> bool {
+        let entry = &symbol.entry;
+        if let Some(ty) = &self.ty {
+            if entry.info.ty != *ty {
+                return false;
+            }
+        }
+        if let Some(defined) = self.defined {
+            if entry.is_defined() != defined {
+                return false;
+            }
+        }
+        if let Some(section) = &self.section {
+            if entry.section_index != *section {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<'a> Iterator for Symbols<'a> {
+    type Item = Result<Symbol<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.end {
+            let index = self.position;
+            if self
+                .source
+                .limit
+                .is_some_and(|limit| index > limit as usize)
+            {
+                self.position = self.end;
+                return Some(Err(Error::LimitExceeded(Limit::Symbols)));
+            }
+            self.position += 1;
+            match self.source.get(index) {
+                Ok(symbol) if self.accepts(&symbol) => return Some(Ok(symbol)),
+                Ok(_) => (),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end.saturating_sub(self.position)))
+    }
+}
+
+// `SHT_SYMTAB_SHNDX`, a word for each symbol of the table of its link
+pub(crate) const SHT_SYMTAB_SHNDX: u32 = 0x12;
+
+// A `SHT_SYMTAB` or `SHT_DYNSYM` section with the string table of its link
+#[derive(Clone)]
+pub struct SymbolTable<'a> {
+    pub index: SectionIndex,
+    pub table: Table<'a, SymbolEntry>,
+    // with the null symbol
+    pub number: usize,
+    // `sh_info`, the locals are first
+    pub number_of_locals: usize,
+    pub names: StringTable<'a>,
+    // `Limits::symbols` of the file
+    limit: Option<u32>,
+}
+
+impl<'a> SymbolTable<'a> {
+    fn source(&self) -> SymbolSource<'a> {
+        SymbolSource {
+            table: self.table.clone(),
+            number: self.number,
+            locals: self.number_of_locals,
+            names: self.names.clone(),
+            limit: self.limit,
+            #[cfg(feature = "alloc")]
+            indexed: None,
+        }
+    }
+
+    // the symbol with the index and its name
+    pub fn get(&self, index: usize) -> Result<Symbol<'a>, Error> {
+        if index >= self.number {
+            return Err(Error::IndexOutOfBounds);
+        }
+        self.source().get(index)
+    }
+
+    pub fn symbols(&self) -> Symbols<'a> {
+        Symbols::new(self.source())
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // `SHT_SYMTAB_SHNDX` of the symbol table, the indices of the symbols that are
+    // `SHN_XINDEX`, usually `.symtab_shndx`
+    pub fn extended_section_indices(
+        &self,
+        table: SectionIndex,
+    ) -> Result<Option<WordTable<'a, u32>>, Error> {
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            if header.ty == SectionType::Unknown(SHT_SYMTAB_SHNDX)
+                && header.link == Index::Regular(table)
+            {
+                let slice = file_slice(self.raw, header.offset, header.size)?;
+                return Ok(Some(WordTable::new(slice, self.encoding())));
+            }
+        }
+        Ok(None)
+    }
+
+    // `st_shndx` of the symbol of the table, the word of `SHT_SYMTAB_SHNDX`
+    // if it is `SHN_XINDEX`, the result is never `Index::Extended`
+    pub fn symbol_section_index(&self, table: SectionIndex, symbol: usize) -> Result<Index, Error> {
+        let entry = self.symbol_source_at(table.0)?.table.pick(symbol)?;
+        if entry.section_index != Index::Extended {
+            return Ok(entry.section_index);
+        }
+        let word = self
+            .extended_section_indices(table)?
+            .ok_or(Error::NotPresent)?
+            .get(symbol)
+            .ok_or(Error::SliceTooShort)?;
+        Ok(Index::Regular(SectionIndex(word as usize)))
+    }
+
+    fn symbol_table_of(&self, ty: SectionType) -> Result<Option<SymbolTable<'a>>, Error> {
+        for index in 0..self.section_number() {
+            if self.section_table.pick(index)?.ty == ty {
+                let source = self.symbol_source_at(index)?;
+                return Ok(Some(SymbolTable {
+                    index: SectionIndex(index),
+                    table: source.table,
+                    number: source.number,
+                    number_of_locals: source.locals,
+                    names: source.names,
+                    limit: source.limit,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    // the first `SHT_SYMTAB`, `.symtab`
+    pub fn symbol_table(&self) -> Result<Option<SymbolTable<'a>>, Error> {
+        self.symbol_table_of(SectionType::SymbolTable)
+    }
+
+    // the first `SHT_DYNSYM`, `.dynsym`
+    pub fn dynamic_symbol_table(&self) -> Result<Option<SymbolTable<'a>>, Error> {
+        self.symbol_table_of(SectionType::DynamicSymbolTable)
+    }
+
+    // `.symtab`
+    pub fn symbols(&self) -> Result<Option<Symbols<'a>>, Error> {
+        Ok(self
+            .symbol_source(SectionType::SymbolTable)?
+            .map(Symbols::new))
+    }
+
+    // `.dynsym`
+    pub fn dynamic_symbols(&self) -> Result<Option<Symbols<'a>>, Error> {
+        Ok(self
+            .symbol_source(SectionType::DynamicSymbolTable)?
+            .map(Symbols::new))
+    }
+
+    // the symbols of any `SHT_SYMTAB` or `SHT_DYNSYM` by its index, with the names
+    // of its linked string table
+    pub fn symbols_of(&self, index: SectionIndex) -> Result<Symbols<'a>, Error> {
+        self.symbol_source_at(index.0).map(Symbols::new)
+    }
+
+    // The name of an entry of the symbol table section, in the string table of its link
+    pub fn symbol_name(
+        &self,
+        symbol: &SymbolEntry,
+        symbol_table: &Section<'a>,
+    ) -> Result<&'a [u8], Error> {
+        match &symbol_table.data {
+            SectionData::SymbolTable { .. } | SectionData::DynamicSymbolTable { .. } => (),
+            _ => return Err(Error::UnexpectedSectionType),
+        }
+        let names = match symbol_table.link {
+            Index::Regular(link) => match self.section(link)?.map(|s| s.data) {
+                Some(SectionData::StringTable(names)) => names,
+                _ => return Err(Error::UnexpectedSectionType),
+            },
+            _ => return Err(Error::UnexpectedSectionType),
+        };
+        names.pick(symbol.name as usize)
+    }
+
+    // The imports, the undefined symbols of `.symtab`, or of `.dynsym` if it is stripped
+    pub fn undefined_symbols(&self) -> Result<Option<Symbols<'a>>, Error> {
+        let symbols = match self.symbols()? {
+            Some(symbols) => Some(symbols),
+            None => self.dynamic_symbols()?,
+        };
+        Ok(symbols.map(Symbols::undefined))
+    }
+}
@@ -1,4 +1,10 @@
-use core::marker::PhantomData;
+use core::{any, fmt, marker::PhantomData, convert::TryFrom};
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use super::{Encoding, Error};
 
@@ -13,6 +19,13 @@ where
     fn new(slice: &[u8], encoding: Encoding) -> Result<Self, Self::Error>;
 }
 
+pub trait Encode
+where
+    Self: Entry,
+{
+    fn encode(&self, slice: &mut [u8], encoding: Encoding) -> Result<(), Self::Error>;
+}
+
 #[derive(Clone)]
 pub struct Table<'a, E>
 where
@@ -20,6 +33,11 @@ where
 {
     slice: &'a [u8],
     encoding: Encoding,
+    // the number of the entries, of the header or of the size of the slice
+    number: usize,
+    // the distance between the entries, `sh_entsize` if it is larger than the entry,
+    // 32 bits keep `Elf64` small
+    stride: u32,
     phantom_data: PhantomData<E>,
 }
 
@@ -27,19 +45,211 @@ impl<'a, E> Table<'a, E>
 where
     E: Entry<Error = Error>,
 {
+    // the whole entries of the slice, a trailing partial one is not an entry
     pub fn new(slice: &'a [u8], encoding: Encoding) -> Self {
+        Table::with_entry_size(slice, encoding, E::SIZE as u64)
+    }
+
+    // `sh_entsize` of the section, a smaller one, e.g. zero, is the size of the entry,
+    // the tail of a larger one is skipped
+    pub fn with_entry_size(slice: &'a [u8], encoding: Encoding, entry_size: u64) -> Self {
+        let stride = Table::<E>::stride(entry_size);
         Table {
             slice,
             encoding,
+            number: Table::<E>::number_of_entries(slice.len(), entry_size),
+            stride,
             phantom_data: PhantomData,
         }
     }
 
+    // the number of the header, `e_phnum` or `e_shnum`, rather than of the slice,
+    // the entries of a truncated table past the end of the slice are
+    // `Error::SliceTooShort`, up to one past the slice, so a loop over the
+    // table of a crafted number meets the error and does not run for ever
+    pub fn with_len(self, number: usize) -> Self {
+        let number = number.min(self.number.saturating_add(1));
+        Table { number, ..self }
+    }
+
+    // the number of the entries of that many bytes
+    pub fn number_of_entries(size: usize, entry_size: u64) -> usize {
+        match size.checked_sub(E::SIZE) {
+            Some(rest) => rest / Table::<E>::stride(entry_size) as usize + 1,
+            None => 0,
+        }
+    }
+
+    fn stride(entry_size: u64) -> u32 {
+        u32::try_from(entry_size)
+            .unwrap_or(u32::MAX)
+            .max(E::SIZE as u32)
+    }
+
+    pub fn len(&self) -> usize {
+        self.number
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.number == 0
+    }
+
+    // an index past the table is `Error::IndexOutOfBounds`, not the bytes after it
     pub fn pick(&self, index: usize) -> Result<E, E::Error> {
-        if self.slice.len() < index * E::SIZE {
-            return Err(Error::SliceTooShort);
+        if index >= self.number {
+            return Err(Error::IndexOutOfBounds);
+        }
+        entry_at(
+            self.slice,
+            self.stride as usize,
+            index,
+            self.encoding.clone(),
+        )
+    }
+
+    // every entry in the order of the table
+    pub fn iter(&self) -> impl Iterator<Item = Result<E, E::Error>> + 'a
+    where
+        E: 'a,
+    {
+        let (slice, encoding) = (self.slice, self.encoding.clone());
+        let stride = self.stride as usize;
+        (0..self.number).map(move |index| entry_at(slice, stride, index, encoding.clone()))
+    }
+
+    // every entry, each one decoded by any thread
+    #[cfg(feature = "rayon")]
+    pub fn par_entries(&self) -> impl IndexedParallelIterator<Item = Result<E, E::Error>> + 'a
+    where
+        E: Send,
+    {
+        let (slice, encoding) = (self.slice, self.encoding.clone());
+        let stride = self.stride as usize;
+        (0..self.number)
+            .into_par_iter()
+            .map(move |index| entry_at(slice, stride, index, encoding.clone()))
+    }
+}
+
+// an index past `usize` in bytes is `Error::ArithmeticOverflow`, not a wrapped one
+fn entry_at<E>(slice: &[u8], stride: usize, index: usize, encoding: Encoding) -> Result<E, Error>
+where
+    E: Entry<Error = Error>,
+{
+    let offset = index.checked_mul(stride).ok_or(Error::ArithmeticOverflow)?;
+    let slice = slice.get(offset..).ok_or(Error::SliceTooShort)?;
+    E::new(slice, encoding)
+}
+
+// A word of an array section, e.g. `SHT_INIT_ARRAY`, `.got` or `SHT_RELR`
+pub trait Word
+where
+    Self: Copy,
+{
+    const SIZE: usize;
+
+    fn read(slice: &[u8], encoding: &Encoding) -> Self;
+}
+
+impl Word for u32 {
+    const SIZE: usize = 4;
+
+    fn read(slice: &[u8], encoding: &Encoding) -> Self {
+        read_int!(slice, encoding, u32)
+    }
+}
+
+impl Word for u64 {
+    const SIZE: usize = 8;
+
+    fn read(slice: &[u8], encoding: &Encoding) -> Self {
+        read_int!(slice, encoding, u64)
+    }
+}
+
+// The words of the slice, a partial word at the end is not one
+#[derive(Clone)]
+pub struct WordTable<'a, W>
+where
+    W: Word,
+{
+    slice: &'a [u8],
+    encoding: Encoding,
+    phantom_data: PhantomData<W>,
+}
+
+impl<'a, W> WordTable<'a, W>
+where
+    W: Word,
+{
+    pub fn new(slice: &'a [u8], encoding: Encoding) -> Self {
+        WordTable {
+            slice,
+            encoding,
+            phantom_data: PhantomData,
         }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slice.len() / W::SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<W> {
+        let offset = index.checked_mul(W::SIZE)?;
+        let slice = self.slice.get(offset..offset.checked_add(W::SIZE)?)?;
+        Some(W::read(slice, &self.encoding))
+    }
 
-        E::new(&self.slice[(index * E::SIZE)..], self.encoding.clone())
+    pub fn iter(&self) -> impl Iterator<Item = W> + 'a {
+        let encoding = self.encoding.clone();
+        self.slice
+            .chunks_exact(W::SIZE)
+            .map(move |slice| W::read(slice, &encoding))
+    }
+
+    pub fn as_raw(&self) -> &'a [u8] {
+        self.slice
+    }
+}
+
+impl<'a, W> fmt::Debug for WordTable<'a, W>
+where
+    W: Word,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WordTable")
+            .field("word", &format_args!("{}", any::type_name::<W>()))
+            .field("number", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub fn encode_entries<E>(entries: &[E], encoding: Encoding) -> Result<Vec<u8>, E::Error>
+where
+    E: Encode,
+{
+    let mut bytes = vec![0; entries.len() * E::SIZE];
+    for (entry, slice) in entries.iter().zip(bytes.chunks_mut(E::SIZE)) {
+        entry.encode(slice, encoding.clone())?;
+    }
+    Ok(bytes)
+}
+
+impl<'a, E> fmt::Debug for Table<'a, E>
+where
+    E: Entry,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Table")
+            .field("entry", &format_args!("{}", any::type_name::<E>()))
+            .field("size", &self.slice.len())
+            .field("entry_size", &self.stride)
+            .field("number", &self.number)
+            .finish()
     }
 }
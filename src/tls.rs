@@ -0,0 +1,159 @@
+use super::{
+    Error, Address, Elf64, Machine, Type, ProgramType, SectionFlags, Index, Symbol, SymbolEntry,
+    SymbolType,
+};
+
+// `STT_TLS`
+pub(crate) const THREAD_LOCAL: SymbolType = SymbolType::Unknown(6);
+
+// `PT_TLS`, the image the block of the module of each thread starts with,
+// the part past `file_size` is zeros, `.tbss`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TlsTemplate {
+    pub program: usize,
+    pub address: Address,
+    pub file_size: u64,
+    pub memory_size: u64,
+    pub alignment: u64,
+}
+
+fn round_up(value: u64, alignment: u64) -> Option<u64> {
+    let alignment = alignment.max(1);
+    value
+        .checked_add(alignment - 1)
+        .map(|value| value / alignment * alignment)
+}
+
+impl TlsTemplate {
+    // The block of the executable from the thread pointer, the static model, the
+    // blocks of the libraries are where their loader put them. x86-64 puts the
+    // block below the thread pointer, AArch64 after the two words of the TCB,
+    // RISC-V at the thread pointer. `None` for another machine.
+    pub fn block_from_thread_pointer(
+        &self,
+        thread_pointer: Address,
+        machine: &Machine,
+    ) -> Option<Address> {
+        match machine {
+            Machine::X86_64 => {
+                let size = round_up(self.memory_size, self.alignment)?;
+                thread_pointer.0.checked_sub(size).map(Address)
+            }
+            Machine::AArch64 => {
+                let tcb = round_up(16, self.alignment)?;
+                thread_pointer.0.checked_add(tcb).map(Address)
+            }
+            Machine::RiscV => Some(thread_pointer),
+            _ => None,
+        }
+    }
+}
+
+// What the value of a symbol is
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SymbolAddress {
+    // the virtual address, without the load bias, relative to the section
+    // in a relocatable file
+    Virtual(Address),
+    // `SHN_ABS`, not relocated with the file
+    Absolute(u64),
+    // `STT_TLS`, the offset in the block of the module, each thread has one
+    ThreadLocal(u64),
+}
+
+impl SymbolAddress {
+    // The run time address in the file loaded at `bias` and for the thread with
+    // the block, `None` for a thread local symbol without the block
+    pub fn resolve(&self, bias: Address, tls_block: Option<Address>) -> Option<Address> {
+        match self {
+            SymbolAddress::Virtual(address) => Some(bias.wrapping_add(address.0)),
+            SymbolAddress::Absolute(value) => Some(Address(*value)),
+            SymbolAddress::ThreadLocal(offset) => {
+                tls_block.map(|block| block.wrapping_add(*offset))
+            }
+        }
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // the first `PT_TLS`
+    pub fn tls_template(&self) -> Result<Option<TlsTemplate>, Error> {
+        for program in 0..self.program_number() {
+            let header = self.program_table.pick(program)?;
+            if header.ty == ProgramType::Tls {
+                return Ok(Some(TlsTemplate {
+                    program,
+                    address: header.virtual_address,
+                    file_size: header.file_size,
+                    memory_size: header.memory_size,
+                    alignment: header.address_alignment,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    // The offset of the section in the block of a relocatable file, the `SHF_TLS`
+    // sections in the order of the file, each aligned, as the linker would lay them
+    pub(crate) fn tls_section_offset(&self, section: usize) -> Result<Option<u64>, Error> {
+        let mut offset = 0u64;
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            if !header
+                .flags
+                .contains(SectionFlags::ALLOC | SectionFlags::TLS)
+            {
+                continue;
+            }
+            offset = round_up(offset, header.address_alignment).ok_or(Error::ArithmeticOverflow)?;
+            if index == section {
+                return Ok(Some(offset));
+            }
+            offset = offset
+                .checked_add(header.size)
+                .ok_or(Error::ArithmeticOverflow)?;
+        }
+        Ok(None)
+    }
+
+    // The value of the symbol by what it is, `st_value` of `STT_TLS` is an offset in
+    // the block rather than an address, of a relocatable file it is relative to
+    // the section, the offset is of the section laid in the block then.
+    // `None` if the symbol is undefined or common.
+    pub fn symbol_address(&self, entry: &SymbolEntry) -> Result<Option<SymbolAddress>, Error> {
+        let address = match (&entry.section_index, entry.info.ty == THREAD_LOCAL) {
+            (Index::Undefined, _) | (Index::Common, _) => None,
+            (Index::AbsoluteValue, _) => Some(SymbolAddress::Absolute(entry.value.0)),
+            (Index::Regular(index), true) if self.ty() == Type::Relocatable => {
+                let offset = self
+                    .tls_section_offset(index.0)?
+                    .ok_or(Error::UnexpectedSectionType)?;
+                Some(SymbolAddress::ThreadLocal(
+                    offset.wrapping_add(entry.value.0),
+                ))
+            }
+            (_, true) => Some(SymbolAddress::ThreadLocal(entry.value.0)),
+            (_, false) => Some(SymbolAddress::Virtual(entry.value)),
+        };
+        Ok(address)
+    }
+
+    // The defined `STT_TLS` symbol that covers the offset in the block of the module,
+    // an address of the thread is the offset from its block, `symbolize` only finds
+    // the symbols of the addresses
+    pub fn symbolize_tls(&self, offset: u64) -> Result<Option<Symbol<'a>>, Error> {
+        for symbol in self.merged_symbols()? {
+            let symbol = symbol?;
+            if symbol.entry.info.ty != THREAD_LOCAL {
+                continue;
+            }
+            if let Some(SymbolAddress::ThreadLocal(start)) = self.symbol_address(&symbol.entry)? {
+                let end = start.saturating_add(symbol.entry.size.max(1));
+                if (start..end).contains(&offset) {
+                    return Ok(Some(symbol));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
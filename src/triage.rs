@@ -0,0 +1,179 @@
+use super::{
+    Error, Address, Elf64, Entry, Table, Type, Machine, Encoding, ProgramType, ProgramFlags,
+    SectionType, NoteTable, DynamicEntry, DynamicTag, DynamicFlags,
+};
+use super::common::file_slice;
+
+// the largest note table the triage reads, the rest are skipped
+const NOTE_LIMIT: u64 = 0x1000;
+
+bitflags! {
+    pub struct TriageFlags: u32 {
+        // a table or a segment goes past the end of the file
+        const TRUNCATED = 0b00000001;
+        const NO_SECTION_HEADERS = 0b00000010;
+        // `e_entry` is not in an executable `PT_LOAD`
+        const ENTRY_NOT_EXECUTABLE = 0b00000100;
+        // a `PT_LOAD` is writable and executable
+        const WRITABLE_EXECUTABLE = 0b00001000;
+        // `PT_GNU_STACK` is executable or missing in a file with segments
+        const EXECUTABLE_STACK = 0b00010000;
+        // `DT_TEXTREL` or `DF_TEXTREL`
+        const TEXT_RELOCATIONS = 0b00100000;
+        // `DT_RPATH` or `DT_RUNPATH`
+        const SEARCH_PATH = 0b01000000;
+        // a note table larger than the triage reads
+        const LARGE_NOTES = 0b10000000;
+    }
+}
+
+// What the headers tell of the file at a glance
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Triage<'a> {
+    pub ty: Type,
+    pub machine: Machine,
+    pub encoding: Encoding,
+    pub entry: Address,
+    pub interpreter: bool,
+    pub build_id: Option<&'a [u8]>,
+    // `DT_NEEDED`, the strings are not read
+    pub needed: usize,
+    pub program_number: usize,
+    pub section_number: usize,
+    pub flags: TriageFlags,
+}
+
+impl<'a> Elf64<'a> {
+    // The summary of the file in one pass over the program headers, it reads
+    // `PT_DYNAMIC` and the note tables up to a page, never the content of sections.
+    // A corrupted header is a flag, not an error, it is for scanning many files.
+    pub fn triage(&self) -> Triage<'a> {
+        let mut triage = Triage {
+            ty: self.ty(),
+            machine: self.machine(),
+            encoding: self.encoding(),
+            entry: self.entry(),
+            interpreter: false,
+            build_id: None,
+            needed: 0,
+            program_number: self.program_number(),
+            section_number: self.section_number(),
+            flags: TriageFlags::empty(),
+        };
+        if self.section_number() == 0 {
+            triage.flags |= TriageFlags::NO_SECTION_HEADERS;
+        }
+        if self.triage_programs(&mut triage).is_err() {
+            triage.flags |= TriageFlags::TRUNCATED;
+        }
+        if self.program_number() == 0 && self.triage_sections(&mut triage).is_err() {
+            triage.flags |= TriageFlags::TRUNCATED;
+        }
+        triage
+    }
+
+    fn triage_programs(&self, triage: &mut Triage<'a>) -> Result<(), Error> {
+        let mut stack = false;
+        let mut entry = false;
+        for index in 0..self.program_number() {
+            let header = self.program_table.pick(index)?;
+            let end = header.file_offset.saturating_add(header.file_size);
+            if end.0 > self.raw.len() as u64 {
+                triage.flags |= TriageFlags::TRUNCATED;
+                continue;
+            }
+            match header.ty {
+                ProgramType::Load => {
+                    let executable = header.flags.contains(ProgramFlags::EXECUTE);
+                    if executable && header.flags.contains(ProgramFlags::WRITE) {
+                        triage.flags |= TriageFlags::WRITABLE_EXECUTABLE;
+                    }
+                    let start = header.virtual_address;
+                    let inside = start <= self.entry()
+                        && self.entry() < start.saturating_add(header.memory_size);
+                    entry |= executable && inside;
+                }
+                ProgramType::Interpreter => triage.interpreter = true,
+                ProgramType::Note if triage.build_id.is_none() => {
+                    if header.file_size > NOTE_LIMIT {
+                        triage.flags |= TriageFlags::LARGE_NOTES;
+                        continue;
+                    }
+                    let slice = file_slice(self.raw, header.file_offset, header.file_size)?;
+                    let table =
+                        NoteTable::with_alignment(slice, self.encoding(), header.address_alignment)
+                            .limited(self.limits.note_size);
+                    triage.build_id = build_id(&table);
+                }
+                ProgramType::Dynamic => {
+                    let slice = file_slice(self.raw, header.file_offset, header.file_size)?;
+                    self.triage_dynamic(slice, triage)?;
+                }
+                ProgramType::GnuStack => {
+                    stack = true;
+                    if header.flags.contains(ProgramFlags::EXECUTE) {
+                        triage.flags |= TriageFlags::EXECUTABLE_STACK;
+                    }
+                }
+                _ => (),
+            }
+        }
+        if let Type::Executable | Type::SharedObject = self.ty() {
+            if !stack {
+                triage.flags |= TriageFlags::EXECUTABLE_STACK;
+            }
+            if !entry && self.entry() != Address(0) {
+                triage.flags |= TriageFlags::ENTRY_NOT_EXECUTABLE;
+            }
+        }
+        Ok(())
+    }
+
+    fn triage_dynamic(&self, slice: &'a [u8], triage: &mut Triage<'a>) -> Result<(), Error> {
+        let table = Table::<DynamicEntry>::new(slice, self.encoding());
+        for index in 0..(slice.len() / DynamicEntry::SIZE) {
+            let entry = table.pick(index)?;
+            match entry.tag {
+                DynamicTag::Null => break,
+                DynamicTag::Needed => triage.needed += 1,
+                DynamicTag::TextRelocation => triage.flags |= TriageFlags::TEXT_RELOCATIONS,
+                DynamicTag::Flags if entry.value & DynamicFlags::TEXTREL.bits() != 0 => {
+                    triage.flags |= TriageFlags::TEXT_RELOCATIONS
+                }
+                DynamicTag::Rpath | DynamicTag::Runpath => triage.flags |= TriageFlags::SEARCH_PATH,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    // the notes of a relocatable file are in sections
+    fn triage_sections(&self, triage: &mut Triage<'a>) -> Result<(), Error> {
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            if header.ty != SectionType::Note {
+                continue;
+            }
+            if header.size > NOTE_LIMIT {
+                triage.flags |= TriageFlags::LARGE_NOTES;
+                continue;
+            }
+            let slice = file_slice(self.raw, header.offset, header.size)?;
+            let table = NoteTable::with_alignment(slice, self.encoding(), header.address_alignment)
+                .limited(self.limits.note_size);
+            triage.build_id = build_id(&table);
+            if triage.build_id.is_some() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn build_id<'a>(table: &NoteTable<'a>) -> Option<&'a [u8]> {
+    table
+        .iter()
+        .filter_map(Result::ok)
+        .find(|note| note.name == b"GNU" && note.ty == 3)
+        .map(|note| note.description)
+}
@@ -0,0 +1,502 @@
+use alloc::vec::Vec;
+
+use super::{
+    Error, Offset, SectionIndex, Elf64, Index, Table, Entry, SectionType, ProgramType,
+    ProgramHeader, SectionHeader, SymbolEntry, SymbolType, RelEntry, RelaEntry, Diagnostic,
+    Warning, Type, ProgramFlags,
+};
+use super::dynamic::DynamicEntry;
+use super::common::{file_slice, to_usize};
+use super::hash::SHT_GNU_HASH;
+use super::tls::THREAD_LOCAL;
+use super::version::{SHT_GNU_VERDEF, SHT_GNU_VERNEED, SHT_GNU_VERSYM};
+
+// Structural defects, any of them makes the file unsafe to trust
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Violation {
+    ProgramTableOutOfBounds,
+    SectionTableOutOfBounds,
+    // `e_shstrndx` is not a string table
+    BadSectionNames,
+    // the content is past the end of the file
+    ProgramOutOfBounds { program: usize },
+    SectionOutOfBounds { section: usize },
+    // a `PT_LOAD` has more bytes in the file than in memory
+    FileSizeExceedsMemorySize { program: usize },
+    // the offset and the address are not congruent modulo the alignment
+    MisalignedProgram { program: usize },
+    // `PT_PHDR` is not at `e_phoff` or its size is not of `e_phnum` headers
+    ProgramHeaderTableMismatch { program: usize },
+    // no `PT_LOAD` has `PT_PHDR` at its address, the process gets a bad `AT_PHDR`
+    ProgramHeaderTableNotLoaded { program: usize },
+    // the address is not a multiple of the alignment
+    MisalignedSection { section: usize },
+    // `sh_link` is out of range or points at a section of a wrong type
+    BadLink { section: usize },
+    // `sh_info` of a relocation section is out of range
+    BadInfo { section: usize },
+    // `sh_entsize` does not match the entry, or the size is not a multiple of it
+    BadEntrySize { section: usize },
+    // the number of local symbols in `sh_info` is more than the symbols
+    BadLocalCount { section: usize },
+    // a string table that does not end with a terminator
+    UnterminatedStringTable { section: usize },
+    SectionNameOutOfRange { section: usize },
+    // the name starts in the string table, but there is no terminator before its end
+    UnterminatedSectionName { section: usize },
+    SymbolNameOutOfRange { section: usize, symbol: usize },
+    UnterminatedSymbolName { section: usize, symbol: usize },
+    SymbolSectionOutOfRange { section: usize, symbol: usize },
+    // `[st_value, st_value + st_size)` is not within the section of the symbol
+    SymbolOutsideSection { section: usize, symbol: usize },
+    // an executable or a shared object without program headers cannot be loaded
+    NoProgramHeaders,
+    // `e_entry` is not in an executable `PT_LOAD`
+    EntryNotExecutable,
+    // the content of the section in the file starts within the one of another
+    OverlappingSections { section: usize, other: usize },
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValidationReport {
+    pub violations: Vec<Violation>,
+    // `lint` and `diagnostics`, only collected if there are no violations,
+    // they expect the structure to be sound
+    pub diagnostics: Vec<Diagnostic>,
+    pub warnings: Vec<Warning>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+// a name that is at or past the end of its string table, or runs to the end
+#[derive(Clone, Copy)]
+enum Dangling {
+    OutOfRange,
+    Unterminated,
+}
+
+fn dangling(strings: &[u8], offset: u32) -> Option<Dangling> {
+    match strings.get(offset as usize..) {
+        None | Some([]) => Some(Dangling::OutOfRange),
+        Some(name) if !name.contains(&0) => Some(Dangling::Unterminated),
+        Some(_) => None,
+    }
+}
+
+fn out_of_bounds(length: usize, offset: Offset, size: u64) -> bool {
+    match offset.checked_add(size) {
+        Some(end) => end.0 > length as u64,
+        None => true,
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Every check the crate has, a pre-flight before loading an untrusted file
+    pub fn validate(&self) -> Result<ValidationReport, Error> {
+        let span = enter_span!(
+            "validate",
+            bytes = self.raw.len(),
+            programs = self.program_number(),
+            sections = self.section_number();
+            violations
+        );
+        let mut report = ValidationReport::default();
+        let violations = &mut report.violations;
+
+        let size = (self.program_number() * ProgramHeader::SIZE) as u64;
+        if out_of_bounds(self.raw.len(), self.header.program_headers_offset, size) {
+            violations.push(Violation::ProgramTableOutOfBounds);
+        }
+        let size = (self.section_number() * SectionHeader::SIZE) as u64;
+        if out_of_bounds(self.raw.len(), self.header.section_headers_offset, size) {
+            violations.push(Violation::SectionTableOutOfBounds);
+        }
+        if !violations.is_empty() {
+            record_span!(span, violations, violations.len());
+            return Ok(report);
+        }
+
+        self.validate_programs(violations)?;
+        self.validate_entry(violations)?;
+        self.validate_sections(violations)?;
+        self.validate_overlaps(violations)?;
+
+        if report.violations.is_empty() {
+            report.diagnostics = self.lint()?;
+            report.warnings = self.diagnostics().collect::<Result<_, _>>()?;
+        }
+        record_span!(span, violations, report.violations.len());
+        Ok(report)
+    }
+
+    fn validate_programs(&self, violations: &mut Vec<Violation>) -> Result<(), Error> {
+        for program in 0..self.program_number() {
+            let header = self.program_table.pick(program)?;
+            if out_of_bounds(self.raw.len(), header.file_offset, header.file_size) {
+                violations.push(Violation::ProgramOutOfBounds { program });
+            }
+            if header.ty == ProgramType::Load && header.file_size > header.memory_size {
+                violations.push(Violation::FileSizeExceedsMemorySize { program });
+            }
+            let alignment = header.address_alignment;
+            if alignment > 1
+                && alignment.is_power_of_two()
+                && header.file_offset.0 % alignment != header.virtual_address.0 % alignment
+            {
+                violations.push(Violation::MisalignedProgram { program });
+            }
+            if header.ty == ProgramType::ProgramHeaderTable {
+                let size = (self.program_number() * ProgramHeader::SIZE) as u64;
+                if header.file_offset != self.header.program_headers_offset
+                    || header.file_size != size
+                {
+                    violations.push(Violation::ProgramHeaderTableMismatch { program });
+                }
+                let loaded = self
+                    .program_header_load()?
+                    .map(|(_, address)| address == header.virtual_address);
+                if loaded != Some(true) {
+                    violations.push(Violation::ProgramHeaderTableNotLoaded { program });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_entry(&self, violations: &mut Vec<Violation>) -> Result<(), Error> {
+        match self.ty() {
+            Type::Executable | Type::SharedObject => (),
+            _ => return Ok(()),
+        }
+        if self.program_number() == 0 {
+            violations.push(Violation::NoProgramHeaders);
+            return Ok(());
+        }
+        // a library without an entry point has zero
+        let entry = self.entry();
+        if entry.0 == 0 {
+            return Ok(());
+        }
+        for header in self.load_segments()? {
+            let header = header?;
+            let start = header.virtual_address;
+            let inside = entry >= start && entry.0 - start.0 < header.memory_size;
+            if inside && header.flags.contains(ProgramFlags::EXECUTE) {
+                return Ok(());
+            }
+        }
+        violations.push(Violation::EntryNotExecutable);
+        Ok(())
+    }
+
+    // the sections by their offsets, each is compared with the one that reaches
+    // the farthest before it
+    fn validate_overlaps(&self, violations: &mut Vec<Violation>) -> Result<(), Error> {
+        let mut ranges = Vec::new();
+        for section in 0..self.section_number() {
+            let header = self.section_table.pick(section)?;
+            let in_file = header.ty != SectionType::NoBits && header.ty != SectionType::Null;
+            if in_file && header.size != 0 {
+                let end = header.offset.0.saturating_add(header.size);
+                ranges.push((header.offset.0, end, section));
+            }
+        }
+        ranges.sort_unstable();
+        let mut farthest: Option<(u64, usize)> = None;
+        for (start, end, section) in ranges {
+            match farthest {
+                Some((other_end, other)) if start < other_end => {
+                    violations.push(Violation::OverlappingSections { section, other });
+                    if end > other_end {
+                        farthest = Some((end, section));
+                    }
+                }
+                _ => farthest = Some((end, section)),
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_sections(&self, violations: &mut Vec<Violation>) -> Result<(), Error> {
+        let number = self.section_number();
+        let ty_of = |index: SectionIndex| -> Result<Option<SectionType>, Error> {
+            if index.0 < number {
+                Ok(Some(self.section_table.pick(index.0)?.ty))
+            } else {
+                Ok(None)
+            }
+        };
+
+        let names = match self.section_names() {
+            Index::Regular(index) => match ty_of(index)? {
+                Some(SectionType::StringTable) => self.section_names_slice(index),
+                _ => {
+                    violations.push(Violation::BadSectionNames);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        for section in 0..number {
+            let header = self.section_table.pick(section)?;
+
+            if let Some(names) = names {
+                self.validate_section_name(names, section, &header, violations);
+            }
+
+            let in_file = header.ty != SectionType::NoBits && header.ty != SectionType::Null;
+            if in_file && out_of_bounds(self.raw.len(), header.offset, header.size) {
+                violations.push(Violation::SectionOutOfBounds { section });
+                continue;
+            }
+
+            let alignment = header.address_alignment;
+            if alignment > 1 && alignment.is_power_of_two() && !header.address.is_aligned(alignment)
+            {
+                violations.push(Violation::MisalignedSection { section });
+            }
+
+            let link = match header.link {
+                Index::Regular(link) => ty_of(link)?,
+                _ => None,
+            };
+            let link_is_symbols = matches!(
+                link,
+                Some(SectionType::SymbolTable) | Some(SectionType::DynamicSymbolTable)
+            );
+            let link_is_strings = link == Some(SectionType::StringTable);
+            let (entry_size, link_is_good) = match &header.ty {
+                SectionType::SymbolTable | SectionType::DynamicSymbolTable => {
+                    (Some(SymbolEntry::SIZE), link_is_strings)
+                }
+                SectionType::Rela => (Some(RelaEntry::SIZE), {
+                    // `.rela.dyn` of some linkers has no symbol table
+                    header.link == Index::Undefined || link_is_symbols
+                }),
+                SectionType::Rel => (
+                    Some(RelEntry::SIZE),
+                    header.link == Index::Undefined || link_is_symbols,
+                ),
+                SectionType::Dynamic => (Some(DynamicEntry::SIZE), link_is_strings),
+                SectionType::Hash => (None, link_is_symbols),
+                SectionType::OsSpecific(SHT_GNU_HASH) | SectionType::OsSpecific(SHT_GNU_VERSYM) => {
+                    (None, link_is_symbols)
+                }
+                SectionType::OsSpecific(SHT_GNU_VERDEF)
+                | SectionType::OsSpecific(SHT_GNU_VERNEED) => (None, link_is_strings),
+                _ => (
+                    None,
+                    match header.link {
+                        Index::Regular(_) => link.is_some(),
+                        _ => true,
+                    },
+                ),
+            };
+            if !link_is_good {
+                violations.push(Violation::BadLink { section });
+            }
+
+            if let Some(entry_size) = entry_size {
+                let declared = header.number_of_entries;
+                if (declared != 0 && declared != entry_size as u64)
+                    || header.size % entry_size as u64 != 0
+                {
+                    violations.push(Violation::BadEntrySize { section });
+                }
+            }
+
+            match header.ty {
+                SectionType::Rela | SectionType::Rel if header.info as usize >= number => {
+                    violations.push(Violation::BadInfo { section });
+                }
+                SectionType::SymbolTable | SectionType::DynamicSymbolTable => {
+                    let count = Table::<SymbolEntry>::number_of_entries(
+                        to_usize(header.size)?,
+                        header.number_of_entries,
+                    );
+                    if header.info as usize > count {
+                        violations.push(Violation::BadLocalCount { section });
+                    }
+                    if link_is_good {
+                        self.validate_symbols(section, &header, violations)?;
+                    }
+                }
+                SectionType::StringTable => {
+                    let slice = file_slice(self.raw, header.offset, header.size)?;
+                    if header.size != 0 && slice.last() != Some(&0) {
+                        violations.push(Violation::UnterminatedStringTable { section });
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    // the table of the section names if it is in the file, `SectionOutOfBounds` otherwise
+    fn section_names_slice(&self, index: SectionIndex) -> Option<&'a [u8]> {
+        let header = self.section_table.pick(index.0).ok()?;
+        file_slice(self.raw, header.offset, header.size).ok()
+    }
+
+    fn validate_section_name(
+        &self,
+        names: &[u8],
+        section: usize,
+        header: &SectionHeader,
+        violations: &mut Vec<Violation>,
+    ) {
+        match dangling(names, header.name) {
+            Some(Dangling::OutOfRange) => {
+                violations.push(Violation::SectionNameOutOfRange { section })
+            }
+            Some(Dangling::Unterminated) => {
+                violations.push(Violation::UnterminatedSectionName { section })
+            }
+            None => (),
+        }
+    }
+
+    fn validate_symbols(
+        &self,
+        section: usize,
+        header: &SectionHeader,
+        violations: &mut Vec<Violation>,
+    ) -> Result<(), Error> {
+        self.validate_symbol_names(section, header, violations)?;
+        let slice = file_slice(self.raw, header.offset, header.size)?;
+        let table =
+            Table::<SymbolEntry>::with_entry_size(slice, self.encoding(), header.number_of_entries);
+        for symbol in 0..table.len() {
+            let entry = table.pick(symbol)?;
+            // the real index of `SHN_XINDEX` is in `SHT_SYMTAB_SHNDX`
+            let index = match &entry.section_index {
+                Index::Extended => self
+                    .symbol_section_index(SectionIndex(section), symbol)
+                    .ok(),
+                index => Some(index.clone()),
+            };
+            match index {
+                Some(Index::Regular(index)) if index.0 < self.section_number() => {
+                    if !self.symbol_within_section(&entry, index)? {
+                        violations.push(Violation::SymbolOutsideSection { section, symbol })
+                    }
+                }
+                Some(Index::Regular(_)) | None => {
+                    violations.push(Violation::SymbolSectionOutOfRange { section, symbol })
+                }
+                Some(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    // The value of a symbol of a relocatable file is relative to the section. A symbol of
+    // no size may be at the end. Of a linked file, `STT_TLS` is an offset in the block,
+    // not an address, and the markers the linker defines, e.g. `__bss_start` or `_end`,
+    // are in the padding between the sections, neither is checked.
+    fn symbol_within_section(
+        &self,
+        entry: &SymbolEntry,
+        index: SectionIndex,
+    ) -> Result<bool, Error> {
+        let relocatable = self.ty() == Type::Relocatable;
+        match entry.info.ty {
+            SymbolType::Section | SymbolType::File => return Ok(true),
+            THREAD_LOCAL if !relocatable => return Ok(true),
+            SymbolType::Nothing if !relocatable && entry.size == 0 => return Ok(true),
+            _ => (),
+        }
+        let header = self.section_table.pick(index.0)?;
+        if header.ty == SectionType::Null {
+            return Ok(true);
+        }
+        let start = if relocatable { 0 } else { header.address.0 };
+        let end = match start.checked_add(header.size) {
+            Some(end) => end,
+            None => return Ok(false),
+        };
+        Ok(match entry.value.0.checked_add(entry.size) {
+            Some(symbol_end) => start <= entry.value.0 && symbol_end <= end,
+            None => false,
+        })
+    }
+
+    // the empty name is zero, it needs no table
+    fn validate_symbol_names(
+        &self,
+        section: usize,
+        header: &SectionHeader,
+        violations: &mut Vec<Violation>,
+    ) -> Result<(), Error> {
+        let strings = match header.link {
+            Index::Regular(link) if link.0 < self.section_number() => {
+                let link = self.section_table.pick(link.0)?;
+                if link.ty != SectionType::StringTable {
+                    return Ok(());
+                }
+                match file_slice(self.raw, link.offset, link.size) {
+                    Ok(strings) => strings,
+                    Err(_) => return Ok(()),
+                }
+            }
+            _ => return Ok(()),
+        };
+        let slice = file_slice(self.raw, header.offset, header.size)?;
+        let table =
+            Table::<SymbolEntry>::with_entry_size(slice, self.encoding(), header.number_of_entries);
+        for symbol in 0..table.len() {
+            let entry = table.pick(symbol)?;
+            if entry.name == 0 {
+                continue;
+            }
+            match dangling(strings, entry.name) {
+                Some(Dangling::OutOfRange) => {
+                    violations.push(Violation::SymbolNameOutOfRange { section, symbol })
+                }
+                Some(Dangling::Unterminated) => {
+                    violations.push(Violation::UnterminatedSymbolName { section, symbol })
+                }
+                None => (),
+            }
+        }
+        Ok(())
+    }
+
+    // The names of the sections and of the symbols that are not in their string table
+    // or run to its end, the checks of `validate` alone. A table that is not in the file
+    // or is not a string table is not checked, `validate` tells it.
+    pub fn validate_names(&self) -> Result<Vec<Violation>, Error> {
+        let mut violations = Vec::new();
+        let number = self.section_number();
+        let names = match self.section_names() {
+            Index::Regular(index) if index.0 < number => {
+                if self.section_table.pick(index.0)?.ty == SectionType::StringTable {
+                    self.section_names_slice(index)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        for section in 0..number {
+            let header = self.section_table.pick(section)?;
+            if let Some(names) = names {
+                self.validate_section_name(names, section, &header, &mut violations);
+            }
+            let is_symbols = matches!(
+                header.ty,
+                SectionType::SymbolTable | SectionType::DynamicSymbolTable
+            );
+            if is_symbols && !out_of_bounds(self.raw.len(), header.offset, header.size) {
+                self.validate_symbol_names(section, &header, &mut violations)?;
+            }
+        }
+        Ok(violations)
+    }
+}
@@ -0,0 +1,414 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use super::{Error, Encoding, Elf64, SectionType, SectionData, StringTable, Index};
+use super::common::file_slice;
+
+pub(crate) const SHT_GNU_VERDEF: u32 = 0x6ffffffd;
+pub(crate) const SHT_GNU_VERNEED: u32 = 0x6ffffffe;
+pub(crate) const SHT_GNU_VERSYM: u32 = 0x6fffffff;
+
+// the version index of `.gnu.version` without the hidden bit
+pub(crate) const VERSYM_HIDDEN: u16 = 0x8000;
+pub(crate) const VER_NDX_LOCAL: u16 = 0;
+pub(crate) const VER_NDX_GLOBAL: u16 = 1;
+
+// `VER_FLG_BASE`, the definition of the file itself
+pub(crate) const VER_FLG_BASE: u16 = 0x1;
+
+// An entry of `.gnu.version`, the index of a definition or of a requirement
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct VersionIndex {
+    pub index: u16,
+    // not the default version, a `@` rather than `@@` symbol, only an exact request binds to it
+    pub hidden: bool,
+}
+
+impl From<u16> for VersionIndex {
+    fn from(v: u16) -> Self {
+        VersionIndex {
+            index: v & !VERSYM_HIDDEN,
+            hidden: v & VERSYM_HIDDEN != 0,
+        }
+    }
+}
+
+impl VersionIndex {
+    // `VER_NDX_LOCAL`, the symbol is not visible outside
+    pub fn is_local(&self) -> bool {
+        self.index == VER_NDX_LOCAL
+    }
+
+    // `VER_NDX_GLOBAL`, the symbol has no version
+    pub fn is_global(&self) -> bool {
+        self.index == VER_NDX_GLOBAL
+    }
+}
+
+// An auxiliary entry of `.gnu.version_r`, a version of a library this file needs
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionRequirement<'a> {
+    // the library, `vn_file`
+    pub file: &'a [u8],
+    pub name: &'a [u8],
+    pub index: u16,
+    pub flags: u16,
+    pub hash: u32,
+}
+
+// An entry of `.gnu.version_d`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionDefinition<'a> {
+    pub index: u16,
+    pub flags: u16,
+    pub hash: u32,
+    pub name: &'a [u8],
+    // the second auxiliary entry, the version this one inherits
+    pub parent: Option<&'a [u8]>,
+}
+
+impl<'a> VersionDefinition<'a> {
+    // `VER_FLG_BASE`, the name is the soname of the file, not a version of symbols
+    pub fn is_base(&self) -> bool {
+        self.flags & VER_FLG_BASE != 0
+    }
+}
+
+// The version of a symbol by `.gnu.version`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SymbolVersion<'a> {
+    Local,
+    Global,
+    Defined {
+        definition: VersionDefinition<'a>,
+        hidden: bool,
+    },
+    Required(VersionRequirement<'a>),
+}
+
+#[derive(Clone)]
+struct VersionSection<'a> {
+    slice: &'a [u8],
+    number: usize,
+    strings: StringTable<'a>,
+}
+
+// The GNU symbol versioning sections of the file, `.gnu.version`, `.gnu.version_d`
+// and `.gnu.version_r`, the names are in the string tables of their links
+#[derive(Clone)]
+pub struct Versions<'a> {
+    encoding: Encoding,
+    versym: Option<&'a [u8]>,
+    verdef: Option<VersionSection<'a>>,
+    verneed: Option<VersionSection<'a>>,
+}
+
+fn u16_at(slice: &[u8], offset: usize, encoding: &Encoding) -> Result<u16, Error> {
+    let slice = slice
+        .get(offset..(offset + 2))
+        .ok_or(Error::SliceTooShort)?;
+    Ok(read_int!(slice, encoding, u16))
+}
+
+fn u32_at(slice: &[u8], offset: usize, encoding: &Encoding) -> Result<u32, Error> {
+    let slice = slice
+        .get(offset..(offset + 4))
+        .ok_or(Error::SliceTooShort)?;
+    Ok(read_int!(slice, encoding, u32))
+}
+
+// `vd_aux`, `vd_next` and the others are relative, the sum is checked and stays
+// in the section, so the fields of the entry there are in range of `usize`
+fn advance(slice: &[u8], offset: usize, next: u32) -> Result<usize, Error> {
+    let offset = offset
+        .checked_add(next as usize)
+        .ok_or(Error::ArithmeticOverflow)?;
+    if offset > slice.len() {
+        return Err(Error::SliceTooShort);
+    }
+    Ok(offset)
+}
+
+// The entries of `.gnu.version_d` in the order of the section, `vd_next` links them,
+// a corrupted entry is the last item
+#[derive(Clone)]
+pub struct VersionDefinitions<'a> {
+    encoding: Encoding,
+    verdef: Option<VersionSection<'a>>,
+    offset: usize,
+    remaining: usize,
+}
+
+impl<'a> VersionDefinitions<'a> {
+    fn read(&self, verdef: &VersionSection<'a>) -> Result<(VersionDefinition<'a>, u32), Error> {
+        let (slice, encoding, offset) = (verdef.slice, &self.encoding, self.offset);
+        let count = u16_at(slice, offset + 0x06, encoding)?;
+        let aux = advance(slice, offset, u32_at(slice, offset + 0x0c, encoding)?)?;
+        let name = verdef
+            .strings
+            .pick(u32_at(slice, aux, encoding)? as usize)?;
+        let parent = if count > 1 {
+            let next = advance(slice, aux, u32_at(slice, aux + 0x04, encoding)?)?;
+            let parent = u32_at(slice, next, encoding)?;
+            Some(verdef.strings.pick(parent as usize)?)
+        } else {
+            None
+        };
+        let definition = VersionDefinition {
+            index: u16_at(slice, offset + 0x04, encoding)?,
+            flags: u16_at(slice, offset + 0x02, encoding)?,
+            hash: u32_at(slice, offset + 0x08, encoding)?,
+            name,
+            parent,
+        };
+        Ok((definition, u32_at(slice, offset + 0x10, encoding)?))
+    }
+}
+
+impl<'a> Iterator for VersionDefinitions<'a> {
+    type Item = Result<VersionDefinition<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let verdef = self.verdef.clone()?;
+        match self.read(&verdef) {
+            Ok((definition, 0)) => {
+                self.remaining = 0;
+                Some(Ok(definition))
+            }
+            Ok((definition, next)) => {
+                self.remaining -= 1;
+                // past the section the next read is the error
+                self.offset =
+                    advance(verdef.slice, self.offset, next).unwrap_or(verdef.slice.len());
+                Some(Ok(definition))
+            }
+            Err(error) => {
+                self.remaining = 0;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+// The versions of `.gnu.version_r`, library by library, `vn_next` and `vna_next`
+// link them, a corrupted entry is the last item
+#[derive(Clone)]
+pub struct VersionRequirements<'a> {
+    encoding: Encoding,
+    verneed: Option<VersionSection<'a>>,
+    offset: usize,
+    remaining: usize,
+    // the library of the auxiliary entries, the next one and how many are left
+    file: &'a [u8],
+    aux: usize,
+    aux_remaining: usize,
+}
+
+impl<'a> VersionRequirements<'a> {
+    fn read(
+        &mut self,
+        verneed: &VersionSection<'a>,
+    ) -> Result<Option<VersionRequirement<'a>>, Error> {
+        let (slice, encoding) = (verneed.slice, self.encoding.clone());
+        while self.aux_remaining == 0 {
+            if self.remaining == 0 {
+                return Ok(None);
+            }
+            let offset = self.offset;
+            self.aux_remaining = u16_at(slice, offset + 0x02, &encoding)? as usize;
+            self.file = verneed
+                .strings
+                .pick(u32_at(slice, offset + 0x04, &encoding)? as usize)?;
+            self.aux = advance(slice, offset, u32_at(slice, offset + 0x08, &encoding)?)?;
+            match u32_at(slice, offset + 0x0c, &encoding)? {
+                0 => self.remaining = 0,
+                next => {
+                    self.remaining -= 1;
+                    self.offset = advance(slice, offset, next)?;
+                }
+            }
+        }
+        let aux = self.aux;
+        let name = u32_at(slice, aux + 0x08, &encoding)?;
+        let requirement = VersionRequirement {
+            file: self.file,
+            name: verneed.strings.pick(name as usize)?,
+            index: u16_at(slice, aux + 0x06, &encoding)?,
+            flags: u16_at(slice, aux + 0x04, &encoding)?,
+            hash: u32_at(slice, aux, &encoding)?,
+        };
+        match u32_at(slice, aux + 0x0c, &encoding)? {
+            0 => self.aux_remaining = 0,
+            next => {
+                self.aux_remaining -= 1;
+                self.aux = advance(slice, aux, next)?;
+            }
+        }
+        Ok(Some(requirement))
+    }
+}
+
+impl<'a> Iterator for VersionRequirements<'a> {
+    type Item = Result<VersionRequirement<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let verneed = self.verneed.clone()?;
+        match self.read(&verneed) {
+            Ok(requirement) => requirement.map(Ok),
+            Err(error) => {
+                self.remaining = 0;
+                self.aux_remaining = 0;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl<'a> Versions<'a> {
+    // the raw entry of `.gnu.version` of the symbol, `None` if there is no such section
+    pub fn versym(&self, symbol: usize) -> Result<Option<u16>, Error> {
+        match self.versym {
+            Some(slice) => {
+                let offset = symbol.checked_mul(2).ok_or(Error::ArithmeticOverflow)?;
+                u16_at(slice, offset, &self.encoding).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn version_index(&self, symbol: usize) -> Result<Option<VersionIndex>, Error> {
+        self.versym(symbol)
+            .map(|versym| versym.map(VersionIndex::from))
+    }
+
+    // The version of the symbol of `.dynsym` with the index, the definition of the file
+    // or the requirement of a library, `None` if there is no `.gnu.version`
+    pub fn symbol_version(&self, symbol: usize) -> Result<Option<SymbolVersion<'a>>, Error> {
+        let index = match self.version_index(symbol)? {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        if index.is_local() {
+            return Ok(Some(SymbolVersion::Local));
+        }
+        if index.is_global() {
+            return Ok(Some(SymbolVersion::Global));
+        }
+        for definition in self.definition_entries() {
+            let definition = definition?;
+            if definition.index == index.index {
+                return Ok(Some(SymbolVersion::Defined {
+                    definition,
+                    hidden: index.hidden,
+                }));
+            }
+        }
+        Ok(self.requirement(index.index)?.map(SymbolVersion::Required))
+    }
+
+    pub fn definition_entries(&self) -> VersionDefinitions<'a> {
+        VersionDefinitions {
+            encoding: self.encoding.clone(),
+            remaining: self.verdef.as_ref().map_or(0, |verdef| verdef.number),
+            verdef: self.verdef.clone(),
+            offset: 0,
+        }
+    }
+
+    pub fn requirements(&self) -> VersionRequirements<'a> {
+        VersionRequirements {
+            encoding: self.encoding.clone(),
+            remaining: self.verneed.as_ref().map_or(0, |verneed| verneed.number),
+            verneed: self.verneed.clone(),
+            offset: 0,
+            file: &[],
+            aux: 0,
+            aux_remaining: 0,
+        }
+    }
+
+    // the name of the version defined in this file with the index
+    pub fn definition(&self, index: u16) -> Result<Option<&'a [u8]>, Error> {
+        for definition in self.definition_entries() {
+            let definition = definition?;
+            if definition.index == index {
+                return Ok(Some(definition.name));
+            }
+        }
+        Ok(None)
+    }
+
+    // every version defined in this file in the order of the section
+    #[cfg(feature = "alloc")]
+    pub fn definitions(&self) -> Result<Vec<VersionDefinition<'a>>, Error> {
+        self.definition_entries().collect()
+    }
+
+    // the library and the name of the version required by this file with the index
+    pub fn requirement(&self, index: u16) -> Result<Option<VersionRequirement<'a>>, Error> {
+        self.find_requirement(|i, _| i == index)
+    }
+
+    // the first requirement the predicate accepts, it gets the version index too
+    pub fn find_requirement<F>(&self, mut f: F) -> Result<Option<VersionRequirement<'a>>, Error>
+    where
+        F: FnMut(u16, &VersionRequirement<'a>) -> bool,
+    {
+        for requirement in self.requirements() {
+            let requirement = requirement?;
+            if f(requirement.index, &requirement) {
+                return Ok(Some(requirement));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // `Versions` of a file without them has nothing in it
+    pub fn versions(&self) -> Result<Versions<'a>, Error> {
+        let mut versions = Versions {
+            encoding: self.encoding(),
+            versym: None,
+            verdef: None,
+            verneed: None,
+        };
+        for index in 0..self.section_number() {
+            let header = self.section_table.pick(index)?;
+            let code = match header.ty {
+                SectionType::OsSpecific(code) => code,
+                _ => continue,
+            };
+            let slice = file_slice(self.raw, header.offset, header.size)?;
+            let strings = || match header.link {
+                Index::Regular(link) => match self.section(link)?.map(|s| s.data) {
+                    Some(SectionData::StringTable(strings)) => Ok(strings),
+                    _ => Err(Error::UnexpectedSectionType),
+                },
+                _ => Err(Error::UnexpectedSectionType),
+            };
+            match code {
+                SHT_GNU_VERSYM => versions.versym = Some(slice),
+                SHT_GNU_VERDEF => {
+                    versions.verdef = Some(VersionSection {
+                        slice,
+                        number: header.info as usize,
+                        strings: strings()?,
+                    })
+                }
+                SHT_GNU_VERNEED => {
+                    versions.verneed = Some(VersionSection {
+                        slice,
+                        number: header.info as usize,
+                        strings: strings()?,
+                    })
+                }
+                _ => (),
+            }
+        }
+        Ok(versions)
+    }
+}
@@ -0,0 +1,140 @@
+use core::fmt;
+use alloc::{vec, vec::Vec};
+
+use super::{Error, Elf64, Index, SectionType, SymbolBinding, Lossy};
+use super::version::{VERSYM_HIDDEN, VER_FLG_BASE};
+
+// `STV_DEFAULT` and `STV_PROTECTED`, the others are not visible outside
+const STV_DEFAULT: u8 = 0;
+const STV_PROTECTED: u8 = 3;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionNode<'a> {
+    // `None` is the anonymous version
+    pub name: Option<&'a [u8]>,
+    pub parent: Option<&'a [u8]>,
+    // sorted
+    pub symbols: Vec<&'a [u8]>,
+}
+
+// A GNU ld version script that exports exactly what the file exports,
+// every other symbol is local
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionScript<'a> {
+    pub nodes: Vec<VersionNode<'a>>,
+}
+
+impl<'a> Elf64<'a> {
+    // The defined, visible, not local symbols of `.dynsym`. If `by_version` and the file
+    // defines versions, a node for each version in the order of `.gnu.version_d`,
+    // the symbols of the base version go to the first node, otherwise a single
+    // anonymous node.
+    pub fn version_script(&self, by_version: bool) -> Result<VersionScript<'a>, Error> {
+        let versions = self.versions()?;
+        let definitions = versions
+            .definitions()?
+            .into_iter()
+            .filter(|definition| definition.flags & VER_FLG_BASE == 0)
+            .collect::<Vec<_>>();
+        let by_version = by_version && !definitions.is_empty();
+        let mut nodes = if !by_version {
+            vec![VersionNode {
+                name: None,
+                parent: None,
+                symbols: Vec::new(),
+            }]
+        } else {
+            definitions
+                .iter()
+                .map(|definition| VersionNode {
+                    name: Some(definition.name),
+                    parent: definition.parent,
+                    symbols: Vec::new(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let symbols = match self.symbol_source(SectionType::DynamicSymbolTable)? {
+            Some(symbols) => symbols,
+            None => return Ok(VersionScript { nodes }),
+        };
+        for index in 1..symbols.number {
+            let symbol = symbols.get(index)?;
+            let entry = &symbol.entry;
+            let visible = matches!(entry.reserved & 0x3, STV_DEFAULT | STV_PROTECTED);
+            let exported = matches!(
+                entry.info.binding,
+                SymbolBinding::Global | SymbolBinding::Weak
+            );
+            if !entry.is_defined() || !visible || !exported || symbol.name.is_empty() {
+                continue;
+            }
+            // the linker defines an absolute symbol for each version
+            if entry.section_index == Index::AbsoluteValue
+                && definitions.iter().any(|d| d.name == symbol.name)
+            {
+                continue;
+            }
+            let node = match versions.versym(index)? {
+                Some(versym) if by_version => {
+                    let version = versym & !VERSYM_HIDDEN;
+                    definitions
+                        .iter()
+                        .position(|definition| definition.index == version)
+                        .unwrap_or(0)
+                }
+                _ => 0,
+            };
+            nodes[node].symbols.push(symbol.name);
+        }
+        for node in &mut nodes {
+            node.symbols.sort_unstable();
+            node.symbols.dedup();
+        }
+        Ok(VersionScript { nodes })
+    }
+}
+
+// the name as is, or quoted if it has characters the script treats specially,
+// a quoted name is not a pattern
+struct Pattern<'a>(&'a [u8]);
+
+impl<'a> fmt::Display for Pattern<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plain = self
+            .0
+            .iter()
+            .all(|&c| c.is_ascii_alphanumeric() || c == b'_' || c == b'.' || c == b'$');
+        if plain {
+            write!(f, "{}", Lossy(self.0))
+        } else {
+            write!(f, "\"{}\"", Lossy(self.0))
+        }
+    }
+}
+
+impl<'a> fmt::Display for VersionScript<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, node) in self.nodes.iter().enumerate() {
+            match node.name {
+                Some(name) => writeln!(f, "{} {{", Lossy(name))?,
+                None => writeln!(f, "{{")?,
+            }
+            if !node.symbols.is_empty() {
+                writeln!(f, "  global:")?;
+                for &symbol in &node.symbols {
+                    writeln!(f, "    {};", Pattern(symbol))?;
+                }
+            }
+            if index == 0 {
+                writeln!(f, "  local:")?;
+                writeln!(f, "    *;")?;
+            }
+            match node.parent {
+                Some(parent) => writeln!(f, "}} {};", Lossy(parent))?,
+                None => writeln!(f, "}};")?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use super::{
+    Error, Encoding, Elf64, NoteTable, ProgramType, ProgramFlags, SectionType, SectionFlags, Entry,
+};
+use super::{ProgramHeader, SectionHeader};
+use super::common::saturating_usize;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Origin {
+    Program(usize),
+    Section(usize),
+}
+
+// Oddities the parser tolerates
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Warning {
+    // the last note does not fit the section or the segment, it is not visible
+    TruncatedNote(Origin),
+    // the alignment is neither zero nor a power of two
+    BogusAlignment { origin: Origin, alignment: u64 },
+    // the flags have bits the parser does not know, they are dropped
+    UnknownFlags { origin: Origin, bits: u64 },
+}
+
+const CHECKS: usize = 3;
+
+#[derive(Clone)]
+pub struct Warnings<'a> {
+    elf: Elf64<'a>,
+    position: usize,
+}
+
+impl<'a> Warnings<'a> {
+    fn check(&self, item: usize, check: usize) -> Result<Option<Warning>, Error> {
+        let elf = &self.elf;
+        let encoding = elf.encoding();
+        let (origin, slice, alignment, is_note, bits, known) = if item < elf.program_number() {
+            let header = elf.program_table.pick(item)?;
+            let offset = saturating_usize(elf.header.program_headers_offset.0)
+                .saturating_add(item * ProgramHeader::SIZE);
+            let bits = read_int!(
+                elf.raw.get(offset + 0x04, Some(offset + 0x08))?,
+                &encoding,
+                u32
+            ) as u64;
+            let start = saturating_usize(header.file_offset.0);
+            let end = start.saturating_add(saturating_usize(header.file_size));
+            (
+                Origin::Program(item),
+                elf.raw.get(start, Some(end)).ok(),
+                header.address_alignment,
+                header.ty == ProgramType::Note,
+                bits,
+                ProgramFlags::all().bits() as u64,
+            )
+        } else {
+            let index = item - elf.program_number();
+            let header = elf.section_table.pick(index)?;
+            let offset = saturating_usize(elf.header.section_headers_offset.0)
+                .saturating_add(index * SectionHeader::SIZE);
+            let bits = read_int!(
+                elf.raw.get(offset + 0x08, Some(offset + 0x10))?,
+                &encoding,
+                u64
+            );
+            let start = saturating_usize(header.offset.0);
+            let end = start.saturating_add(saturating_usize(header.size));
+            (
+                Origin::Section(index),
+                elf.raw.get(start, Some(end)).ok(),
+                header.address_alignment,
+                header.ty == SectionType::Note,
+                bits,
+                (SectionFlags::all() - SectionFlags::RESERVED).bits(),
+            )
+        };
+
+        match check {
+            0 if is_note => {
+                let table = NoteTable::with_alignment(
+                    slice.ok_or(Error::SliceTooShort)?,
+                    encoding,
+                    alignment,
+                );
+                let length = slice.map(<[u8]>::len).unwrap_or(0);
+                let mut position = 0;
+                while position < length {
+                    if table.next(&mut position).is_err() {
+                        return Ok(Some(Warning::TruncatedNote(origin)));
+                    }
+                }
+                Ok(None)
+            }
+            1 if alignment != 0 && !alignment.is_power_of_two() => {
+                Ok(Some(Warning::BogusAlignment { origin, alignment }))
+            }
+            2 if bits & !known != 0 => Ok(Some(Warning::UnknownFlags {
+                origin,
+                bits: bits & !known,
+            })),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<'a> Iterator for Warnings<'a> {
+    type Item = Result<Warning, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let items = self.elf.program_number() + self.elf.section_number();
+        while self.position < items * CHECKS {
+            let (item, check) = (self.position / CHECKS, self.position % CHECKS);
+            self.position += 1;
+            match self.check(item, check) {
+                Ok(Some(warning)) => return Some(Ok(warning)),
+                Ok(None) => (),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Elf64<'a> {
+    // Everything the parser recovers from silently, the headers are checked in order,
+    // programs first
+    pub fn diagnostics(&self) -> Warnings<'a> {
+        Warnings {
+            elf: self.clone(),
+            position: 0,
+        }
+    }
+}
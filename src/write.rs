@@ -0,0 +1,54 @@
+use alloc::{vec, vec::Vec};
+
+use super::{Error, Offset, Elf64, Entry, Encode, SectionType, ProgramHeader, SectionHeader};
+use super::common::{file_slice, to_usize};
+use super::fragments::Source;
+
+fn copy_range(to: &mut [u8], from: Source<'_>, offset: Offset, size: u64) -> Result<(), Error> {
+    let from = file_slice(from, offset, size)?;
+    let start = to_usize(offset.0)?;
+    to.get_mut(start..(start + from.len()))
+        .ok_or(Error::SliceTooShort)?
+        .clone_from_slice(from);
+    Ok(())
+}
+
+impl<'a> Elf64<'a> {
+    // Writes the parsed headers and copies the content of every section and segment,
+    // the bytes that no structure claims are zero.
+    pub fn serialize(&self) -> Result<Vec<u8>, Error> {
+        let encoding = self.encoding();
+        let mut raw = vec![0; self.raw.len()];
+
+        for i in 0..self.program_number() {
+            let header = self.program_table.pick(i)?;
+            copy_range(&mut raw, self.raw, header.file_offset, header.file_size)?;
+        }
+        for i in 0..self.section_number() {
+            let header = self.section_table.pick(i)?;
+            if header.ty != SectionType::NoBits {
+                copy_range(&mut raw, self.raw, header.offset, header.size)?;
+            }
+        }
+
+        self.header.encode(&mut raw)?;
+        let start = self.header.program_headers_offset.0 as usize;
+        for i in 0..self.program_number() {
+            let offset = start + i * ProgramHeader::SIZE;
+            let slice = raw.get_mut(offset..).ok_or(Error::SliceTooShort)?;
+            self.program_table
+                .pick(i)?
+                .encode(slice, encoding.clone())?;
+        }
+        let start = self.header.section_headers_offset.0 as usize;
+        for i in 0..self.section_number() {
+            let offset = start + i * SectionHeader::SIZE;
+            let slice = raw.get_mut(offset..).ok_or(Error::SliceTooShort)?;
+            self.section_table
+                .pick(i)?
+                .encode(slice, encoding.clone())?;
+        }
+
+        Ok(raw)
+    }
+}